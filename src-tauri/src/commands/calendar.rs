@@ -1,4 +1,10 @@
 use crate::models::action::CalendarEvent;
+use crate::services::caldav::CalDavAccount;
+use crate::services::calendar_cache::CalendarCacheStatus;
+use crate::services::calendar_selection::CalendarInfo;
+use crate::services::ics_feeds::IcsFeed;
+use crate::services::meeting_links::JoinInfo;
+use crate::services::meeting_reminders::{self, MeetingReminderConfig};
 use crate::services::calendar_service::{get_calendar_service, CalendarProvider};
 use tauri::Manager;
 use std::sync::atomic::{AtomicBool, Ordering};
@@ -19,12 +25,33 @@ pub async fn get_calendar_events() -> Result<Vec<CalendarEvent>, String> {
     }
 }
 
+#[tauri::command]
+pub async fn get_calendar_cache_status() -> Result<CalendarCacheStatus, String> {
+    Ok(get_calendar_service().await.lock().await.cache_status().await)
+}
+
 #[tauri::command]
 pub async fn get_next_meeting() -> Result<Option<CalendarEvent>, String> {
     // Direct call with error handling
     Ok(get_calendar_service().await.lock().await.get_next_meeting().await)
 }
 
+/// Join link, alternatives and dial-in numbers for the overlay's "Join" button
+#[tauri::command]
+pub async fn get_join_info(event_id: String) -> Result<JoinInfo, String> {
+    get_calendar_service().await.lock().await.get_join_info(&event_id).await
+}
+
+#[tauri::command]
+pub async fn get_meeting_reminder_config() -> Result<MeetingReminderConfig, String> {
+    Ok(meeting_reminders::config())
+}
+
+#[tauri::command]
+pub async fn set_meeting_reminder_config(config: MeetingReminderConfig) -> Result<(), String> {
+    meeting_reminders::set_config(config)
+}
+
 #[tauri::command]
 pub async fn time_until_next_meeting() -> Result<Option<i64>, String> {
     // Direct call with error handling
@@ -185,10 +212,65 @@ pub async fn handle_oauth_callback(provider: String, code: String) -> Result<(),
 
 #[tauri::command]
 pub async fn is_calendar_authenticated() -> Result<bool, String> {
-    // Direct call with error handling
-    use crate::services::calendar_service::CalendarService;
-    let is_auth = CalendarService::is_authenticated(CalendarProvider::Google).await;
+    let service = get_calendar_service().await;
+    let is_auth = service.lock().await.is_authenticated(CalendarProvider::Google).await;
     println!("[Calendar] is_calendar_authenticated check: {}", is_auth);
     Ok(is_auth)
 }
 
+#[tauri::command]
+pub async fn list_calendars() -> Result<Vec<CalendarInfo>, String> {
+    let service = get_calendar_service().await;
+    let service = service.lock().await;
+    service.list_calendars().await
+}
+
+#[tauri::command]
+pub async fn set_calendar_selection(provider: String, calendar_ids: Vec<String>) -> Result<(), String> {
+    let calendar_provider = match provider.as_str() {
+        "google" => CalendarProvider::Google,
+        "microsoft" => CalendarProvider::Microsoft,
+        "caldav" => CalendarProvider::CalDav,
+        "ics" => CalendarProvider::IcsUrl,
+        _ => return Err("Invalid provider".to_string()),
+    };
+
+    let service = get_calendar_service().await;
+    let service = service.lock().await;
+    service.set_calendar_selection(calendar_provider, calendar_ids).await
+}
+
+#[tauri::command]
+pub async fn connect_caldav(server_url: String, username: String, password: String) -> Result<Vec<CalendarInfo>, String> {
+    let service = get_calendar_service().await;
+    let service = service.lock().await;
+    service.connect_caldav(CalDavAccount { server_url, username, password }).await
+}
+
+#[tauri::command]
+pub async fn disconnect_caldav() -> Result<(), String> {
+    let service = get_calendar_service().await;
+    let service = service.lock().await;
+    service.disconnect_caldav().await
+}
+
+#[tauri::command]
+pub async fn list_ics_feeds() -> Result<Vec<IcsFeed>, String> {
+    let service = get_calendar_service().await;
+    let service = service.lock().await;
+    service.list_ics_feeds()
+}
+
+#[tauri::command]
+pub async fn add_ics_feed(url: String, name: Option<String>, color: Option<String>) -> Result<IcsFeed, String> {
+    let service = get_calendar_service().await;
+    let service = service.lock().await;
+    service.add_ics_feed(&url, name, color).await
+}
+
+#[tauri::command]
+pub async fn remove_ics_feed(url: String) -> Result<(), String> {
+    let service = get_calendar_service().await;
+    let service = service.lock().await;
+    service.remove_ics_feed(&url).await
+}
@@ -1,7 +1,30 @@
-use crate::services::focus_service::{get_focus_service, FocusMode, FocusSession};
+use crate::commands::launch::{close_app, launch_app, launch_url};
+use crate::services::distraction_blocker::DistractionConfig;
+use crate::services::event_bus::EventBusConfig;
+use crate::services::focus_history::{ExportFormat, FocusRecord, FocusStats, MAX_STATS_DAYS};
+use crate::services::focus_planner::{FocusPlan, PlannerConfig};
+use crate::services::focus_presets::FocusPreset;
+use crate::services::focus_service::{get_focus_service, FocusMode, FocusOptions, FocusSession};
+use crate::services::idle_detector::IdleConfig;
+use crate::services::meeting_guard::MeetingGuardConfig;
+use crate::services::notification_allowlist::NotificationAllowlist;
+use crate::services::notification_digest::NotificationDigest;
+use crate::services::pomodoro::{PomodoroConfig, PomodoroState};
+use crate::services::quiet_hours::QuietHoursRule;
 
 #[tauri::command]
-pub async fn start_focus_mode(mode_str: String, custom_minutes: Option<u32>) -> Result<FocusSession, String> {
+pub async fn start_focus_mode(
+    mode_str: String,
+    custom_minutes: Option<u32>,
+    preset_id: Option<String>,
+    goal: Option<String>,
+    tags: Option<Vec<String>>,
+) -> Result<FocusSession, String> {
+    let tags = tags.unwrap_or_default();
+    if let Some(preset_id) = preset_id {
+        return start_focus_preset(preset_id, goal, tags).await;
+    }
+
     let mode = match mode_str.as_str() {
         "focus1" => FocusMode::Focus1,
         "focus15" => FocusMode::Focus15,
@@ -23,7 +46,8 @@ pub async fn start_focus_mode(mode_str: String, custom_minutes: Option<u32>) ->
     
     println!("[Focus] Service acquired, starting focus mode...");
     
-    match service.start_focus_mode(mode).await {
+    let options = FocusOptions { goal, tags, ..FocusOptions::default() };
+    match service.start_focus_mode_with_options(mode, options).await {
         Ok(session) => {
             println!("[Focus] ✓ Focus mode started successfully: {:?}", session.mode);
             Ok(session)
@@ -35,6 +59,70 @@ pub async fn start_focus_mode(mode_str: String, custom_minutes: Option<u32>) ->
     }
 }
 
+/// Start a session from a preset, applying its app and URL actions
+async fn start_focus_preset(preset_id: String, goal: Option<String>, tags: Vec<String>) -> Result<FocusSession, String> {
+    let (preset, session) = {
+        let service = get_focus_service().await;
+        let service = service.lock().await;
+
+        let preset = service.presets().get(&preset_id)?;
+        println!("[Focus] Starting focus preset: {} ({} minutes)", preset.name, preset.duration_minutes);
+
+        let session = service
+            .start_focus_mode_with_options(preset.focus_mode(), FocusOptions { goal, tags, ..FocusOptions::from(&preset) })
+            .await?;
+        (preset, session)
+    };
+
+    // Preset actions run after the focus service is unlocked, so a slow launch doesn't hold it up.
+    // They're best-effort - a missing app shouldn't cancel the session
+    for app in &preset.close_apps {
+        if let Err(e) = close_app(app.clone()).await {
+            eprintln!("[Focus] Preset could not close {}: {}", app, e);
+        }
+    }
+    for app in &preset.launch_apps {
+        if let Err(e) = launch_app(app.clone()).await {
+            eprintln!("[Focus] Preset could not launch {}: {}", app, e);
+        }
+    }
+    if let Some(ref url) = preset.open_url {
+        if let Err(e) = launch_url(url.clone()).await {
+            eprintln!("[Focus] Preset could not open {}: {}", url, e);
+        }
+    }
+
+    Ok(session)
+}
+
+#[tauri::command]
+pub async fn list_focus_presets() -> Result<Vec<FocusPreset>, String> {
+    let service = get_focus_service().await;
+    let service = service.lock().await;
+    service.presets().list()
+}
+
+#[tauri::command]
+pub async fn create_focus_preset(preset: FocusPreset) -> Result<FocusPreset, String> {
+    let service = get_focus_service().await;
+    let service = service.lock().await;
+    service.presets().create(preset)
+}
+
+#[tauri::command]
+pub async fn update_focus_preset(preset: FocusPreset) -> Result<FocusPreset, String> {
+    let service = get_focus_service().await;
+    let service = service.lock().await;
+    service.presets().update(preset)
+}
+
+#[tauri::command]
+pub async fn delete_focus_preset(preset_id: String) -> Result<(), String> {
+    let service = get_focus_service().await;
+    let service = service.lock().await;
+    service.presets().delete(&preset_id)
+}
+
 #[tauri::command]
 pub async fn stop_focus_mode() -> Result<(), String> {
     let service = get_focus_service().await;
@@ -42,6 +130,20 @@ pub async fn stop_focus_mode() -> Result<(), String> {
     service.stop_focus_mode().await
 }
 
+#[tauri::command]
+pub async fn pause_focus_mode(restore_notifications: Option<bool>) -> Result<FocusSession, String> {
+    let service = get_focus_service().await;
+    let service = service.lock().await;
+    service.pause_focus_mode(restore_notifications.unwrap_or(false)).await
+}
+
+#[tauri::command]
+pub async fn resume_focus_mode() -> Result<FocusSession, String> {
+    let service = get_focus_service().await;
+    let service = service.lock().await;
+    service.resume_focus_mode().await
+}
+
 #[tauri::command]
 pub async fn get_current_focus_session() -> Result<Option<FocusSession>, String> {
     let service = get_focus_service().await;
@@ -106,14 +208,14 @@ pub async fn open_focus_assist_settings() -> Result<(), String> {
 pub async fn temporarily_mute_notifications(duration_minutes: Option<u32>) -> Result<(), String> {
     let service = get_focus_service().await;
     let service = service.lock().await;
-    service.temporarily_mute_notifications(duration_minutes).await
+    service.temporarily_mute_notifications_by_user(duration_minutes).await
 }
 
 #[tauri::command]
 pub async fn unmute_notifications() -> Result<(), String> {
     let service = get_focus_service().await;
     let service = service.lock().await;
-    service.unmute_notifications().await
+    service.unmute_notifications_by_user().await
 }
 
 #[tauri::command]
@@ -141,3 +243,247 @@ pub async fn reschedule_meeting(event_id: String, minutes_offset: i64) -> Result
     
     calendar_service.reschedule_event(event_id, minutes_offset).await
 }
+
+#[tauri::command]
+pub async fn get_focus_history(limit: Option<usize>) -> Result<Vec<FocusRecord>, String> {
+    let service = get_focus_service().await;
+    let service = service.lock().await;
+    service.get_history(limit)
+}
+
+#[tauri::command]
+pub async fn search_focus_history(tag: String, limit: Option<usize>) -> Result<Vec<FocusRecord>, String> {
+    let service = get_focus_service().await;
+    let service = service.lock().await;
+    service.search_history(&tag, limit)
+}
+
+#[tauri::command]
+pub async fn reflect_on_focus_session(session_id: Option<String>, rating: u8, note: String) -> Result<FocusRecord, String> {
+    let service = get_focus_service().await;
+    let service = service.lock().await;
+    service.reflect_on_session(session_id, rating, &note)
+}
+
+#[tauri::command]
+pub async fn export_focus_history(format: ExportFormat, days: Option<u32>, tag: Option<String>) -> Result<String, String> {
+    let service = get_focus_service().await;
+    let service = service.lock().await;
+    service.export_history(format, days, tag.as_deref())
+}
+
+#[tauri::command]
+pub async fn get_focus_stats(days: Option<u32>) -> Result<FocusStats, String> {
+    let days = days.unwrap_or(7);
+    if days == 0 || days > MAX_STATS_DAYS {
+        return Err(format!("Stats range must be between 1 and {} days", MAX_STATS_DAYS));
+    }
+    let service = get_focus_service().await;
+    let service = service.lock().await;
+    service.get_stats(days)
+}
+
+#[tauri::command]
+pub async fn start_pomodoro(
+    work_minutes: Option<u32>,
+    short_break_minutes: Option<u32>,
+    long_break_minutes: Option<u32>,
+    cycles_before_long_break: Option<u32>,
+) -> Result<PomodoroState, String> {
+    let defaults = PomodoroConfig::default();
+    let config = PomodoroConfig {
+        work_minutes: work_minutes.unwrap_or(defaults.work_minutes),
+        short_break_minutes: short_break_minutes.unwrap_or(defaults.short_break_minutes),
+        long_break_minutes: long_break_minutes.unwrap_or(defaults.long_break_minutes),
+        cycles_before_long_break: cycles_before_long_break.unwrap_or(defaults.cycles_before_long_break),
+    };
+
+    let service = get_focus_service().await;
+    let service = service.lock().await;
+    service.start_pomodoro(config).await
+}
+
+#[tauri::command]
+pub async fn stop_pomodoro() -> Result<(), String> {
+    let service = get_focus_service().await;
+    let service = service.lock().await;
+    service.stop_pomodoro().await
+}
+
+#[tauri::command]
+pub async fn pause_pomodoro() -> Result<PomodoroState, String> {
+    let service = get_focus_service().await;
+    let service = service.lock().await;
+    service.pause_pomodoro().await
+}
+
+#[tauri::command]
+pub async fn resume_pomodoro() -> Result<PomodoroState, String> {
+    let service = get_focus_service().await;
+    let service = service.lock().await;
+    service.resume_pomodoro().await
+}
+
+#[tauri::command]
+pub async fn skip_pomodoro_phase() -> Result<PomodoroState, String> {
+    let service = get_focus_service().await;
+    let service = service.lock().await;
+    service.skip_pomodoro_phase().await
+}
+
+#[tauri::command]
+pub async fn reset_pomodoro() -> Result<PomodoroState, String> {
+    let service = get_focus_service().await;
+    let service = service.lock().await;
+    service.reset_pomodoro().await
+}
+
+#[tauri::command]
+pub async fn get_pomodoro_state() -> Result<Option<PomodoroState>, String> {
+    let service = get_focus_service().await;
+    let service = service.lock().await;
+    Ok(service.get_pomodoro_state().await)
+}
+
+#[tauri::command]
+pub async fn get_distraction_config() -> Result<DistractionConfig, String> {
+    let service = get_focus_service().await;
+    let service = service.lock().await;
+    Ok(service.get_distraction_config().await)
+}
+
+#[tauri::command]
+pub async fn set_distraction_config(config: DistractionConfig) -> Result<(), String> {
+    let service = get_focus_service().await;
+    let service = service.lock().await;
+    service.set_distraction_config(config).await
+}
+
+fn parse_plan_date(date: Option<String>) -> Result<chrono::NaiveDate, String> {
+    match date {
+        Some(date) => chrono::NaiveDate::parse_from_str(&date, "%Y-%m-%d")
+            .map_err(|e| format!("Invalid date '{}': {}", date, e)),
+        None => Ok(chrono::Local::now().date_naive()),
+    }
+}
+
+#[tauri::command]
+pub async fn get_focus_plan(date: Option<String>) -> Result<FocusPlan, String> {
+    let date = parse_plan_date(date)?;
+    let service = get_focus_service().await;
+    let service = service.lock().await;
+    service.get_focus_plan(date).await
+}
+
+#[tauri::command]
+pub async fn add_focus_plan_to_calendar(date: Option<String>) -> Result<Vec<crate::models::action::CalendarEvent>, String> {
+    let date = parse_plan_date(date)?;
+    let service = get_focus_service().await;
+    let service = service.lock().await;
+    service.add_focus_plan_to_calendar(date).await
+}
+
+#[tauri::command]
+pub async fn get_focus_planner_config() -> Result<PlannerConfig, String> {
+    let service = get_focus_service().await;
+    let service = service.lock().await;
+    Ok(service.get_planner_config().await)
+}
+
+#[tauri::command]
+pub async fn set_focus_planner_config(config: PlannerConfig) -> Result<(), String> {
+    let service = get_focus_service().await;
+    let service = service.lock().await;
+    service.set_planner_config(config).await
+}
+
+#[tauri::command]
+pub async fn get_meeting_guard_config() -> Result<MeetingGuardConfig, String> {
+    let service = get_focus_service().await;
+    let service = service.lock().await;
+    Ok(service.get_meeting_guard_config().await)
+}
+
+#[tauri::command]
+pub async fn set_meeting_guard_config(config: MeetingGuardConfig) -> Result<(), String> {
+    let service = get_focus_service().await;
+    let service = service.lock().await;
+    service.set_meeting_guard_config(config).await
+}
+
+#[tauri::command]
+pub async fn restore_all_notification_settings() -> Result<usize, String> {
+    let service = get_focus_service().await;
+    let service = service.lock().await;
+    service.restore_all_notification_settings().await
+}
+
+#[tauri::command]
+pub async fn get_notification_allowlist() -> Result<NotificationAllowlist, String> {
+    let service = get_focus_service().await;
+    let service = service.lock().await;
+    Ok(service.get_notification_allowlist().await)
+}
+
+#[tauri::command]
+pub async fn set_notification_allowlist(allowlist: NotificationAllowlist) -> Result<(), String> {
+    let service = get_focus_service().await;
+    let service = service.lock().await;
+    service.set_notification_allowlist(allowlist).await
+}
+
+#[tauri::command]
+pub async fn get_event_bus_config() -> Result<EventBusConfig, String> {
+    let service = get_focus_service().await;
+    let service = service.lock().await;
+    Ok(service.get_event_bus_config())
+}
+
+#[tauri::command]
+pub async fn set_event_bus_config(config: EventBusConfig) -> Result<(), String> {
+    let service = get_focus_service().await;
+    let service = service.lock().await;
+    service.set_event_bus_config(config)
+}
+
+#[tauri::command]
+pub async fn get_idle_config() -> Result<IdleConfig, String> {
+    let service = get_focus_service().await;
+    let service = service.lock().await;
+    Ok(service.get_idle_config().await)
+}
+
+#[tauri::command]
+pub async fn set_idle_config(config: IdleConfig) -> Result<(), String> {
+    let service = get_focus_service().await;
+    let service = service.lock().await;
+    service.set_idle_config(config).await
+}
+
+#[tauri::command]
+pub async fn get_notification_digest() -> Result<Option<NotificationDigest>, String> {
+    let service = get_focus_service().await;
+    let service = service.lock().await;
+    Ok(service.get_notification_digest().await)
+}
+
+#[tauri::command]
+pub async fn list_quiet_hours_rules() -> Result<Vec<QuietHoursRule>, String> {
+    let service = get_focus_service().await;
+    let service = service.lock().await;
+    service.quiet_hours().list()
+}
+
+#[tauri::command]
+pub async fn add_quiet_hours_rule(rule: QuietHoursRule) -> Result<QuietHoursRule, String> {
+    let service = get_focus_service().await;
+    let service = service.lock().await;
+    service.quiet_hours().add(rule)
+}
+
+#[tauri::command]
+pub async fn remove_quiet_hours_rule(rule_id: String) -> Result<(), String> {
+    let service = get_focus_service().await;
+    let service = service.lock().await;
+    service.quiet_hours().remove(&rule_id)
+}
@@ -1,3 +1,4 @@
+use crate::services::focus_presets::validate_process_name;
 use std::path::Path;
 use std::process::Command;
 use std::result::Result;
@@ -344,6 +345,48 @@ pub async fn launch_url(url: String) -> Result<(), String> {
     }
 }
 
+/// Close a running application by process name (e.g. "slack" or "Slack.exe")
+/// Asks the app to close rather than force-killing it, so unsaved work can be handled.
+/// Only the exact process name is matched; used by focus presets, not exposed to the webview
+pub async fn close_app(process_name: String) -> Result<(), String> {
+    validate_process_name(&process_name)?;
+    println!("[Launch] Closing app: {}", process_name);
+
+    #[cfg(windows)]
+    {
+        let image_name = if process_name.to_lowercase().ends_with(".exe") {
+            process_name.clone()
+        } else {
+            format!("{}.exe", process_name)
+        };
+
+        let output = Command::new("taskkill")
+            .args(["/IM", &image_name])
+            .output()
+            .map_err(|e| format!("Failed to close {}: {}", process_name, e))?;
+
+        if !output.status.success() {
+            return Err(format!("Failed to close {}: {}", process_name, String::from_utf8_lossy(&output.stderr).trim()));
+        }
+        Ok(())
+    }
+
+    #[cfg(not(windows))]
+    {
+        // pkill treats its pattern as a regex; escape dots so only the literal name matches
+        let pattern = process_name.replace('.', "\\.");
+        let status = Command::new("pkill")
+            .args(["-x", &pattern])
+            .status()
+            .map_err(|e| format!("Failed to close {}: {}", process_name, e))?;
+
+        if !status.success() {
+            return Err(format!("No running process named {}", process_name));
+        }
+        Ok(())
+    }
+}
+
 /// Get list of installed applications from Start Menu (with caching)
 #[tauri::command]
 pub async fn get_installed_apps(filter_system: Option<bool>) -> Result<Vec<AppInfo>, String> {
@@ -302,18 +302,14 @@ pub fn run() {
                                                 
                                                 // Unmute in background
                                                 use crate::services::focus_service::get_focus_service;
-                                                let is_manually_muted = {
+                                                let (is_manually_muted, suppressor) = {
                                                     let focus_service = get_focus_service().await;
                                                     let service = focus_service.lock().await;
-                                                    service.is_notifications_muted().await
+                                                    (service.is_notifications_muted().await, service.suppressor())
                                                 };
                                                 if !is_manually_muted {
-                                                    #[cfg(windows)]
-                                                    {
-                                                        use crate::utils::notification_suppression::unmute_notifications_windows_api;
-                                                        if let Err(e) = unmute_notifications_windows_api() {
-                                                            eprintln!("Error unmuting notifications: {}", e);
-                                                        }
+                                                    if let Err(e) = suppressor.unmute() {
+                                                        eprintln!("Error unmuting notifications: {}", e);
                                                     }
                                                 }
                                             }
@@ -350,21 +346,21 @@ pub fn run() {
                                                 // Mute in background (async, non-blocking)
                                                 println!("[Shortcut] Starting mute notifications check...");
                                                 use crate::services::focus_service::get_focus_service;
-                                                let is_manually_muted = {
+                                                let (is_manually_muted, suppressor, allowlist) = {
                                                     let focus_service = get_focus_service().await;
                                                     let service = focus_service.lock().await;
-                                                    service.is_notifications_muted().await
+                                                    (
+                                                        service.is_notifications_muted().await,
+                                                        service.suppressor(),
+                                                        service.notification_allowlist_for(&[]).await,
+                                                    )
                                                 };
                                                 if !is_manually_muted {
-                                                    #[cfg(windows)]
-                                                    {
-                                                        println!("[Shortcut] Muting notifications...");
-                                                        use crate::utils::notification_suppression::mute_notifications_windows_api;
-                                                        if let Err(e) = mute_notifications_windows_api() {
-                                                            eprintln!("Error muting notifications: {}", e);
-                                                        } else {
-                                                            println!("[Shortcut] Notifications muted successfully");
-                                                        }
+                                                    println!("[Shortcut] Muting notifications...");
+                                                    if let Err(e) = suppressor.mute(&allowlist) {
+                                                        eprintln!("Error muting notifications: {}", e);
+                                                    } else {
+                                                        println!("[Shortcut] Notifications muted successfully");
                                                     }
                                                 }
                                                 println!("[Shortcut] show_overlay task completed successfully");
@@ -560,6 +556,20 @@ pub fn run() {
                 eprintln!("✗✗✗ ERROR: Main window not found during setup! ✗✗✗");
             }
 
+            // Let token refresh tell the UI when a calendar account needs signing in again
+            crate::services::token_refresh::set_app_handle(app.handle().clone());
+
+            // Set app handle for calendar service so sync can emit calendar-changed
+            use crate::services::calendar_service::get_calendar_service;
+            let app_handle_for_calendar = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                let calendar_service = get_calendar_service().await;
+                calendar_service.lock().await.set_app_handle(app_handle_for_calendar);
+            });
+
+            // Alert before meetings start, re-armed whenever a sync changes the calendar
+            tauri::async_runtime::spawn(crate::services::meeting_reminders::run(app.handle().clone()));
+
             // Set app handle for focus service so it can emit events
             use crate::services::focus_service::get_focus_service;
             let app_handle_for_focus = app.handle().clone();
@@ -614,7 +624,11 @@ pub fn run() {
             get_recent_files_by_type,
             refresh_recent_files,
             get_calendar_events,
+            get_calendar_cache_status,
             get_next_meeting,
+            get_join_info,
+            get_meeting_reminder_config,
+            set_meeting_reminder_config,
             time_until_next_meeting,
             refresh_calendar_events,
             get_google_auth_url,
@@ -627,8 +641,28 @@ pub fn run() {
             handle_oauth_callback,
             is_calendar_authenticated,
             start_google_oauth_flow,
+            list_calendars,
+            set_calendar_selection,
+            connect_caldav,
+            disconnect_caldav,
+            list_ics_feeds,
+            add_ics_feed,
+            remove_ics_feed,
             start_focus_mode,
             stop_focus_mode,
+            list_focus_presets,
+            create_focus_preset,
+            update_focus_preset,
+            delete_focus_preset,
+            pause_focus_mode,
+            resume_focus_mode,
+            start_pomodoro,
+            stop_pomodoro,
+            pause_pomodoro,
+            resume_pomodoro,
+            skip_pomodoro_phase,
+            reset_pomodoro,
+            get_pomodoro_state,
             get_current_focus_session,
             get_focus_remaining_seconds,
             is_focus_active,
@@ -639,6 +673,30 @@ pub fn run() {
             unmute_notifications,
             get_temporary_mute_remaining,
             is_notifications_muted,
+            get_focus_history,
+            get_focus_stats,
+            search_focus_history,
+            reflect_on_focus_session,
+            export_focus_history,
+            get_distraction_config,
+            set_distraction_config,
+            get_focus_plan,
+            add_focus_plan_to_calendar,
+            get_focus_planner_config,
+            set_focus_planner_config,
+            get_meeting_guard_config,
+            set_meeting_guard_config,
+            restore_all_notification_settings,
+            get_notification_allowlist,
+            set_notification_allowlist,
+            get_notification_digest,
+            get_event_bus_config,
+            set_event_bus_config,
+            get_idle_config,
+            set_idle_config,
+            list_quiet_hours_rules,
+            add_quiet_hours_rule,
+            remove_quiet_hours_rule,
             launch_app,
             launch_file,
             open_folder,
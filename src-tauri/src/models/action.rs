@@ -1,7 +1,8 @@
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
+use crate::utils::serde_defaults::default_true;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct CalendarEvent {
     pub id: String,
     pub title: String,
@@ -11,5 +12,98 @@ pub struct CalendarEvent {
     pub end_time: DateTime<Utc>,
     pub location: Option<String>,
     pub description: Option<String>,
+    #[serde(default)]
+    pub all_day: bool,
+    #[serde(default = "default_true")]
+    pub is_organizer: bool, // False for invitations from someone else; those can't be moved
+    #[serde(default)]
+    pub calendar_id: Option<String>, // Provider calendar the event came from
+    #[serde(default)]
+    pub calendar_name: Option<String>,
+    #[serde(default)]
+    pub calendar_color: Option<String>, // "#rrggbb", so the overlay can tell calendars apart
+    #[serde(default)]
+    pub stale: bool, // Served from a cache that hasn't been refreshed recently (e.g. offline)
+    #[serde(default)]
+    pub organizer: Option<EventPerson>,
+    #[serde(default)]
+    pub attendees: Vec<Attendee>, // Meeting rooms and other resources are left out
+    #[serde(default)]
+    pub my_response: Option<ResponseStatus>, // The signed-in user's RSVP; None when they weren't invited
+    #[serde(default)]
+    pub conference_url: Option<String>, // Meet/Teams/Zoom link the provider attached to the event
+    #[serde(default)]
+    pub time_zone: Option<String>, // Zone the event was created in, e.g. "Europe/Berlin"
+    #[serde(default)]
+    pub recurrence_id: Option<String>, // Id of the series this is an occurrence of
+    #[serde(default)]
+    pub visibility: Visibility,
+}
+
+// Same as deserializing an event that only has the required fields
+impl Default for CalendarEvent {
+    fn default() -> Self {
+        Self {
+            id: String::new(),
+            title: String::new(),
+            start_time: DateTime::default(),
+            end_time: DateTime::default(),
+            location: None,
+            description: None,
+            all_day: false,
+            is_organizer: default_true(),
+            calendar_id: None,
+            calendar_name: None,
+            calendar_color: None,
+            stale: false,
+            organizer: None,
+            attendees: Vec::new(),
+            my_response: None,
+            conference_url: None,
+            time_zone: None,
+            recurrence_id: None,
+            visibility: Visibility::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EventPerson {
+    pub email: String,
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Attendee {
+    pub email: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub response: ResponseStatus,
+    #[serde(default)]
+    pub optional: bool,
+    #[serde(default)]
+    pub is_self: bool, // The signed-in user
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResponseStatus {
+    #[default]
+    NeedsAction,
+    Accepted,
+    Tentative,
+    Declined,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Visibility {
+    #[default]
+    Default, // Whatever the calendar's default is
+    Public,
+    Private,
+    Confidential,
 }
 
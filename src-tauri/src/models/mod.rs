@@ -3,3 +3,5 @@ pub mod file;
 pub mod action;
 pub mod gmail;
 
+#[cfg(test)]
+pub mod test_support;
@@ -0,0 +1,28 @@
+// Calendar test helpers
+// One place to build times and events for the calendar, focus and reminder tests.
+
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use crate::models::action::CalendarEvent;
+
+/// Parse a UTC time, either RFC 3339 ("2024-06-14T09:00:00Z") or a time of day ("10:30", "09:59:30")
+/// on Wednesday 2024-05-15
+pub fn utc(s: &str) -> DateTime<Utc> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(s) {
+        return time.with_timezone(&Utc);
+    }
+    let time = NaiveTime::parse_from_str(s, "%H:%M:%S")
+        .or_else(|_| NaiveTime::parse_from_str(s, "%H:%M"))
+        .unwrap_or_else(|_| panic!("not a test time: {}", s));
+    NaiveDate::from_ymd_opt(2024, 5, 15).unwrap().and_time(time).and_utc()
+}
+
+/// An event with `id` as both id and title, running from `start` to `end` (see `utc`)
+pub fn event(id: &str, start: &str, end: &str) -> CalendarEvent {
+    CalendarEvent {
+        id: id.to_string(),
+        title: id.to_string(),
+        start_time: utc(start),
+        end_time: utc(end),
+        ..Default::default()
+    }
+}
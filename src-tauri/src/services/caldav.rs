@@ -0,0 +1,415 @@
+// CalDAV calendars (Nextcloud, Fastmail, iCloud, Radicale, ...)
+// Signs in with a username and (app) password kept in the OS keyring, discovers the user's
+// calendars through the standard principal -> calendar-home-set lookup, and fetches events
+// with a calendar-query REPORT. The .ics objects that come back are expanded by `ics`.
+
+use chrono::{DateTime, Utc};
+use reqwest::{Client, Method, StatusCode};
+use roxmltree::Node;
+use serde::{Deserialize, Serialize};
+use url::Url;
+use crate::models::action::CalendarEvent;
+use crate::services::calendar_selection::{self, CalendarInfo, CalendarSelection};
+use crate::services::calendar_service::CalendarProvider;
+use crate::services::ics;
+
+const KEYRING_SERVICE: &str = "pause-menu-caldav-calendar";
+const KEYRING_USER: &str = "account";
+
+const DAV: &str = "DAV:";
+const CALDAV: &str = "urn:ietf:params:xml:ns:caldav";
+const APPLE_ICAL: &str = "http://apple.com/ns/ical/";
+
+const PRINCIPAL_QUERY: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<d:propfind xmlns:d="DAV:"><d:prop><d:current-user-principal/></d:prop></d:propfind>"#;
+
+const HOME_SET_QUERY: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<d:propfind xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav"><d:prop><c:calendar-home-set/></d:prop></d:propfind>"#;
+
+const CALENDARS_QUERY: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<d:propfind xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav" xmlns:a="http://apple.com/ns/ical/">
+  <d:prop><d:resourcetype/><d:displayname/><a:calendar-color/><c:supported-calendar-component-set/></d:prop>
+</d:propfind>"#;
+
+// Server address and credentials, stored as one JSON blob in the keyring
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CalDavAccount {
+    pub server_url: String, // e.g. https://cloud.example.com/remote.php/dav
+    pub username: String,
+    pub password: String,   // Preferably an app password
+}
+
+impl CalDavAccount {
+    pub fn validate(&self) -> Result<(), String> {
+        let url = Url::parse(&self.server_url).map_err(|e| format!("Invalid CalDAV server URL: {}", e))?;
+        if url.scheme() != "https" && url.scheme() != "http" {
+            return Err("CalDAV server URL must start with http:// or https://".to_string());
+        }
+        if self.username.trim().is_empty() {
+            return Err("CalDAV username cannot be empty".to_string());
+        }
+        Ok(())
+    }
+
+    /// Save to the OS keyring
+    pub fn store(&self) -> Result<(), String> {
+        let json = serde_json::to_string(self).map_err(|e| format!("Failed to serialize CalDAV account: {}", e))?;
+        keyring::Entry::new(KEYRING_SERVICE, KEYRING_USER)
+            .and_then(|entry| entry.set_password(&json))
+            .map_err(|e| format!("Failed to store CalDAV account: {}", e))
+    }
+
+    /// The account saved in the OS keyring, if any
+    pub fn from_keyring() -> Result<Self, String> {
+        let json = keyring::Entry::new(KEYRING_SERVICE, KEYRING_USER)
+            .and_then(|entry| entry.get_password())
+            .map_err(|e| format!("No CalDAV account connected: {}", e))?;
+        serde_json::from_str(&json).map_err(|e| format!("Failed to deserialize CalDAV account: {}", e))
+    }
+
+    pub fn forget() -> Result<(), String> {
+        match keyring::Entry::new(KEYRING_SERVICE, KEYRING_USER).and_then(|entry| entry.delete_password()) {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(e) => Err(format!("Failed to remove CalDAV account: {}", e)),
+        }
+    }
+
+    fn base_url(&self) -> Result<Url, String> {
+        Url::parse(&self.server_url).map_err(|e| format!("Invalid CalDAV server URL: {}", e))
+    }
+}
+
+/// Calendars on the server that can hold events
+pub async fn discover_calendars(http_client: &Client, account: &CalDavAccount) -> Result<Vec<CalendarInfo>, String> {
+    let base = account.base_url()?;
+
+    let principal = dav_request(http_client, account, "PROPFIND", &base, "0", PRINCIPAL_QUERY)
+        .await?
+        .into_iter()
+        .find_map(|r| r.principal);
+    let home = match principal {
+        Some(ref href) => {
+            let principal_url = join(&base, href)?;
+            dav_request(http_client, account, "PROPFIND", &principal_url, "0", HOME_SET_QUERY)
+                .await?
+                .into_iter()
+                .find_map(|r| r.calendar_home)
+        }
+        None => None,
+    };
+    // Servers without principal support are usually pointed straight at the calendar home
+    let home_url = match home {
+        Some(ref href) => join(&base, href)?,
+        None => base.clone(),
+    };
+
+    let calendars: Vec<CalendarInfo> = dav_request(http_client, account, "PROPFIND", &home_url, "1", CALENDARS_QUERY)
+        .await?
+        .into_iter()
+        .filter(|r| r.is_calendar && r.supports_events)
+        .filter_map(|r| {
+            let url = join(&base, &r.href).ok()?;
+            let fallback_name = url.path_segments()?.rev().find(|s| !s.is_empty())?.to_string();
+            Some(CalendarInfo {
+                provider: CalendarProvider::CalDav,
+                id: url.to_string(),
+                name: r.display_name.filter(|n| !n.trim().is_empty()).unwrap_or(fallback_name),
+                // Apple appends an alpha channel ("#FF2968FF")
+                color: r.color.map(|c| c.chars().take(7).collect()),
+                primary: false,
+                selected: false,
+            })
+        })
+        .collect();
+
+    println!("[CalDav] Found {} calendars at {}", calendars.len(), home_url);
+    Ok(calendars)
+}
+
+/// Occurrences overlapping `from..to` in one calendar
+pub async fn fetch_events(
+    http_client: &Client,
+    account: &CalDavAccount,
+    calendar: &CalendarInfo,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<Vec<ics::IcsEvent>, String> {
+    let url = Url::parse(&calendar.id).map_err(|e| format!("Invalid calendar URL {}: {}", calendar.id, e))?;
+    let query = format!(
+        r#"<?xml version="1.0" encoding="utf-8"?>
+<c:calendar-query xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav">
+  <d:prop><d:getetag/><c:calendar-data/></d:prop>
+  <c:filter><c:comp-filter name="VCALENDAR"><c:comp-filter name="VEVENT">
+    <c:time-range start="{}" end="{}"/>
+  </c:comp-filter></c:comp-filter></c:filter>
+</c:calendar-query>"#,
+        from.format("%Y%m%dT%H%M%SZ"),
+        to.format("%Y%m%dT%H%M%SZ")
+    );
+
+    let mut events = Vec::new();
+    for response in dav_request(http_client, account, "REPORT", &url, "1", &query).await? {
+        let Some(data) = response.calendar_data else { continue };
+        match ics::parse_events(&data, from, to) {
+            Ok(parsed) => events.extend(parsed),
+            Err(e) => eprintln!("[CalDav] Skipping {}: {}", response.href, e),
+        }
+    }
+    events.sort_by_key(|e| e.start);
+    Ok(events)
+}
+
+/// Events from every selected calendar on the server
+pub async fn fetch_selected_events(
+    http_client: &Client,
+    account: &CalDavAccount,
+    selection: &CalendarSelection,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<Vec<CalendarEvent>, String> {
+    let calendars = discover_calendars(http_client, account).await?;
+    let mut events = Vec::new();
+    let mut fetched_any = false;
+    let mut last_error = None;
+    for calendar in calendars.iter().filter(|c| selection.includes(c)) {
+        match fetch_events(http_client, account, calendar, from, to).await {
+            Ok(fetched) => {
+                // We can't write changes back to CalDAV yet, so these are never offered for rescheduling
+                events.extend(fetched.iter().map(|e| e.to_calendar_event("caldav", calendar, false)));
+                fetched_any = true;
+            }
+            Err(e) => {
+                eprintln!("[CalDav] ✗ Failed to fetch calendar '{}': {}", calendar.name, e);
+                last_error = Some(e);
+            }
+        }
+    }
+    // If no calendar answered, the cached events are kept rather than synced away
+    match last_error {
+        Some(e) if !fetched_any => Err(e),
+        _ => Ok(calendar_selection::merge_events(events)),
+    }
+}
+
+fn join(base: &Url, href: &str) -> Result<Url, String> {
+    base.join(href).map_err(|e| format!("Invalid href {}: {}", href, e))
+}
+
+async fn dav_request(
+    http_client: &Client,
+    account: &CalDavAccount,
+    method: &str,
+    url: &Url,
+    depth: &str,
+    body: &str,
+) -> Result<Vec<DavResponse>, String> {
+    let method = Method::from_bytes(method.as_bytes()).map_err(|e| e.to_string())?;
+    let response = http_client
+        .request(method.clone(), url.clone())
+        .basic_auth(&account.username, Some(&account.password))
+        .header("Depth", depth)
+        .header("Content-Type", "application/xml; charset=utf-8")
+        .body(body.to_string())
+        .send()
+        .await
+        .map_err(|e| format!("CalDAV {} {} failed: {}", method, url, e))?;
+
+    match response.status() {
+        StatusCode::MULTI_STATUS => {}
+        StatusCode::UNAUTHORIZED => return Err("CalDAV server rejected the username or password".to_string()),
+        status => return Err(format!("CalDAV {} {} returned {}", method, url, status)),
+    }
+    let xml = response.text().await.map_err(|e| format!("Failed to read CalDAV response: {}", e))?;
+    parse_multistatus(&xml)
+}
+
+// The properties we care about from one <d:response>
+#[derive(Debug, Default)]
+struct DavResponse {
+    href: String,
+    principal: Option<String>,
+    calendar_home: Option<String>,
+    is_calendar: bool,
+    supports_events: bool,
+    display_name: Option<String>,
+    color: Option<String>,
+    calendar_data: Option<String>,
+}
+
+fn parse_multistatus(xml: &str) -> Result<Vec<DavResponse>, String> {
+    let doc = roxmltree::Document::parse(xml).map_err(|e| format!("Invalid CalDAV response: {}", e))?;
+
+    let mut responses = Vec::new();
+    for response in doc.descendants().filter(|n| is(n, DAV, "response")) {
+        let Some(href) = child(response, DAV, "href").and_then(text) else { continue };
+        // Calendars that don't list their component types accept events
+        let mut parsed = DavResponse { href, supports_events: true, ..Default::default() };
+
+        for propstat in response.children().filter(|n| is(n, DAV, "propstat")) {
+            let found = child(propstat, DAV, "status").and_then(text).is_none_or(|s| s.contains(" 200"));
+            let Some(prop) = child(propstat, DAV, "prop").filter(|_| found) else { continue };
+            for property in prop.children().filter(|n| n.is_element()) {
+                let nested_href = || child(property, DAV, "href").and_then(text);
+                match (property.tag_name().namespace(), property.tag_name().name()) {
+                    (Some(DAV), "current-user-principal") => parsed.principal = nested_href(),
+                    (Some(CALDAV), "calendar-home-set") => parsed.calendar_home = nested_href(),
+                    (Some(DAV), "resourcetype") => parsed.is_calendar = child(property, CALDAV, "calendar").is_some(),
+                    (Some(DAV), "displayname") => parsed.display_name = text(property),
+                    (Some(APPLE_ICAL), "calendar-color") => parsed.color = text(property),
+                    (Some(CALDAV), "supported-calendar-component-set") => {
+                        parsed.supports_events = property
+                            .children()
+                            .any(|c| is(&c, CALDAV, "comp") && c.attribute("name") == Some("VEVENT"));
+                    }
+                    (Some(CALDAV), "calendar-data") => parsed.calendar_data = property.text().map(String::from),
+                    _ => {}
+                }
+            }
+        }
+        responses.push(parsed);
+    }
+    Ok(responses)
+}
+
+fn is(node: &Node, ns: &str, name: &str) -> bool {
+    node.is_element() && node.tag_name().namespace() == Some(ns) && node.tag_name().name() == name
+}
+
+fn child<'a, 'input>(node: Node<'a, 'input>, ns: &str, name: &str) -> Option<Node<'a, 'input>> {
+    node.children().find(|c| is(c, ns, name))
+}
+
+fn text(node: Node) -> Option<String> {
+    node.text().map(|t| t.trim().to_string()).filter(|t| !t.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_support::utc;
+    use crate::utils::http_stub::HttpStub;
+    use base64::Engine;
+
+    fn multistatus(responses: &str) -> String {
+        format!(
+            r#"<?xml version="1.0"?><d:multistatus xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav" xmlns:a="http://apple.com/ns/ical/">{}</d:multistatus>"#,
+            responses
+        )
+    }
+
+    fn calendar_response(href: &str, name: &str, extra: &str) -> String {
+        format!(
+            r#"<d:response><d:href>{}</d:href><d:propstat><d:prop>
+<d:resourcetype><d:collection/><c:calendar/></d:resourcetype><d:displayname>{}</d:displayname>{}
+</d:prop><d:status>HTTP/1.1 200 OK</d:status></d:propstat></d:response>"#,
+            href, name, extra
+        )
+    }
+
+    // A Nextcloud-shaped server for alice with a Work calendar, a task list and a Personal calendar
+    // REPORTs on the calendars in `failing` answer with a server error
+    async fn stand_in(failing: &'static [&'static str]) -> HttpStub {
+        let expected_auth = format!("Basic {}", base64::engine::general_purpose::STANDARD.encode("alice:app-password"));
+        HttpStub::start(move |req| {
+            if req.authorization.as_deref() != Some(expected_auth.as_str()) {
+                return (401, String::new());
+            }
+            if req.method == "REPORT" && failing.contains(&req.path.as_str()) {
+                return (500, String::new());
+            }
+            let body = match (req.method.as_str(), req.path.as_str()) {
+                ("PROPFIND", "/dav/") => multistatus(
+                    r#"<d:response><d:href>/dav/</d:href><d:propstat><d:prop><d:current-user-principal><d:href>/dav/principals/alice/</d:href></d:current-user-principal></d:prop><d:status>HTTP/1.1 200 OK</d:status></d:propstat></d:response>"#,
+                ),
+                ("PROPFIND", "/dav/principals/alice/") => multistatus(
+                    r#"<d:response><d:href>/dav/principals/alice/</d:href><d:propstat><d:prop><c:calendar-home-set><d:href>/dav/calendars/alice/</d:href></c:calendar-home-set></d:prop><d:status>HTTP/1.1 200 OK</d:status></d:propstat></d:response>"#,
+                ),
+                ("PROPFIND", "/dav/calendars/alice/") => multistatus(&format!(
+                    r#"<d:response><d:href>/dav/calendars/alice/</d:href><d:propstat><d:prop><d:resourcetype><d:collection/></d:resourcetype></d:prop><d:status>HTTP/1.1 200 OK</d:status></d:propstat></d:response>{}{}{}"#,
+                    calendar_response(
+                        "/dav/calendars/alice/work/",
+                        "Work",
+                        r#"<a:calendar-color>#FF2968FF</a:calendar-color><c:supported-calendar-component-set><c:comp name="VEVENT"/></c:supported-calendar-component-set>"#
+                    ),
+                    calendar_response(
+                        "/dav/calendars/alice/tasks/",
+                        "Tasks",
+                        r#"<c:supported-calendar-component-set><c:comp name="VTODO"/></c:supported-calendar-component-set>"#
+                    ),
+                    calendar_response("/dav/calendars/alice/personal/", "", ""),
+                )),
+                ("REPORT", "/dav/calendars/alice/work/") if req.depth.as_deref() == Some("1") => multistatus(
+                    r#"<d:response><d:href>/dav/calendars/alice/work/planning.ics</d:href><d:propstat><d:prop><d:getetag>"1"</d:getetag><c:calendar-data>BEGIN:VCALENDAR
+VERSION:2.0
+BEGIN:VEVENT
+UID:planning
+DTSTART:20240614T130000Z
+DTEND:20240614T140000Z
+SUMMARY:Sprint planning
+ORGANIZER:mailto:alice@example.com
+END:VEVENT
+END:VCALENDAR
+</c:calendar-data></d:prop><d:status>HTTP/1.1 200 OK</d:status></d:propstat></d:response>"#,
+                ),
+                ("REPORT", _) => multistatus(""),
+                _ => return (404, String::new()),
+            };
+            (207, body)
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_discovers_calendars_and_fetches_events() {
+        let server = stand_in(&[]).await;
+        let account = CalDavAccount {
+            server_url: format!("{}/dav/", server.base_url),
+            username: "alice".to_string(),
+            password: "app-password".to_string(),
+        };
+        let client = Client::new();
+
+        let calendars = discover_calendars(&client, &account).await.unwrap();
+        let names: Vec<(&str, Option<&str>)> = calendars.iter().map(|c| (c.name.as_str(), c.color.as_deref())).collect();
+        // The task list is left out; the unnamed calendar is named after its path
+        assert_eq!(names, vec![("Work", Some("#FF2968")), ("personal", None)]);
+        assert_eq!(calendars[0].id, format!("{}/dav/calendars/alice/work/", server.base_url));
+
+        // Without a saved choice every CalDAV calendar is included
+        let events = fetch_selected_events(&client, &account, &CalendarSelection::default(), utc("2024-06-14T00:00:00Z"), utc("2024-06-21T00:00:00Z"))
+            .await
+            .unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].id, ics::series_id("caldav", &calendars[0], "planning"));
+        assert_eq!(events[0].title, "Sprint planning");
+        assert_eq!(events[0].calendar_name.as_deref(), Some("Work"));
+        assert_eq!(events[0].calendar_color.as_deref(), Some("#FF2968"));
+
+        let report = server.requests().into_iter().find(|r| r.method == "REPORT").unwrap();
+        assert!(report.body.contains(r#"start="20240614T000000Z" end="20240621T000000Z""#));
+
+        let wrong = CalDavAccount { password: "nope".to_string(), ..account };
+        let error = discover_calendars(&client, &wrong).await.unwrap_err();
+        assert!(error.contains("rejected"), "{}", error);
+    }
+
+    #[tokio::test]
+    async fn test_failing_reports_are_errors_only_when_nothing_was_fetched() {
+        let (from, to) = (utc("2024-06-14T00:00:00Z"), utc("2024-06-21T00:00:00Z"));
+        let client = Client::new();
+        let account_for = |server: &HttpStub| CalDavAccount {
+            server_url: format!("{}/dav/", server.base_url),
+            username: "alice".to_string(),
+            password: "app-password".to_string(),
+        };
+
+        // One calendar down: the others still sync
+        let server = stand_in(&["/dav/calendars/alice/personal/"]).await;
+        let events = fetch_selected_events(&client, &account_for(&server), &CalendarSelection::default(), from, to).await.unwrap();
+        assert_eq!(events.len(), 1);
+
+        // All of them down: an error, so the cached CalDAV events are kept
+        let server = stand_in(&["/dav/calendars/alice/work/", "/dav/calendars/alice/personal/"]).await;
+        assert!(fetch_selected_events(&client, &account_for(&server), &CalendarSelection::default(), from, to).await.is_err());
+    }
+}
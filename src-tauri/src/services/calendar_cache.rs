@@ -0,0 +1,98 @@
+// Calendar cache on disk
+// The merged events and when they were last fetched, saved after every sync so the overlay has an
+// agenda straight after launch and keeps one while offline.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use crate::models::action::CalendarEvent;
+use crate::services::calendar_sync;
+use crate::utils::app_data;
+
+const CALENDAR_CACHE_FILE: &str = "calendar_cache.json";
+/// Events fetched longer ago than this are marked stale (three missed polls)
+pub const STALE_AFTER_MINUTES: i64 = 15;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CalendarCache {
+    pub events: Vec<CalendarEvent>,
+    #[serde(default)]
+    pub last_fetch: Option<DateTime<Utc>>,
+}
+
+impl CalendarCache {
+    /// Load the cache, dropping events that ended while the app was closed
+    pub fn load(data_dir: &Path, now: DateTime<Utc>) -> Result<Self, String> {
+        let cache: Self = app_data::read_json(&cache_path(data_dir))?.unwrap_or_default();
+        Ok(Self {
+            events: calendar_sync::in_cache_window(cache.events, now),
+            last_fetch: cache.last_fetch,
+        })
+    }
+
+    pub fn save(&self, data_dir: &Path) -> Result<(), String> {
+        app_data::write_json(&cache_path(data_dir), self)
+    }
+}
+
+fn cache_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(CALENDAR_CACHE_FILE)
+}
+
+/// Never fetched, or not for STALE_AFTER_MINUTES
+pub fn is_stale(last_fetch: Option<DateTime<Utc>>, now: DateTime<Utc>) -> bool {
+    last_fetch.is_none_or(|fetched| now - fetched > Duration::minutes(STALE_AFTER_MINUTES))
+}
+
+// For the overlay's "last updated at…" line
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CalendarCacheStatus {
+    #[serde(with = "chrono::serde::ts_seconds_option")]
+    pub last_fetch: Option<DateTime<Utc>>,
+    pub stale: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::action::Visibility;
+    use crate::models::test_support::{event, utc};
+
+    #[test]
+    fn test_cache_survives_a_restart_and_goes_stale() {
+        let dir = std::env::temp_dir().join(format!("pause-menu-test-calendar-cache-{}", std::process::id()));
+        let fetched = utc("2024-06-14T08:00:00Z");
+        let empty = CalendarCache::load(&dir, fetched).unwrap();
+        assert_eq!(empty, CalendarCache::default());
+        assert!(is_stale(empty.last_fetch, fetched));
+
+        CalendarCache {
+            events: vec![event("google_standup", "2024-06-14T09:00:00Z", "2024-06-14T09:30:00Z"), event("google_review", "2024-06-14T15:00:00Z", "2024-06-14T15:30:00Z")],
+            last_fetch: Some(fetched),
+        }
+        .save(&dir)
+        .unwrap();
+
+        // Relaunched at noon: the standup is over, the review is still to come
+        let noon = utc("2024-06-14T12:00:00Z");
+        let cache = CalendarCache::load(&dir, noon).unwrap();
+        assert_eq!(cache.events.iter().map(|e| e.id.as_str()).collect::<Vec<_>>(), vec!["google_review"]);
+        assert_eq!(cache.last_fetch, Some(fetched));
+        assert!(is_stale(cache.last_fetch, noon));
+        assert!(!is_stale(cache.last_fetch, fetched + Duration::minutes(STALE_AFTER_MINUTES)));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_cache_written_before_the_richer_event_model_still_loads() {
+        let cache: CalendarCache = serde_json::from_str(
+            r#"{"events":[{"id":"google_1","title":"Standup","start_time":1718355600,"end_time":1718357400,"location":null,"description":null}],"last_fetch":"2024-06-14T08:00:00Z"}"#,
+        )
+        .unwrap();
+        let event = &cache.events[0];
+        assert!(event.is_organizer && !event.all_day && !event.stale);
+        assert!(event.attendees.is_empty() && event.organizer.is_none() && event.conference_url.is_none());
+        assert_eq!(event.visibility, Visibility::Default);
+    }
+}
@@ -0,0 +1,148 @@
+// Calendar selection
+// Which of each provider's calendars (primary, shared team calendars, ...) feed the event cache.
+// Providers without a saved selection only contribute their primary calendar, or every
+// calendar when the provider has no notion of one (CalDAV, .ics subscriptions).
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use crate::models::action::CalendarEvent;
+use crate::services::calendar_service::CalendarProvider;
+use crate::utils::app_data;
+
+const CALENDAR_SELECTION_FILE: &str = "calendar_selection.json";
+
+// A calendar the signed-in account can see
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CalendarInfo {
+    pub provider: CalendarProvider,
+    pub id: String,
+    pub name: String,
+    pub color: Option<String>, // "#rrggbb" as set in the provider's UI
+    pub primary: bool,
+    #[serde(default)]
+    pub selected: bool, // Whether its events are included (filled in when listing)
+}
+
+impl CalendarInfo {
+    /// Stand-in for the primary calendar when the calendar list can't be fetched
+    pub fn fallback_primary(provider: CalendarProvider) -> Self {
+        Self {
+            provider,
+            id: String::new(),
+            name: "Calendar".to_string(),
+            color: None,
+            primary: true,
+            selected: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CalendarSelection {
+    pub calendars: BTreeMap<CalendarProvider, Vec<String>>, // Included calendar ids per provider
+}
+
+impl CalendarSelection {
+    pub fn includes(&self, calendar: &CalendarInfo) -> bool {
+        match self.calendars.get(&calendar.provider) {
+            Some(ids) => ids.contains(&calendar.id),
+            None => calendar.primary || !calendar.provider.has_primary_calendar(),
+        }
+    }
+
+    /// Replace the calendars included for `provider`
+    pub fn set(&mut self, provider: CalendarProvider, calendar_ids: Vec<String>) {
+        let mut ids: Vec<String> = Vec::new();
+        for id in calendar_ids {
+            if !id.trim().is_empty() && !ids.contains(&id) {
+                ids.push(id);
+            }
+        }
+        self.calendars.insert(provider, ids);
+    }
+
+    pub fn load(data_dir: &Path) -> Result<Self, String> {
+        Ok(app_data::read_json(&config_path(data_dir))?.unwrap_or_default())
+    }
+
+    pub fn save(&self, data_dir: &Path) -> Result<(), String> {
+        app_data::write_json(&config_path(data_dir), self)
+    }
+}
+
+fn config_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(CALENDAR_SELECTION_FILE)
+}
+
+/// Combine events fetched from several calendars, soonest first
+/// An event that shows up in more than one calendar (e.g. an invite on a shared calendar) is kept once
+pub fn merge_events(events: Vec<CalendarEvent>) -> Vec<CalendarEvent> {
+    let mut merged: Vec<CalendarEvent> = Vec::with_capacity(events.len());
+    for event in events {
+        if !merged.iter().any(|e| e.id == event.id) {
+            merged.push(event);
+        }
+    }
+    merged.sort_by_key(|e| e.start_time);
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_support::{self, utc};
+
+    fn calendar(provider: CalendarProvider, id: &str, primary: bool) -> CalendarInfo {
+        CalendarInfo {
+            provider,
+            id: id.to_string(),
+            name: id.to_string(),
+            color: None,
+            primary,
+            selected: false,
+        }
+    }
+
+    // A half-hour event on `calendar_id`
+    fn event(id: &str, calendar_id: &str, start: &str) -> CalendarEvent {
+        CalendarEvent {
+            end_time: utc(start) + chrono::Duration::minutes(30),
+            calendar_id: Some(calendar_id.to_string()),
+            ..test_support::event(id, start, start)
+        }
+    }
+
+    #[test]
+    fn test_selection_defaults_to_primary_calendars() {
+        let mut selection = CalendarSelection::default();
+        let primary = calendar(CalendarProvider::Google, "me@example.com", true);
+        let team = calendar(CalendarProvider::Google, "team@group.calendar.google.com", false);
+        let outlook = calendar(CalendarProvider::Microsoft, "AAMk", true);
+        let nextcloud = calendar(CalendarProvider::CalDav, "https://cloud.example.com/dav/calendars/me/work/", false);
+        assert!(selection.includes(&primary));
+        assert!(!selection.includes(&team));
+        assert!(selection.includes(&nextcloud));
+
+        selection.set(CalendarProvider::Google, vec![team.id.clone(), team.id.clone()]);
+        assert!(!selection.includes(&primary));
+        assert!(selection.includes(&team));
+        assert!(selection.includes(&outlook));
+        assert_eq!(selection.calendars[&CalendarProvider::Google].len(), 1);
+
+        let json = serde_json::to_string(&selection).unwrap();
+        assert!(json.contains("\"google\""));
+        assert_eq!(serde_json::from_str::<CalendarSelection>(&json).unwrap(), selection);
+    }
+
+    #[test]
+    fn test_merge_keeps_shared_events_once() {
+        let merged = merge_events(vec![
+            event("google_standup", "me", "2024-06-14T09:00:00Z"),
+            event("google_review", "team", "2024-06-14T08:00:00Z"),
+            event("google_standup", "team", "2024-06-14T09:00:00Z"),
+        ]);
+        let ids: Vec<(&str, Option<&str>)> = merged.iter().map(|e| (e.id.as_str(), e.calendar_id.as_deref())).collect();
+        assert_eq!(ids, vec![("google_review", Some("team")), ("google_standup", Some("me"))]);
+    }
+}
@@ -1,5 +1,16 @@
-use crate::models::action::CalendarEvent;
-use chrono::{DateTime, Utc, Duration, NaiveDate};
+use crate::models::action::{Attendee, CalendarEvent, EventPerson, ResponseStatus, Visibility};
+use crate::services::caldav::{self, CalDavAccount};
+use crate::services::calendar_cache::{self, CalendarCache, CalendarCacheStatus};
+use crate::services::calendar_selection::{self, CalendarInfo, CalendarSelection};
+use crate::services::calendar_sync::{self, CalendarDiff, CalendarSyncState, EventChange, SyncBatch, CACHE_WINDOW_DAYS, SYNC_HORIZON_DAYS};
+use crate::services::ics;
+use crate::services::ics_feeds::{self, IcsFeed, IcsFeedsConfig};
+use crate::services::meeting_links::{self, JoinInfo};
+use crate::services::meeting_reminders;
+use crate::services::token_refresh;
+use crate::utils::app_data;
+use crate::utils::serde_defaults::default_true;
+use chrono::{DateTime, Utc, Duration, NaiveDate, NaiveDateTime};
 use oauth2::{
     basic::BasicClient, reqwest::async_http_client, AuthUrl, AuthorizationCode, ClientId,
     ClientSecret, RedirectUrl, Scope, TokenResponse, TokenUrl,
@@ -7,9 +18,11 @@ use oauth2::{
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tokio::time::{interval, Duration as TokioDuration};
+use tauri::{AppHandle, Emitter};
 
 // OAuth token storage
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -20,16 +33,54 @@ pub struct TokenData {
 }
 
 // Calendar provider type
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum CalendarProvider {
     Google,
     Microsoft,
+    CalDav, // Any CalDAV server, signed in with a username and app password
+    #[serde(rename = "ics")]
+    IcsUrl, // Read-only .ics subscriptions
+}
+
+impl CalendarProvider {
+    /// CalDAV servers and feed lists have no primary calendar, so all of their calendars count until the user picks
+    pub fn has_primary_calendar(self) -> bool {
+        matches!(self, CalendarProvider::Google | CalendarProvider::Microsoft)
+    }
 }
 
 // Google Calendar API response structures
 #[derive(Debug, Deserialize)]
 struct GoogleCalendarResponse {
+    #[serde(default)]
     items: Vec<GoogleEvent>,
+    #[serde(rename = "nextPageToken", default)]
+    next_page_token: Option<String>,
+    #[serde(rename = "nextSyncToken", default)]
+    next_sync_token: Option<String>, // Only on the last page
+}
+
+// Google calendarList response
+#[derive(Debug, Deserialize)]
+struct GoogleCalendarListResponse {
+    #[serde(default)]
+    items: Vec<GoogleCalendarListEntry>,
+    #[serde(rename = "nextPageToken", default)]
+    next_page_token: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GoogleCalendarListEntry {
+    id: String,
+    #[serde(default)]
+    summary: Option<String>,
+    #[serde(rename = "summaryOverride", default)]
+    summary_override: Option<String>, // The user's own name for a shared calendar
+    #[serde(rename = "backgroundColor", default)]
+    background_color: Option<String>,
+    #[serde(default)]
+    primary: bool,
 }
 
 #[derive(Debug, Deserialize)]
@@ -37,18 +88,76 @@ struct GoogleEvent {
     id: String,
     #[serde(default)]
     summary: Option<String>, // Optional because events can have no title
-    start: GoogleDateTime,
+    #[serde(default)]
+    status: Option<String>, // "cancelled" in an incremental sync means the event was deleted
+    #[serde(default)]
+    start: GoogleDateTime, // Cancelled events come without start and end
+    #[serde(default)]
     end: GoogleDateTime,
     location: Option<String>,
     description: Option<String>,
+    #[serde(default)]
+    organizer: Option<GoogleOrganizer>,
+    #[serde(default)]
+    attendees: Vec<GoogleAttendee>,
+    #[serde(rename = "hangoutLink", default)]
+    hangout_link: Option<String>, // Meet link
+    #[serde(rename = "conferenceData", default)]
+    conference_data: Option<GoogleConferenceData>, // Meet, or a Zoom/Teams add-on
+    #[serde(rename = "recurringEventId", default)]
+    recurring_event_id: Option<String>,
+    #[serde(default)]
+    visibility: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GoogleOrganizer {
+    #[serde(rename = "self", default)]
+    is_self: bool, // True when the signed-in user organizes the event
+    #[serde(default)]
+    email: Option<String>,
+    #[serde(rename = "displayName", default)]
+    display_name: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
+struct GoogleAttendee {
+    #[serde(default)]
+    email: Option<String>,
+    #[serde(rename = "displayName", default)]
+    display_name: Option<String>,
+    #[serde(rename = "responseStatus", default)]
+    response_status: Option<String>, // needsAction, declined, tentative or accepted
+    #[serde(default)]
+    optional: bool,
+    #[serde(rename = "self", default)]
+    is_self: bool,
+    #[serde(default)]
+    resource: bool, // Meeting rooms
+}
+
+#[derive(Debug, Deserialize)]
+struct GoogleConferenceData {
+    #[serde(rename = "entryPoints", default)]
+    entry_points: Vec<GoogleEntryPoint>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GoogleEntryPoint {
+    #[serde(rename = "entryPointType", default)]
+    entry_point_type: String, // video, phone, sip or more
+    #[serde(default)]
+    uri: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
 struct GoogleDateTime {
     #[serde(rename = "dateTime", default, deserialize_with = "deserialize_optional_rfc3339")]
     date_time: Option<DateTime<Utc>>,
     #[serde(default)]
     date: Option<String>, // For all-day events
+    #[serde(rename = "timeZone", default)]
+    time_zone: Option<String>,
 }
 
 fn deserialize_optional_rfc3339<'de, D>(deserializer: D) -> Result<Option<DateTime<Utc>>, D::Error>
@@ -68,50 +177,157 @@ where
     }
 }
 
+fn google_response_status(status: Option<&str>) -> ResponseStatus {
+    match status {
+        Some("accepted") => ResponseStatus::Accepted,
+        Some("tentative") => ResponseStatus::Tentative,
+        Some("declined") => ResponseStatus::Declined,
+        _ => ResponseStatus::NeedsAction,
+    }
+}
+
 // Microsoft Graph API response structures
+// One page of calendarView/delta; items are parsed one by one since removed ones only carry an id
+#[derive(Debug, Deserialize)]
+struct MicrosoftDeltaResponse {
+    #[serde(default)]
+    value: Vec<serde_json::Value>,
+    #[serde(rename = "@odata.nextLink", default)]
+    next_link: Option<String>,
+    #[serde(rename = "@odata.deltaLink", default)]
+    delta_link: Option<String>, // Only on the last page
+}
+
+// Microsoft Graph /me/calendars response
+#[derive(Debug, Deserialize)]
+struct MicrosoftCalendarListResponse {
+    value: Vec<MicrosoftCalendar>,
+    #[serde(rename = "@odata.nextLink", default)]
+    next_link: Option<String>,
+}
+
 #[derive(Debug, Deserialize)]
-struct MicrosoftGraphResponse {
-    value: Vec<MicrosoftEvent>,
+struct MicrosoftCalendar {
+    id: String,
+    name: String,
+    #[serde(rename = "hexColor", default)]
+    hex_color: Option<String>, // Empty when the user never picked a color
+    #[serde(rename = "isDefaultCalendar", default)]
+    is_default_calendar: bool,
 }
 
 #[derive(Debug, Deserialize)]
 struct MicrosoftEvent {
     id: String,
-    subject: String,
+    #[serde(default)]
+    subject: Option<String>,
     start: MicrosoftDateTime,
     end: MicrosoftDateTime,
     location: Option<MicrosoftLocation>,
     body: Option<MicrosoftBody>,
+    #[serde(rename = "isAllDay", default)]
+    is_all_day: bool,
+    #[serde(rename = "isOrganizer", default = "default_true")]
+    is_organizer: bool,
+    #[serde(default)]
+    organizer: Option<MicrosoftRecipient>,
+    #[serde(default)]
+    attendees: Vec<MicrosoftAttendee>,
+    #[serde(rename = "responseStatus", default)]
+    response_status: Option<MicrosoftResponseStatus>, // The signed-in user's
+    #[serde(rename = "onlineMeeting", default)]
+    online_meeting: Option<MicrosoftOnlineMeeting>,
+    #[serde(rename = "onlineMeetingUrl", default)]
+    online_meeting_url: Option<String>, // Older events; usually empty for Teams
+    #[serde(rename = "originalStartTimeZone", default)]
+    original_start_time_zone: Option<String>, // start/end are in UTC because we ask for that
+    #[serde(rename = "seriesMasterId", default)]
+    series_master_id: Option<String>,
+    #[serde(default)]
+    sensitivity: Option<String>, // normal, personal, private or confidential
+}
+
+#[derive(Debug, Deserialize)]
+struct MicrosoftRecipient {
+    #[serde(rename = "emailAddress")]
+    email_address: MicrosoftEmailAddress,
+}
+
+#[derive(Debug, Deserialize)]
+struct MicrosoftEmailAddress {
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    address: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MicrosoftAttendee {
+    #[serde(rename = "emailAddress")]
+    email_address: MicrosoftEmailAddress,
+    #[serde(default)]
+    status: Option<MicrosoftResponseStatus>,
+    #[serde(rename = "type", default)]
+    attendee_type: Option<String>, // required, optional or resource
+}
+
+#[derive(Debug, Deserialize)]
+struct MicrosoftResponseStatus {
+    #[serde(default)]
+    response: Option<String>, // none, organizer, tentativelyAccepted, accepted, declined or notResponded
+}
+
+fn microsoft_response_status(response: Option<&str>) -> ResponseStatus {
+    match response {
+        Some("accepted") | Some("organizer") => ResponseStatus::Accepted,
+        Some("tentativelyAccepted") => ResponseStatus::Tentative,
+        Some("declined") => ResponseStatus::Declined,
+        _ => ResponseStatus::NeedsAction, // none or notResponded
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct MicrosoftOnlineMeeting {
+    #[serde(rename = "joinUrl", default)]
+    join_url: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
 struct MicrosoftDateTime {
-    #[serde(deserialize_with = "deserialize_rfc3339")]
+    #[serde(rename = "dateTime", deserialize_with = "deserialize_graph_datetime")]
     date_time: DateTime<Utc>,
+    #[serde(rename = "timeZone")]
     time_zone: String,
 }
 
-fn deserialize_rfc3339<'de, D>(deserializer: D) -> Result<DateTime<Utc>, D::Error>
+fn deserialize_graph_datetime<'de, D>(deserializer: D) -> Result<DateTime<Utc>, D::Error>
 where
     D: serde::Deserializer<'de>,
 {
+    // Graph sends "2024-06-14T09:00:00.0000000" in the zone asked for with Prefer: outlook.timezone (we ask for UTC)
     let s: String = String::deserialize(deserializer)?;
-    DateTime::parse_from_rfc3339(&s)
-        .map(|dt| dt.with_timezone(&Utc))
+    if let Ok(dt) = DateTime::parse_from_rfc3339(&s) {
+        return Ok(dt.with_timezone(&Utc));
+    }
+    NaiveDateTime::parse_from_str(&s, "%Y-%m-%dT%H:%M:%S%.f")
+        .map(|dt| dt.and_utc())
         .map_err(serde::de::Error::custom)
 }
 
 #[derive(Debug, Deserialize)]
 struct MicrosoftLocation {
+    #[serde(rename = "displayName", default)]
     display_name: String,
 }
 
 #[derive(Debug, Deserialize)]
 struct MicrosoftBody {
     content: String,
+    #[serde(rename = "contentType")]
     content_type: String,
 }
 
+#[derive(Clone)]
 pub struct CalendarService {
     google_client_id: Option<String>,
     google_client_secret: Option<String>,
@@ -119,8 +335,12 @@ pub struct CalendarService {
     microsoft_client_secret: Option<String>,
     http_client: Client,
     cached_events: Arc<RwLock<Vec<CalendarEvent>>>,
-    last_fetch: Arc<RwLock<Option<DateTime<Utc>>>>,
+    last_fetch: Arc<RwLock<Option<DateTime<Utc>>>>, // Last sync that reached every connected calendar
     authenticated_providers: Arc<RwLock<HashMap<CalendarProvider, bool>>>,
+    data_dir: PathBuf,
+    selection: Arc<RwLock<CalendarSelection>>, // Which calendars to include per provider
+    sync_states: Arc<RwLock<HashMap<CalendarProvider, HashMap<String, CalendarSyncState>>>>, // Incremental sync state per Google/Microsoft calendar id
+    app_handle: Arc<std::sync::RwLock<Option<AppHandle>>>,
 }
 
 impl CalendarService {
@@ -141,28 +361,52 @@ impl CalendarService {
             eprintln!("[Calendar] ✗ GOOGLE_CLIENT_SECRET not found in environment");
         }
         
+        let data_dir = app_data::app_data_dir_or_temp();
+        let selection = CalendarSelection::load(&data_dir).unwrap_or_else(|e| {
+            eprintln!("[Calendar] Failed to load calendar selection: {}", e);
+            CalendarSelection::default()
+        });
+        // Serve the last synced events until the first sync finishes (or while offline)
+        let cache = CalendarCache::load(&data_dir, Utc::now()).unwrap_or_else(|e| {
+            eprintln!("[Calendar] Failed to load calendar cache: {}", e);
+            CalendarCache::default()
+        });
+        println!("[Calendar] Loaded {} cached events (last fetched {:?})", cache.events.len(), cache.last_fetch);
+
         Self {
             google_client_id,
             google_client_secret,
             microsoft_client_id: std::env::var("MICROSOFT_CLIENT_ID").ok(),
             microsoft_client_secret: std::env::var("MICROSOFT_CLIENT_SECRET").ok(),
             http_client: Client::new(),
-            cached_events: Arc::new(RwLock::new(Vec::new())),
-            last_fetch: Arc::new(RwLock::new(None)),
+            cached_events: Arc::new(RwLock::new(cache.events)),
+            last_fetch: Arc::new(RwLock::new(cache.last_fetch)),
             authenticated_providers: Arc::new(RwLock::new(HashMap::new())),
+            data_dir,
+            selection: Arc::new(RwLock::new(selection)),
+            sync_states: Arc::new(RwLock::new(HashMap::new())),
+            app_handle: Arc::new(std::sync::RwLock::new(None)),
+        }
+    }
+
+    /// Set the app handle so sync can emit calendar-changed
+    pub fn set_app_handle(&mut self, app: AppHandle) {
+        if let Ok(mut handle) = self.app_handle.write() {
+            *handle = Some(app);
+        }
+    }
+
+    fn emit<S: Serialize + Clone>(&self, event: &str, payload: S) {
+        let app = self.app_handle.read().ok().and_then(|handle| handle.clone());
+        if let Some(app) = app {
+            let _ = app.emit(event, payload);
         }
     }
 
     /// Initialize the calendar service and start background fetching
     pub async fn initialize(&self) -> Result<(), String> {
-        // Start background task to fetch events every 5 minutes
-        let cached_events = Arc::clone(&self.cached_events);
-        let last_fetch = Arc::clone(&self.last_fetch);
-        let http_client = self.http_client.clone();
-        let google_client_id = self.google_client_id.clone();
-        let google_client_secret = self.google_client_secret.clone();
-        let microsoft_client_id = self.microsoft_client_id.clone();
-        let microsoft_client_secret = self.microsoft_client_secret.clone();
+        // Start background task to sync events every 5 minutes
+        let service = self.clone();
 
         tokio::spawn(async move {
             let mut interval = interval(TokioDuration::from_secs(300)); // 5 minutes
@@ -170,54 +414,9 @@ impl CalendarService {
 
             loop {
                 interval.tick().await;
-
-                // Fetch from Google Calendar if authenticated
-                if let (Some(client_id), Some(client_secret)) =
-                    (google_client_id.as_ref(), google_client_secret.as_ref())
-                {
-                    if Self::is_authenticated(CalendarProvider::Google).await {
-                        match Self::fetch_google_events_internal(
-                            &http_client,
-                            client_id,
-                            client_secret,
-                        )
-                        .await
-                        {
-                            Ok(events) => {
-                                let mut cached = cached_events.write().await;
-                                // Remove old Google events and add new ones
-                                cached.retain(|e| !e.id.starts_with("google_"));
-                                cached.extend(events);
-                            }
-                            Err(e) => eprintln!("Error fetching Google Calendar events: {}", e),
-                        }
-                    }
-                }
-
-                // Fetch from Microsoft Graph if authenticated
-                if let (Some(client_id), Some(client_secret)) =
-                    (microsoft_client_id.as_ref(), microsoft_client_secret.as_ref())
-                {
-                    if Self::is_authenticated(CalendarProvider::Microsoft).await {
-                        match Self::fetch_microsoft_events_internal(
-                            &http_client,
-                            client_id,
-                            client_secret,
-                        )
-                        .await
-                        {
-                            Ok(events) => {
-                                let mut cached = cached_events.write().await;
-                                // Remove old Microsoft events and add new ones
-                                cached.retain(|e| !e.id.starts_with("microsoft_"));
-                                cached.extend(events);
-                            }
-                            Err(e) => eprintln!("Error fetching Microsoft Calendar events: {}", e),
-                        }
-                    }
+                if let Err(e) = service.sync_events().await {
+                    eprintln!("[Calendar] ✗ Background sync failed: {}", e);
                 }
-
-                *last_fetch.write().await = Some(Utc::now());
             }
         });
 
@@ -398,6 +597,9 @@ impl CalendarService {
             CalendarProvider::Microsoft => {
                 self.handle_microsoft_callback(code).await?;
             }
+            CalendarProvider::CalDav | CalendarProvider::IcsUrl => {
+                return Err(format!("{:?} calendars don't use OAuth", provider));
+            }
         }
 
         // Mark provider as authenticated
@@ -561,10 +763,13 @@ impl CalendarService {
     }
 
     /// Store OAuth token securely using keyring
-    async fn store_token(provider: CalendarProvider, token_data: &TokenData) -> Result<(), String> {
+    pub(crate) async fn store_token(provider: CalendarProvider, token_data: &TokenData) -> Result<(), String> {
         let service_name = match provider {
             CalendarProvider::Google => "pause-menu-google-calendar",
             CalendarProvider::Microsoft => "pause-menu-microsoft-calendar",
+            CalendarProvider::CalDav | CalendarProvider::IcsUrl => {
+                return Err(format!("{:?} calendars don't use OAuth tokens", provider));
+            }
         };
 
         println!("[Calendar] store_token called for provider: {:?}, service_name: {}", provider, service_name);
@@ -609,11 +814,19 @@ impl CalendarService {
         Ok(())
     }
 
-    /// Retrieve OAuth token from secure storage
+    /// A valid OAuth token for the provider, refreshed first if it's about to expire
     pub async fn get_token(provider: CalendarProvider) -> Result<TokenData, String> {
+        token_refresh::token_refresher().token(provider).await
+    }
+
+    /// Retrieve the stored OAuth token from secure storage, expired or not
+    pub(crate) async fn read_stored_token(provider: CalendarProvider) -> Result<TokenData, String> {
         let service_name = match provider {
             CalendarProvider::Google => "pause-menu-google-calendar",
             CalendarProvider::Microsoft => "pause-menu-microsoft-calendar",
+            CalendarProvider::CalDav | CalendarProvider::IcsUrl => {
+                return Err(format!("{:?} calendars don't use OAuth tokens", provider));
+            }
         };
 
         println!("[Calendar] get_token called for provider: {:?}, service_name: {}", provider, service_name);
@@ -646,31 +859,29 @@ impl CalendarService {
 
         println!("[Calendar] ✓ Token deserialized successfully");
 
-        // Check if token is expired and refresh if needed
         if let Some(expires_at) = token_data.expires_at {
-            let now = Utc::now();
-            println!("[Calendar] Token expires at: {}, current time: {}", expires_at, now);
-            if expires_at <= now {
-                eprintln!("[Calendar] ✗ Token expired");
-                // Token expired, would need to refresh
-                // For now, return error - refresh logic can be added later
-                return Err("Token expired. Please re-authenticate.".to_string());
-            } else {
-                let remaining = expires_at - now;
-                println!("[Calendar] ✓ Token is valid, expires in: {} seconds", remaining.num_seconds());
-            }
+            println!("[Calendar] Token expires at: {}, current time: {}", expires_at, Utc::now());
         } else {
             println!("[Calendar] Token has no expiration time");
         }
 
-        println!("[Calendar] ✓ Token retrieved successfully");
         Ok(token_data)
     }
 
     /// Check if a provider is authenticated
-    pub async fn is_authenticated(provider: CalendarProvider) -> bool {
+    pub async fn is_authenticated(&self, provider: CalendarProvider) -> bool {
         println!("[Calendar] is_authenticated called for provider: {:?}", provider);
-        match Self::get_token(provider).await {
+        let connected = match provider {
+            CalendarProvider::Google | CalendarProvider::Microsoft => Self::get_token(provider).await.map(|_| ()),
+            CalendarProvider::CalDav => CalDavAccount::from_keyring().map(|_| ()),
+            // Subscriptions need no sign-in; they count as connected once one is added
+            CalendarProvider::IcsUrl => match IcsFeedsConfig::load(&self.data_dir) {
+                Ok(config) if !config.feeds.is_empty() => Ok(()),
+                Ok(_) => Err("No calendar feeds added".to_string()),
+                Err(e) => Err(e),
+            },
+        };
+        match connected {
             Ok(_) => {
                 println!("[Calendar] ✓ Provider is authenticated");
                 true
@@ -682,273 +893,468 @@ impl CalendarService {
         }
     }
 
-    /// Fetch events from Google Calendar
-    async fn fetch_google_events_internal(
-        http_client: &Client,
-        _client_id: &str,
-        _client_secret: &str,
+    /// Sync every selected calendar of a signed-in provider and return their events
+    /// A calendar whose sync fails keeps the events from its last successful sync; it's an error
+    /// only when none of them could be synced
+    async fn sync_provider_events(
+        &self,
+        provider: CalendarProvider,
+        selection: &CalendarSelection,
+        states: &mut HashMap<String, CalendarSyncState>,
+        now: DateTime<Utc>,
     ) -> Result<Vec<CalendarEvent>, String> {
-        let token_data = Self::get_token(CalendarProvider::Google).await?;
-
-        let now = Utc::now();
-        // Fetch events for the next 7 days instead of just 24 hours
-        let future = now + Duration::days(7);
-        let time_min = now.to_rfc3339();
-        let time_max = future.to_rfc3339();
-
-        println!("[Calendar] Fetching Google Calendar events from {} to {}", time_min, time_max);
-
-        // URL encode the datetime parameters
-        let time_min_encoded = urlencoding::encode(&time_min);
-        let time_max_encoded = urlencoding::encode(&time_max);
-        
-        let url = format!(
-            "https://www.googleapis.com/calendar/v3/calendars/primary/events?timeMin={}&timeMax={}&singleEvents=true&orderBy=startTime",
-            time_min_encoded, time_max_encoded
-        );
-        
-        println!("[Calendar] Google Calendar API URL: {}", url);
+        let token_data = Self::get_token(provider).await?;
 
-        let response = http_client
-            .get(&url)
-            .bearer_auth(&token_data.access_token)
-            .send()
-            .await
-            .map_err(|e| format!("Failed to fetch Google Calendar events: {}", e))?;
+        let listed = match provider {
+            CalendarProvider::Google => Self::list_google_calendars_internal(&self.http_client, &token_data).await,
+            _ => Self::list_microsoft_calendars_internal(&self.http_client, &token_data).await,
+        };
+        let calendars = match listed {
+            Ok(calendars) => {
+                // Forget deselected calendars, so picking one again starts with a full sync
+                states.retain(|id, _| calendars.iter().any(|c| &c.id == id && selection.includes(c)));
+                calendars
+            }
+            Err(e) if provider == CalendarProvider::Google => {
+                eprintln!("[Calendar] ✗ Failed to list Google calendars, using the primary one: {}", e);
+                vec![CalendarInfo { id: "primary".to_string(), ..CalendarInfo::fallback_primary(CalendarProvider::Google) }]
+            }
+            Err(e) => {
+                eprintln!("[Calendar] ✗ Failed to list Microsoft calendars, using the default one: {}", e);
+                vec![CalendarInfo::fallback_primary(CalendarProvider::Microsoft)]
+            }
+        };
 
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response.text().await.unwrap_or_else(|_| "Unable to read error response".to_string());
-            return Err(format!(
-                "Google Calendar API error: {} - {}",
-                status, error_text
-            ));
+        let mut events = Vec::new();
+        let mut last_error = None;
+        let mut synced_any = false;
+        for calendar in calendars.iter().filter(|c| selection.includes(c)) {
+            let state = states.entry(calendar.id.clone()).or_default();
+            match self.sync_calendar(&token_data, calendar, state, now).await {
+                Ok(()) => synced_any = true,
+                Err(e) => {
+                    eprintln!("[Calendar] ✗ Failed to sync {:?} calendar '{}': {}", provider, calendar.name, e);
+                    last_error = Some(e);
+                }
+            }
+            events.extend(state.events.values().cloned());
+        }
+        // Nothing could be synced (e.g. offline): let the caller keep what it had
+        match last_error {
+            Some(e) if !synced_any => Err(e),
+            _ => Ok(events),
         }
+    }
 
-        // Get the raw response text for debugging and parsing
-        let response_text = response.text().await
-            .map_err(|e| format!("Failed to read Google Calendar response: {}", e))?;
-        
-        println!("[Calendar] Google Calendar API response length: {} chars", response_text.len());
-        if response_text.len() < 1000 {
-            println!("[Calendar] Full response: {}", response_text);
-        } else {
-            println!("[Calendar] Response preview (first 500 chars): {}", 
-                &response_text.chars().take(500).collect::<String>());
+    /// Bring one calendar's sync state up to date, starting over if the provider dropped our cursor
+    async fn sync_calendar(
+        &self,
+        token_data: &TokenData,
+        calendar: &CalendarInfo,
+        state: &mut CalendarSyncState,
+        now: DateTime<Utc>,
+    ) -> Result<(), String> {
+        let batch = self.fetch_sync_batch(token_data, calendar, state, now).await?;
+        if !state.apply(batch, now) {
+            println!("[Calendar] Sync token for '{}' expired, doing a full sync", calendar.name);
+            let batch = self.fetch_sync_batch(token_data, calendar, state, now).await?;
+            state.apply(batch, now);
         }
-        
-        // Try to find and print the first event's start/end structure for debugging
-        if let Some(start_idx) = response_text.find("\"start\"") {
-            let end_idx = response_text[start_idx..].find("\"end\"").unwrap_or(200);
-            let snippet = &response_text[start_idx..start_idx + end_idx.min(400)];
-            println!("[Calendar] First event 'start' field structure: {}", snippet);
+        Ok(())
+    }
+
+    async fn fetch_sync_batch(
+        &self,
+        token_data: &TokenData,
+        calendar: &CalendarInfo,
+        state: &CalendarSyncState,
+        now: DateTime<Utc>,
+    ) -> Result<SyncBatch, String> {
+        let cursor = if state.needs_full_sync(now) { None } else { state.cursor.as_deref() };
+        match calendar.provider {
+            CalendarProvider::Google => Self::sync_google_calendar(&self.http_client, token_data, calendar, cursor, now).await,
+            CalendarProvider::Microsoft => Self::sync_microsoft_calendar(&self.http_client, token_data, calendar, cursor, now).await,
+            provider => Err(format!("{:?} calendars don't support incremental sync", provider)),
         }
-        if let Some(end_idx) = response_text.find("\"end\"") {
-            let next_field = response_text[end_idx..].find("\",").unwrap_or(200);
-            let snippet = &response_text[end_idx..end_idx + next_field.min(400)];
-            println!("[Calendar] First event 'end' field structure: {}", snippet);
+    }
+
+    /// List the calendars on the user's Google calendar list
+    async fn list_google_calendars_internal(http_client: &Client, token_data: &TokenData) -> Result<Vec<CalendarInfo>, String> {
+        let mut calendars = Vec::new();
+        let mut page_token: Option<String> = None;
+        loop {
+            let mut url = "https://www.googleapis.com/calendar/v3/users/me/calendarList?maxResults=250".to_string();
+            if let Some(ref token) = page_token {
+                url.push_str(&format!("&pageToken={}", urlencoding::encode(token)));
+            }
+
+            let response = http_client
+                .get(&url)
+                .bearer_auth(&token_data.access_token)
+                .send()
+                .await
+                .map_err(|e| format!("Failed to list Google calendars: {}", e))?;
+            if !response.status().is_success() {
+                let status = response.status();
+                let error_text = response.text().await.unwrap_or_else(|_| "Unable to read error response".to_string());
+                return Err(format!("Google Calendar API error: {} - {}", status, error_text));
+            }
+
+            let page: GoogleCalendarListResponse = response
+                .json()
+                .await
+                .map_err(|e| format!("Failed to parse Google calendar list: {}", e))?;
+            calendars.extend(page.items.into_iter().map(|entry| CalendarInfo {
+                provider: CalendarProvider::Google,
+                name: entry.summary_override.or(entry.summary).unwrap_or_else(|| entry.id.clone()),
+                id: entry.id,
+                color: entry.background_color,
+                primary: entry.primary,
+                selected: false,
+            }));
+
+            match page.next_page_token {
+                Some(token) => page_token = Some(token),
+                None => break,
+            }
         }
 
-        // Parse the JSON response
-        let calendar_response: GoogleCalendarResponse = serde_json::from_str(&response_text)
-            .map_err(|e| {
-                eprintln!("[Calendar] JSON parse error: {}", e);
-                eprintln!("[Calendar] Error location: line {} column {}", 
-                    e.line(), e.column());
-                // Print a snippet around the error
-                let lines: Vec<&str> = response_text.lines().collect();
-                if let Some(line_num) = e.line().checked_sub(1) {
-                    if (line_num as usize) < lines.len() {
-                        eprintln!("[Calendar] Error at line {}: {}", line_num + 1, lines[line_num as usize]);
-                    }
+        println!("[Calendar] Found {} Google calendars", calendars.len());
+        Ok(calendars)
+    }
+
+    /// Fetch one Google calendar: everything up to the sync horizon, or only what changed since `sync_token`
+    async fn sync_google_calendar(
+        http_client: &Client,
+        token_data: &TokenData,
+        calendar: &CalendarInfo,
+        sync_token: Option<&str>,
+        now: DateTime<Utc>,
+    ) -> Result<SyncBatch, String> {
+        let horizon_end = now + Duration::days(SYNC_HORIZON_DAYS);
+        // A sync token can't be combined with a time range; the one from the full sync carries over
+        let query = match sync_token {
+            Some(token) => format!("syncToken={}", urlencoding::encode(token)),
+            None => format!(
+                "timeMin={}&timeMax={}",
+                urlencoding::encode(&now.to_rfc3339()),
+                urlencoding::encode(&horizon_end.to_rfc3339())
+            ),
+        };
+
+        let mut changes = Vec::new();
+        let mut page_token: Option<String> = None;
+        loop {
+            let mut url = format!(
+                "https://www.googleapis.com/calendar/v3/calendars/{}/events?singleEvents=true&maxResults=250&{}",
+                urlencoding::encode(&calendar.id), query
+            );
+            if let Some(ref token) = page_token {
+                url.push_str(&format!("&pageToken={}", urlencoding::encode(token)));
+            }
+
+            let response = http_client
+                .get(&url)
+                .bearer_auth(&token_data.access_token)
+                .send()
+                .await
+                .map_err(|e| format!("Failed to fetch Google Calendar events: {}", e))?;
+            if response.status() == reqwest::StatusCode::GONE {
+                return Ok(SyncBatch::CursorExpired);
+            }
+            if !response.status().is_success() {
+                let status = response.status();
+                let error_text = response.text().await.unwrap_or_else(|_| "Unable to read error response".to_string());
+                return Err(format!("Google Calendar API error: {} - {}", status, error_text));
+            }
+
+            let page: GoogleCalendarResponse = response
+                .json()
+                .await
+                .map_err(|e| format!("Failed to parse Google Calendar response: {}", e))?;
+            for event in page.items {
+                if event.status.as_deref() == Some("cancelled") {
+                    changes.push(EventChange::Delete(format!("google_{}", event.id)));
+                } else if let Some(event) = Self::google_event_to_calendar_event(event, calendar) {
+                    changes.push(EventChange::Upsert(Box::new(event)));
                 }
-                // Try to find and print the first event's start/end structure
-                if let Some(start_idx) = response_text.find("\"start\"") {
-                    let snippet = &response_text[start_idx..start_idx.min(response_text.len()).min(start_idx + 200)];
-                    eprintln!("[Calendar] First 'start' field snippet: {}", snippet);
+            }
+
+            match page.next_page_token {
+                Some(token) => page_token = Some(token),
+                None => {
+                    println!(
+                        "[Calendar] {} changes from Google calendar '{}' ({} sync)",
+                        changes.len(), calendar.name, if sync_token.is_some() { "incremental" } else { "full" }
+                    );
+                    return Ok(match sync_token {
+                        Some(_) => SyncBatch::Delta { changes, cursor: page.next_sync_token },
+                        None => SyncBatch::full(changes, page.next_sync_token, horizon_end),
+                    });
                 }
-                format!("Failed to parse Google Calendar response: {} at line {} column {}", 
-                    e, e.line(), e.column())
-            })?;
+            }
+        }
+    }
 
-        println!("[Calendar] Google Calendar API returned {} events", calendar_response.items.len());
+    /// Convert a Google event; all-day events run from midnight to 23:59:59 UTC
+    fn google_event_to_calendar_event(event: GoogleEvent, calendar: &CalendarInfo) -> Option<CalendarEvent> {
+        let time = |time: &GoogleDateTime, hour: u32, min: u32, sec: u32| {
+            time.date_time.or_else(|| {
+                // For all-day events, Google returns date as "YYYY-MM-DD"
+                let date = NaiveDate::parse_from_str(time.date.as_deref()?, "%Y-%m-%d").ok()?;
+                date.and_hms_opt(hour, min, sec).map(|dt| dt.and_utc())
+            })
+        };
+        let (Some(start_time), Some(end_time)) = (time(&event.start, 0, 0, 0), time(&event.end, 23, 59, 59)) else {
+            eprintln!("[Calendar] Event {} has no valid start or end time, skipping", event.id);
+            return None;
+        };
 
-        let events: Vec<CalendarEvent> = calendar_response
-            .items
+        // Events without organizer info are the user's own
+        let is_organizer = event.organizer.as_ref().map(|o| o.is_self).unwrap_or(true);
+        let attendees: Vec<Attendee> = event
+            .attendees
             .into_iter()
-            .enumerate()
-            .filter_map(|(idx, event)| {
-                println!("[Calendar] Processing event {}: id={}, summary={:?}", idx, event.id, event.summary);
-                println!("[Calendar] Event {} start: date_time={:?}, date={:?}", 
-                    idx, event.start.date_time, event.start.date);
-                println!("[Calendar] Event {} end: date_time={:?}, date={:?}", 
-                    idx, event.end.date_time, event.end.date);
-                
-                // Handle both dateTime and date (all-day events)
-                let start_time = if let Some(dt) = event.start.date_time {
-                    // Has dateTime field - use it directly
-                    println!("[Calendar] Event {} using date_time for start: {}", idx, dt);
-                    Some(dt)
-                } else if let Some(date_str) = &event.start.date {
-                    // Has date field (all-day event) - parse it
-                    println!("[Calendar] Event {} has date (all-day) for start: {}", idx, date_str);
-                    // For all-day events, Google returns date as "YYYY-MM-DD"
-                    // We need to parse it and set to start of day in UTC
-                    match NaiveDate::parse_from_str(date_str, "%Y-%m-%d") {
-                        Ok(naive_date) => {
-                            let dt = naive_date.and_hms_opt(0, 0, 0)
-                                .unwrap()
-                                .and_utc();
-                            println!("[Calendar] Event {} parsed all-day start: {}", idx, dt);
-                            Some(dt)
-                        },
-                        Err(e) => {
-                            eprintln!("[Calendar] Event {} failed to parse start date '{}': {}", idx, date_str, e);
-                            None
-                        }
-                    }
-                } else {
-                    eprintln!("[Calendar] Event {} has neither date_time nor date for start", idx);
-                    None
-                };
-                
-                let start_time = match start_time {
-                    Some(st) => {
-                        println!("[Calendar] Event {} start_time: {}", idx, st);
-                        st
-                    },
-                    None => {
-                        eprintln!("[Calendar] Event {} has no valid start time, skipping", idx);
-                        return None;
-                    }
-                };
-
-                let end_time = if let Some(dt) = event.end.date_time {
-                    // Has dateTime field - use it directly
-                    println!("[Calendar] Event {} using date_time for end: {}", idx, dt);
-                    Some(dt)
-                } else if let Some(date_str) = &event.end.date {
-                    // Has date field (all-day event) - parse it
-                    println!("[Calendar] Event {} has date (all-day) for end: {}", idx, date_str);
-                    // For all-day events, Google returns date as "YYYY-MM-DD"
-                    // We need to parse it and set to end of day in UTC
-                    match NaiveDate::parse_from_str(date_str, "%Y-%m-%d") {
-                        Ok(naive_date) => {
-                            let dt = naive_date.and_hms_opt(23, 59, 59)
-                                .unwrap()
-                                .and_utc();
-                            println!("[Calendar] Event {} parsed all-day end: {}", idx, dt);
-                            Some(dt)
-                        },
-                        Err(e) => {
-                            eprintln!("[Calendar] Event {} failed to parse end date '{}': {}", idx, date_str, e);
-                            None
-                        }
-                    }
-                } else {
-                    eprintln!("[Calendar] Event {} has neither date_time nor date for end", idx);
-                    None
-                };
-                
-                let end_time = match end_time {
-                    Some(et) => {
-                        println!("[Calendar] Event {} end_time: {}", idx, et);
-                        et
-                    },
-                    None => {
-                        eprintln!("[Calendar] Event {} has no valid end time, skipping", idx);
-                        return None;
-                    }
-                };
-
-                let cal_event = CalendarEvent {
-                    id: format!("google_{}", event.id),
-                    title: event.summary.clone().unwrap_or_else(|| "(No title)".to_string()),
-                    start_time,
-                    end_time,
-                    location: event.location.clone(),
-                    description: event.description.clone(),
-                };
-                
-                println!("[Calendar] ✓ Parsed event: '{}' at {} (Unix: {})", 
-                    cal_event.title, 
-                    cal_event.start_time,
-                    cal_event.start_time.timestamp()
-                );
-                
-                Some(cal_event)
+            .filter(|a| !a.resource)
+            .filter_map(|a| {
+                Some(Attendee {
+                    email: a.email?,
+                    name: a.display_name,
+                    response: google_response_status(a.response_status.as_deref()),
+                    optional: a.optional,
+                    is_self: a.is_self,
+                })
             })
             .collect();
+        let my_response = attendees
+            .iter()
+            .find(|a| a.is_self)
+            .map(|a| a.response)
+            .or(is_organizer.then_some(ResponseStatus::Accepted));
+        let conference_url = event.hangout_link.or_else(|| {
+            event
+                .conference_data?
+                .entry_points
+                .into_iter()
+                .find(|entry| entry.entry_point_type == "video")
+                .and_then(|entry| entry.uri)
+        });
 
-        println!("[Calendar] Successfully parsed {} events from Google Calendar", events.len());
-        Ok(events)
+        Some(CalendarEvent {
+            id: format!("google_{}", event.id),
+            title: event.summary.unwrap_or_else(|| "(No title)".to_string()),
+            start_time,
+            end_time,
+            location: event.location,
+            description: event.description,
+            all_day: event.start.date_time.is_none(),
+            is_organizer,
+            calendar_id: Some(calendar.id.clone()),
+            calendar_name: Some(calendar.name.clone()),
+            calendar_color: calendar.color.clone(),
+            stale: false,
+            organizer: event.organizer.and_then(|o| Some(EventPerson { email: o.email?, name: o.display_name })),
+            attendees,
+            my_response,
+            conference_url,
+            time_zone: event.start.time_zone,
+            recurrence_id: event.recurring_event_id.map(|id| format!("google_{}", id)),
+            visibility: match event.visibility.as_deref() {
+                Some("public") => Visibility::Public,
+                Some("private") => Visibility::Private,
+                Some("confidential") => Visibility::Confidential,
+                _ => Visibility::Default,
+            },
+        })
     }
 
-    /// Fetch events from Microsoft Graph
-    async fn fetch_microsoft_events_internal(
-        http_client: &Client,
-        _client_id: &str,
-        _client_secret: &str,
-    ) -> Result<Vec<CalendarEvent>, String> {
-        let token_data = Self::get_token(CalendarProvider::Microsoft).await?;
+    /// List the user's Outlook calendars, including ones shared with them
+    async fn list_microsoft_calendars_internal(http_client: &Client, token_data: &TokenData) -> Result<Vec<CalendarInfo>, String> {
+        let mut calendars = Vec::new();
+        let mut url = "https://graph.microsoft.com/v1.0/me/calendars?$top=100".to_string();
+        loop {
+            let response = http_client
+                .get(&url)
+                .bearer_auth(&token_data.access_token)
+                .send()
+                .await
+                .map_err(|e| format!("Failed to list Microsoft calendars: {}", e))?;
+            if !response.status().is_success() {
+                return Err(format!("Microsoft Graph API error: {}", response.status()));
+            }
 
-        let now = Utc::now();
-        let tomorrow = now + Duration::hours(24);
-        let start_datetime = now.to_rfc3339();
-        let end_datetime = tomorrow.to_rfc3339();
+            let page: MicrosoftCalendarListResponse = response
+                .json()
+                .await
+                .map_err(|e| format!("Failed to parse Microsoft calendar list: {}", e))?;
+            calendars.extend(page.value.into_iter().map(|calendar| CalendarInfo {
+                provider: CalendarProvider::Microsoft,
+                id: calendar.id,
+                name: calendar.name,
+                color: calendar.hex_color.filter(|c| !c.is_empty()),
+                primary: calendar.is_default_calendar,
+                selected: false,
+            }));
+
+            match page.next_link {
+                Some(next) => url = next,
+                None => break,
+            }
+        }
 
-        // URL encode the datetime parameters
-        let start_encoded = urlencoding::encode(&start_datetime);
-        let end_encoded = urlencoding::encode(&end_datetime);
-        
-        let url = format!(
-            "https://graph.microsoft.com/v1.0/me/calendar/calendarView?startDateTime={}&endDateTime={}&$orderby=start/dateTime",
-            start_encoded, end_encoded
-        );
+        println!("[Calendar] Found {} Microsoft calendars", calendars.len());
+        Ok(calendars)
+    }
 
-        let response = http_client
-            .get(&url)
-            .bearer_auth(&token_data.access_token)
-            .send()
-            .await
-            .map_err(|e| format!("Failed to fetch Microsoft Calendar events: {}", e))?;
+    /// Fetch one Microsoft calendar through calendarView/delta: the whole sync horizon, or only what
+    /// changed since `delta_link`
+    async fn sync_microsoft_calendar(
+        http_client: &Client,
+        token_data: &TokenData,
+        calendar: &CalendarInfo,
+        delta_link: Option<&str>,
+        now: DateTime<Utc>,
+    ) -> Result<SyncBatch, String> {
+        let horizon_end = now + Duration::days(SYNC_HORIZON_DAYS);
+        let mut url = match delta_link {
+            Some(link) => link.to_string(),
+            None => {
+                // An empty id means the default calendar (used when the calendar list couldn't be fetched)
+                let calendar_path = if calendar.id.is_empty() {
+                    "me".to_string()
+                } else {
+                    format!("me/calendars/{}", urlencoding::encode(&calendar.id))
+                };
+                format!(
+                    "https://graph.microsoft.com/v1.0/{}/calendarView/delta?startDateTime={}&endDateTime={}",
+                    calendar_path,
+                    urlencoding::encode(&now.to_rfc3339()),
+                    urlencoding::encode(&horizon_end.to_rfc3339())
+                )
+            }
+        };
 
-        if !response.status().is_success() {
-            return Err(format!(
-                "Microsoft Graph API error: {}",
-                response.status()
-            ));
-        }
+        let mut changes = Vec::new();
+        loop {
+            let response = http_client
+                .get(&url)
+                .bearer_auth(&token_data.access_token)
+                .header("Prefer", "outlook.timezone=\"UTC\", odata.maxpagesize=100")
+                .send()
+                .await
+                .map_err(|e| format!("Failed to fetch Microsoft Calendar events: {}", e))?;
+            if response.status() == reqwest::StatusCode::GONE {
+                return Ok(SyncBatch::CursorExpired);
+            }
+            if !response.status().is_success() {
+                return Err(format!("Microsoft Graph API error: {}", response.status()));
+            }
 
-        let graph_response: MicrosoftGraphResponse = response
-            .json()
-            .await
-            .map_err(|e| format!("Failed to parse Microsoft Graph response: {}", e))?;
+            let page: MicrosoftDeltaResponse = response
+                .json()
+                .await
+                .map_err(|e| format!("Failed to parse Microsoft Graph response: {}", e))?;
+            for item in page.value {
+                if item.get("@removed").is_some() {
+                    if let Some(id) = item.get("id").and_then(|id| id.as_str()) {
+                        changes.push(EventChange::Delete(format!("microsoft_{}", id)));
+                    }
+                    continue;
+                }
+                match serde_json::from_value::<MicrosoftEvent>(item) {
+                    Ok(event) => changes.push(EventChange::Upsert(Box::new(Self::microsoft_event_to_calendar_event(event, calendar)))),
+                    Err(e) => eprintln!("[Calendar] Skipping unreadable Microsoft event: {}", e),
+                }
+            }
+
+            match page.next_link {
+                Some(next) => url = next,
+                None => {
+                    println!(
+                        "[Calendar] {} changes from Microsoft calendar '{}' ({} sync)",
+                        changes.len(), calendar.name, if delta_link.is_some() { "incremental" } else { "full" }
+                    );
+                    return Ok(match delta_link {
+                        Some(_) => SyncBatch::Delta { changes, cursor: page.delta_link },
+                        None => SyncBatch::full(changes, page.delta_link, horizon_end),
+                    });
+                }
+            }
+        }
+    }
 
-        let events: Vec<CalendarEvent> = graph_response
-            .value
+    fn microsoft_event_to_calendar_event(event: MicrosoftEvent, calendar: &CalendarInfo) -> CalendarEvent {
+        let attendees = event
+            .attendees
             .into_iter()
-            .map(|event| CalendarEvent {
-                id: format!("microsoft_{}", event.id),
-                title: event.subject,
-                start_time: event.start.date_time,
-                end_time: event.end.date_time,
-                location: event.location.map(|l| l.display_name),
-                description: event.body.map(|b| b.content),
+            .filter(|a| a.attendee_type.as_deref() != Some("resource"))
+            .filter_map(|a| {
+                Some(Attendee {
+                    email: a.email_address.address?,
+                    name: a.email_address.name,
+                    response: microsoft_response_status(a.status.and_then(|s| s.response).as_deref()),
+                    optional: a.attendee_type.as_deref() == Some("optional"),
+                    // Graph doesn't mark the signed-in attendee; my_response covers them
+                    is_self: false,
+                })
             })
             .collect();
-
-        Ok(events)
+        let my_response = if event.is_organizer {
+            Some(ResponseStatus::Accepted)
+        } else {
+            event.response_status.map(|s| microsoft_response_status(s.response.as_deref()))
+        };
+        let conference_url = event
+            .online_meeting
+            .and_then(|m| m.join_url)
+            .or(event.online_meeting_url)
+            .filter(|url| !url.is_empty());
+
+        CalendarEvent {
+            id: format!("microsoft_{}", event.id),
+            title: event.subject.unwrap_or_else(|| "(No title)".to_string()),
+            start_time: event.start.date_time,
+            end_time: event.end.date_time,
+            location: event.location.map(|l| l.display_name).filter(|l| !l.is_empty()),
+            description: event.body.map(|b| b.content),
+            all_day: event.is_all_day,
+            is_organizer: event.is_organizer,
+            calendar_id: Some(calendar.id.clone()),
+            calendar_name: Some(calendar.name.clone()),
+            calendar_color: calendar.color.clone(),
+            stale: false,
+            organizer: event.organizer.and_then(|o| {
+                Some(EventPerson { email: o.email_address.address?, name: o.email_address.name })
+            }),
+            attendees,
+            my_response,
+            conference_url,
+            time_zone: event.original_start_time_zone,
+            recurrence_id: event.series_master_id.map(|id| format!("microsoft_{}", id)),
+            visibility: match event.sensitivity.as_deref() {
+                Some("personal") | Some("private") => Visibility::Private,
+                Some("confidential") => Visibility::Confidential,
+                _ => Visibility::Default,
+            },
+        }
     }
 
-    /// Get all upcoming events (cached)
+    /// Get all upcoming events (cached), marked stale when the last sync is too old
     pub async fn get_events(&self) -> Result<Vec<CalendarEvent>, String> {
+        let stale = self.cache_status().await.stale;
         let events = self.cached_events.read().await;
-        Ok(events.clone())
+        Ok(events.iter().map(|event| CalendarEvent { stale, ..event.clone() }).collect())
+    }
+
+    /// When the cache was last refreshed, for "last updated at…"
+    pub async fn cache_status(&self) -> CalendarCacheStatus {
+        let last_fetch = *self.last_fetch.read().await;
+        CalendarCacheStatus { last_fetch, stale: calendar_cache::is_stale(last_fetch, Utc::now()) }
     }
 
     /// Get the next upcoming meeting
     pub async fn get_next_meeting(&self) -> Option<CalendarEvent> {
+        let stale = self.cache_status().await.stale;
         let events = self.cached_events.read().await;
         let now = Utc::now();
 
@@ -956,7 +1362,7 @@ impl CalendarService {
             .iter()
             .filter(|event| event.start_time > now)
             .min_by_key(|event| event.start_time)
-            .cloned()
+            .map(|event| CalendarEvent { stale, ..event.clone() })
     }
 
     /// Calculate time until next meeting
@@ -966,65 +1372,230 @@ impl CalendarService {
             .map(|event| event.start_time - Utc::now())
     }
 
+    /// How to join a cached event: best link, alternatives and dial-ins
+    pub async fn get_join_info(&self, event_id: &str) -> Result<JoinInfo, String> {
+        let events = self.cached_events.read().await;
+        let event = events
+            .iter()
+            .find(|e| e.id == event_id)
+            .ok_or_else(|| format!("Event with ID {} not found", event_id))?;
+        Ok(meeting_links::join_info(event))
+    }
+
+    /// Write the cached events to disk, where the reminder scheduler reads them, and wake it
+    async fn save_cache(&self) {
+        let events = self.cached_events.read().await.clone();
+        let last_fetch = *self.last_fetch.read().await;
+        if let Err(e) = (CalendarCache { events, last_fetch }).save(&self.data_dir) {
+            eprintln!("[Calendar] Failed to save calendar cache: {}", e);
+        }
+        meeting_reminders::rearm();
+    }
+
     /// Manually trigger a refresh of events
     pub async fn refresh_events(&self) -> Result<(), String> {
         println!("[Calendar] ========== refresh_events called ==========");
+        self.sync_events().await.map(|_| ())
+    }
+
+    /// Sync every connected calendar into the cache
+    /// Emits calendar-changed with the diff when the cached events actually changed
+    pub async fn sync_events(&self) -> Result<CalendarDiff, String> {
+        let selection = self.selection.read().await.clone();
+        let previous = self.cached_events.read().await.clone();
+        let now = Utc::now();
+        let until = now + Duration::days(CACHE_WINDOW_DAYS);
         let mut all_events = Vec::new();
+        let mut reached_all = true; // last_fetch only moves when every source answered
 
-        // Fetch from Google Calendar if authenticated
-        if let (Some(client_id), Some(client_secret)) = (
-            self.google_client_id.as_ref(),
-            self.google_client_secret.as_ref(),
-        ) {
-            let is_auth = Self::is_authenticated(CalendarProvider::Google).await;
-            println!("[Calendar] Google Calendar authenticated: {}", is_auth);
-            if is_auth {
-                println!("[Calendar] Fetching Google Calendar events...");
-                match Self::fetch_google_events_internal(&self.http_client, client_id, client_secret)
-                    .await
-                {
-                    Ok(events) => {
-                        println!("[Calendar] ✓ Fetched {} events from Google Calendar", events.len());
-                        all_events.extend(events);
-                    },
-                    Err(e) => {
-                        eprintln!("[Calendar] ✗ Error fetching Google Calendar events: {}", e);
-                    },
+        // Events from the last sync, for providers that can't be reached right now
+        let previous_with_prefix = |prefix: &str| -> Vec<CalendarEvent> {
+            previous.iter().filter(|e| e.id.starts_with(prefix)).cloned().collect()
+        };
+
+        let providers = [
+            (CalendarProvider::Google, "google_", self.google_client_id.is_some() && self.google_client_secret.is_some()),
+            (CalendarProvider::Microsoft, "microsoft_", self.microsoft_client_id.is_some() && self.microsoft_client_secret.is_some()),
+        ];
+        for (provider, prefix, configured) in providers {
+            // A stored token means signed in, even if it can't be refreshed right now (offline)
+            if !configured || Self::read_stored_token(provider).await.is_err() {
+                self.sync_states.write().await.remove(&provider);
+                continue;
+            }
+            // Synced on a copy so the lock isn't held while the provider's requests are in flight
+            let mut states = self.sync_states.read().await.get(&provider).cloned().unwrap_or_default();
+            let synced = self.sync_provider_events(provider, &selection, &mut states, now).await;
+            self.sync_states.write().await.insert(provider, states);
+            match synced {
+                Ok(events) => {
+                    println!("[Calendar] ✓ {} events from {:?} calendars", events.len(), provider);
+                    all_events.extend(events);
+                }
+                Err(e) => {
+                    eprintln!("[Calendar] ✗ Error syncing {:?} calendars: {}", provider, e);
+                    all_events.extend(previous_with_prefix(prefix));
+                    reached_all = false;
                 }
-            } else {
-                println!("[Calendar] Google Calendar not authenticated, skipping fetch");
             }
-        } else {
-            println!("[Calendar] Google Calendar credentials not configured");
         }
 
-        // Fetch from Microsoft Graph if authenticated
-        if let (Some(client_id), Some(client_secret)) = (
-            self.microsoft_client_id.as_ref(),
-            self.microsoft_client_secret.as_ref(),
-        ) {
-            if Self::is_authenticated(CalendarProvider::Microsoft).await {
-                match Self::fetch_microsoft_events_internal(
-                    &self.http_client,
-                    client_id,
-                    client_secret,
-                )
-                .await
-                {
-                    Ok(events) => all_events.extend(events),
-                    Err(e) => eprintln!("Error fetching Microsoft Calendar events: {}", e),
+        // CalDAV servers have no change tokens worth relying on, so they're fetched in full
+        if let Ok(account) = CalDavAccount::from_keyring() {
+            match caldav::fetch_selected_events(&self.http_client, &account, &selection, now, until).await {
+                Ok(events) => {
+                    println!("[Calendar] ✓ Fetched {} events from CalDAV", events.len());
+                    all_events.extend(events);
+                }
+                Err(e) => {
+                    eprintln!("[Calendar] ✗ Error fetching CalDAV events: {}", e);
+                    all_events.extend(previous_with_prefix("caldav_"));
+                    reached_all = false;
                 }
             }
         }
 
-        println!("[Calendar] Total events after refresh: {}", all_events.len());
-        *self.cached_events.write().await = all_events;
-        *self.last_fetch.write().await = Some(Utc::now());
+        // Poll .ics subscriptions
+        let feeds = IcsFeedsConfig::load(&self.data_dir)?;
+        match ics_feeds::fetch_selected_events(&self.http_client, &feeds, &selection, now, until).await {
+            Ok(events) => all_events.extend(events),
+            Err(e) => {
+                eprintln!("[Calendar] ✗ Error fetching .ics subscriptions: {}", e);
+                all_events.extend(previous_with_prefix("ics_"));
+                reached_all = false;
+            }
+        }
+
+        let events = calendar_sync::in_cache_window(calendar_selection::merge_events(all_events), now);
+        let diff = CalendarDiff::between(&previous, &events);
+        println!(
+            "[Calendar] Total events after sync: {} ({} added, {} updated, {} removed)",
+            events.len(), diff.added.len(), diff.updated.len(), diff.deleted.len()
+        );
+        *self.cached_events.write().await = events.clone();
+        let last_fetch = {
+            let mut last_fetch = self.last_fetch.write().await;
+            if reached_all {
+                *last_fetch = Some(now);
+            }
+            *last_fetch
+        };
+        if let Err(e) = (CalendarCache { events, last_fetch }).save(&self.data_dir) {
+            eprintln!("[Calendar] Failed to save calendar cache: {}", e);
+        }
+
+        if !diff.is_empty() {
+            self.emit("calendar-changed", diff.clone());
+            meeting_reminders::rearm();
+        }
+        Ok(diff)
+    }
+
+    /// Calendars of every connected provider, marked with whether they're included
+    pub async fn list_calendars(&self) -> Result<Vec<CalendarInfo>, String> {
+        let selection = self.selection.read().await.clone();
+        let mut calendars = Vec::new();
+
+        for provider in [CalendarProvider::Google, CalendarProvider::Microsoft] {
+            let token_data = match Self::get_token(provider).await {
+                Ok(token_data) => token_data,
+                Err(_) => continue,
+            };
+            let listed = if provider == CalendarProvider::Google {
+                Self::list_google_calendars_internal(&self.http_client, &token_data).await
+            } else {
+                Self::list_microsoft_calendars_internal(&self.http_client, &token_data).await
+            };
+            match listed {
+                Ok(listed) => calendars.extend(listed),
+                Err(e) => eprintln!("[Calendar] ✗ Failed to list {:?} calendars: {}", provider, e),
+            }
+        }
+
+        if let Ok(account) = CalDavAccount::from_keyring() {
+            match caldav::discover_calendars(&self.http_client, &account).await {
+                Ok(listed) => calendars.extend(listed),
+                Err(e) => eprintln!("[Calendar] ✗ Failed to list CalDAV calendars: {}", e),
+            }
+        }
+        calendars.extend(IcsFeedsConfig::load(&self.data_dir)?.feeds.iter().map(IcsFeed::calendar_info));
+
+        for calendar in calendars.iter_mut() {
+            calendar.selected = selection.includes(calendar);
+        }
+        Ok(calendars)
+    }
+
+    /// Choose which of a provider's calendars to include, then refetch events
+    pub async fn set_calendar_selection(&self, provider: CalendarProvider, calendar_ids: Vec<String>) -> Result<(), String> {
+        {
+            let mut selection = self.selection.write().await;
+            selection.set(provider, calendar_ids);
+            selection.save(&self.data_dir)?;
+            println!("[Calendar] Including {:?} calendars: {:?}", provider, selection.calendars.get(&provider));
+        }
+        self.refresh_events().await
+    }
+
+    /// Sign in to a CalDAV server; the account is only saved once its calendars can be listed
+    pub async fn connect_caldav(&self, account: CalDavAccount) -> Result<Vec<CalendarInfo>, String> {
+        account.validate()?;
+        let calendars = caldav::discover_calendars(&self.http_client, &account).await?;
+        account.store()?;
+        self.authenticated_providers.write().await.insert(CalendarProvider::CalDav, true);
+        println!("[Calendar] ✓ Connected CalDAV account {} ({} calendars)", account.username, calendars.len());
+        self.refresh_events().await?;
+        Ok(calendars)
+    }
 
-        println!("[Calendar] ✓ Events cached successfully");
+    pub async fn disconnect_caldav(&self) -> Result<(), String> {
+        CalDavAccount::forget()?;
+        self.authenticated_providers.write().await.remove(&CalendarProvider::CalDav);
+        self.cached_events.write().await.retain(|e| !e.id.starts_with("caldav_"));
+        self.save_cache().await;
         Ok(())
     }
 
+    pub fn list_ics_feeds(&self) -> Result<Vec<IcsFeed>, String> {
+        Ok(IcsFeedsConfig::load(&self.data_dir)?.feeds)
+    }
+
+    /// Subscribe to an .ics feed, checking that it parses first
+    /// The name and color default to the ones the feed advertises
+    pub async fn add_ics_feed(&self, url: &str, name: Option<String>, color: Option<String>) -> Result<IcsFeed, String> {
+        let url = ics_feeds::normalize_url(url)?;
+        let probe = IcsFeed { name: String::new(), url: url.clone(), color: None };
+        let text = probe.download(&self.http_client).await?;
+        let now = Utc::now();
+        ics::parse_events(&text, now, now + Duration::days(CACHE_WINDOW_DAYS))?;
+
+        let (feed_name, feed_color) = ics::calendar_properties(&text);
+        let feed = IcsFeed {
+            name: name
+                .filter(|n| !n.trim().is_empty())
+                .or(feed_name)
+                .unwrap_or_else(|| url::Url::parse(&url).ok().and_then(|u| u.host_str().map(String::from)).unwrap_or_else(|| url.clone())),
+            url,
+            color: color.or(feed_color),
+        };
+
+        let mut config = IcsFeedsConfig::load(&self.data_dir)?;
+        config.upsert(feed.clone());
+        config.save(&self.data_dir)?;
+        println!("[Calendar] ✓ Subscribed to calendar feed '{}'", feed.name);
+        self.refresh_events().await?;
+        Ok(feed)
+    }
+
+    pub async fn remove_ics_feed(&self, url: &str) -> Result<(), String> {
+        let mut config = IcsFeedsConfig::load(&self.data_dir)?;
+        if !config.remove(url) {
+            return Err(format!("Not subscribed to {}", url));
+        }
+        config.save(&self.data_dir)?;
+        self.refresh_events().await
+    }
+
     /// Reschedule a calendar event by moving it forward by specified minutes
     pub async fn reschedule_event(&self, event_id: String, minutes_offset: i64) -> Result<CalendarEvent, String> {
         println!("[Calendar] Rescheduling event {} by {} minutes", event_id, minutes_offset);
@@ -1048,17 +1619,19 @@ impl CalendarService {
         println!("[Calendar] New time: {} - {}", new_start, new_end);
         
         // Determine provider based on event ID
-        if event_id.starts_with("microsoft_") {
+        if event_id.starts_with("caldav_") || event_id.starts_with("ics_") {
+            return Err("Events from CalDAV and subscribed calendars can't be rescheduled from here".to_string());
+        } else if event_id.starts_with("microsoft_") {
             // Microsoft Calendar event - strip the prefix
             let actual_id = event_id.strip_prefix("microsoft_").unwrap();
             self.reschedule_microsoft_event(actual_id, new_start, new_end).await?;
         } else if event_id.starts_with("google_") {
             // Google Calendar event - strip the prefix
             let actual_id = event_id.strip_prefix("google_").unwrap();
-            self.reschedule_google_event(actual_id, new_start, new_end).await?;
+            self.reschedule_google_event(event.calendar_id.as_deref(), actual_id, new_start, new_end).await?;
         } else {
             // Assume it's a Google Calendar event ID without prefix (for backward compatibility)
-            self.reschedule_google_event(&event_id, new_start, new_end).await?;
+            self.reschedule_google_event(event.calendar_id.as_deref(), &event_id, new_start, new_end).await?;
         }
         
         // Refresh events to get updated data
@@ -1077,8 +1650,135 @@ impl CalendarService {
         Ok(updated_event)
     }
 
-    /// Reschedule a Google Calendar event
-    async fn reschedule_google_event(&self, event_id: &str, new_start: DateTime<Utc>, new_end: DateTime<Utc>) -> Result<(), String> {
+    /// Create an event on the user's primary calendar
+    /// Uses Google Calendar if it's connected, otherwise Microsoft
+    pub async fn create_event(
+        &self,
+        title: &str,
+        description: Option<&str>,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<CalendarEvent, String> {
+        let id = if self.is_authenticated(CalendarProvider::Google).await {
+            format!("google_{}", self.create_google_event(title, description, start, end).await?)
+        } else if self.is_authenticated(CalendarProvider::Microsoft).await {
+            format!("microsoft_{}", self.create_microsoft_event(title, description, start, end).await?)
+        } else {
+            return Err("No calendar connected".to_string());
+        };
+
+        let event = CalendarEvent {
+            id,
+            title: title.to_string(),
+            start_time: start,
+            end_time: end,
+            description: description.map(|d| d.to_string()),
+            ..Default::default()
+        };
+
+        // Add to the cache right away so planners and reminders see it before the next refresh
+        self.cached_events.write().await.push(event.clone());
+        self.save_cache().await;
+
+        println!("[Calendar] ✓ Created event '{}' ({})", event.title, event.id);
+        Ok(event)
+    }
+
+    /// Create a Google Calendar event, returning its id
+    async fn create_google_event(&self, title: &str, description: Option<&str>, start: DateTime<Utc>, end: DateTime<Utc>) -> Result<String, String> {
+        let token_data = Self::get_token(CalendarProvider::Google).await?;
+
+        let payload = serde_json::json!({
+            "summary": title,
+            "description": description,
+            "start": {
+                "dateTime": start.to_rfc3339(),
+                "timeZone": "UTC"
+            },
+            "end": {
+                "dateTime": end.to_rfc3339(),
+                "timeZone": "UTC"
+            }
+        });
+
+        let response = self.http_client
+            .post("https://www.googleapis.com/calendar/v3/calendars/primary/events")
+            .bearer_auth(&token_data.access_token)
+            .json(&payload)
+            .send()
+            .await
+            .map_err(|e| format!("Failed to create Google Calendar event: {}", e))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_else(|_| "Unable to read error response".to_string());
+            return Err(format!(
+                "Google Calendar API error: {} - {}",
+                status, error_text
+            ));
+        }
+
+        let created: serde_json::Value = response
+            .json()
+            .await
+            .map_err(|e| format!("Failed to parse Google Calendar response: {}", e))?;
+
+        created["id"]
+            .as_str()
+            .map(|id| id.to_string())
+            .ok_or_else(|| "Google Calendar response has no event id".to_string())
+    }
+
+    /// Create a Microsoft Calendar event, returning its id
+    async fn create_microsoft_event(&self, title: &str, description: Option<&str>, start: DateTime<Utc>, end: DateTime<Utc>) -> Result<String, String> {
+        let token_data = Self::get_token(CalendarProvider::Microsoft).await?;
+
+        let payload = serde_json::json!({
+            "subject": title,
+            "body": {
+                "contentType": "text",
+                "content": description.unwrap_or_default()
+            },
+            "start": {
+                "dateTime": start.to_rfc3339(),
+                "timeZone": "UTC"
+            },
+            "end": {
+                "dateTime": end.to_rfc3339(),
+                "timeZone": "UTC"
+            }
+        });
+
+        let response = self.http_client
+            .post("https://graph.microsoft.com/v1.0/me/events")
+            .bearer_auth(&token_data.access_token)
+            .json(&payload)
+            .send()
+            .await
+            .map_err(|e| format!("Failed to create Microsoft Calendar event: {}", e))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_else(|_| "Unable to read error response".to_string());
+            return Err(format!(
+                "Microsoft Graph API error: {} - {}",
+                status, error_text
+            ));
+        }
+
+        let created: serde_json::Value = response
+            .json()
+            .await
+            .map_err(|e| format!("Failed to parse Microsoft Graph response: {}", e))?;
+
+        created["id"]
+            .as_str()
+            .map(|id| id.to_string())
+            .ok_or_else(|| "Microsoft Graph response has no event id".to_string())
+    }
+
+    /// Reschedule a Google Calendar event on the calendar it came from (primary if unknown)
+    async fn reschedule_google_event(&self, calendar_id: Option<&str>, event_id: &str, new_start: DateTime<Utc>, new_end: DateTime<Utc>) -> Result<(), String> {
         let token_data = Self::get_token(CalendarProvider::Google).await?;
         
         // Format times in RFC3339 format for Google Calendar API
@@ -1086,7 +1786,8 @@ impl CalendarService {
         let end_time_str = new_end.to_rfc3339();
         
         let url = format!(
-            "https://www.googleapis.com/calendar/v3/calendars/primary/events/{}",
+            "https://www.googleapis.com/calendar/v3/calendars/{}/events/{}",
+            urlencoding::encode(calendar_id.unwrap_or("primary")),
             event_id
         );
         
@@ -1196,3 +1897,98 @@ pub async fn get_calendar_service() -> Arc<Mutex<CalendarService>> {
         service
     }).clone()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn calendar(provider: CalendarProvider) -> CalendarInfo {
+        CalendarInfo { id: "work".to_string(), name: "Work".to_string(), ..CalendarInfo::fallback_primary(provider) }
+    }
+
+    #[test]
+    fn test_google_events_carry_people_and_meeting_details() {
+        let event: GoogleEvent = serde_json::from_str(
+            r#"{
+                "id": "abc_20240614T090000Z",
+                "summary": "Design review",
+                "start": { "dateTime": "2024-06-14T11:00:00+02:00", "timeZone": "Europe/Berlin" },
+                "end": { "dateTime": "2024-06-14T12:00:00+02:00", "timeZone": "Europe/Berlin" },
+                "organizer": { "email": "jane@example.com", "displayName": "Jane Doe" },
+                "attendees": [
+                    { "email": "jane@example.com", "organizer": true, "responseStatus": "accepted" },
+                    { "email": "me@example.com", "self": true, "responseStatus": "tentative" },
+                    { "email": "bob@example.com", "optional": true },
+                    { "email": "c_room@resource.calendar.google.com", "resource": true, "responseStatus": "accepted" }
+                ],
+                "conferenceData": { "entryPoints": [
+                    { "entryPointType": "phone", "uri": "tel:+1-555-0100" },
+                    { "entryPointType": "video", "uri": "https://meet.google.com/abc-defg-hij" }
+                ] },
+                "recurringEventId": "abc",
+                "visibility": "private"
+            }"#,
+        )
+        .unwrap();
+        let event = CalendarService::google_event_to_calendar_event(event, &calendar(CalendarProvider::Google)).unwrap();
+
+        assert_eq!(event.start_time, DateTime::parse_from_rfc3339("2024-06-14T09:00:00Z").unwrap());
+        assert!(!event.is_organizer);
+        assert_eq!(event.organizer, Some(EventPerson { email: "jane@example.com".to_string(), name: Some("Jane Doe".to_string()) }));
+        assert_eq!(
+            event.attendees.iter().map(|a| (a.email.as_str(), a.response)).collect::<Vec<_>>(),
+            vec![
+                ("jane@example.com", ResponseStatus::Accepted),
+                ("me@example.com", ResponseStatus::Tentative),
+                ("bob@example.com", ResponseStatus::NeedsAction),
+            ]
+        );
+        assert!(event.attendees[2].optional);
+        assert_eq!(event.my_response, Some(ResponseStatus::Tentative));
+        assert_eq!(event.conference_url.as_deref(), Some("https://meet.google.com/abc-defg-hij"));
+        assert_eq!(event.time_zone.as_deref(), Some("Europe/Berlin"));
+        assert_eq!(event.recurrence_id.as_deref(), Some("google_abc"));
+        assert_eq!(event.visibility, Visibility::Private);
+    }
+
+    #[test]
+    fn test_microsoft_events_carry_people_and_meeting_details() {
+        let event: MicrosoftEvent = serde_json::from_str(
+            r#"{
+                "id": "AAMk1",
+                "subject": "Quarterly planning",
+                "start": { "dateTime": "2024-06-14T09:00:00.0000000", "timeZone": "UTC" },
+                "end": { "dateTime": "2024-06-14T10:00:00.0000000", "timeZone": "UTC" },
+                "location": { "displayName": "" },
+                "isOrganizer": false,
+                "organizer": { "emailAddress": { "name": "Jane Doe", "address": "jane@example.com" } },
+                "attendees": [
+                    { "type": "required", "status": { "response": "accepted" }, "emailAddress": { "name": "Bob", "address": "bob@example.com" } },
+                    { "type": "optional", "status": { "response": "none" }, "emailAddress": { "address": "carol@example.com" } },
+                    { "type": "resource", "status": { "response": "accepted" }, "emailAddress": { "name": "Room 4", "address": "room4@example.com" } }
+                ],
+                "responseStatus": { "response": "declined" },
+                "onlineMeeting": { "joinUrl": "https://teams.microsoft.com/l/meetup-join/19%3ameeting_abc" },
+                "onlineMeetingUrl": null,
+                "originalStartTimeZone": "W. Europe Standard Time",
+                "seriesMasterId": "AAMk0",
+                "sensitivity": "confidential"
+            }"#,
+        )
+        .unwrap();
+        let event = CalendarService::microsoft_event_to_calendar_event(event, &calendar(CalendarProvider::Microsoft));
+
+        assert_eq!(event.start_time, DateTime::parse_from_rfc3339("2024-06-14T09:00:00Z").unwrap());
+        assert_eq!(event.location, None);
+        assert_eq!(event.organizer.as_ref().map(|o| o.email.as_str()), Some("jane@example.com"));
+        assert_eq!(
+            event.attendees.iter().map(|a| (a.email.as_str(), a.response, a.optional)).collect::<Vec<_>>(),
+            vec![("bob@example.com", ResponseStatus::Accepted, false), ("carol@example.com", ResponseStatus::NeedsAction, true)]
+        );
+        assert_eq!(event.my_response, Some(ResponseStatus::Declined));
+        assert_eq!(event.conference_url.as_deref(), Some("https://teams.microsoft.com/l/meetup-join/19%3ameeting_abc"));
+        assert_eq!(event.time_zone.as_deref(), Some("W. Europe Standard Time"));
+        assert_eq!(event.recurrence_id.as_deref(), Some("microsoft_AAMk0"));
+        assert_eq!(event.visibility, Visibility::Confidential);
+    }
+}
@@ -0,0 +1,231 @@
+// Incremental calendar sync
+// Each Google or Microsoft calendar keeps its events by id plus the provider's cursor (Google's
+// nextSyncToken, Graph's deltaLink). A poll only fetches what changed since the cursor. The first
+// sync, and any resync after the provider drops the cursor (410 Gone), fetches SYNC_HORIZON_DAYS
+// ahead; once that horizon gets within the cache window we resync so new days come into view.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use crate::models::action::CalendarEvent;
+
+/// How far ahead the cache (and so the overlay) looks
+pub const CACHE_WINDOW_DAYS: i64 = 7;
+/// How far ahead a full sync fetches, so most polls can be incremental
+pub const SYNC_HORIZON_DAYS: i64 = 28;
+
+// One change reported by the provider
+#[derive(Debug, Clone, PartialEq)]
+pub enum EventChange {
+    Upsert(Box<CalendarEvent>),
+    Delete(String), // Event id, with provider prefix
+}
+
+// What a provider returned for one calendar
+#[derive(Debug, Clone, PartialEq)]
+pub enum SyncBatch {
+    // Every event up to the horizon; replaces what we had
+    Full { events: Vec<CalendarEvent>, cursor: Option<String>, horizon_end: DateTime<Utc> },
+    // Changes since the last cursor
+    Delta { changes: Vec<EventChange>, cursor: Option<String> },
+    // The provider no longer knows our cursor (410 Gone): do a full sync
+    CursorExpired,
+}
+
+impl SyncBatch {
+    /// A full sync comes back as a list of changes too; keep just the events
+    pub fn full(changes: Vec<EventChange>, cursor: Option<String>, horizon_end: DateTime<Utc>) -> Self {
+        let events = changes
+            .into_iter()
+            .filter_map(|change| match change {
+                EventChange::Upsert(event) => Some(*event),
+                EventChange::Delete(_) => None,
+            })
+            .collect();
+        SyncBatch::Full { events, cursor, horizon_end }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CalendarSyncState {
+    pub cursor: Option<String>,
+    pub horizon_end: Option<DateTime<Utc>>,
+    pub events: BTreeMap<String, CalendarEvent>,
+}
+
+impl CalendarSyncState {
+    /// No cursor yet, or the horizon is about to fall inside the cache window
+    pub fn needs_full_sync(&self, now: DateTime<Utc>) -> bool {
+        match (&self.cursor, self.horizon_end) {
+            (Some(_), Some(horizon_end)) => horizon_end < now + Duration::days(CACHE_WINDOW_DAYS),
+            _ => true,
+        }
+    }
+
+    /// Apply a batch; returns false if the caller should come back with a full sync
+    pub fn apply(&mut self, batch: SyncBatch, now: DateTime<Utc>) -> bool {
+        match batch {
+            SyncBatch::Full { events, cursor, horizon_end } => {
+                self.events = events.into_iter().map(|e| (e.id.clone(), e)).collect();
+                self.cursor = cursor;
+                self.horizon_end = Some(horizon_end);
+            }
+            SyncBatch::Delta { changes, cursor } => {
+                let horizon_end = self.horizon_end.unwrap_or(now);
+                for change in changes {
+                    match change {
+                        // Google reports changes anywhere in the calendar; keep only what's in range
+                        EventChange::Upsert(event) if event.end_time > now && event.start_time < horizon_end => {
+                            self.events.insert(event.id.clone(), *event);
+                        }
+                        EventChange::Upsert(event) => {
+                            self.events.remove(&event.id);
+                        }
+                        EventChange::Delete(id) => {
+                            self.events.remove(&id);
+                        }
+                    }
+                }
+                if cursor.is_some() {
+                    self.cursor = cursor;
+                }
+            }
+            SyncBatch::CursorExpired => {
+                self.cursor = None;
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Events that belong in the cache: not over yet and starting within the window
+pub fn in_cache_window(events: Vec<CalendarEvent>, now: DateTime<Utc>) -> Vec<CalendarEvent> {
+    let window_end = now + Duration::days(CACHE_WINDOW_DAYS);
+    events
+        .into_iter()
+        .filter(|e| e.end_time > now && e.start_time < window_end)
+        .collect()
+}
+
+// Payload of the calendar-changed event
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CalendarDiff {
+    pub added: Vec<CalendarEvent>,
+    pub updated: Vec<CalendarEvent>,
+    pub deleted: Vec<String>, // Ids of events that left the cache
+}
+
+impl CalendarDiff {
+    pub fn between(old: &[CalendarEvent], new: &[CalendarEvent]) -> Self {
+        let before: HashMap<&str, &CalendarEvent> = old.iter().map(|e| (e.id.as_str(), e)).collect();
+        let after: HashMap<&str, &CalendarEvent> = new.iter().map(|e| (e.id.as_str(), e)).collect();
+        let mut diff = CalendarDiff::default();
+        for event in new {
+            match before.get(event.id.as_str()) {
+                None => diff.added.push(event.clone()),
+                Some(previous) if *previous != event => diff.updated.push(event.clone()),
+                Some(_) => {}
+            }
+        }
+        diff.deleted = old
+            .iter()
+            .filter(|e| !after.contains_key(e.id.as_str()))
+            .map(|e| e.id.clone())
+            .collect();
+        diff
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.updated.is_empty() && self.deleted.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_support::{self, utc};
+
+    // A half-hour event
+    fn event(id: &str, title: &str, start: &str) -> CalendarEvent {
+        CalendarEvent {
+            title: title.to_string(),
+            end_time: utc(start) + Duration::minutes(30),
+            ..test_support::event(id, start, start)
+        }
+    }
+
+    #[test]
+    fn test_deltas_merge_into_the_synced_calendar() {
+        let now = utc("2024-06-14T08:00:00Z");
+        let mut state = CalendarSyncState::default();
+        assert!(state.needs_full_sync(now));
+
+        assert!(state.apply(
+            SyncBatch::Full {
+                events: vec![event("google_a", "Standup", "2024-06-14T09:00:00Z"), event("google_b", "1:1", "2024-06-15T09:00:00Z")],
+                cursor: Some("sync-1".to_string()),
+                horizon_end: now + Duration::days(SYNC_HORIZON_DAYS),
+            },
+            now
+        ));
+        assert!(!state.needs_full_sync(now));
+
+        assert!(state.apply(
+            SyncBatch::Delta {
+                changes: vec![
+                    EventChange::Upsert(Box::new(event("google_a", "Standup (moved)", "2024-06-14T10:00:00Z"))),
+                    EventChange::Delete("google_b".to_string()),
+                    EventChange::Upsert(Box::new(event("google_c", "Planning", "2024-06-20T09:00:00Z"))),
+                    // Beyond the horizon: not kept
+                    EventChange::Upsert(Box::new(event("google_d", "Offsite", "2024-09-01T09:00:00Z"))),
+                ],
+                cursor: Some("sync-2".to_string()),
+            },
+            now
+        ));
+        let titles: Vec<&str> = state.events.values().map(|e| e.title.as_str()).collect();
+        assert_eq!(titles, vec!["Standup (moved)", "Planning"]);
+        assert_eq!(state.cursor.as_deref(), Some("sync-2"));
+
+        // 410 Gone drops the cursor so the next poll does a full sync
+        assert!(!state.apply(SyncBatch::CursorExpired, now));
+        assert!(state.needs_full_sync(now));
+        // So does the horizon coming within a week
+        let mut state = CalendarSyncState { cursor: Some("sync-3".to_string()), horizon_end: Some(now + Duration::days(6)), ..Default::default() };
+        assert!(state.needs_full_sync(now));
+        state.horizon_end = Some(now + Duration::days(8));
+        assert!(!state.needs_full_sync(now));
+    }
+
+    #[test]
+    fn test_diff_reports_only_real_changes() {
+        let now = utc("2024-06-14T08:00:00Z");
+        let old = in_cache_window(
+            vec![
+                event("google_a", "Standup", "2024-06-14T09:00:00Z"),
+                event("google_b", "1:1", "2024-06-15T09:00:00Z"),
+                event("google_old", "Yesterday", "2024-06-13T09:00:00Z"),
+            ],
+            now,
+        );
+        assert_eq!(old.len(), 2);
+        assert!(CalendarDiff::between(&old, &old.clone()).is_empty());
+
+        let new = in_cache_window(
+            vec![
+                event("google_a", "Standup", "2024-06-14T09:00:00Z"),
+                event("google_b", "1:1 (room change)", "2024-06-15T09:00:00Z"),
+                event("microsoft_c", "Review", "2024-06-16T09:00:00Z"),
+            ],
+            now,
+        );
+        let diff = CalendarDiff::between(&old, &new);
+        assert_eq!(diff.added.iter().map(|e| e.id.as_str()).collect::<Vec<_>>(), vec!["microsoft_c"]);
+        assert_eq!(diff.updated.iter().map(|e| e.id.as_str()).collect::<Vec<_>>(), vec!["google_b"]);
+        assert!(diff.deleted.is_empty());
+
+        let diff = CalendarDiff::between(&new, &new[..1]);
+        assert_eq!(diff.deleted, vec!["google_b".to_string(), "microsoft_c".to_string()]);
+    }
+}
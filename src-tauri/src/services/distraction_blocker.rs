@@ -0,0 +1,379 @@
+// Distraction blocker
+// Matches the foreground window against a blocklist during focus sessions and decides how to react.
+// Window access goes through the WindowSource trait so the rules can be tested without a window system.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use crate::models::window::WindowInfo;
+use crate::services::window_tracker::WindowTracker;
+use crate::utils::app_data;
+
+const DISTRACTION_CONFIG_FILE: &str = "distraction_blocker.json";
+
+// What to do when a blocklisted window gains focus
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DistractionPolicy {
+    LogOnly,     // Emit the event and log the attempt
+    Minimize,    // Also minimize the offending window
+    ShowOverlay, // Also bring the Pause Menu overlay back
+}
+
+// A blocklist entry; patterns support `*` wildcards and are case-insensitive
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DistractionRule {
+    #[serde(default)]
+    pub process_name: Option<String>, // e.g. "slack" or "discord*" (".exe" is ignored)
+    #[serde(default)]
+    pub title_pattern: Option<String>, // e.g. "*YouTube*"
+}
+
+impl DistractionRule {
+    pub fn matches(&self, window: &WindowInfo) -> bool {
+        if self.process_name.is_none() && self.title_pattern.is_none() {
+            return false;
+        }
+
+        let process_ok = self
+            .process_name
+            .as_ref()
+            .map(|pattern| glob_match(&normalize_process_name(pattern), &normalize_process_name(&window.process_name)))
+            .unwrap_or(true);
+        let title_ok = self
+            .title_pattern
+            .as_ref()
+            .map(|pattern| glob_match(&pattern.to_lowercase(), &window.title.to_lowercase()))
+            .unwrap_or(true);
+
+        process_ok && title_ok
+    }
+
+    pub fn describe(&self) -> String {
+        match (&self.process_name, &self.title_pattern) {
+            (Some(p), Some(t)) => format!("{} ({})", p, t),
+            (Some(p), None) => p.clone(),
+            (None, Some(t)) => t.clone(),
+            (None, None) => String::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DistractionConfig {
+    pub enabled: bool,
+    pub policy: DistractionPolicy,
+    pub rules: Vec<DistractionRule>,
+}
+
+impl Default for DistractionConfig {
+    fn default() -> Self {
+        let process = |name: &str| DistractionRule {
+            process_name: Some(name.to_string()),
+            title_pattern: None,
+        };
+        let title = |pattern: &str| DistractionRule {
+            process_name: None,
+            title_pattern: Some(pattern.to_string()),
+        };
+
+        Self {
+            enabled: false,
+            policy: DistractionPolicy::LogOnly,
+            rules: vec![
+                process("slack"),
+                process("discord"),
+                process("telegram"),
+                process("whatsapp"),
+                title("*YouTube*"),
+                title("*Reddit*"),
+                title("*Netflix*"),
+            ],
+        }
+    }
+}
+
+impl DistractionConfig {
+    /// Find the rule a window breaks, unless the app is on the session's allowlist
+    pub fn find_match(&self, window: &WindowInfo, allowlist: &[String]) -> Option<&DistractionRule> {
+        if !self.enabled {
+            return None;
+        }
+
+        let process = normalize_process_name(&window.process_name);
+        if allowlist.iter().any(|allowed| glob_match(&normalize_process_name(allowed), &process)) {
+            return None;
+        }
+
+        self.rules.iter().find(|rule| rule.matches(window))
+    }
+
+    pub fn load(data_dir: &Path) -> Result<Self, String> {
+        Ok(app_data::read_json(&config_path(data_dir))?.unwrap_or_default())
+    }
+
+    pub fn save(&self, data_dir: &Path) -> Result<(), String> {
+        app_data::write_json(&config_path(data_dir), self)
+    }
+}
+
+fn config_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(DISTRACTION_CONFIG_FILE)
+}
+
+// Payload of the focus-distraction event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DistractionEvent {
+    pub session_id: String,
+    pub window_title: String,
+    pub process_name: String,
+    pub rule: String,
+    pub policy: DistractionPolicy,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Access to the window system
+pub trait WindowSource: Send + Sync {
+    /// The foreground window, if it changed since the last call
+    fn focus_change(&self) -> Result<Option<WindowInfo>, String>;
+    fn minimize(&self, handle: isize) -> Result<(), String>;
+}
+
+impl WindowSource for WindowTracker {
+    fn focus_change(&self) -> Result<Option<WindowInfo>, String> {
+        self.track_focus_changes()
+    }
+
+    fn minimize(&self, handle: isize) -> Result<(), String> {
+        self.minimize_window(handle)
+    }
+}
+
+/// Check the foreground window once and apply the policy if it's a distraction
+/// Returns the event to emit; showing the overlay is left to the caller since it needs the AppHandle
+pub fn check_for_distraction(
+    source: &dyn WindowSource,
+    config: &DistractionConfig,
+    session_id: &str,
+    allowlist: &[String],
+) -> Result<Option<DistractionEvent>, String> {
+    let window = match source.focus_change()? {
+        Some(window) => window,
+        None => return Ok(None),
+    };
+
+    let rule = match config.find_match(&window, allowlist) {
+        Some(rule) => rule,
+        None => return Ok(None),
+    };
+
+    println!("[Distraction] Blocked app gained focus: {} ({}) - rule: {}", window.title, window.process_name, rule.describe());
+
+    if config.policy == DistractionPolicy::Minimize {
+        if let Err(e) = source.minimize(window.handle) {
+            eprintln!("[Distraction] Failed to minimize window: {}", e);
+        }
+    }
+
+    Ok(Some(DistractionEvent {
+        session_id: session_id.to_string(),
+        window_title: window.title,
+        process_name: window.process_name,
+        rule: rule.describe(),
+        policy: config.policy,
+        timestamp: Utc::now(),
+    }))
+}
+
+fn normalize_process_name(name: &str) -> String {
+    let name = name.trim().to_lowercase();
+    name.strip_suffix(".exe").map(|s| s.to_string()).unwrap_or(name)
+}
+
+/// Match `text` against a pattern where `*` matches any run of characters
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let parts: Vec<&str> = pattern.split('*').collect();
+    if parts.len() == 1 {
+        return pattern == text;
+    }
+
+    let mut rest = text;
+    for (i, part) in parts.iter().enumerate() {
+        if part.is_empty() {
+            continue;
+        }
+        if i == 0 {
+            match rest.strip_prefix(part) {
+                Some(r) => rest = r,
+                None => return false,
+            }
+        } else if i == parts.len() - 1 {
+            return rest.ends_with(part);
+        } else {
+            match rest.find(part) {
+                Some(pos) => rest = &rest[pos + part.len()..],
+                None => return false,
+            }
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+pub mod mock {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Scripted window source: each call to focus_change pops the next window
+    #[derive(Default)]
+    pub struct MockWindowSource {
+        pub focus_changes: Mutex<Vec<Option<WindowInfo>>>,
+        pub minimized: Mutex<Vec<isize>>,
+    }
+
+    impl MockWindowSource {
+        pub fn with_changes(changes: Vec<Option<WindowInfo>>) -> Self {
+            let mut changes = changes;
+            changes.reverse();
+            Self {
+                focus_changes: Mutex::new(changes),
+                minimized: Mutex::new(Vec::new()),
+            }
+        }
+    }
+
+    impl WindowSource for MockWindowSource {
+        fn focus_change(&self) -> Result<Option<WindowInfo>, String> {
+            Ok(self.focus_changes.lock().unwrap().pop().flatten())
+        }
+
+        fn minimize(&self, handle: isize) -> Result<(), String> {
+            self.minimized.lock().unwrap().push(handle);
+            Ok(())
+        }
+    }
+
+    pub fn window(handle: isize, process_name: &str, title: &str) -> WindowInfo {
+        WindowInfo {
+            handle,
+            title: title.to_string(),
+            process_name: process_name.to_string(),
+            executable_path: String::new(),
+            last_active: Utc::now(),
+            is_visible: true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::mock::{window, MockWindowSource};
+    use super::*;
+
+    fn enabled_config(policy: DistractionPolicy) -> DistractionConfig {
+        DistractionConfig {
+            enabled: true,
+            policy,
+            ..DistractionConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("*youtube*", "cat videos - youtube - chrome"));
+        assert!(glob_match("discord*", "discordcanary"));
+        assert!(glob_match("*.pdf", "notes.pdf"));
+        assert!(!glob_match("slack", "slackware"));
+        assert!(glob_match("a*b*c", "a123b456c"));
+        assert!(!glob_match("a*b*c", "a123c456b"));
+    }
+
+    #[test]
+    fn test_rules_match_process_and_title() {
+        let config = enabled_config(DistractionPolicy::LogOnly);
+        assert!(config.find_match(&window(1, "Slack.exe", "general"), &[]).is_some());
+        assert!(config.find_match(&window(2, "chrome.exe", "Funny cats - YouTube"), &[]).is_some());
+        assert!(config.find_match(&window(3, "Code.exe", "main.rs"), &[]).is_none());
+
+        // The session allowlist wins over the blocklist
+        assert!(config.find_match(&window(1, "Slack.exe", "general"), &["slack".to_string()]).is_none());
+
+        let disabled = DistractionConfig::default();
+        assert!(disabled.find_match(&window(1, "Slack.exe", "general"), &[]).is_none());
+    }
+
+    #[test]
+    fn test_rule_with_both_fields_requires_both() {
+        let rule = DistractionRule {
+            process_name: Some("chrome".to_string()),
+            title_pattern: Some("*twitch*".to_string()),
+        };
+        assert!(rule.matches(&window(1, "chrome.exe", "Live on Twitch")));
+        assert!(!rule.matches(&window(1, "firefox.exe", "Live on Twitch")));
+        assert!(!rule.matches(&window(1, "chrome.exe", "Docs")));
+    }
+
+    // The real tracker's change detection, fed a scripted foreground window
+    struct ScriptedForeground {
+        tracker: WindowTracker,
+        foreground: std::sync::Mutex<Vec<WindowInfo>>,
+    }
+
+    impl WindowSource for ScriptedForeground {
+        fn focus_change(&self) -> Result<Option<WindowInfo>, String> {
+            let active = self.foreground.lock().unwrap().pop();
+            Ok(self.tracker.note_foreground(active))
+        }
+
+        fn minimize(&self, _handle: isize) -> Result<(), String> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_tracker_reports_new_windows_and_title_changes() {
+        let mut foreground = vec![
+            window(10, "discord.exe", "friends"),
+            window(20, "chrome.exe", "Docs"),
+            window(20, "chrome.exe", "Docs"),
+            window(20, "chrome.exe", "Funny cats - YouTube"),
+        ];
+        foreground.reverse();
+        let source = ScriptedForeground { tracker: WindowTracker::new(), foreground: std::sync::Mutex::new(foreground) };
+        let config = enabled_config(DistractionPolicy::LogOnly);
+
+        // A distracting app that opens and takes focus is caught the first time it's seen
+        let event = check_for_distraction(&source, &config, "s1", &[]).unwrap().unwrap();
+        assert_eq!(event.process_name, "discord.exe");
+        // Another window gains focus
+        assert_eq!(source.focus_change().unwrap().map(|w| w.handle), Some(20));
+        // Same window, same title: nothing to report
+        assert!(source.focus_change().unwrap().is_none());
+        // Same window, new title: the browser switched to a blocked tab
+        let event = check_for_distraction(&source, &config, "s1", &[]).unwrap().unwrap();
+        assert_eq!(event.window_title, "Funny cats - YouTube");
+    }
+
+    #[test]
+    fn test_policy_minimizes_only_when_configured() {
+        let source = MockWindowSource::with_changes(vec![
+            Some(window(10, "discord.exe", "friends")),
+            None,
+            Some(window(11, "Code.exe", "main.rs")),
+        ]);
+        let config = enabled_config(DistractionPolicy::Minimize);
+
+        let event = check_for_distraction(&source, &config, "s1", &[]).unwrap().unwrap();
+        assert_eq!(event.process_name, "discord.exe");
+        assert_eq!(event.rule, "discord");
+        assert!(check_for_distraction(&source, &config, "s1", &[]).unwrap().is_none());
+        assert!(check_for_distraction(&source, &config, "s1", &[]).unwrap().is_none());
+        assert_eq!(*source.minimized.lock().unwrap(), vec![10]);
+
+        let source = MockWindowSource::with_changes(vec![Some(window(12, "slack.exe", "general"))]);
+        let event = check_for_distraction(&source, &enabled_config(DistractionPolicy::LogOnly), "s1", &[]).unwrap().unwrap();
+        assert_eq!(event.policy, DistractionPolicy::LogOnly);
+        assert!(source.minimized.lock().unwrap().is_empty());
+    }
+}
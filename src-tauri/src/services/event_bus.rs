@@ -0,0 +1,485 @@
+// Focus event bus
+// Typed focus events (session started/paused/ended, distractions, mute changes) delivered to
+// sinks outside the webview: an HTTP webhook, a local command, or an append-only JSONL log.
+// Sinks live in event_bus.json. Each sink has its own queue, so events reach it in order and a
+// slow webhook never holds up the log; failed deliveries are retried with exponential backoff.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::mpsc;
+use crate::services::distraction_blocker::DistractionEvent;
+use crate::services::focus_history::FocusEndReason;
+use crate::services::focus_service::FocusSession;
+use crate::utils::app_data;
+use crate::utils::serde_defaults::default_true;
+
+const EVENT_BUS_FILE: &str = "event_bus.json";
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Every event kind, as used in `EventSink::events` and `PAUSE_MENU_EVENT`
+pub const EVENT_KINDS: &[&str] = &[
+    "session_started",
+    "session_paused",
+    "session_resumed",
+    "session_ended",
+    "distraction_detected",
+    "mute_toggled",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FocusBusEvent {
+    SessionStarted { session: FocusSession },
+    SessionPaused { session: FocusSession },
+    SessionResumed { session: FocusSession },
+    SessionEnded { session: FocusSession, reason: FocusEndReason },
+    DistractionDetected { distraction: DistractionEvent },
+    MuteToggled { muted: bool },
+}
+
+impl FocusBusEvent {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            FocusBusEvent::SessionStarted { .. } => "session_started",
+            FocusBusEvent::SessionPaused { .. } => "session_paused",
+            FocusBusEvent::SessionResumed { .. } => "session_resumed",
+            FocusBusEvent::SessionEnded { .. } => "session_ended",
+            FocusBusEvent::DistractionDetected { .. } => "distraction_detected",
+            FocusBusEvent::MuteToggled { .. } => "mute_toggled",
+        }
+    }
+
+    fn session_id(&self) -> Option<&str> {
+        match self {
+            FocusBusEvent::SessionStarted { session }
+            | FocusBusEvent::SessionPaused { session }
+            | FocusBusEvent::SessionResumed { session }
+            | FocusBusEvent::SessionEnded { session, .. } => Some(&session.id),
+            FocusBusEvent::DistractionDetected { distraction } => Some(&distraction.session_id),
+            FocusBusEvent::MuteToggled { .. } => None,
+        }
+    }
+}
+
+// What a sink receives: the event plus an id and timestamp, as one flat JSON object
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BusEnvelope {
+    pub id: String,
+    pub emitted_at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub event: FocusBusEvent,
+}
+
+impl BusEnvelope {
+    pub fn new(event: FocusBusEvent) -> Self {
+        let emitted_at = Utc::now();
+        Self {
+            id: format!("{}-{:08x}", emitted_at.timestamp_millis(), rand::random::<u32>()),
+            emitted_at,
+            event,
+        }
+    }
+
+    /// Environment handed to command sinks
+    fn env_vars(&self, json: &str) -> Vec<(&'static str, String)> {
+        let mut vars = vec![
+            ("PAUSE_MENU_EVENT", self.event.kind().to_string()),
+            ("PAUSE_MENU_EVENT_ID", self.id.clone()),
+            ("PAUSE_MENU_EVENT_TIME", self.emitted_at.to_rfc3339()),
+            ("PAUSE_MENU_EVENT_JSON", json.to_string()),
+        ];
+        if let Some(id) = self.event.session_id() {
+            vars.push(("PAUSE_MENU_SESSION_ID", id.to_string()));
+        }
+        if let FocusBusEvent::MuteToggled { muted } = self.event {
+            vars.push(("PAUSE_MENU_MUTED", muted.to_string()));
+        }
+        vars
+    }
+}
+
+// Where a sink delivers events
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SinkTarget {
+    // POSTs each event as JSON
+    Webhook {
+        url: String,
+        #[serde(default)]
+        headers: BTreeMap<String, String>,
+    },
+    // Runs a program per event with PAUSE_MENU_* environment variables; non-zero exit counts as a failure
+    Command {
+        program: String,
+        #[serde(default)]
+        args: Vec<String>,
+    },
+    // Appends one JSON object per line; relative paths are under the app data dir
+    JsonlLog { path: PathBuf },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,      // Including the first try
+    pub initial_backoff_ms: u64, // Doubles after every failed attempt
+    pub max_backoff_ms: u64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff_ms: 1_000,
+            max_backoff_ms: 60_000,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// How long to wait after the given failed attempt (1-based)
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u64.saturating_pow(attempt.saturating_sub(1));
+        Duration::from_millis(self.initial_backoff_ms.saturating_mul(factor).min(self.max_backoff_ms))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EventSink {
+    pub name: String,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub events: Vec<String>, // Event kinds to deliver; empty means all of them
+    #[serde(flatten)]
+    pub target: SinkTarget,
+    #[serde(default)]
+    pub retry: RetryPolicy,
+}
+
+impl EventSink {
+    pub fn accepts(&self, kind: &str) -> bool {
+        self.enabled && (self.events.is_empty() || self.events.iter().any(|e| e == kind))
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EventBusConfig {
+    pub sinks: Vec<EventSink>,
+}
+
+impl EventBusConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        for (i, sink) in self.sinks.iter().enumerate() {
+            if sink.name.trim().is_empty() {
+                return Err("Event sink name cannot be empty".to_string());
+            }
+            if self.sinks[..i].iter().any(|s| s.name == sink.name) {
+                return Err(format!("Duplicate event sink name: {}", sink.name));
+            }
+            if let Some(kind) = sink.events.iter().find(|e| !EVENT_KINDS.contains(&e.as_str())) {
+                return Err(format!("Unknown event kind for sink {}: {}", sink.name, kind));
+            }
+            if sink.retry.max_attempts == 0 {
+                return Err(format!("Event sink {} needs at least one attempt", sink.name));
+            }
+            match sink.target {
+                SinkTarget::Webhook { ref url, .. } => {
+                    if !url.starts_with("http://") && !url.starts_with("https://") {
+                        return Err(format!("Webhook URL must start with http:// or https://: {}", url));
+                    }
+                }
+                SinkTarget::Command { ref program, .. } => {
+                    if program.trim().is_empty() {
+                        return Err(format!("Event sink {} needs a program to run", sink.name));
+                    }
+                }
+                SinkTarget::JsonlLog { ref path } => {
+                    if path.as_os_str().is_empty() {
+                        return Err(format!("Event sink {} needs a log path", sink.name));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn load(data_dir: &Path) -> Result<Self, String> {
+        Ok(app_data::read_json(&config_path(data_dir))?.unwrap_or_default())
+    }
+
+    pub fn save(&self, data_dir: &Path) -> Result<(), String> {
+        app_data::write_json(&config_path(data_dir), self)
+    }
+}
+
+fn config_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(EVENT_BUS_FILE)
+}
+
+/// Publishes focus events to the configured sinks; cheap to clone
+#[derive(Clone)]
+pub struct EventBus {
+    data_dir: PathBuf,
+    config: Arc<RwLock<EventBusConfig>>,
+    client: reqwest::Client,
+    // One delivery queue per sink, started on first use and dropped when the config changes
+    workers: Arc<Mutex<HashMap<String, mpsc::UnboundedSender<BusEnvelope>>>>,
+}
+
+impl EventBus {
+    pub fn new(data_dir: &Path) -> Self {
+        let config = EventBusConfig::load(data_dir).unwrap_or_else(|e| {
+            eprintln!("[EventBus] Failed to load event bus config: {}", e);
+            EventBusConfig::default()
+        });
+        Self {
+            data_dir: data_dir.to_path_buf(),
+            config: Arc::new(RwLock::new(config)),
+            client: reqwest::Client::builder().timeout(WEBHOOK_TIMEOUT).build().unwrap_or_default(),
+            workers: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    pub fn config(&self) -> EventBusConfig {
+        self.config.read().map(|c| c.clone()).unwrap_or_default()
+    }
+
+    /// Validate, save and apply a new sink configuration
+    /// Events already queued for the old sinks are still delivered
+    pub fn set_config(&self, config: EventBusConfig) -> Result<(), String> {
+        config.validate()?;
+        config.save(&self.data_dir)?;
+        *self.config.write().map_err(|e| e.to_string())? = config;
+        if let Ok(mut workers) = self.workers.lock() {
+            workers.clear();
+        }
+        Ok(())
+    }
+
+    /// Queue an event for every sink that wants it; must be called from within the tokio runtime
+    pub fn publish(&self, event: FocusBusEvent) {
+        let config = self.config();
+        let kind = event.kind();
+        if !config.sinks.iter().any(|sink| sink.accepts(kind)) {
+            return;
+        }
+
+        let envelope = BusEnvelope::new(event);
+        let Ok(mut workers) = self.workers.lock() else { return };
+        for sink in config.sinks.iter().filter(|sink| sink.accepts(kind)) {
+            let sender = workers
+                .entry(sink.name.clone())
+                .or_insert_with(|| self.spawn_worker(sink.clone()));
+            if sender.send(envelope.clone()).is_err() {
+                eprintln!("[EventBus] Delivery queue for {} closed, dropping {}", sink.name, kind);
+            }
+        }
+    }
+
+    /// Deliver events to one sink in the order they were published
+    fn spawn_worker(&self, sink: EventSink) -> mpsc::UnboundedSender<BusEnvelope> {
+        let (sender, mut receiver) = mpsc::unbounded_channel::<BusEnvelope>();
+        let bus = self.clone();
+        tokio::spawn(async move {
+            while let Some(envelope) = receiver.recv().await {
+                if let Err(e) = bus.deliver_with_retry(&sink, &envelope).await {
+                    eprintln!("[EventBus] Gave up delivering {} to {}: {}", envelope.event.kind(), sink.name, e);
+                }
+            }
+        });
+        sender
+    }
+
+    /// Deliver an event, backing off between failed attempts
+    /// Returns how many attempts it took
+    pub async fn deliver_with_retry(&self, sink: &EventSink, envelope: &BusEnvelope) -> Result<u32, String> {
+        let max_attempts = sink.retry.max_attempts.max(1);
+        let mut attempt = 1;
+        loop {
+            match self.deliver(&sink.target, envelope).await {
+                Ok(()) => return Ok(attempt),
+                Err(e) if attempt >= max_attempts => return Err(e),
+                Err(e) => {
+                    let backoff = sink.retry.backoff(attempt);
+                    eprintln!(
+                        "[EventBus] {} failed for {} (attempt {}/{}): {} - retrying in {:?}",
+                        envelope.event.kind(), sink.name, attempt, max_attempts, e, backoff
+                    );
+                    tokio::time::sleep(backoff).await;
+                    attempt += 1;
+                }
+            }
+        }
+    }
+
+    async fn deliver(&self, target: &SinkTarget, envelope: &BusEnvelope) -> Result<(), String> {
+        let json = serde_json::to_string(envelope).map_err(|e| format!("Failed to serialize event: {}", e))?;
+        match target {
+            SinkTarget::Webhook { url, headers } => {
+                let mut request = self
+                    .client
+                    .post(url)
+                    .header("Content-Type", "application/json")
+                    .body(json);
+                for (name, value) in headers {
+                    request = request.header(name.as_str(), value.as_str());
+                }
+                let response = request.send().await.map_err(|e| format!("Webhook request failed: {}", e))?;
+                if !response.status().is_success() {
+                    return Err(format!("Webhook returned {}", response.status()));
+                }
+                Ok(())
+            }
+            SinkTarget::Command { program, args } => {
+                let mut command = tokio::process::Command::new(program);
+                command
+                    .args(args)
+                    .envs(envelope.env_vars(&json))
+                    .stdin(std::process::Stdio::null())
+                    .kill_on_drop(true);
+                let output = tokio::time::timeout(COMMAND_TIMEOUT, command.output())
+                    .await
+                    .map_err(|_| format!("{} timed out after {:?}", program, COMMAND_TIMEOUT))?
+                    .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+                if !output.status.success() {
+                    let stderr = String::from_utf8_lossy(&output.stderr);
+                    return Err(format!("{} exited with {}: {}", program, output.status, stderr.trim()));
+                }
+                Ok(())
+            }
+            SinkTarget::JsonlLog { path } => {
+                let path = self.data_dir.join(path);
+                if let Some(parent) = path.parent() {
+                    tokio::fs::create_dir_all(parent)
+                        .await
+                        .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+                }
+                let mut file = tokio::fs::OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&path)
+                    .await
+                    .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+                file.write_all(format!("{}\n", json).as_bytes())
+                    .await
+                    .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("pause-menu-test-bus-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn sink(name: &str, target: SinkTarget) -> EventSink {
+        EventSink {
+            name: name.to_string(),
+            enabled: true,
+            events: Vec::new(),
+            target,
+            retry: RetryPolicy { max_attempts: 3, initial_backoff_ms: 10, max_backoff_ms: 50 },
+        }
+    }
+
+    #[test]
+    fn test_envelope_is_flat_json_tagged_by_kind() {
+        let envelope = BusEnvelope::new(FocusBusEvent::MuteToggled { muted: true });
+        let json: serde_json::Value = serde_json::to_value(&envelope).unwrap();
+        assert_eq!(json["type"], "mute_toggled");
+        assert_eq!(json["muted"], true);
+        assert_eq!(serde_json::from_value::<BusEnvelope>(json).unwrap().id, envelope.id);
+
+        let vars = envelope.env_vars("{}");
+        assert!(vars.contains(&("PAUSE_MENU_EVENT", "mute_toggled".to_string())));
+        assert!(vars.contains(&("PAUSE_MENU_MUTED", "true".to_string())));
+    }
+
+    #[test]
+    fn test_validate_and_backoff() {
+        let webhook = sink("slack", SinkTarget::Webhook { url: "https://hooks.example.com/x".to_string(), headers: BTreeMap::new() });
+        let mut config = EventBusConfig { sinks: vec![webhook.clone()] };
+        assert!(config.validate().is_ok());
+
+        config.sinks.push(webhook.clone());
+        assert!(config.validate().is_err());
+        let bad_url = sink("bad", SinkTarget::Webhook { url: "ftp://example.com".to_string(), headers: BTreeMap::new() });
+        assert!(EventBusConfig { sinks: vec![bad_url] }.validate().is_err());
+        let bad_kind = EventSink { events: vec!["session_exploded".to_string()], ..webhook };
+        assert!(EventBusConfig { sinks: vec![bad_kind] }.validate().is_err());
+
+        let retry = RetryPolicy { max_attempts: 5, initial_backoff_ms: 100, max_backoff_ms: 350 };
+        let waits: Vec<u128> = (1..=4).map(|attempt| retry.backoff(attempt).as_millis()).collect();
+        assert_eq!(waits, vec![100, 200, 350, 350]);
+    }
+
+    #[tokio::test]
+    async fn test_jsonl_sink_appends_events_in_order() {
+        let dir = test_dir("jsonl");
+        let bus = EventBus::new(&dir);
+        let mut log = sink("log", SinkTarget::JsonlLog { path: PathBuf::from("logs/events.jsonl") });
+        log.events = vec!["mute_toggled".to_string()];
+        bus.set_config(EventBusConfig { sinks: vec![log] }).unwrap();
+
+        for muted in [true, false, true] {
+            bus.publish(FocusBusEvent::MuteToggled { muted });
+        }
+
+        let path = dir.join("logs/events.jsonl");
+        let mut lines = Vec::new();
+        for _ in 0..100 {
+            lines = std::fs::read_to_string(&path).unwrap_or_default().lines().map(String::from).collect();
+            if lines.len() >= 3 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        let muted: Vec<bool> = lines
+            .iter()
+            .map(|line| match serde_json::from_str::<BusEnvelope>(line).unwrap().event {
+                FocusBusEvent::MuteToggled { muted } => muted,
+                other => panic!("unexpected event {:?}", other),
+            })
+            .collect();
+        assert_eq!(muted, vec![true, false, true]);
+        assert_eq!(EventBusConfig::load(&dir).unwrap().sinks.len(), 1);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_command_sink_retries_until_it_succeeds() {
+        let dir = test_dir("command");
+        let bus = EventBus::new(&dir);
+        // Fails on the first run, succeeds on the second
+        let script = format!(
+            "echo \"$PAUSE_MENU_EVENT $PAUSE_MENU_MUTED\" >> '{0}/runs'; [ $(wc -l < '{0}/runs') -ge 2 ]",
+            dir.display()
+        );
+        let runner = sink("sign", SinkTarget::Command { program: "sh".to_string(), args: vec!["-c".to_string(), script] });
+        let envelope = BusEnvelope::new(FocusBusEvent::MuteToggled { muted: true });
+
+        assert_eq!(bus.deliver_with_retry(&runner, &envelope).await, Ok(2));
+        let runs = std::fs::read_to_string(dir.join("runs")).unwrap();
+        assert_eq!(runs.lines().collect::<Vec<_>>(), vec!["mute_toggled true", "mute_toggled true"]);
+
+        let once = EventSink { retry: RetryPolicy { max_attempts: 1, ..runner.retry.clone() }, ..runner };
+        let failing = EventSink { target: SinkTarget::Command { program: "false".to_string(), args: Vec::new() }, ..once };
+        assert!(bus.deliver_with_retry(&failing, &envelope).await.is_err());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
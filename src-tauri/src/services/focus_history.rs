@@ -0,0 +1,684 @@
+// Focus session history
+// Append-only JSON Lines store of finished focus sessions, plus daily/weekly statistics,
+// tag search, and Markdown/CSV export for weekly reviews
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use crate::services::focus_service::{FocusMode, FocusSession};
+
+const FOCUS_HISTORY_FILE: &str = "focus_history.jsonl";
+/// Longest range the statistics cover, in days
+pub const MAX_STATS_DAYS: u32 = 366;
+
+// Why a focus session ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FocusEndReason {
+    Completed,   // Timer ran out
+    Cancelled,   // User stopped the session early
+    Interrupted, // App crashed or was closed mid-session
+    Meeting,     // Ended or shortened because a meeting was about to start
+}
+
+impl FocusEndReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FocusEndReason::Completed => "completed",
+            FocusEndReason::Cancelled => "cancelled",
+            FocusEndReason::Interrupted => "interrupted",
+            FocusEndReason::Meeting => "meeting",
+        }
+    }
+}
+
+// What the user thought of a session once it was over
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionReflection {
+    pub rating: u8,   // 1 (wasted) - 5 (great)
+    pub note: String, // Outcome, e.g. "Draft done, still need the intro"
+    pub reflected_at: DateTime<Utc>,
+}
+
+impl SessionReflection {
+    pub fn new(rating: u8, note: &str) -> Result<Self, String> {
+        if !(1..=5).contains(&rating) {
+            return Err("Rating must be between 1 and 5".to_string());
+        }
+        Ok(Self {
+            rating,
+            note: note.trim().to_string(),
+            reflected_at: Utc::now(),
+        })
+    }
+}
+
+/// Trim tags, drop a leading '#', lowercase and remove duplicates
+pub fn normalize_tags(tags: &[String]) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = tag.trim().trim_start_matches('#').trim().to_lowercase();
+        if !tag.is_empty() && !normalized.contains(&tag) {
+            normalized.push(tag);
+        }
+    }
+    normalized
+}
+
+// A finished focus session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FocusRecord {
+    pub id: String,
+    pub mode: FocusMode,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub started_at: DateTime<Utc>,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub ended_at: DateTime<Utc>,
+    pub planned_minutes: u32,
+    pub actual_minutes: u32, // Excludes paused time
+    #[serde(default)]
+    pub paused_minutes: u32,
+    #[serde(default)]
+    pub idle_minutes: u32, // Time away from the keyboard, usually also counted as paused
+    pub end_reason: FocusEndReason,
+    #[serde(default)]
+    pub goal: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub reflection: Option<SessionReflection>,
+}
+
+impl FocusRecord {
+    pub fn from_session(session: &FocusSession, ended_at: DateTime<Utc>, end_reason: FocusEndReason) -> Self {
+        let paused_seconds = session.paused_seconds_at(ended_at) as i64;
+        let actual_seconds = ((ended_at - session.start_time).num_seconds() - paused_seconds).max(0);
+        Self {
+            id: session.id.clone(),
+            mode: session.mode.clone(),
+            started_at: session.start_time,
+            ended_at,
+            planned_minutes: session.duration_minutes,
+            // Round to the nearest minute
+            actual_minutes: ((actual_seconds + 30) / 60) as u32,
+            paused_minutes: ((paused_seconds + 30) / 60) as u32,
+            idle_minutes: ((session.idle_seconds + 30) / 60) as u32,
+            end_reason,
+            goal: session.goal.clone(),
+            tags: session.tags.clone(),
+            reflection: None,
+        }
+    }
+
+    /// Whether the record carries `tag` (case-insensitive, '#' optional)
+    pub fn has_tag(&self, tag: &str) -> bool {
+        let tag = tag.trim().trim_start_matches('#').to_lowercase();
+        self.tags.contains(&tag)
+    }
+}
+
+// Minutes focused on a single (local) day
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DailyFocus {
+    pub date: NaiveDate,
+    pub minutes: u32,
+    pub sessions: u32,
+}
+
+// Minutes focused in a week (weeks start on Monday)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WeeklyFocus {
+    pub week_start: NaiveDate,
+    pub minutes: u32,
+    pub sessions: u32,
+}
+
+// Per-mode totals and completion rate
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModeStats {
+    pub mode: FocusMode,
+    pub mode_name: String,
+    pub sessions: u32,
+    pub completed: u32,
+    pub completion_rate: f64, // 0.0 - 1.0
+    pub minutes: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FocusStats {
+    pub total_sessions: u32,
+    pub total_minutes: u32,
+    pub completion_rate: f64,
+    pub today_minutes: u32,
+    pub this_week_minutes: u32,
+    pub current_streak_days: u32,
+    pub longest_streak_days: u32,
+    pub daily: Vec<DailyFocus>,   // Oldest first, one entry per day in the range
+    pub weekly: Vec<WeeklyFocus>, // Oldest first, one entry per week in the range
+    pub by_mode: Vec<ModeStats>,
+}
+
+#[derive(Debug, Clone)]
+pub struct FocusHistory {
+    path: PathBuf,
+}
+
+impl FocusHistory {
+    pub fn new(data_dir: &Path) -> Self {
+        Self {
+            path: data_dir.join(FOCUS_HISTORY_FILE),
+        }
+    }
+
+    /// Append a finished session to the history file
+    pub fn append(&self, record: &FocusRecord) -> Result<(), String> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+
+        let line = serde_json::to_string(record)
+            .map_err(|e| format!("Failed to serialize focus record: {}", e))?;
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| format!("Failed to open {}: {}", self.path.display(), e))?;
+
+        writeln!(file, "{}", line)
+            .map_err(|e| format!("Failed to write {}: {}", self.path.display(), e))
+    }
+
+    /// Load all records, oldest first
+    /// Lines that fail to parse (e.g. a torn write after a crash) are skipped
+    pub fn load(&self) -> Result<Vec<FocusRecord>, String> {
+        let contents = match std::fs::read_to_string(&self.path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(format!("Failed to read {}: {}", self.path.display(), e)),
+        };
+
+        let mut records = Vec::new();
+        for (index, line) in contents.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str::<FocusRecord>(line) {
+                Ok(record) => records.push(record),
+                Err(e) => eprintln!("[FocusHistory] Skipping malformed line {}: {}", index + 1, e),
+            }
+        }
+
+        Ok(records)
+    }
+
+    /// Change one record in place, rewriting the file atomically
+    /// Other lines are kept byte-for-byte, including ones that don't parse
+    pub fn update(&self, id: &str, change: impl FnOnce(&mut FocusRecord)) -> Result<FocusRecord, String> {
+        let contents = std::fs::read_to_string(&self.path)
+            .map_err(|e| format!("Failed to read {}: {}", self.path.display(), e))?;
+
+        let mut lines: Vec<String> = contents.lines().map(String::from).collect();
+        let index = lines
+            .iter()
+            .position(|line| serde_json::from_str::<FocusRecord>(line).map(|r| r.id == id).unwrap_or(false))
+            .ok_or_else(|| format!("Focus session not found in history: {}", id))?;
+        let mut record: FocusRecord = serde_json::from_str(&lines[index])
+            .map_err(|e| format!("Failed to parse focus record: {}", e))?;
+        change(&mut record);
+        lines[index] = serde_json::to_string(&record)
+            .map_err(|e| format!("Failed to serialize focus record: {}", e))?;
+
+        let tmp_path = self.path.with_extension("jsonl.tmp");
+        std::fs::write(&tmp_path, lines.join("\n") + "\n")
+            .map_err(|e| format!("Failed to write {}: {}", tmp_path.display(), e))?;
+        std::fs::rename(&tmp_path, &self.path)
+            .map_err(|e| format!("Failed to replace {}: {}", self.path.display(), e))?;
+        Ok(record)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Markdown,
+    Csv,
+}
+
+/// Render records (oldest first) for a weekly review, with times in `tz`
+pub fn export_records<Tz: TimeZone>(records: &[FocusRecord], format: ExportFormat, tz: &Tz) -> String
+where
+    Tz::Offset: std::fmt::Display,
+{
+    match format {
+        ExportFormat::Markdown => export_markdown(records, tz),
+        ExportFormat::Csv => export_csv(records, tz),
+    }
+}
+
+fn export_markdown<Tz: TimeZone>(records: &[FocusRecord], tz: &Tz) -> String
+where
+    Tz::Offset: std::fmt::Display,
+{
+    // Table cells can't hold pipes or line breaks
+    fn cell(text: &str) -> String {
+        text.replace('|', "\\|").replace(['\r', '\n'], " ")
+    }
+
+    let minutes: u32 = records.iter().map(|r| r.actual_minutes).sum();
+    let ratings: Vec<u32> = records.iter().filter_map(|r| r.reflection.as_ref()).map(|r| r.rating as u32).collect();
+    let mut out = String::from("# Focus review\n\n");
+    out.push_str(&format!("{} sessions, {} minutes focused", records.len(), minutes));
+    if !ratings.is_empty() {
+        out.push_str(&format!(", average rating {:.1}/5", ratings.iter().sum::<u32>() as f64 / ratings.len() as f64));
+    }
+    out.push_str("\n\n| Date | Start | Mode | Minutes | Goal | Tags | Rating | Outcome |\n");
+    out.push_str("|------|-------|------|---------|------|------|--------|---------|\n");
+
+    for record in records {
+        let started = record.started_at.with_timezone(tz);
+        let (rating, note) = match record.reflection {
+            Some(ref r) => (r.rating.to_string(), cell(&r.note)),
+            None => (String::new(), String::new()),
+        };
+        out.push_str(&format!(
+            "| {} | {} | {} | {}/{} | {} | {} | {} | {} |\n",
+            started.format("%Y-%m-%d"),
+            started.format("%H:%M"),
+            cell(&record.mode.name()),
+            record.actual_minutes,
+            record.planned_minutes,
+            cell(record.goal.as_deref().unwrap_or("")),
+            cell(&record.tags.iter().map(|t| format!("#{}", t)).collect::<Vec<_>>().join(" ")),
+            rating,
+            note,
+        ));
+    }
+    out
+}
+
+fn export_csv<Tz: TimeZone>(records: &[FocusRecord], tz: &Tz) -> String
+where
+    Tz::Offset: std::fmt::Display,
+{
+    // RFC 4180: quote fields containing separators, quotes or line breaks
+    fn field(text: &str) -> String {
+        if text.contains([',', '"', '\n', '\r']) {
+            format!("\"{}\"", text.replace('"', "\"\""))
+        } else {
+            text.to_string()
+        }
+    }
+
+    let mut out = String::from(
+        "id,started_at,ended_at,mode,planned_minutes,actual_minutes,paused_minutes,idle_minutes,end_reason,goal,tags,rating,note\n",
+    );
+    for record in records {
+        let (rating, note) = match record.reflection {
+            Some(ref r) => (r.rating.to_string(), r.note.as_str()),
+            None => (String::new(), ""),
+        };
+        let row = [
+            field(&record.id),
+            record.started_at.with_timezone(tz).to_rfc3339(),
+            record.ended_at.with_timezone(tz).to_rfc3339(),
+            field(&record.mode.name()),
+            record.planned_minutes.to_string(),
+            record.actual_minutes.to_string(),
+            record.paused_minutes.to_string(),
+            record.idle_minutes.to_string(),
+            record.end_reason.as_str().to_string(),
+            field(record.goal.as_deref().unwrap_or("")),
+            field(&record.tags.join(";")),
+            rating,
+            field(note),
+        ];
+        out.push_str(&row.join(","));
+        out.push('\n');
+    }
+    out
+}
+
+fn local_date<Tz: TimeZone>(time: &DateTime<Utc>, tz: &Tz) -> NaiveDate {
+    time.with_timezone(tz).date_naive()
+}
+
+fn week_start(date: NaiveDate) -> NaiveDate {
+    date - Duration::days(date.weekday().num_days_from_monday() as i64)
+}
+
+fn rate(completed: u32, total: u32) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        completed as f64 / total as f64
+    }
+}
+
+/// Aggregate history into statistics
+/// Days are bucketed in `tz` by session start; `days` (at most MAX_STATS_DAYS) controls how far back the
+/// daily/weekly series go
+pub fn compute_stats<Tz: TimeZone>(records: &[FocusRecord], today: NaiveDate, tz: &Tz, days: u32) -> FocusStats {
+    let days = days.clamp(1, MAX_STATS_DAYS);
+    let range_start = today.checked_sub_signed(Duration::days(days as i64 - 1)).unwrap_or(NaiveDate::MIN);
+
+    // Daily series (including days with no focus time)
+    let mut daily: Vec<DailyFocus> = (0..days)
+        .map(|offset| DailyFocus {
+            date: range_start + Duration::days(offset as i64),
+            minutes: 0,
+            sessions: 0,
+        })
+        .collect();
+
+    // Weekly series covering the same range
+    let first_week = week_start(range_start);
+    let weeks = ((week_start(today) - first_week).num_days() / 7 + 1) as usize;
+    let mut weekly: Vec<WeeklyFocus> = (0..weeks)
+        .map(|offset| WeeklyFocus {
+            week_start: first_week + Duration::weeks(offset as i64),
+            minutes: 0,
+            sessions: 0,
+        })
+        .collect();
+
+    let mut by_mode: Vec<ModeStats> = Vec::new();
+    let mut focus_days: Vec<NaiveDate> = Vec::new();
+    let mut total_minutes = 0;
+    let mut completed = 0;
+
+    for record in records {
+        let date = local_date(&record.started_at, tz);
+        total_minutes += record.actual_minutes;
+        if record.end_reason == FocusEndReason::Completed {
+            completed += 1;
+        }
+        if record.actual_minutes > 0 {
+            focus_days.push(date);
+        }
+
+        if date >= range_start && date <= today {
+            let day = &mut daily[(date - range_start).num_days() as usize];
+            day.minutes += record.actual_minutes;
+            day.sessions += 1;
+        }
+
+        if date >= first_week && date <= today {
+            let week = &mut weekly[((week_start(date) - first_week).num_days() / 7) as usize];
+            week.minutes += record.actual_minutes;
+            week.sessions += 1;
+        }
+
+        let index = match by_mode.iter().position(|m| m.mode == record.mode) {
+            Some(index) => index,
+            None => {
+                by_mode.push(ModeStats {
+                    mode: record.mode.clone(),
+                    mode_name: record.mode.name(),
+                    sessions: 0,
+                    completed: 0,
+                    completion_rate: 0.0,
+                    minutes: 0,
+                });
+                by_mode.len() - 1
+            }
+        };
+        let mode = &mut by_mode[index];
+        mode.sessions += 1;
+        mode.minutes += record.actual_minutes;
+        if record.end_reason == FocusEndReason::Completed {
+            mode.completed += 1;
+        }
+    }
+
+    for mode in by_mode.iter_mut() {
+        mode.completion_rate = rate(mode.completed, mode.sessions);
+    }
+    by_mode.sort_by_key(|m| std::cmp::Reverse(m.sessions));
+
+    focus_days.sort();
+    focus_days.dedup();
+
+    // Longest run of consecutive focus days
+    let mut longest_streak_days = 0;
+    let mut run = 0;
+    let mut previous: Option<NaiveDate> = None;
+    for date in &focus_days {
+        run = match previous {
+            Some(prev) if *date - prev == Duration::days(1) => run + 1,
+            _ => 1,
+        };
+        longest_streak_days = longest_streak_days.max(run);
+        previous = Some(*date);
+    }
+
+    // Current streak ends today, or yesterday if there's no focus time yet today
+    let mut current_streak_days = 0;
+    let mut cursor = if focus_days.binary_search(&today).is_ok() {
+        today
+    } else {
+        today - Duration::days(1)
+    };
+    while focus_days.binary_search(&cursor).is_ok() {
+        current_streak_days += 1;
+        cursor -= Duration::days(1);
+    }
+
+    let today_minutes = daily.last().map(|d| d.minutes).unwrap_or(0);
+    let this_week_minutes = weekly.last().map(|w| w.minutes).unwrap_or(0);
+
+    FocusStats {
+        total_sessions: records.len() as u32,
+        total_minutes,
+        completion_rate: rate(completed, records.len() as u32),
+        today_minutes,
+        this_week_minutes,
+        current_streak_days,
+        longest_streak_days,
+        daily,
+        weekly,
+        by_mode,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(mode: FocusMode, started_at: &str, actual_minutes: u32, end_reason: FocusEndReason) -> FocusRecord {
+        let started_at = DateTime::parse_from_rfc3339(started_at).unwrap().with_timezone(&Utc);
+        FocusRecord {
+            id: started_at.timestamp().to_string(),
+            planned_minutes: mode.duration_minutes(),
+            mode,
+            started_at,
+            ended_at: started_at + Duration::minutes(actual_minutes as i64),
+            actual_minutes,
+            paused_minutes: 0,
+            idle_minutes: 0,
+            end_reason,
+            goal: None,
+            tags: Vec::new(),
+            reflection: None,
+        }
+    }
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn test_record_from_session_rounds_actual_minutes() {
+        let start_time = Utc::now();
+        let session = FocusSession::new("abc".to_string(), FocusMode::Focus25, start_time);
+
+        let record = FocusRecord::from_session(&session, start_time + Duration::seconds(10 * 60 + 40), FocusEndReason::Cancelled);
+        assert_eq!(record.id, "abc");
+        assert_eq!(record.planned_minutes, 25);
+        assert_eq!(record.actual_minutes, 11);
+        assert_eq!(record.end_reason, FocusEndReason::Cancelled);
+    }
+
+    #[test]
+    fn test_record_from_session_excludes_paused_time() {
+        let start_time = Utc::now();
+        let mut session = FocusSession::new("abc".to_string(), FocusMode::DeepWork60, start_time);
+        // Paused for 10 minutes earlier, and again for 5 minutes when stopped
+        session.is_paused = true;
+        session.paused_at = Some(start_time + Duration::minutes(40));
+        session.paused_seconds = 10 * 60;
+
+        let record = FocusRecord::from_session(&session, start_time + Duration::minutes(45), FocusEndReason::Cancelled);
+        assert_eq!(record.actual_minutes, 30);
+        assert_eq!(record.paused_minutes, 15);
+    }
+
+    #[test]
+    fn test_daily_and_weekly_minutes() {
+        // 2024-05-13 is a Monday
+        let records = vec![
+            record(FocusMode::Focus25, "2024-05-10T09:00:00Z", 25, FocusEndReason::Completed),
+            record(FocusMode::Focus25, "2024-05-13T09:00:00Z", 25, FocusEndReason::Completed),
+            record(FocusMode::DeepWork60, "2024-05-13T14:00:00Z", 40, FocusEndReason::Cancelled),
+            record(FocusMode::Focus15, "2024-05-15T09:00:00Z", 15, FocusEndReason::Completed),
+        ];
+
+        let stats = compute_stats(&records, date("2024-05-15"), &Utc, 7);
+
+        assert_eq!(stats.daily.len(), 7);
+        assert_eq!(stats.daily[0].date, date("2024-05-09"));
+        assert_eq!(stats.daily[1].minutes, 25);
+        assert_eq!(stats.daily[4].minutes, 65);
+        assert_eq!(stats.daily[4].sessions, 2);
+        assert_eq!(stats.today_minutes, 15);
+
+        assert_eq!(stats.weekly.len(), 2);
+        assert_eq!(stats.weekly[0].week_start, date("2024-05-06"));
+        assert_eq!(stats.weekly[0].minutes, 25);
+        assert_eq!(stats.weekly[1].minutes, 80);
+        assert_eq!(stats.this_week_minutes, 80);
+        assert_eq!(stats.total_minutes, 105);
+    }
+
+    #[test]
+    fn test_streaks() {
+        let records = vec![
+            record(FocusMode::Focus25, "2024-05-01T09:00:00Z", 25, FocusEndReason::Completed),
+            record(FocusMode::Focus25, "2024-05-02T09:00:00Z", 25, FocusEndReason::Completed),
+            record(FocusMode::Focus25, "2024-05-03T09:00:00Z", 25, FocusEndReason::Completed),
+            record(FocusMode::Focus25, "2024-05-06T09:00:00Z", 25, FocusEndReason::Completed),
+            record(FocusMode::Focus25, "2024-05-07T09:00:00Z", 25, FocusEndReason::Completed),
+            // Zero-minute sessions don't count towards a streak
+            record(FocusMode::Focus25, "2024-05-08T09:00:00Z", 0, FocusEndReason::Cancelled),
+        ];
+
+        // No focus yet today, so the streak through yesterday still counts
+        let stats = compute_stats(&records, date("2024-05-08"), &Utc, 7);
+        assert_eq!(stats.current_streak_days, 2);
+        assert_eq!(stats.longest_streak_days, 3);
+
+        let stats = compute_stats(&records, date("2024-05-09"), &Utc, 7);
+        assert_eq!(stats.current_streak_days, 0);
+    }
+
+    #[test]
+    fn test_completion_rate_per_mode() {
+        let records = vec![
+            record(FocusMode::Focus25, "2024-05-01T09:00:00Z", 25, FocusEndReason::Completed),
+            record(FocusMode::Focus25, "2024-05-01T10:00:00Z", 10, FocusEndReason::Cancelled),
+            record(FocusMode::Focus25, "2024-05-01T11:00:00Z", 25, FocusEndReason::Completed),
+            record(FocusMode::DeepWork60, "2024-05-01T13:00:00Z", 30, FocusEndReason::Interrupted),
+        ];
+
+        let stats = compute_stats(&records, date("2024-05-01"), &Utc, 1);
+        assert_eq!(stats.by_mode.len(), 2);
+        assert_eq!(stats.by_mode[0].mode, FocusMode::Focus25);
+        assert_eq!(stats.by_mode[0].sessions, 3);
+        assert!((stats.by_mode[0].completion_rate - 2.0 / 3.0).abs() < 1e-9);
+        assert_eq!(stats.by_mode[1].completion_rate, 0.0);
+        assert!((stats.completion_rate - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_stats_range_is_capped() {
+        let records = vec![record(FocusMode::Focus25, "2024-05-01T09:00:00Z", 25, FocusEndReason::Completed)];
+        let stats = compute_stats(&records, date("2024-05-01"), &Utc, u32::MAX);
+        assert_eq!(stats.daily.len(), MAX_STATS_DAYS as usize);
+        assert_eq!(stats.daily.last().unwrap().minutes, 25);
+    }
+
+    #[test]
+    fn test_history_file_roundtrip_skips_bad_lines() {
+        let dir = std::env::temp_dir().join(format!("pause-menu-test-history-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let history = FocusHistory::new(&dir);
+
+        assert!(history.load().unwrap().is_empty());
+
+        history.append(&record(FocusMode::Focus15, "2024-05-01T09:00:00Z", 15, FocusEndReason::Completed)).unwrap();
+        let mut file = OpenOptions::new().append(true).open(dir.join(FOCUS_HISTORY_FILE)).unwrap();
+        writeln!(file, "{{\"id\": \"torn").unwrap();
+        history.append(&record(FocusMode::Custom(40), "2024-05-02T09:00:00Z", 40, FocusEndReason::Completed)).unwrap();
+
+        let records = history.load().unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[1].mode, FocusMode::Custom(40));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_update_rewrites_one_record_and_keeps_other_lines() {
+        let dir = std::env::temp_dir().join(format!("pause-menu-test-history-update-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let history = FocusHistory::new(&dir);
+
+        let first = record(FocusMode::Focus25, "2024-05-01T09:00:00Z", 25, FocusEndReason::Completed);
+        history.append(&first).unwrap();
+        let mut file = OpenOptions::new().append(true).open(dir.join(FOCUS_HISTORY_FILE)).unwrap();
+        writeln!(file, "{{\"id\": \"torn").unwrap();
+        history.append(&record(FocusMode::Focus15, "2024-05-02T09:00:00Z", 15, FocusEndReason::Completed)).unwrap();
+
+        let reflection = SessionReflection::new(4, "  Shipped the draft ").unwrap();
+        let updated = history.update(&first.id, |r| r.reflection = Some(reflection.clone())).unwrap();
+        assert_eq!(updated.reflection.unwrap().note, "Shipped the draft");
+
+        let contents = std::fs::read_to_string(dir.join(FOCUS_HISTORY_FILE)).unwrap();
+        assert_eq!(contents.lines().nth(1), Some("{\"id\": \"torn"));
+        let records = history.load().unwrap();
+        assert_eq!(records[0].reflection.as_ref().map(|r| r.rating), Some(4));
+        assert!(records[1].reflection.is_none());
+
+        assert!(history.update("missing", |_| {}).is_err());
+        assert!(SessionReflection::new(6, "").is_err());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_tags_and_export() {
+        assert_eq!(
+            normalize_tags(&["#Writing".to_string(), " docs ".to_string(), "writing".to_string(), "#".to_string()]),
+            vec!["writing".to_string(), "docs".to_string()]
+        );
+
+        let mut spec = record(FocusMode::DeepWork60, "2024-05-13T09:00:00Z", 50, FocusEndReason::Cancelled);
+        spec.goal = Some("Spec, part 1 | intro".to_string());
+        spec.tags = vec!["writing".to_string(), "docs".to_string()];
+        spec.reflection = Some(SessionReflection::new(3, "Got \"most\" of it\nmore tomorrow").unwrap());
+        let inbox = record(FocusMode::ClearInbox10, "2024-05-13T11:00:00Z", 10, FocusEndReason::Completed);
+        assert!(spec.has_tag("#Writing"));
+        assert!(!inbox.has_tag("writing"));
+
+        let csv = export_records(&[spec.clone(), inbox.clone()], ExportFormat::Csv, &Utc);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert!(lines[0].starts_with("id,started_at,"));
+        assert!(lines[1].contains(",cancelled,\"Spec, part 1 | intro\",writing;docs,3,\"Got \"\"most\"\" of it"));
+        assert!(lines[3].ends_with(",completed,,,,"));
+
+        let markdown = export_records(&[spec, inbox], ExportFormat::Markdown, &Utc);
+        assert!(markdown.contains("2 sessions, 60 minutes focused, average rating 3.0/5"));
+        assert!(markdown.contains("| 2024-05-13 | 09:00 | Deep Work 60 | 50/60 | Spec, part 1 \\| intro | #writing #docs | 3 | Got \"most\" of it more tomorrow |"));
+    }
+}
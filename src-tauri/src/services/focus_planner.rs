@@ -0,0 +1,306 @@
+// Focus block planner
+// Finds free gaps between calendar events during working hours and proposes focus blocks in them
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, TimeZone, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use crate::models::action::CalendarEvent;
+use crate::utils::app_data;
+
+const PLANNER_CONFIG_FILE: &str = "focus_planner.json";
+/// Description of the events the planner puts on the calendar, so it can recognise them later
+pub const PLANNED_BLOCK_DESCRIPTION: &str = "Scheduled by Pause Menu";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlannerConfig {
+    pub work_start: NaiveTime, // Local time, e.g. "09:00:00"
+    pub work_end: NaiveTime,
+    pub work_days: Vec<Weekday>,
+    pub min_block_minutes: u32, // Gaps shorter than this (after the buffer) are skipped
+    pub max_block_minutes: u32, // Longer gaps are capped to one block of this length
+    pub buffer_minutes: u32,    // Time left free before the next meeting
+    pub auto_start: bool,       // Start a focus session automatically when a block begins
+    pub block_title: String,    // Title used when adding blocks to the calendar
+}
+
+impl Default for PlannerConfig {
+    fn default() -> Self {
+        Self {
+            work_start: NaiveTime::from_hms_opt(9, 0, 0).unwrap(),
+            work_end: NaiveTime::from_hms_opt(17, 0, 0).unwrap(),
+            work_days: vec![Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri],
+            min_block_minutes: 30,
+            max_block_minutes: 90,
+            buffer_minutes: 10,
+            auto_start: false,
+            block_title: "Focus time".to_string(),
+        }
+    }
+}
+
+impl PlannerConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.work_end <= self.work_start {
+            return Err("Working hours must end after they start".to_string());
+        }
+        if self.min_block_minutes == 0 || self.max_block_minutes < self.min_block_minutes {
+            return Err("Block length limits are invalid".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn load(data_dir: &Path) -> Result<Self, String> {
+        Ok(app_data::read_json(&config_path(data_dir))?.unwrap_or_default())
+    }
+
+    pub fn save(&self, data_dir: &Path) -> Result<(), String> {
+        app_data::write_json(&config_path(data_dir), self)
+    }
+}
+
+fn config_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(PLANNER_CONFIG_FILE)
+}
+
+// A span of time with no events
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FreeGap {
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub start: DateTime<Utc>,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub end: DateTime<Utc>,
+}
+
+// A proposed focus block
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FocusBlock {
+    pub id: String, // Stays the same while the gap it fills doesn't move, e.g. as the day goes on
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub start: DateTime<Utc>,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub end: DateTime<Utc>,
+    pub minutes: u32,
+    pub next_event_title: Option<String>, // The meeting the buffer protects, if any
+    pub event_id: Option<String>,         // Calendar event of a block that's already been added
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FocusPlan {
+    pub date: NaiveDate,
+    pub blocks: Vec<FocusBlock>,
+    pub total_minutes: u32,
+}
+
+/// Events that occupy time; all-day events (holidays, OOO markers, birthdays) don't block focus time
+fn is_busy(event: &CalendarEvent) -> bool {
+    !event.all_day && event.end_time > event.start_time
+}
+
+/// A focus block the planner added to the calendar earlier
+pub fn is_planned_block(event: &CalendarEvent, config: &PlannerConfig) -> bool {
+    is_busy(event)
+        && (event.title == config.block_title
+            || event.description.as_deref().is_some_and(|d| d.contains(PLANNED_BLOCK_DESCRIPTION)))
+}
+
+/// Free gaps between `window_start` and `window_end`, merging overlapping events
+pub fn find_free_gaps(events: &[CalendarEvent], window_start: DateTime<Utc>, window_end: DateTime<Utc>) -> Vec<FreeGap> {
+    let mut busy: Vec<(DateTime<Utc>, DateTime<Utc>)> = events
+        .iter()
+        .filter(|e| is_busy(e) && e.end_time > window_start && e.start_time < window_end)
+        .map(|e| (e.start_time.max(window_start), e.end_time.min(window_end)))
+        .collect();
+    busy.sort();
+
+    let mut gaps = Vec::new();
+    let mut cursor = window_start;
+    for (start, end) in busy {
+        if start > cursor {
+            gaps.push(FreeGap { start: cursor, end: start });
+        }
+        cursor = cursor.max(end);
+    }
+    if cursor < window_end {
+        gaps.push(FreeGap { start: cursor, end: window_end });
+    }
+    gaps
+}
+
+/// Working hours for `date` in `tz`, or None on a non-working day
+pub fn working_window<Tz: TimeZone>(date: NaiveDate, config: &PlannerConfig, tz: &Tz) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    if !config.work_days.contains(&date.weekday()) {
+        return None;
+    }
+    let start = tz.from_local_datetime(&date.and_time(config.work_start)).earliest()?;
+    let end = tz.from_local_datetime(&date.and_time(config.work_end)).earliest()?;
+    Some((start.with_timezone(&Utc), end.with_timezone(&Utc)))
+}
+
+/// Propose focus blocks for `date`, leaving out those that ended before `now`
+/// Gaps are measured from the start of the working day, so a block doesn't move as the day goes on.
+/// Blocks already on the calendar count as the plan for the gap they sit in, not as busy time
+pub fn plan_day<Tz: TimeZone>(events: &[CalendarEvent], date: NaiveDate, now: DateTime<Utc>, config: &PlannerConfig, tz: &Tz) -> FocusPlan {
+    let mut blocks = Vec::new();
+
+    if let Some((work_start, work_end)) = working_window(date, config, tz) {
+        let (planned, others): (Vec<&CalendarEvent>, Vec<&CalendarEvent>) =
+            events.iter().partition(|e| is_planned_block(e, config));
+        let others: Vec<CalendarEvent> = others.into_iter().cloned().collect();
+
+        for gap in find_free_gaps(&others, work_start, work_end) {
+            // Leave a buffer when the gap ends at a meeting rather than at the end of the day
+            let next_event = others
+                .iter()
+                .filter(|e| is_busy(e) && e.start_time == gap.end)
+                .min_by_key(|e| e.start_time);
+            let next_event_title = next_event.map(|e| e.title.clone());
+
+            let mut in_gap: Vec<&CalendarEvent> = planned
+                .iter()
+                .copied()
+                .filter(|e| e.start_time >= gap.start && e.start_time < gap.end)
+                .collect();
+            in_gap.sort_by_key(|e| e.start_time);
+            if !in_gap.is_empty() {
+                for event in in_gap {
+                    blocks.push(FocusBlock {
+                        id: event.id.clone(),
+                        start: event.start_time,
+                        end: event.end_time,
+                        minutes: (event.end_time - event.start_time).num_minutes().max(0) as u32,
+                        next_event_title: next_event_title.clone(),
+                        event_id: Some(event.id.clone()),
+                    });
+                }
+                continue;
+            }
+
+            let end = if next_event.is_some() {
+                gap.end - Duration::minutes(config.buffer_minutes as i64)
+            } else {
+                gap.end
+            };
+
+            let available = (end - gap.start).num_minutes();
+            if available < config.min_block_minutes as i64 {
+                continue;
+            }
+
+            let minutes = available.min(config.max_block_minutes as i64) as u32;
+            blocks.push(FocusBlock {
+                id: format!("gap-{}", gap.end.timestamp()),
+                start: gap.start,
+                end: gap.start + Duration::minutes(minutes as i64),
+                minutes,
+                next_event_title,
+                event_id: None,
+            });
+        }
+    }
+    blocks.retain(|b| b.end > now);
+
+    FocusPlan {
+        date,
+        total_minutes: blocks.iter().map(|b| b.minutes).sum(),
+        blocks,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_support::{event, utc};
+
+    fn date() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 5, 15).unwrap() // Wednesday
+    }
+
+    #[test]
+    fn test_free_gaps_merge_overlapping_events() {
+        let events = vec![
+            event("standup", "09:30", "10:00"),
+            event("1:1", "09:45", "10:30"),
+            event("lunch", "12:00", "13:00"),
+        ];
+        let gaps = find_free_gaps(&events, utc("09:00"), utc("17:00"));
+        assert_eq!(gaps, vec![
+            FreeGap { start: utc("09:00"), end: utc("09:30") },
+            FreeGap { start: utc("10:30"), end: utc("12:00") },
+            FreeGap { start: utc("13:00"), end: utc("17:00") },
+        ]);
+    }
+
+    #[test]
+    fn test_all_day_events_do_not_block() {
+        let all_day = CalendarEvent {
+            end_time: utc("00:00") + Duration::days(1),
+            all_day: true,
+            ..event("holiday", "00:00", "00:00")
+        };
+        let gaps = find_free_gaps(&[all_day], utc("09:00"), utc("17:00"));
+        assert_eq!(gaps.len(), 1);
+        assert_eq!(gaps[0], FreeGap { start: utc("09:00"), end: utc("17:00") });
+    }
+
+    #[test]
+    fn test_plan_day_applies_buffer_and_limits() {
+        let events = vec![
+            event("standup", "09:30", "10:00"),
+            event("lunch", "12:00", "13:00"),
+            event("review", "13:40", "14:00"),
+        ];
+        let plan = plan_day(&events, date(), utc("08:00"), &PlannerConfig::default(), &Utc);
+
+        // 09:00-09:30 is too short once the buffer is taken out; 13:00-13:40 leaves exactly 30
+        assert_eq!(plan.blocks.len(), 3);
+        assert_eq!(plan.blocks[0].start, utc("10:00"));
+        assert_eq!(plan.blocks[0].minutes, 90);
+        assert_eq!(plan.blocks[0].next_event_title.as_deref(), Some("lunch"));
+        assert_eq!(plan.blocks[1].start, utc("13:00"));
+        assert_eq!(plan.blocks[1].end, utc("13:30"));
+        // The gap at the end of the day has no meeting after it, so no buffer
+        assert_eq!(plan.blocks[2].start, utc("14:00"));
+        assert_eq!(plan.blocks[2].next_event_title, None);
+        assert_eq!(plan.total_minutes, 90 + 30 + 90);
+    }
+
+    #[test]
+    fn test_plan_day_is_anchored_to_the_working_day_and_skips_weekends() {
+        // A block stays put while it's under way and drops out once it's over
+        let early = plan_day(&[], date(), utc("09:01"), &PlannerConfig::default(), &Utc);
+        let later = plan_day(&[], date(), utc("10:00"), &PlannerConfig::default(), &Utc);
+        assert_eq!(early.blocks, later.blocks);
+        assert_eq!(early.blocks[0].start, utc("09:00"));
+        assert_eq!(early.blocks[0].minutes, 90);
+        assert!(plan_day(&[], date(), utc("10:30"), &PlannerConfig::default(), &Utc).blocks.is_empty());
+
+        let saturday = NaiveDate::from_ymd_opt(2024, 5, 18).unwrap();
+        assert!(plan_day(&[], saturday, utc("08:00"), &PlannerConfig::default(), &Utc).blocks.is_empty());
+    }
+
+    #[test]
+    fn test_blocks_on_the_calendar_are_part_of_the_plan() {
+        let config = PlannerConfig::default();
+        let events = vec![event("standup", "09:30", "10:00"), event("lunch", "12:00", "13:00")];
+        let plan = plan_day(&events, date(), utc("08:00"), &config, &Utc);
+        assert!(plan.blocks.iter().all(|b| b.event_id.is_none()));
+
+        // Put the plan on the calendar, as add_focus_plan_to_calendar does
+        let mut with_blocks = events.clone();
+        with_blocks.extend(plan.blocks.iter().map(|b| CalendarEvent {
+            id: format!("google_{}", b.id),
+            title: config.block_title.clone(),
+            description: Some(PLANNED_BLOCK_DESCRIPTION.to_string()),
+            start_time: b.start,
+            end_time: b.end,
+            ..Default::default()
+        }));
+        let replanned = plan_day(&with_blocks, date(), utc("08:00"), &config, &Utc);
+        assert_eq!(replanned.blocks.len(), plan.blocks.len());
+        for (block, original) in replanned.blocks.iter().zip(&plan.blocks) {
+            assert_eq!((block.start, block.end), (original.start, original.end));
+            assert_eq!(block.event_id.as_deref(), Some(format!("google_{}", original.id).as_str()));
+            assert_eq!(block.next_event_title, original.next_event_title);
+        }
+    }
+}
@@ -0,0 +1,292 @@
+// Focus mode presets
+// Built-in modes plus user-defined presets stored in focus_presets.json under the app data dir
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use crate::services::focus_service::FocusMode;
+use crate::utils::app_data;
+use crate::utils::serde_defaults::default_true;
+
+const FOCUS_PRESETS_FILE: &str = "focus_presets.json";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FocusPreset {
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    pub duration_minutes: u32,
+    #[serde(default = "default_true")]
+    pub mute_notifications: bool,
+    #[serde(default)]
+    pub app_allowlist: Vec<String>, // Apps allowed to stay in the foreground / notify during the session
+    #[serde(default)]
+    pub launch_apps: Vec<String>,   // Apps to launch when the session starts
+    #[serde(default)]
+    pub close_apps: Vec<String>,    // Process names to close when the session starts
+    #[serde(default)]
+    pub open_url: Option<String>,
+    #[serde(default)]
+    pub builtin: bool,
+}
+
+impl FocusPreset {
+    fn builtin(mode: FocusMode, id: &str) -> Self {
+        Self {
+            id: id.to_string(),
+            name: mode.name(),
+            duration_minutes: mode.duration_minutes(),
+            mute_notifications: true,
+            app_allowlist: Vec::new(),
+            launch_apps: Vec::new(),
+            close_apps: Vec::new(),
+            open_url: None,
+            builtin: true,
+        }
+    }
+
+    /// Focus mode for sessions started from this preset
+    /// Built-in presets map back onto their original modes so history stays comparable
+    pub fn focus_mode(&self) -> FocusMode {
+        if self.builtin {
+            if let Some(mode) = builtin_mode(&self.id) {
+                return mode;
+            }
+        }
+        FocusMode::Preset {
+            id: self.id.clone(),
+            name: self.name.clone(),
+            minutes: self.duration_minutes,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("Preset name cannot be empty".to_string());
+        }
+        if self.duration_minutes == 0 || self.duration_minutes > 24 * 60 {
+            return Err("Preset duration must be between 1 and 1440 minutes".to_string());
+        }
+        for app in &self.close_apps {
+            validate_process_name(app)?;
+        }
+        if let Some(ref url) = self.open_url {
+            if !url.starts_with("http://") && !url.starts_with("https://") && !url.starts_with("file://") {
+                return Err(format!("Invalid URL format: {}", url));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Check a process name before it's handed to taskkill/pkill
+/// Both tools take patterns, so anything that could match more than one exact name is rejected
+pub fn validate_process_name(name: &str) -> Result<(), String> {
+    const REJECTED: &[char] = &['*', '?', '[', ']', '(', ')', '{', '}', '|', '^', '$', '+', '\\', '/', '%', '"', '\''];
+    if name.trim().is_empty() {
+        return Err("App name to close cannot be empty".to_string());
+    }
+    if name.trim() != name || name.chars().all(|c| c == '.') || name.contains(REJECTED) || name.chars().any(char::is_control) {
+        return Err(format!("Invalid app name to close: {}", name));
+    }
+    Ok(())
+}
+
+fn builtin_mode(id: &str) -> Option<FocusMode> {
+    match id {
+        "focus1" => Some(FocusMode::Focus1),
+        "focus15" => Some(FocusMode::Focus15),
+        "focus25" => Some(FocusMode::Focus25),
+        "deepwork60" => Some(FocusMode::DeepWork60),
+        "clearinbox10" => Some(FocusMode::ClearInbox10),
+        "prepformeeting" => Some(FocusMode::PrepForMeeting),
+        _ => None,
+    }
+}
+
+/// The built-in modes, exposed as read-only presets
+pub fn builtin_presets() -> Vec<FocusPreset> {
+    vec![
+        FocusPreset::builtin(FocusMode::Focus15, "focus15"),
+        FocusPreset::builtin(FocusMode::Focus25, "focus25"),
+        FocusPreset::builtin(FocusMode::DeepWork60, "deepwork60"),
+        FocusPreset::builtin(FocusMode::ClearInbox10, "clearinbox10"),
+        FocusPreset::builtin(FocusMode::PrepForMeeting, "prepformeeting"),
+    ]
+}
+
+/// Turn a preset name into an id ("Deep Work (Writing)" -> "deep-work-writing")
+fn slugify(name: &str) -> String {
+    let mut slug = String::new();
+    for c in name.trim().to_lowercase().chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c);
+        } else if !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug = slug.trim_matches('-').to_string();
+    if slug.is_empty() {
+        "preset".to_string()
+    } else {
+        slug
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FocusPresetStore {
+    path: PathBuf,
+}
+
+impl FocusPresetStore {
+    pub fn new(data_dir: &Path) -> Self {
+        Self {
+            path: data_dir.join(FOCUS_PRESETS_FILE),
+        }
+    }
+
+    fn load_user_presets(&self) -> Result<Vec<FocusPreset>, String> {
+        Ok(app_data::read_json(&self.path)?.unwrap_or_default())
+    }
+
+    fn save_user_presets(&self, presets: &[FocusPreset]) -> Result<(), String> {
+        app_data::write_json(&self.path, &presets)
+    }
+
+    /// Built-in presets followed by user presets
+    pub fn list(&self) -> Result<Vec<FocusPreset>, String> {
+        let mut presets = builtin_presets();
+        presets.extend(self.load_user_presets()?);
+        Ok(presets)
+    }
+
+    pub fn get(&self, id: &str) -> Result<FocusPreset, String> {
+        self.list()?
+            .into_iter()
+            .find(|p| p.id == id)
+            .ok_or_else(|| format!("Focus preset not found: {}", id))
+    }
+
+    /// Add a new preset; its id is derived from the name
+    pub fn create(&self, mut preset: FocusPreset) -> Result<FocusPreset, String> {
+        preset.validate()?;
+
+        let existing = self.list()?;
+        let base = slugify(&preset.name);
+        let mut id = base.clone();
+        let mut suffix = 2;
+        while existing.iter().any(|p| p.id == id) || builtin_mode(&id).is_some() {
+            id = format!("{}-{}", base, suffix);
+            suffix += 1;
+        }
+
+        preset.id = id;
+        preset.builtin = false;
+
+        let mut user_presets = self.load_user_presets()?;
+        user_presets.push(preset.clone());
+        self.save_user_presets(&user_presets)?;
+        Ok(preset)
+    }
+
+    pub fn update(&self, mut preset: FocusPreset) -> Result<FocusPreset, String> {
+        preset.validate()?;
+        if builtin_mode(&preset.id).is_some() {
+            return Err("Built-in presets can't be modified".to_string());
+        }
+
+        let mut user_presets = self.load_user_presets()?;
+        let existing = user_presets
+            .iter_mut()
+            .find(|p| p.id == preset.id)
+            .ok_or_else(|| format!("Focus preset not found: {}", preset.id))?;
+
+        preset.builtin = false;
+        *existing = preset.clone();
+        self.save_user_presets(&user_presets)?;
+        Ok(preset)
+    }
+
+    pub fn delete(&self, id: &str) -> Result<(), String> {
+        if builtin_mode(id).is_some() {
+            return Err("Built-in presets can't be deleted".to_string());
+        }
+
+        let mut user_presets = self.load_user_presets()?;
+        let before = user_presets.len();
+        user_presets.retain(|p| p.id != id);
+        if user_presets.len() == before {
+            return Err(format!("Focus preset not found: {}", id));
+        }
+        self.save_user_presets(&user_presets)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn preset(name: &str, minutes: u32) -> FocusPreset {
+        FocusPreset {
+            id: String::new(),
+            name: name.to_string(),
+            duration_minutes: minutes,
+            mute_notifications: true,
+            app_allowlist: vec!["code".to_string()],
+            launch_apps: Vec::new(),
+            close_apps: vec!["slack".to_string()],
+            open_url: None,
+            builtin: false,
+        }
+    }
+
+    #[test]
+    fn test_slugify() {
+        assert_eq!(slugify("Deep Work (Writing)"), "deep-work-writing");
+        assert_eq!(slugify("  "), "preset");
+    }
+
+    #[test]
+    fn test_validate_rejects_wildcard_close_apps() {
+        for bad in ["", " ", "*", ".*", "sl?ck", "C:\\Apps\\slack", "/usr/bin/slack", "a|b", ".."] {
+            let mut p = preset("Writing", 45);
+            p.close_apps = vec![bad.to_string()];
+            assert!(p.validate().is_err(), "{:?} should be rejected", bad);
+        }
+        let mut p = preset("Writing", 45);
+        p.close_apps = vec!["slack".to_string(), "Slack.exe".to_string(), "Microsoft Teams".to_string()];
+        assert!(p.validate().is_ok());
+    }
+
+    #[test]
+    fn test_builtin_presets_map_to_modes() {
+        let presets = builtin_presets();
+        let deep_work = presets.iter().find(|p| p.id == "deepwork60").unwrap();
+        assert_eq!(deep_work.focus_mode(), FocusMode::DeepWork60);
+        assert_eq!(deep_work.duration_minutes, 60);
+    }
+
+    #[test]
+    fn test_preset_crud() {
+        let dir = std::env::temp_dir().join(format!("pause-menu-test-presets-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let store = FocusPresetStore::new(&dir);
+
+        let created = store.create(preset("Writing", 45)).unwrap();
+        assert_eq!(created.id, "writing");
+        let duplicate = store.create(preset("Writing", 30)).unwrap();
+        assert_eq!(duplicate.id, "writing-2");
+
+        let mut updated = created.clone();
+        updated.duration_minutes = 50;
+        store.update(updated).unwrap();
+        let loaded = store.get("writing").unwrap();
+        assert_eq!(loaded.duration_minutes, 50);
+        assert_eq!(loaded.focus_mode(), FocusMode::Preset { id: "writing".to_string(), name: "Writing".to_string(), minutes: 50 });
+
+        assert!(store.delete("focus25").is_err());
+        store.delete("writing").unwrap();
+        assert!(store.get("writing").is_err());
+        assert_eq!(store.list().unwrap().len(), builtin_presets().len() + 1);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
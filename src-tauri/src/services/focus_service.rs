@@ -3,10 +3,32 @@
 
 use chrono::{DateTime, Utc, Duration};
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use tokio::sync::RwLock;
-use tokio::time::{sleep, Duration as TokioDuration, Instant};
+use tokio::time::{sleep, Duration as TokioDuration};
 use tauri::{AppHandle, Emitter};
+use crate::models::action::CalendarEvent;
+use crate::services::distraction_blocker::{self, DistractionConfig, DistractionEvent, DistractionPolicy, WindowSource};
+use crate::services::event_bus::{EventBus, EventBusConfig, FocusBusEvent};
+use crate::services::idle_detector::{IdleConfig, IdleEvent, IdleState, IdleTransition};
+use crate::services::focus_planner::{self, FocusBlock, FocusPlan, PlannerConfig};
+use crate::services::focus_history::{self, ExportFormat, FocusEndReason, FocusHistory, FocusRecord, FocusStats, SessionReflection};
+use crate::services::focus_presets::{FocusPreset, FocusPresetStore};
+use crate::services::meeting_guard::{self, MeetingGuardConfig, MeetingWarning};
+use crate::services::meeting_scheduler::{self, ConflictKind, SuggestedSlot};
+use crate::services::notification_allowlist::NotificationAllowlist;
+use crate::services::notification_digest::{NotificationDigest, NotificationQueue};
+use crate::services::pomodoro::{PomodoroConfig, PomodoroPhase, PomodoroState};
+use crate::services::quiet_hours::{self, ActiveQuietHours, QuietHoursStore, QuietHoursTransition};
+use crate::services::window_tracker::WindowTracker;
+use crate::utils::app_data;
+use crate::utils::idle_source::{self, IdleSource};
+use crate::utils::notification_ledger::{NotificationLedger, PlatformRestorer};
+use crate::utils::notification_source::{self, NotificationSource};
+use crate::utils::notification_suppressor::{self, NotificationSuppressor};
+use crate::utils::serde_defaults::default_true;
 
 #[cfg(windows)]
 use windows::Win32::System::Com::*;
@@ -21,6 +43,7 @@ pub enum FocusMode {
     ClearInbox10, // 10 minutes
     PrepForMeeting, // Custom duration
     Custom(u32),  // Custom duration in minutes
+    Preset { id: String, name: String, minutes: u32 }, // User-defined preset
 }
 
 // Custom serialization for FocusMode
@@ -43,6 +66,15 @@ impl Serialize for FocusMode {
                 state.serialize_field("minutes", mins)?;
                 state.end()
             }
+            FocusMode::Preset { id, name, minutes } => {
+                use serde::ser::SerializeStruct;
+                let mut state = serializer.serialize_struct("Preset", 4)?;
+                state.serialize_field("type", "preset")?;
+                state.serialize_field("id", id)?;
+                state.serialize_field("name", name)?;
+                state.serialize_field("minutes", minutes)?;
+                state.end()
+            }
         }
     }
 }
@@ -86,6 +118,8 @@ impl<'de> Deserialize<'de> for FocusMode {
             {
                 let mut mode_type: Option<String> = None;
                 let mut minutes: Option<u32> = None;
+                let mut id: Option<String> = None;
+                let mut name: Option<String> = None;
 
                 while let Some(key) = map.next_key::<String>()? {
                     match key.as_str() {
@@ -101,6 +135,8 @@ impl<'de> Deserialize<'de> for FocusMode {
                             }
                             minutes = Some(map.next_value()?);
                         }
+                        "id" => id = Some(map.next_value()?),
+                        "name" => name = Some(map.next_value()?),
                         _ => {
                             let _ = map.next_value::<de::IgnoredAny>()?;
                         }
@@ -114,8 +150,16 @@ impl<'de> Deserialize<'de> for FocusMode {
                         } else {
                             Err(de::Error::missing_field("minutes"))
                         }
+                    } else if t == "preset" {
+                        let id = id.ok_or_else(|| de::Error::missing_field("id"))?;
+                        let minutes = minutes.ok_or_else(|| de::Error::missing_field("minutes"))?;
+                        Ok(FocusMode::Preset {
+                            name: name.unwrap_or_else(|| id.clone()),
+                            id,
+                            minutes,
+                        })
                     } else {
-                        Err(de::Error::unknown_variant(&t, &["custom"]))
+                        Err(de::Error::unknown_variant(&t, &["custom", "preset"]))
                     }
                 } else {
                     Err(de::Error::missing_field("type"))
@@ -137,6 +181,7 @@ impl FocusMode {
             FocusMode::ClearInbox10 => 10,
             FocusMode::PrepForMeeting => 15, // Default for prep
             FocusMode::Custom(minutes) => *minutes,
+            FocusMode::Preset { minutes, .. } => *minutes,
         }
     }
 
@@ -149,6 +194,7 @@ impl FocusMode {
             FocusMode::ClearInbox10 => "Clear Inbox 10".to_string(),
             FocusMode::PrepForMeeting => "Prep for Meeting".to_string(),
             FocusMode::Custom(mins) => format!("Custom {} min", mins),
+            FocusMode::Preset { name, .. } => name.clone(),
         }
     }
 }
@@ -156,6 +202,8 @@ impl FocusMode {
 // Focus session state
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FocusSession {
+    #[serde(default)]
+    pub id: String,
     pub mode: FocusMode,
     #[serde(with = "chrono::serde::ts_seconds")]
     pub start_time: DateTime<Utc>,
@@ -164,6 +212,98 @@ pub struct FocusSession {
     pub duration_minutes: u32,
     pub is_active: bool,
     pub remaining_seconds: u64,
+    #[serde(default)]
+    pub is_paused: bool,
+    #[serde(default, with = "chrono::serde::ts_seconds_option")]
+    pub paused_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub paused_seconds: u64, // Total time spent paused (excluding a pause in progress)
+    #[serde(default)]
+    pub unmuted_while_paused: bool, // Notifications were restored for the pause and need re-muting on resume
+    #[serde(default = "default_true")]
+    pub mute_notifications: bool,
+    #[serde(default)]
+    pub app_allowlist: Vec<String>, // Apps the user still wants to hear from during the session
+    #[serde(default)]
+    pub distraction_attempts: u32, // Times a blocklisted app gained focus
+    #[serde(default)]
+    pub warned_meeting_id: Option<String>, // Meeting the user has already been warned about
+    #[serde(default)]
+    pub end_reason: Option<String>, // Why the session ended (or will end) early, e.g. an upcoming meeting
+    #[serde(default)]
+    pub idle_seconds: u64, // Time the user was away from the keyboard during the session
+    #[serde(default)]
+    pub goal: Option<String>, // What the user is working on, e.g. "Write the Q3 plan"
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+impl FocusSession {
+    pub fn new(id: String, mode: FocusMode, start_time: DateTime<Utc>) -> Self {
+        let duration_minutes = mode.duration_minutes();
+        Self {
+            id,
+            mode,
+            start_time,
+            end_time: start_time + Duration::minutes(duration_minutes as i64),
+            duration_minutes,
+            is_active: true,
+            remaining_seconds: duration_minutes as u64 * 60,
+            is_paused: false,
+            paused_at: None,
+            paused_seconds: 0,
+            unmuted_while_paused: false,
+            mute_notifications: true,
+            app_allowlist: Vec::new(),
+            distraction_attempts: 0,
+            warned_meeting_id: None,
+            end_reason: None,
+            idle_seconds: 0,
+            goal: None,
+            tags: Vec::new(),
+        }
+    }
+
+    /// Total paused time, including a pause that's still in progress
+    pub fn paused_seconds_at(&self, now: DateTime<Utc>) -> u64 {
+        let current = match self.paused_at {
+            Some(paused_at) if self.is_paused => (now - paused_at).num_seconds().max(0) as u64,
+            _ => 0,
+        };
+        self.paused_seconds + current
+    }
+}
+
+// Per-session behavior, usually taken from a focus preset
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FocusOptions {
+    pub mute_notifications: bool,
+    pub app_allowlist: Vec<String>,
+    #[serde(default)]
+    pub goal: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+impl Default for FocusOptions {
+    fn default() -> Self {
+        Self {
+            mute_notifications: true,
+            app_allowlist: Vec::new(),
+            goal: None,
+            tags: Vec::new(),
+        }
+    }
+}
+
+impl From<&FocusPreset> for FocusOptions {
+    fn from(preset: &FocusPreset) -> Self {
+        Self {
+            mute_notifications: preset.mute_notifications,
+            app_allowlist: preset.app_allowlist.clone(),
+            ..Self::default()
+        }
+    }
 }
 
 // Meeting reschedule suggestion
@@ -174,42 +314,184 @@ pub struct MeetingSuggestion {
     #[serde(with = "chrono::serde::ts_seconds")]
     pub original_time: DateTime<Utc>,
     #[serde(with = "chrono::serde::ts_seconds")]
-    pub suggested_time: DateTime<Utc>,
+    pub suggested_time: DateTime<Utc>, // Best alternative's start
     pub reason: String,
+    pub conflict: ConflictKind,
+    pub alternatives: Vec<SuggestedSlot>, // Free slots of the same length, best first
 }
 
+const MEETING_ALTERNATIVES: usize = 3;
+
 // Windows Focus Assist state (for restoration)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 struct FocusAssistState {
     was_enabled: bool,
     previous_mode: u32, // 0 = Off, 1 = Priority only, 2 = Alarms only
 }
 
+// On-disk record of the active session, used to recover after a crash or restart
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct FocusJournal {
+    session: FocusSession,
+    focus_assist_state: Option<FocusAssistState>,
+    notifications_muted: bool,
+}
+
+const FOCUS_JOURNAL_FILE: &str = "focus_session.json";
+
 pub struct FocusService {
     current_session: Arc<RwLock<Option<FocusSession>>>,
     focus_assist_state: Arc<RwLock<Option<FocusAssistState>>>,
     notification_state: Arc<RwLock<bool>>, // true if notifications were muted
     temporary_mute_end_time: Arc<RwLock<Option<DateTime<Utc>>>>, // when temporary mute expires
-    app_handle: Option<AppHandle>,
+    pomodoro: Arc<RwLock<Option<PomodoroState>>>,
+    pomodoro_generation: Arc<AtomicU64>, // bumped whenever a new pomodoro timer is spawned
+    timer_generation: Arc<AtomicU64>, // bumped whenever a new session timer is spawned
+    data_dir: PathBuf,
+    history: FocusHistory,
+    presets: FocusPresetStore,
+    quiet_hours: QuietHoursStore,
+    quiet_hours_active: Arc<RwLock<Option<ActiveQuietHours>>>,
+    distraction_config: Arc<RwLock<DistractionConfig>>,
+    window_source: Arc<dyn WindowSource>,
+    suppressor: Arc<dyn NotificationSuppressor>,
+    notification_ledger: NotificationLedger,
+    notification_source: Arc<dyn NotificationSource>,
+    notification_queue: NotificationQueue, // notifications that arrived while muted
+    last_digest: Arc<RwLock<Option<NotificationDigest>>>,
+    planner_config: Arc<RwLock<PlannerConfig>>,
+    meeting_guard_config: Arc<RwLock<MeetingGuardConfig>>,
+    notification_allowlist: Arc<RwLock<NotificationAllowlist>>,
+    event_bus: EventBus, // webhook, command and log sinks outside the webview
+    idle_source: Arc<dyn IdleSource>,
+    idle_config: Arc<RwLock<IdleConfig>>,
+    idle_state: Arc<RwLock<IdleState>>,
+    // Shared so timers spawned before the handle is set can still emit events
+    app_handle: Arc<std::sync::RwLock<Option<AppHandle>>>,
 }
 
 impl FocusService {
     pub fn new() -> Self {
+        Self::with_data_dir(app_data::app_data_dir_or_temp())
+    }
+
+    /// Create a service that persists its state under the given directory
+    pub fn with_data_dir(data_dir: PathBuf) -> Self {
+        let quiet_hours = QuietHoursStore::new(&data_dir);
+        let notification_ledger = NotificationLedger::new(&data_dir);
         Self {
             current_session: Arc::new(RwLock::new(None)),
             focus_assist_state: Arc::new(RwLock::new(None)),
             notification_state: Arc::new(RwLock::new(false)),
             temporary_mute_end_time: Arc::new(RwLock::new(None)),
-            app_handle: None,
+            pomodoro: Arc::new(RwLock::new(None)),
+            pomodoro_generation: Arc::new(AtomicU64::new(0)),
+            timer_generation: Arc::new(AtomicU64::new(0)),
+            history: FocusHistory::new(&data_dir),
+            presets: FocusPresetStore::new(&data_dir),
+            quiet_hours_active: Arc::new(RwLock::new(quiet_hours.load_active())),
+            quiet_hours,
+            distraction_config: Arc::new(RwLock::new(DistractionConfig::load(&data_dir).unwrap_or_else(|e| {
+                eprintln!("[Focus] Failed to load distraction blocker config: {}", e);
+                DistractionConfig::default()
+            }))),
+            window_source: Arc::new(WindowTracker::new()),
+            suppressor: notification_suppressor::platform_suppressor(notification_ledger.clone()),
+            notification_ledger,
+            notification_source: notification_source::platform_source(),
+            notification_queue: NotificationQueue::new(),
+            last_digest: Arc::new(RwLock::new(None)),
+            planner_config: Arc::new(RwLock::new(PlannerConfig::load(&data_dir).unwrap_or_else(|e| {
+                eprintln!("[Focus] Failed to load focus planner config: {}", e);
+                PlannerConfig::default()
+            }))),
+            meeting_guard_config: Arc::new(RwLock::new(MeetingGuardConfig::load(&data_dir).unwrap_or_else(|e| {
+                eprintln!("[Focus] Failed to load meeting guard config: {}", e);
+                MeetingGuardConfig::default()
+            }))),
+            notification_allowlist: Arc::new(RwLock::new(NotificationAllowlist::load(&data_dir).unwrap_or_else(|e| {
+                eprintln!("[Focus] Failed to load notification allowlist: {}", e);
+                NotificationAllowlist::default()
+            }))),
+            event_bus: EventBus::new(&data_dir),
+            idle_source: idle_source::platform_idle_source(),
+            idle_config: Arc::new(RwLock::new(IdleConfig::load(&data_dir).unwrap_or_else(|e| {
+                eprintln!("[Focus] Failed to load idle detection config: {}", e);
+                IdleConfig::default()
+            }))),
+            idle_state: Arc::new(RwLock::new(IdleState::default())),
+            data_dir,
+            app_handle: Arc::new(std::sync::RwLock::new(None)),
         }
     }
 
+    /// Replace the window source used by the distraction blocker
+    #[cfg(test)]
+    pub fn with_window_source(mut self, source: Arc<dyn WindowSource>) -> Self {
+        self.window_source = source;
+        self
+    }
+
+    /// Replace the notification backend
+    #[cfg(test)]
+    pub fn with_suppressor(mut self, suppressor: Arc<dyn NotificationSuppressor>) -> Self {
+        self.suppressor = suppressor;
+        self
+    }
+
+    /// Replace where notifications are captured from while muted
+    #[cfg(test)]
+    pub fn with_notification_source(mut self, source: Arc<dyn NotificationSource>) -> Self {
+        self.notification_source = source;
+        self
+    }
+
+    /// Replace where idle time is read from
+    #[cfg(test)]
+    pub fn with_idle_source(mut self, source: Arc<dyn IdleSource>) -> Self {
+        self.idle_source = source;
+        self
+    }
+
+    /// The notification backend, for callers that mute outside of focus sessions
+    pub fn suppressor(&self) -> Arc<dyn NotificationSuppressor> {
+        Arc::clone(&self.suppressor)
+    }
+
     pub fn set_app_handle(&mut self, app: AppHandle) {
-        self.app_handle = Some(app);
+        if let Ok(mut handle) = self.app_handle.write() {
+            *handle = Some(app);
+        }
+    }
+
+    /// Emit an event to the frontend if the app handle has been set
+    fn emit<S: Serialize + Clone>(&self, event: &str, payload: S) {
+        let app = self.app_handle.read().ok().and_then(|handle| handle.clone());
+        if let Some(app) = app {
+            let _ = app.emit(event, payload);
+        }
+    }
+
+    /// Send an event to the webhook, command and log sinks
+    fn publish(&self, event: FocusBusEvent) {
+        self.event_bus.publish(event);
+    }
+
+    /// Record whether notifications are muted, publishing a mute_toggled event when it changes
+    async fn set_notifications_muted(&self, muted: bool) {
+        let was_muted = std::mem::replace(&mut *self.notification_state.write().await, muted);
+        if was_muted != muted {
+            self.publish(FocusBusEvent::MuteToggled { muted });
+        }
     }
 
     /// Start a focus mode session
     pub async fn start_focus_mode(&self, mode: FocusMode) -> std::result::Result<FocusSession, String> {
+        self.start_focus_mode_with_options(mode, FocusOptions::default()).await
+    }
+
+    /// Start a focus session with per-session behavior (e.g. from a preset)
+    pub async fn start_focus_mode_with_options(&self, mode: FocusMode, options: FocusOptions) -> std::result::Result<FocusSession, String> {
         println!("[Focus] ========== start_focus_mode called ==========");
         println!("[Focus] Mode: {:?} ({} minutes)", mode, mode.duration_minutes());
         
@@ -224,262 +506,918 @@ impl FocusService {
                 }
             }
         }
+        if self.pomodoro.read().await.is_some() {
+            let err = "A pomodoro cycle is running - stop it first".to_string();
+            eprintln!("[Focus] ✗ {}", err);
+            return Err(err);
+        }
 
-        let duration_minutes = mode.duration_minutes();
         let start_time = Utc::now();
-        let end_time = start_time + Duration::minutes(duration_minutes as i64);
+        let id = format!("{}-{:08x}", start_time.timestamp_millis(), rand::random::<u32>());
+        let mut session = FocusSession::new(id, mode, start_time);
+        session.mute_notifications = options.mute_notifications;
+        session.app_allowlist = options.app_allowlist;
+        session.goal = options.goal.map(|g| g.trim().to_string()).filter(|g| !g.is_empty());
+        session.tags = focus_history::normalize_tags(&options.tags);
 
         println!("[Focus] Creating session: {} minutes, from {} to {}", 
-            duration_minutes, start_time, end_time);
-
-        let session = FocusSession {
-            mode,
-            start_time,
-            end_time,
-            duration_minutes,
-            is_active: true,
-            remaining_seconds: (duration_minutes * 60) as u64,
-        };
-
-        println!("[Focus] Session created, enabling Focus Assist...");
-        // Enable Focus Assist and mute notifications
-        match self.enable_focus_assist().await {
-            Ok(_) => println!("[Focus] ✓ Focus Assist enabled"),
-            Err(e) => {
-                eprintln!("[Focus] ✗ Failed to enable Focus Assist: {}", e);
-                return Err(format!("Failed to enable Focus Assist: {}", e));
+            session.duration_minutes, session.start_time, session.end_time);
+
+        if session.mute_notifications {
+            let allowlist = self.notification_allowlist_for(&session.app_allowlist).await;
+            // Focus Assist silences every app, so it's left off when some should still get through
+            if allowlist.is_empty() {
+                println!("[Focus] Session created, enabling Focus Assist...");
+                match self.enable_focus_assist().await {
+                    Ok(_) => println!("[Focus] ✓ Focus Assist enabled"),
+                    Err(e) => {
+                        eprintln!("[Focus] ✗ Failed to enable Focus Assist: {}", e);
+                        return Err(format!("Failed to enable Focus Assist: {}", e));
+                    }
+                }
+            } else {
+                println!("[Focus] Session created, leaving Focus Assist off for allowlisted apps: {:?}", allowlist);
             }
-        }
-        
-        println!("[Focus] Muting notifications...");
-        match self.mute_notifications().await {
-            Ok(_) => println!("[Focus] ✓ Notifications muted"),
-            Err(e) => {
-                eprintln!("[Focus] ✗ Failed to mute notifications: {}", e);
-                return Err(format!("Failed to mute notifications: {}", e));
+            
+            println!("[Focus] Muting notifications...");
+            match self.mute_notifications(&allowlist).await {
+                Ok(_) => println!("[Focus] ✓ Notifications muted"),
+                Err(e) => {
+                    eprintln!("[Focus] ✗ Failed to mute notifications: {}", e);
+                    return Err(format!("Failed to mute notifications: {}", e));
+                }
             }
+        } else {
+            println!("[Focus] Session created, leaving notifications on");
         }
 
         // Store session
         *self.current_session.write().await = Some(session.clone());
+        self.write_journal().await;
 
         // Start timer task
-        let session_arc = Arc::clone(&self.current_session);
+        self.spawn_session_timer();
+
+        self.publish(FocusBusEvent::SessionStarted { session: session.clone() });
+        Ok(session)
+    }
+
+    /// Spawn the countdown task for the current session
+    /// The task exits early if the session is stopped or a newer timer replaces it
+    fn spawn_session_timer(&self) {
+        let generation = self.timer_generation.fetch_add(1, Ordering::SeqCst) + 1;
         let focus_service = Arc::new(self.clone_for_timer());
-        let app_handle = self.app_handle.clone();
 
         tokio::spawn(async move {
-            let duration = TokioDuration::from_secs(duration_minutes as u64 * 60);
-            let start = Instant::now();
             let mut last_second = 0u64;
 
             loop {
                 sleep(TokioDuration::from_secs(1)).await;
-                let elapsed = start.elapsed();
 
-                if elapsed >= duration {
-                    // Time's up!
-                    break;
+                if focus_service.timer_generation.load(Ordering::SeqCst) != generation {
+                    return;
                 }
 
-                let remaining = duration.as_secs() - elapsed.as_secs();
-                
                 // Update remaining seconds every second
-                {
-                    let mut session = session_arc.write().await;
-                    if let Some(ref mut s) = *session {
-                        s.remaining_seconds = remaining;
-                        
-                        // Emit countdown update every second
-                        if let Some(ref app) = app_handle {
-                            let _ = app.emit("focus-countdown", remaining);
+                let remaining = {
+                    let mut session = focus_service.current_session.write().await;
+                    match *session {
+                        // Paused sessions keep their timer alive but don't count down
+                        Some(ref s) if s.is_active && s.is_paused => continue,
+                        Some(ref mut s) if s.is_active => {
+                            let remaining = (s.end_time - Utc::now()).num_seconds().max(0) as u64;
+                            s.remaining_seconds = remaining;
+                            remaining
                         }
+                        // Session was stopped early
+                        _ => return,
                     }
+                };
+
+                if remaining == 0 {
+                    // Time's up!
+                    break;
                 }
 
+                // Emit countdown update every second
+                focus_service.emit("focus-countdown", remaining);
+
                 // Emit update every 10 seconds for efficiency
                 if remaining % 10 == 0 && remaining != last_second {
                     last_second = remaining;
-                    if let Some(ref app) = app_handle {
-                        let _ = app.emit("focus-update", remaining);
-                    }
+                    focus_service.emit("focus-update", remaining);
                 }
             }
 
-            // Timer finished - restore and notify
-            {
-                let mut session = session_arc.write().await;
-                if let Some(ref mut s) = *session {
-                    s.is_active = false;
-                    s.remaining_seconds = 0;
+            focus_service.complete_session().await;
+        });
+
+        self.spawn_distraction_watcher(generation);
+        self.spawn_meeting_guard(generation);
+    }
+
+    /// Watch for the next meeting overlapping the session
+    fn spawn_meeting_guard(&self, generation: u64) {
+        use crate::services::calendar_service::get_calendar_service;
+
+        let focus_service = Arc::new(self.clone_for_timer());
+
+        tokio::spawn(async move {
+            loop {
+                sleep(TokioDuration::from_secs(15)).await;
+
+                if focus_service.timer_generation.load(Ordering::SeqCst) != generation {
+                    return;
+                }
+                if !focus_service.is_active().await {
+                    return;
+                }
+                if !focus_service.meeting_guard_config.read().await.enabled {
+                    continue;
                 }
-            }
 
-            // Restore notifications and Focus Assist
-            println!("[Focus] Focus session completed - auto-restoring notifications...");
-            
-            // First, stop the notification blocker if it's running
-            #[cfg(windows)]
-            {
-                use crate::utils::notification_suppression::stop_notification_blocker;
-                stop_notification_blocker();
-                println!("[Focus] Stopped notification blocker");
-            }
-            
-            // Restore Focus Assist
-            let _ = focus_service.restore_focus_assist().await;
-            
-            // Use the comprehensive unmute method to properly restore notifications
-            #[cfg(windows)]
-            {
-                use crate::utils::notification_suppression::unmute_notifications_windows_api;
-                println!("[Focus] Calling unmute_notifications_windows_api...");
-                match unmute_notifications_windows_api() {
-                    Ok(_) => {
-                        println!("[Focus] ✓ Notifications auto-unmuted successfully via Windows API");
-                        // Update notification state
-                        *focus_service.notification_state.write().await = false;
-                        
-                        // Give Windows time to process the changes
-                        sleep(TokioDuration::from_millis(500)).await;
-                        println!("[Focus] Waiting for Windows to apply notification changes...");
-                    }
-                    Err(e) => {
-                        eprintln!("[Focus] Failed to auto-unmute via Windows API: {}, using fallback", e);
-            let _ = focus_service.restore_notifications().await;
-                        // Update notification state even on fallback
-                        *focus_service.notification_state.write().await = false;
-                        sleep(TokioDuration::from_millis(500)).await;
-                    }
+                let Some(session) = focus_service.get_current_session().await else {
+                    return;
+                };
+                let events = {
+                    let calendar_service = get_calendar_service().await;
+                    let calendar_service = calendar_service.lock().await;
+                    calendar_service.get_events().await.unwrap_or_default()
+                };
+
+                let config = focus_service.meeting_guard_config.read().await.clone();
+                if let Some(meeting) = meeting_guard::next_conflict(&config, session.end_time, &events, Utc::now()) {
+                    focus_service.apply_meeting_guard(meeting).await;
                 }
             }
-            
-            #[cfg(not(windows))]
-            {
-                let _ = focus_service.restore_notifications().await;
-                *focus_service.notification_state.write().await = false;
-            }
+        });
+    }
 
-            // Emit completion event
-            if let Some(ref app) = app_handle {
-                let _ = app.emit("focus-complete", ());
-                let _ = app.emit("notifications-restored", ());
-                println!("[Focus] ✓ Emitted focus-complete and notifications-restored events");
-            }
+    /// Warn about, shorten, or end the session for an upcoming meeting
+    async fn apply_meeting_guard(&self, meeting: &CalendarEvent) {
+        let config = self.meeting_guard_config.read().await.clone();
+        let now = Utc::now();
 
-            // Clear session after a delay
-            tokio::spawn(async move {
-                sleep(TokioDuration::from_secs(5)).await;
-                let mut session = session_arc.write().await;
-                *session = None;
-            });
-        });
+        let (decision, session_end) = {
+            let mut session = self.current_session.write().await;
+            let s = match session.as_mut() {
+                Some(s) if s.is_active && !s.is_paused => s,
+                _ => return,
+            };
 
-        Ok(session)
-    }
+            let already_warned = s.warned_meeting_id.as_deref() == Some(meeting.id.as_str());
+            let decision = meeting_guard::evaluate(&config, s.end_time, meeting, now, already_warned);
 
-    /// Stop the current focus session early
-    pub async fn stop_focus_mode(&self) -> std::result::Result<(), String> {
-        let mut session = self.current_session.write().await;
-        
-        if let Some(ref mut s) = *session {
-            if !s.is_active {
-                return Err("No active focus session".to_string());
+            if decision.warn {
+                s.warned_meeting_id = Some(meeting.id.clone());
             }
-            s.is_active = false;
-        } else {
-            return Err("No active focus session".to_string());
+            if let Some(new_end) = decision.new_end {
+                if new_end < s.end_time {
+                    println!("[Focus] Shortening session to end at {} for '{}'", new_end, meeting.title);
+                    s.end_time = new_end;
+                    s.remaining_seconds = (new_end - now).num_seconds().max(0) as u64;
+                    s.end_reason = Some(format!("Shortened for meeting '{}'", meeting.title));
+                }
+            }
+            if decision.end_now {
+                s.end_reason = Some(format!("Ended for meeting '{}'", meeting.title));
+            }
+            (decision, s.end_time)
+        };
+
+        if decision.warn {
+            let ends_at = if decision.end_now {
+                now
+            } else {
+                decision.new_end.unwrap_or(meeting.start_time - Duration::minutes(config.grace_minutes as i64))
+            };
+            println!("[Focus] Meeting '{}' starts at {} - warning user", meeting.title, meeting.start_time);
+            self.emit("focus-meeting-warning", MeetingWarning {
+                event_id: meeting.id.clone(),
+                title: meeting.title.clone(),
+                meeting_start: meeting.start_time,
+                minutes_until: (meeting.start_time - now).num_minutes(),
+                action: config.action,
+                session_ends_at: ends_at.min(session_end),
+            });
         }
 
-        // Restore notifications and Focus Assist
-        // Always unmute notifications when stopping focus, even if they were muted independently
-        println!("[Focus] Stopping focus mode - unmuting notifications...");
-        
-        // Stop the notification blocker first
-        #[cfg(windows)]
-        {
-            use crate::utils::notification_suppression::stop_notification_blocker;
-            stop_notification_blocker();
-            println!("[Focus] Stopped notification blocker");
+        if decision.warn || decision.new_end.is_some() {
+            self.write_journal().await;
         }
-        
-        self.restore_focus_assist().await?;
-        self.restore_notifications().await?;
-        
-        // Also explicitly unmute notifications (handles both focus-muted and independently-muted notifications)
-        let is_muted = self.is_notifications_muted().await;
-        if is_muted {
-            println!("[Focus] Notifications are muted - unmuting them...");
-            if let Err(e) = self.unmute_notifications().await {
-                eprintln!("[Focus] Warning: Failed to unmute notifications: {}", e);
-                // Continue anyway - we tried
+
+        if decision.end_now {
+            println!("[Focus] Ending session for meeting '{}'", meeting.title);
+            if let Err(e) = self.end_session(FocusEndReason::Meeting).await {
+                eprintln!("[Focus] Failed to end session for meeting: {}", e);
             }
         }
+    }
 
-        // Clear session
-        *session = None;
+    pub async fn get_meeting_guard_config(&self) -> MeetingGuardConfig {
+        self.meeting_guard_config.read().await.clone()
+    }
 
-        if let Some(ref app) = self.app_handle {
-            let _ = app.emit("focus-stopped", ());
-        }
+    pub async fn set_meeting_guard_config(&self, config: MeetingGuardConfig) -> std::result::Result<(), String> {
+        config.validate()?;
+        config.save(&self.data_dir)?;
+        *self.meeting_guard_config.write().await = config;
+        Ok(())
+    }
+
+    pub async fn get_notification_allowlist(&self) -> NotificationAllowlist {
+        self.notification_allowlist.read().await.clone()
+    }
 
+    pub async fn set_notification_allowlist(&self, allowlist: NotificationAllowlist) -> std::result::Result<(), String> {
+        allowlist.validate()?;
+        allowlist.save(&self.data_dir)?;
+        self.warn_if_allowlist_unsupported(&allowlist.apps);
+        *self.notification_allowlist.write().await = allowlist;
         Ok(())
     }
 
-    /// Get current focus session
-    pub async fn get_current_session(&self) -> Option<FocusSession> {
-        self.current_session.read().await.clone()
+    /// Tell the UI when allowlisted apps will be muted anyway because the backend can't spare them
+    /// Returns whether the warning was sent
+    fn warn_if_allowlist_unsupported(&self, allowlist: &[String]) -> bool {
+        if allowlist.is_empty() || self.suppressor.supports_allowlist() {
+            return false;
+        }
+        eprintln!("[Focus] The {} backend can't spare allowlisted apps, muting them too: {}", self.suppressor.name(), allowlist.join(", "));
+        self.emit("notification-allowlist-unsupported", allowlist.to_vec());
+        true
     }
 
-    /// Enable Windows Focus Assist
-    #[cfg(windows)]
-    async fn enable_focus_assist(&self) -> std::result::Result<(), String> {
-        unsafe {
-            // Initialize COM
-            let _ = CoInitializeEx(None, COINIT_APARTMENTTHREADED);
-            // Note: CoInitializeEx can return S_FALSE if already initialized, which is OK
+    pub fn get_event_bus_config(&self) -> EventBusConfig {
+        self.event_bus.config()
+    }
 
-            // Try to get current Focus Assist state
-            let current_state = self.get_focus_assist_state().await;
-            
-            // Save current state for restoration
-            {
-                let mut state = self.focus_assist_state.write().await;
-                *state = Some(FocusAssistState {
-                    was_enabled: current_state.is_some(),
-                    previous_mode: current_state.unwrap_or(0),
-                });
-            }
+    pub fn set_event_bus_config(&self, config: EventBusConfig) -> std::result::Result<(), String> {
+        self.event_bus.set_config(config)
+    }
 
-            // Enable Focus Assist using registry
-            // Focus Assist is controlled via registry: HKEY_CURRENT_USER\Software\Microsoft\Windows\CurrentVersion\CloudStore\Store\Cache\DefaultAccount\$windows.data.notifications.quiescence.win10\Current
-            // For simplicity, we'll use a PowerShell command or direct registry manipulation
-            
-            // Method 1: Use Windows API to set Focus Assist to "Alarms only" (mode 2)
-            // This is the most reliable way
-            match self.set_focus_assist_registry(2) {
-                Ok(_) => {
-                    println!("Focus Assist enabled successfully");
-                    Ok(())
+    /// Apps that may keep notifying: the saved allowlist plus the session's own
+    pub async fn notification_allowlist_for(&self, session_allowlist: &[String]) -> Vec<String> {
+        self.notification_allowlist.read().await.merged_with(session_allowlist)
+    }
+
+    /// Watch for blocklisted apps gaining focus while the session runs
+    fn spawn_distraction_watcher(&self, generation: u64) {
+        let focus_service = Arc::new(self.clone_for_timer());
+
+        tokio::spawn(async move {
+            loop {
+                sleep(TokioDuration::from_secs(1)).await;
+
+                if focus_service.timer_generation.load(Ordering::SeqCst) != generation {
+                    return;
                 }
-                Err(e) => {
-                    eprintln!("Warning: Failed to enable Focus Assist via registry: {}", e);
-                    // Fallback: Just log that we tried
-        Ok(())
+                if !focus_service.is_active().await {
+                    return;
+                }
+
+                if let Some(event) = focus_service.check_distractions().await {
+                    if event.policy == DistractionPolicy::ShowOverlay {
+                        let app = focus_service.app_handle.read().ok().and_then(|handle| handle.clone());
+                        if let Some(app) = app {
+                            if let Err(e) = crate::commands::overlay::show_overlay(app).await {
+                                eprintln!("[Distraction] Failed to show overlay: {}", e);
+                            }
+                        }
+                    }
+                }
+            }
+        });
     }
-}
+
+    /// Check the foreground window once against the blocklist
+    /// Emits `focus-distraction` and counts the attempt on the session when it matches
+    async fn check_distractions(&self) -> Option<DistractionEvent> {
+        let (session_id, allowlist) = {
+            let session = self.current_session.read().await;
+            match session.as_ref() {
+                Some(s) if s.is_active && !s.is_paused => (s.id.clone(), s.app_allowlist.clone()),
+                _ => return None,
+            }
+        };
+
+        let config = self.distraction_config.read().await.clone();
+        if !config.enabled {
+            return None;
+        }
+
+        let event = match distraction_blocker::check_for_distraction(self.window_source.as_ref(), &config, &session_id, &allowlist) {
+            Ok(Some(event)) => event,
+            Ok(None) => return None,
+            Err(e) => {
+                eprintln!("[Distraction] Failed to check foreground window: {}", e);
+                return None;
+            }
+        };
+
+        if let Some(ref mut s) = *self.current_session.write().await {
+            s.distraction_attempts += 1;
         }
+        self.emit("focus-distraction", event.clone());
+        self.publish(FocusBusEvent::DistractionDetected { distraction: event.clone() });
+        Some(event)
     }
 
-    #[cfg(not(windows))]
-    async fn enable_focus_assist(&self) -> std::result::Result<(), String> {
-        // Not available on non-Windows platforms
+    pub async fn get_distraction_config(&self) -> DistractionConfig {
+        self.distraction_config.read().await.clone()
+    }
+
+    pub async fn set_distraction_config(&self, config: DistractionConfig) -> std::result::Result<(), String> {
+        config.save(&self.data_dir)?;
+        *self.distraction_config.write().await = config;
         Ok(())
     }
 
-    /// Get current Focus Assist state
-    #[cfg(windows)]
-    async fn get_focus_assist_state(&self) -> Option<u32> {
+    /// Finish the current session once its timer runs out
+    async fn complete_session(&self) {
+        // Timer finished - restore and notify
+        let finished = {
+            let mut session = self.current_session.write().await;
+            if let Some(ref mut s) = *session {
+                s.is_active = false;
+                s.remaining_seconds = 0;
+            }
+            session.clone()
+        };
+        if let Some(ref session) = finished {
+            // A session shortened for a meeting still counts as ending because of it
+            let reason = if session.end_reason.is_some() { FocusEndReason::Meeting } else { FocusEndReason::Completed };
+            self.record_session(session, Utc::now(), reason);
+            self.publish(FocusBusEvent::SessionEnded { session: session.clone(), reason });
+        }
+
+        println!("[Focus] Focus session completed - auto-restoring notifications...");
+        self.restore_after_session().await;
+        self.clear_journal();
+        self.present_notification_digest().await;
+
+        // Emit completion event
+        self.emit("focus-complete", ());
+        self.emit("notifications-restored", ());
+        println!("[Focus] ✓ Emitted focus-complete and notifications-restored events");
+
+        // Clear session after a delay (unless a new session has started meanwhile)
+        let session_arc = Arc::clone(&self.current_session);
+        tokio::spawn(async move {
+            sleep(TokioDuration::from_secs(5)).await;
+            let mut session = session_arc.write().await;
+            if session.as_ref().map(|s| !s.is_active).unwrap_or(false) {
+                *session = None;
+            }
+        });
+    }
+
+    /// Restore Focus Assist and notifications after a session ends on its own
+    async fn restore_after_session(&self) {
+        // First, stop the notification blocker if it's running
+        self.suppressor.stop_blocker();
+        
+        // Restore Focus Assist
+        let _ = self.restore_focus_assist().await;
+        
+        // Release the notifications bus name before unmuting, or anything arriving in between is swallowed
+        self.stop_notification_capture();
+        println!("[Focus] Unmuting notifications via {} backend...", self.suppressor.name());
+        match self.suppressor.unmute() {
+            Ok(_) => println!("[Focus] ✓ Notifications auto-unmuted successfully"),
+            Err(e) => eprintln!("[Focus] Failed to auto-unmute notifications: {}", e),
+        }
+        self.set_notifications_muted(false).await;
+
+        // Give Windows time to process the changes
+        #[cfg(windows)]
+        sleep(TokioDuration::from_millis(500)).await;
+    }
+
+    /// Append a finished session to the history store
+    fn record_session(&self, session: &FocusSession, ended_at: DateTime<Utc>, end_reason: FocusEndReason) {
+        let record = FocusRecord::from_session(session, ended_at, end_reason);
+        match self.history.append(&record) {
+            Ok(_) => println!("[Focus] Recorded {:?} session ({} of {} min)", end_reason, record.actual_minutes, record.planned_minutes),
+            Err(e) => eprintln!("[Focus] Warning: Failed to record focus session: {}", e),
+        }
+    }
+
+    /// Get finished sessions, most recent first
+    pub fn get_history(&self, limit: Option<usize>) -> std::result::Result<Vec<FocusRecord>, String> {
+        let mut records = self.history.load()?;
+        records.reverse();
+        if let Some(limit) = limit {
+            records.truncate(limit);
+        }
+        Ok(records)
+    }
+
+    /// Finished sessions carrying `tag`, most recent first
+    pub fn search_history(&self, tag: &str, limit: Option<usize>) -> std::result::Result<Vec<FocusRecord>, String> {
+        let mut records = self.get_history(None)?;
+        records.retain(|r| r.has_tag(tag));
+        if let Some(limit) = limit {
+            records.truncate(limit);
+        }
+        Ok(records)
+    }
+
+    /// Attach an outcome note and 1-5 rating to a finished session (the most recent one if `session_id` is None)
+    pub fn reflect_on_session(&self, session_id: Option<String>, rating: u8, note: &str) -> std::result::Result<FocusRecord, String> {
+        let reflection = SessionReflection::new(rating, note)?;
+        let session_id = match session_id {
+            Some(id) => id,
+            None => self
+                .history
+                .load()?
+                .pop()
+                .map(|r| r.id)
+                .ok_or("No finished focus sessions to reflect on")?,
+        };
+        let record = self.history.update(&session_id, |r| r.reflection = Some(reflection))?;
+        println!("[Focus] Saved reflection for session {} ({}/5)", record.id, rating);
+        Ok(record)
+    }
+
+    /// Sessions from the last `days` days (all if None), optionally only those with `tag`, as Markdown or CSV
+    pub fn export_history(&self, format: ExportFormat, days: Option<u32>, tag: Option<&str>) -> std::result::Result<String, String> {
+        let mut records = self.history.load()?;
+        if let Some(days) = days {
+            // A range reaching past chrono's earliest date keeps everything
+            if let Some(since) = Utc::now().checked_sub_signed(Duration::days(days as i64)) {
+                records.retain(|r| r.started_at >= since);
+            }
+        }
+        if let Some(tag) = tag {
+            records.retain(|r| r.has_tag(tag));
+        }
+        Ok(focus_history::export_records(&records, format, &chrono::Local))
+    }
+
+    /// Saved focus presets (built-in and user-defined)
+    pub fn presets(&self) -> &FocusPresetStore {
+        &self.presets
+    }
+
+    /// Saved quiet-hours rules
+    pub fn quiet_hours(&self) -> &QuietHoursStore {
+        &self.quiet_hours
+    }
+
+    /// Get focus statistics for the last `days` days (local time)
+    pub fn get_stats(&self, days: u32) -> std::result::Result<FocusStats, String> {
+        let records = self.history.load()?;
+        let today = chrono::Local::now().date_naive();
+        Ok(focus_history::compute_stats(&records, today, &chrono::Local, days))
+    }
+
+    fn journal_path(&self) -> PathBuf {
+        self.data_dir.join(FOCUS_JOURNAL_FILE)
+    }
+
+    /// Persist the active session and the notification state needed to undo it
+    async fn write_journal(&self) {
+        let session = self.current_session.read().await.clone();
+        let session = match session {
+            Some(s) if s.is_active => s,
+            _ => {
+                self.clear_journal();
+                return;
+            }
+        };
+
+        let journal = FocusJournal {
+            session,
+            focus_assist_state: self.focus_assist_state.read().await.clone(),
+            notifications_muted: *self.notification_state.read().await,
+        };
+
+        if let Err(e) = app_data::write_json(&self.journal_path(), &journal) {
+            eprintln!("[Focus] Warning: Failed to write session journal: {}", e);
+        }
+    }
+
+    fn clear_journal(&self) {
+        if let Err(e) = app_data::remove_file(&self.journal_path()) {
+            eprintln!("[Focus] Warning: Failed to clear session journal: {}", e);
+        }
+    }
+
+    /// Recover a session left behind by a crash or restart
+    /// Resumes the countdown if the session is still running, otherwise restores notifications
+    pub async fn recover_from_journal(&self) {
+        let journal: FocusJournal = match app_data::read_json(&self.journal_path()) {
+            Ok(Some(journal)) => journal,
+            Ok(None) => return,
+            Err(e) => {
+                eprintln!("[Focus] Discarding unreadable session journal: {}", e);
+                self.clear_journal();
+                return;
+            }
+        };
+
+        println!("[Focus] Found session journal for {} (ends {})", journal.session.mode.name(), journal.session.end_time);
+
+        *self.focus_assist_state.write().await = journal.focus_assist_state.clone();
+        *self.notification_state.write().await = journal.notifications_muted;
+
+        let now = Utc::now();
+        if journal.session.is_active && (journal.session.is_paused || journal.session.end_time > now) {
+            let mut session = journal.session;
+            if !session.is_paused {
+                session.remaining_seconds = (session.end_time - now).num_seconds().max(0) as u64;
+            }
+            println!("[Focus] Resuming focus session with {} seconds remaining (paused: {})", session.remaining_seconds, session.is_paused);
+
+            // The backend's mute (e.g. a D-Bus inhibit) went away with the old process, so apply it again
+            let remute = session.mute_notifications && !session.unmuted_while_paused;
+            let allowlist = self.notification_allowlist_for(&session.app_allowlist).await;
+            *self.current_session.write().await = Some(session);
+            if remute {
+                if let Err(e) = self.mute_notifications(&allowlist).await {
+                    eprintln!("[Focus] Failed to re-mute notifications: {}", e);
+                }
+            }
+            self.spawn_session_timer();
+        } else {
+            println!("[Focus] Session expired while the app was closed - restoring notifications...");
+            // We can't know exactly when the app went down, so assume focus lasted until the planned end
+            let ended_at = journal.session.end_time.min(now);
+            self.record_session(&journal.session, ended_at, FocusEndReason::Interrupted);
+            self.restore_after_session().await;
+            self.clear_journal();
+            self.emit("notifications-restored", ());
+        }
+    }
+
+    /// Stop the current focus session early
+    pub async fn stop_focus_mode(&self) -> std::result::Result<(), String> {
+        self.end_session(FocusEndReason::Cancelled).await
+    }
+
+    /// End the active session early, recording why in history
+    async fn end_session(&self, end_reason: FocusEndReason) -> std::result::Result<(), String> {
+        let mut session = self.current_session.write().await;
+        
+        if let Some(ref mut s) = *session {
+            if !s.is_active {
+                return Err("No active focus session".to_string());
+            }
+            s.is_active = false;
+        } else {
+            return Err("No active focus session".to_string());
+        }
+
+        // Restore notifications and Focus Assist
+        // Always unmute notifications when stopping focus, even if they were muted independently
+        println!("[Focus] Stopping focus mode - unmuting notifications...");
+        
+        // Stop the notification blocker first
+        self.suppressor.stop_blocker();
+        
+        self.restore_focus_assist().await?;
+        self.restore_notifications().await?;
+        
+        // Also explicitly unmute notifications (handles both focus-muted and independently-muted notifications)
+        let is_muted = self.is_notifications_muted().await;
+        if is_muted {
+            println!("[Focus] Notifications are muted - unmuting them...");
+            if let Err(e) = self.unmute_notifications().await {
+                eprintln!("[Focus] Warning: Failed to unmute notifications: {}", e);
+                // Continue anyway - we tried
+            }
+        }
+        self.present_notification_digest().await;
+
+        // Clear session
+        if let Some(ref s) = *session {
+            self.record_session(s, Utc::now(), end_reason);
+            self.publish(FocusBusEvent::SessionEnded { session: s.clone(), reason: end_reason });
+        }
+        *session = None;
+        self.clear_journal();
+
+        self.emit("focus-stopped", ());
+
+        Ok(())
+    }
+
+    /// Pause the active session, freezing its countdown
+    /// If `restore_notifications` is set, notifications are let through until the session resumes
+    pub async fn pause_focus_mode(&self, restore_notifications: bool) -> std::result::Result<FocusSession, String> {
+        self.pause_session_at(restore_notifications, Utc::now()).await
+    }
+
+    /// Pause the active session as of `at`, which may be in the past (e.g. when the user went idle)
+    async fn pause_session_at(&self, restore_notifications: bool, at: DateTime<Utc>) -> std::result::Result<FocusSession, String> {
+        let paused = {
+            let mut session = self.current_session.write().await;
+            let s = match session.as_mut() {
+                Some(s) if s.is_active => s,
+                _ => return Err("No active focus session".to_string()),
+            };
+            if s.is_paused {
+                return Err("Focus session is already paused".to_string());
+            }
+
+            let now = at.max(s.start_time).min(Utc::now());
+            s.remaining_seconds = (s.end_time - now).num_seconds().max(0) as u64;
+            s.is_paused = true;
+            s.paused_at = Some(now);
+            s.unmuted_while_paused = restore_notifications && s.mute_notifications;
+            s.clone()
+        };
+
+        println!("[Focus] Session paused with {} seconds remaining", paused.remaining_seconds);
+
+        if paused.unmuted_while_paused {
+            self.restore_after_session().await;
+            self.emit("notifications-restored", ());
+        }
+
+        self.write_journal().await;
+        self.emit("focus-paused", paused.clone());
+        self.publish(FocusBusEvent::SessionPaused { session: paused.clone() });
+        Ok(paused)
+    }
+
+    /// Resume a paused session, pushing its end time back by the time spent paused
+    pub async fn resume_focus_mode(&self) -> std::result::Result<FocusSession, String> {
+        let resumed = {
+            let mut session = self.current_session.write().await;
+            let s = match session.as_mut() {
+                Some(s) if s.is_active => s,
+                _ => return Err("No active focus session".to_string()),
+            };
+            if !s.is_paused {
+                return Err("Focus session is not paused".to_string());
+            }
+
+            let now = Utc::now();
+            s.paused_seconds = s.paused_seconds_at(now);
+            s.end_time = now + Duration::seconds(s.remaining_seconds as i64);
+            s.is_paused = false;
+            s.paused_at = None;
+            s.clone()
+        };
+
+        println!("[Focus] Session resumed, now ends at {} ({}s paused in total)", resumed.end_time, resumed.paused_seconds);
+
+        if resumed.unmuted_while_paused {
+            let allowlist = self.notification_allowlist_for(&resumed.app_allowlist).await;
+            if allowlist.is_empty() {
+                if let Err(e) = self.enable_focus_assist().await {
+                    eprintln!("[Focus] Failed to re-enable Focus Assist: {}", e);
+                }
+            }
+            if let Err(e) = self.mute_notifications(&allowlist).await {
+                eprintln!("[Focus] Failed to re-mute notifications: {}", e);
+            }
+            if let Some(ref mut s) = *self.current_session.write().await {
+                s.unmuted_while_paused = false;
+            }
+        }
+
+        self.write_journal().await;
+        let resumed = self.get_current_session().await.unwrap_or(resumed);
+        self.emit("focus-resumed", resumed.clone());
+        self.publish(FocusBusEvent::SessionResumed { session: resumed.clone() });
+        Ok(resumed)
+    }
+
+    /// Get current focus session
+    pub async fn get_current_session(&self) -> Option<FocusSession> {
+        self.current_session.read().await.clone()
+    }
+
+    /// Start a pomodoro cycle, beginning with a work phase
+    pub async fn start_pomodoro(&self, config: PomodoroConfig) -> std::result::Result<PomodoroState, String> {
+        config.validate()?;
+
+        if self.is_active().await {
+            return Err("A focus session is already active".to_string());
+        }
+        if self.pomodoro.read().await.is_some() {
+            return Err("A pomodoro cycle is already running".to_string());
+        }
+
+        println!("[Pomodoro] Starting cycle: {:?}", config);
+        let state = PomodoroState::new(config, Utc::now());
+        *self.pomodoro.write().await = Some(state.clone());
+
+        self.apply_pomodoro_phase(None, state.phase).await;
+        self.emit("focus-phase-changed", state.clone());
+        self.spawn_pomodoro_timer();
+
+        Ok(state)
+    }
+
+    /// Stop the pomodoro cycle and restore notifications
+    pub async fn stop_pomodoro(&self) -> std::result::Result<(), String> {
+        let state = self.pomodoro.write().await.take().ok_or("No pomodoro cycle is running")?;
+        self.pomodoro_generation.fetch_add(1, Ordering::SeqCst);
+
+        if state.phase == PomodoroPhase::Work {
+            self.record_pomodoro_work(&state, Utc::now(), FocusEndReason::Cancelled);
+            self.restore_after_session().await;
+        }
+
+        println!("[Pomodoro] Cycle stopped after {} completed work phases", state.completed_work_cycles);
+        self.emit("pomodoro-stopped", ());
+        Ok(())
+    }
+
+    pub async fn pause_pomodoro(&self) -> std::result::Result<PomodoroState, String> {
+        let mut pomodoro = self.pomodoro.write().await;
+        let state = pomodoro.as_mut().ok_or("No pomodoro cycle is running")?;
+        state.pause(Utc::now())?;
+        self.emit("focus-phase-changed", state.clone());
+        Ok(state.clone())
+    }
+
+    pub async fn resume_pomodoro(&self) -> std::result::Result<PomodoroState, String> {
+        let mut pomodoro = self.pomodoro.write().await;
+        let state = pomodoro.as_mut().ok_or("No pomodoro cycle is running")?;
+        state.resume(Utc::now())?;
+        self.emit("focus-phase-changed", state.clone());
+        Ok(state.clone())
+    }
+
+    /// Skip to the next phase without counting the current one as completed
+    pub async fn skip_pomodoro_phase(&self) -> std::result::Result<PomodoroState, String> {
+        self.advance_pomodoro(false).await
+    }
+
+    /// Restart the set from the first work phase
+    pub async fn reset_pomodoro(&self) -> std::result::Result<PomodoroState, String> {
+        let (previous, state) = {
+            let mut pomodoro = self.pomodoro.write().await;
+            let state = pomodoro.as_mut().ok_or("No pomodoro cycle is running")?;
+            let previous = state.clone();
+            state.reset(Utc::now());
+            (previous, state.clone())
+        };
+
+        if previous.phase == PomodoroPhase::Work {
+            self.record_pomodoro_work(&previous, Utc::now(), FocusEndReason::Cancelled);
+        }
+        self.apply_pomodoro_phase(Some(previous.phase), state.phase).await;
+        self.emit("focus-phase-changed", state.clone());
+        Ok(state)
+    }
+
+    pub async fn get_pomodoro_state(&self) -> Option<PomodoroState> {
+        let mut pomodoro = self.pomodoro.write().await;
+        pomodoro.as_mut().map(|state| {
+            state.tick(Utc::now());
+            state.clone()
+        })
+    }
+
+    /// Move the cycle to its next phase, muting or restoring notifications as needed
+    async fn advance_pomodoro(&self, completed: bool) -> std::result::Result<PomodoroState, String> {
+        let now = Utc::now();
+        let (previous, state) = {
+            let mut pomodoro = self.pomodoro.write().await;
+            let state = pomodoro.as_mut().ok_or("No pomodoro cycle is running")?;
+            let previous = state.clone();
+            state.advance(now, completed);
+            (previous, state.clone())
+        };
+
+        if previous.phase == PomodoroPhase::Work {
+            let reason = if completed { FocusEndReason::Completed } else { FocusEndReason::Cancelled };
+            self.record_pomodoro_work(&previous, now, reason);
+        }
+
+        println!("[Pomodoro] {:?} -> {:?} (cycle {})", previous.phase, state.phase, state.cycle);
+        self.apply_pomodoro_phase(Some(previous.phase), state.phase).await;
+        self.emit("focus-phase-changed", state.clone());
+        Ok(state)
+    }
+
+    /// Notifications are only muted during work phases
+    async fn apply_pomodoro_phase(&self, previous: Option<PomodoroPhase>, phase: PomodoroPhase) {
+        let was_work = previous == Some(PomodoroPhase::Work);
+        let is_work = phase == PomodoroPhase::Work;
+
+        if is_work && !was_work {
+            let allowlist = self.notification_allowlist_for(&[]).await;
+            if allowlist.is_empty() {
+                if let Err(e) = self.enable_focus_assist().await {
+                    eprintln!("[Pomodoro] Failed to enable Focus Assist: {}", e);
+                }
+            }
+            if let Err(e) = self.mute_notifications(&allowlist).await {
+                eprintln!("[Pomodoro] Failed to mute notifications: {}", e);
+            }
+        } else if was_work && !is_work {
+            self.restore_after_session().await;
+            self.emit("notifications-restored", ());
+        }
+    }
+
+    fn record_pomodoro_work(&self, state: &PomodoroState, ended_at: DateTime<Utc>, end_reason: FocusEndReason) {
+        let id = format!("{}-pomodoro-{}", state.phase_started_at.timestamp_millis(), state.cycle);
+        let mut session = FocusSession::new(id, state.config.work_mode(), state.phase_started_at);
+        session.is_active = false;
+        session.remaining_seconds = 0;
+        // Time spent paused isn't focus time, same as for focus sessions
+        session.paused_seconds = state.paused_seconds_at(ended_at);
+        self.record_session(&session, ended_at, end_reason);
+    }
+
+    /// Spawn the countdown task that drives phase transitions
+    fn spawn_pomodoro_timer(&self) {
+        let generation = self.pomodoro_generation.fetch_add(1, Ordering::SeqCst) + 1;
+        let focus_service = Arc::new(self.clone_for_timer());
+
+        tokio::spawn(async move {
+            loop {
+                sleep(TokioDuration::from_secs(1)).await;
+
+                if focus_service.pomodoro_generation.load(Ordering::SeqCst) != generation {
+                    return;
+                }
+
+                let (remaining, is_paused) = {
+                    let mut pomodoro = focus_service.pomodoro.write().await;
+                    match pomodoro.as_mut() {
+                        Some(state) => (state.tick(Utc::now()), state.is_paused),
+                        // Cycle was stopped
+                        None => return,
+                    }
+                };
+
+                if is_paused {
+                    continue;
+                }
+
+                if remaining == 0 {
+                    let _ = focus_service.advance_pomodoro(true).await;
+                } else {
+                    focus_service.emit("pomodoro-countdown", remaining);
+                }
+            }
+        });
+    }
+
+    /// Enable Windows Focus Assist
+    #[cfg(windows)]
+    async fn enable_focus_assist(&self) -> std::result::Result<(), String> {
+        unsafe {
+            // Initialize COM
+            let _ = CoInitializeEx(None, COINIT_APARTMENTTHREADED);
+            // Note: CoInitializeEx can return S_FALSE if already initialized, which is OK
+
+            // Record the current quiet hours settings so a crash can't leave them on
+            match crate::utils::notification_ledger::snapshot_windows_settings() {
+                Ok(snapshot) => self.notification_ledger.record(snapshot)?,
+                Err(e) => return Err(format!("Failed to record Focus Assist settings: {}", e)),
+            }
+
+            // Try to get current Focus Assist state
+            let current_state = self.get_focus_assist_state().await;
+            
+            // Save current state for restoration
+            {
+                let mut state = self.focus_assist_state.write().await;
+                *state = Some(FocusAssistState {
+                    was_enabled: current_state.is_some(),
+                    previous_mode: current_state.unwrap_or(0),
+                });
+            }
+
+            // Enable Focus Assist using registry
+            // Focus Assist is controlled via registry: HKEY_CURRENT_USER\Software\Microsoft\Windows\CurrentVersion\CloudStore\Store\Cache\DefaultAccount\$windows.data.notifications.quiescence.win10\Current
+            // For simplicity, we'll use a PowerShell command or direct registry manipulation
+            
+            // Method 1: Use Windows API to set Focus Assist to "Alarms only" (mode 2)
+            // This is the most reliable way
+            match self.set_focus_assist_registry(2) {
+                Ok(_) => {
+                    println!("Focus Assist enabled successfully");
+                    Ok(())
+                }
+                Err(e) => {
+                    eprintln!("Warning: Failed to enable Focus Assist via registry: {}", e);
+                    // Fallback: Just log that we tried
+        Ok(())
+    }
+}
+        }
+    }
+
+    #[cfg(not(windows))]
+    async fn enable_focus_assist(&self) -> std::result::Result<(), String> {
+        // Not available on non-Windows platforms
+        Ok(())
+    }
+
+    /// Get current Focus Assist state
+    #[cfg(windows)]
+    async fn get_focus_assist_state(&self) -> Option<u32> {
         // Use PowerShell to get Focus Assist state
         // Focus Assist state: 0 = Off, 1 = Priority only, 2 = Alarms only
         let _output = std::process::Command::new("powershell")
@@ -850,200 +1788,52 @@ impl FocusService {
         Ok(())
     }
 
-    /// Mute notifications using Windows Runtime Notification API
-    /// This directly suppresses notifications without relying on Focus Assist registry
-    async fn mute_notifications(&self) -> std::result::Result<(), String> {
-        println!("[Focus] Muting notifications using multi-method approach...");
-        *self.notification_state.write().await = true;
-        
-        #[cfg(windows)]
-        {
-            // Use aggressive multi-method approach
-            println!("[Focus] Applying system-wide notification suppression...");
-            let _ = self.disable_notifications_system_wide().await;
-            
-            println!("[Focus] Disabling app-specific notifications...");
-            let _ = self.disable_app_notifications().await;
-            
-            // Try Windows Runtime API (may fail but worth trying)
-            println!("[Focus] Attempting Windows Runtime API suppression...");
-            let _ = self.suppress_notifications_directly().await;
-            
-            println!("[Focus] ✓ Notifications muted using multiple methods");
-        }
+    /// Mute notifications through the platform backend, sparing apps on `allowlist`
+    /// Failures are logged but don't stop the session from starting
+    async fn mute_notifications(&self, allowlist: &[String]) -> std::result::Result<(), String> {
+        println!("[Focus] Muting notifications via {} backend...", self.suppressor.name());
+        self.set_notifications_muted(true).await;
+        self.warn_if_allowlist_unsupported(allowlist);
 
-        #[cfg(not(windows))]
-        {
-            println!("[Focus] Notification muting not available on this platform");
+        match self.suppressor.mute(allowlist) {
+            Ok(_) => println!("[Focus] ✓ Notifications muted"),
+            Err(e) => eprintln!("[Focus] Warning: Failed to mute notifications: {}", e),
         }
+        self.start_notification_capture();
 
         Ok(())
     }
-    
-    /// Suppress notifications directly using Windows Runtime API via PowerShell
-    #[cfg(windows)]
-    async fn suppress_notifications_directly(&self) -> std::result::Result<(), String> {
-        use std::process::Command;
-        
-        // Use PowerShell to call Windows Runtime API for notification suppression
-        let ps_script = r#"
-            try {
-                # Load Windows Runtime types
-                Add-Type -AssemblyName System.Runtime.WindowsRuntime -ErrorAction Stop
-                
-                # Load Windows.UI.Shell types for FocusSessionManager
-                [Windows.UI.Shell.FocusSessionManager, Windows.UI.Shell, ContentType = WindowsRuntime] | Out-Null
-                
-                # Get the FocusSessionManager
-                $manager = [Windows.UI.Shell.FocusSessionManager]::GetDefault()
-                
-                # Create a helper function to await async operations
-                $asTaskGeneric = ([System.WindowsRuntimeSystemExtensions].GetMethods() | Where-Object { 
-                    $_.Name -eq 'AsTask' -and 
-                    $_.GetParameters().Count -eq 1 -and 
-                    $_.GetParameters()[0].ParameterType.Name -eq 'IAsyncOperation`1' 
-                })[0]
-                
-                Function Await($WinRtTask, $ResultType) {
-                    $asTask = $asTaskGeneric.MakeGenericMethod($ResultType)
-                    $netTask = $asTask.Invoke($null, @($WinRtTask))
-                    $netTask.Wait() | Out-Null
-                    $netTask.Result
-                }
-                
-                # Try multiple methods to create a FocusSession
-                $session = $null
-                
-                # Method 1: Try using reflection to find a constructor
-                try {
-                    $sessionType = [Windows.UI.Shell.FocusSession]
-                    $constructors = $sessionType.GetConstructors([System.Reflection.BindingFlags]::NonPublic -bor [System.Reflection.BindingFlags]::Instance)
-                    if ($constructors.Count -gt 0) {
-                        # Try to invoke the first constructor
-                        $ctor = $constructors[0]
-                        $paramCount = $ctor.GetParameters().Count
-                        if ($paramCount -eq 0) {
-                            $session = $ctor.Invoke($null)
-                        } else {
-                            # Create default parameters
-                            $params = New-Object object[] $paramCount
-                            $session = $ctor.Invoke($params)
-                        }
-                        Write-Host "[Focus] Created FocusSession using reflection constructor"
-                    }
-                } catch {
-                    Write-Host "[Focus] Reflection method failed: $_"
-                }
-                
-                # Method 2: Try using Activator with different parameters
-                if (-not $session) {
-                    try {
-                        $sessionType = [Windows.UI.Shell.FocusSession]
-                        # Try with empty object array
-                        $session = [System.Activator]::CreateInstance($sessionType, $true, $false, $null, $null, $null)
-                        Write-Host "[Focus] Created FocusSession using Activator with parameters"
-                    } catch {
-                        Write-Host "[Focus] Activator method failed: $_"
-                    }
-                }
-                
-                # Method 3: Try using NotificationListener API directly
-                if (-not $session) {
-                    try {
-                        # Try to use NotificationListener to suppress notifications
-                        [Windows.UI.Notifications.Management.NotificationListener, Windows.UI.Notifications, ContentType = WindowsRuntime] | Out-Null
-                        $listener = [Windows.UI.Notifications.Management.NotificationListener]::Current
-                        
-                        # Get all notification sources
-                        $sources = Await ($listener.GetNotificationSourcesAsync()) ([Windows.Foundation.Collections.IVectorView[Windows.UI.Notifications.Management.NotificationListenerAccessStatus]])
-                        
-                        Write-Host "[Focus] Found notification sources, attempting to suppress..."
-                        Write-Host "[Focus] Note: NotificationListener requires app manifest capabilities"
-                        Write-Host "[Focus] This may not work from a desktop app without proper permissions"
-                    } catch {
-                        Write-Host "[Focus] NotificationListener method failed: $_"
-                    }
-                }
-                
-                # Method 4: Try to get an existing session or use a factory method
-                if (-not $session) {
-                    try {
-                        # Check if there's a static factory method
-                        $sessionType = [Windows.UI.Shell.FocusSession]
-                        $methods = $sessionType.GetMethods([System.Reflection.BindingFlags]::Static -bor [System.Reflection.BindingFlags]::Public -bor [System.Reflection.BindingFlags]::NonPublic)
-                        $factoryMethod = $methods | Where-Object { $_.Name -like "*Create*" -or $_.Name -like "*New*" } | Select-Object -First 1
-                        if ($factoryMethod) {
-                            $session = $factoryMethod.Invoke($null, $null)
-                            Write-Host "[Focus] Created FocusSession using factory method: $($factoryMethod.Name)"
-                        }
-                    } catch {
-                        Write-Host "[Focus] Factory method failed: $_"
-                    }
-                }
-                
-                if ($session) {
-                    # Set session properties
-                    try {
-                        $session.Kind = [Windows.UI.Shell.FocusSessionKind]::AlarmsOnly
-                        $endTime = [DateTimeOffset]::Now.AddHours(2)
-                        $session.EndTime = $endTime
-                        
-                        # Start the focus session
-                        $asyncOp = $manager.StartFocusSession($session)
-                        $result = Await $asyncOp ([Windows.UI.Shell.FocusSession])
-                        
-                        Write-Host "[Focus] Focus session started via Windows Runtime API - Session ID: $($result.Id)"
-                        Write-Host "[Focus] SUCCESS - Notifications suppressed directly"
-                        exit 0
-                    } catch {
-                        Write-Host "[Focus] Failed to start session: $_"
-                        exit 1
-                    }
-                } else {
-                    Write-Host "[Focus] Failed to create FocusSession object - all methods failed"
-                    Write-Host "[Focus] LIMITATION: Windows Runtime API is not accessible from desktop apps on Windows 11 24H2"
-                    Write-Host "[Focus] Focus Assist must be enabled manually:"
-                    Write-Host "[Focus] 1. Press Win+A to open Action Center"
-                    Write-Host "[Focus] 2. Click the Focus Assist button to set it to 'Alarms only'"
-                    Write-Host "[Focus] OR go to Settings > System > Focus > Set to 'Alarms only'"
-                    exit 1
-                }
-            } catch {
-                $errorMsg = $_.Exception.Message
-                $errorType = $_.Exception.GetType().FullName
-                Write-Host "[Focus] Windows Runtime API error [$errorType]: $errorMsg"
-                Write-Host "[Focus] Stack trace: $($_.Exception.StackTrace)"
-                exit 1
-            }
-        "#;
-        
-        let output = Command::new("powershell")
-            .args(["-ExecutionPolicy", "Bypass", "-NoProfile", "-Command", &ps_script])
-            .output()
-            .map_err(|e| format!("Failed to execute PowerShell: {}", e))?;
-        
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        
-        println!("[Focus] PowerShell stdout: {}", stdout);
-        if !stderr.is_empty() {
-            println!("[Focus] PowerShell stderr: {}", stderr);
-        }
-        
-        if output.status.success() {
-            Ok(())
-        } else {
-            Err(format!("PowerShell script failed with exit code: {:?}", output.status.code()))
+
+    /// Queue notifications that still arrive while muted so they end up in the digest
+    fn start_notification_capture(&self) {
+        self.notification_queue.start_capture();
+        if let Err(e) = self.notification_source.start(self.notification_queue.clone()) {
+            eprintln!("[Focus] Notifications won't be captured via {}: {}", self.notification_source.name(), e);
         }
     }
-    
-    #[cfg(not(windows))]
-    async fn suppress_notifications_directly(&self) -> std::result::Result<(), String> {
-        Err("Not available on this platform".to_string())
+
+    /// Stop capturing, keeping what was queued for the next digest
+    fn stop_notification_capture(&self) {
+        self.notification_source.stop();
+        self.notification_queue.stop_capture();
+    }
+
+    /// Hand back everything captured since muting, grouped by app
+    async fn present_notification_digest(&self) {
+        self.notification_source.stop();
+        if let Some(digest) = self.notification_queue.take_digest() {
+            println!("[Focus] {} notifications from {} apps arrived while muted", digest.total, digest.groups.len());
+            *self.last_digest.write().await = Some(digest.clone());
+            self.emit("notification-digest", digest);
+        }
     }
 
-    /// Restore notifications
-    /// This restores Focus Assist to its previous state, which restores notifications
+    /// The most recent notification digest
+    pub async fn get_notification_digest(&self) -> Option<NotificationDigest> {
+        self.last_digest.read().await.clone()
+    }
+    
+    /// Restore notifications muted by mute_notifications
     async fn restore_notifications(&self) -> std::result::Result<(), String> {
         let was_muted = {
             let state = self.notification_state.read().await;
@@ -1051,95 +1841,18 @@ impl FocusService {
         };
 
         if was_muted {
-            println!("[Focus] Restoring notifications using multiple methods...");
-            *self.notification_state.write().await = false;
+            println!("[Focus] Restoring notifications via {} backend...", self.suppressor.name());
+            self.set_notifications_muted(false).await;
             
-            #[cfg(windows)]
-            {
-                // Restore system-wide notification settings
-                let _ = self.restore_system_notifications().await;
-                
-                // Restore app-specific notifications
-                let _ = self.restore_app_notifications().await;
-                
-                // Notifications are also restored when Focus Assist is restored
-                // The restore_focus_assist() function handles this
-                println!("[Focus] Notifications restored via multiple methods");
+            self.stop_notification_capture();
+            if let Err(e) = self.suppressor.unmute() {
+                eprintln!("[Focus] Warning: Failed to restore notifications: {}", e);
             }
         }
 
         Ok(())
     }
 
-    /// Restore system-wide notification settings using direct Windows API
-    #[cfg(windows)]
-    async fn restore_system_notifications(&self) -> std::result::Result<(), String> {
-        // Use direct Windows API instead of PowerShell for reliability
-        use crate::utils::notification_suppression::restore_notifications_direct;
-        
-        match restore_notifications_direct() {
-            Ok(_) => {
-                println!("[Focus] ✓ Notifications restored using direct Windows API");
-                Ok(())
-            }
-            Err(e) => {
-                eprintln!("[Focus] Direct API restoration failed: {}, falling back to PowerShell", e);
-                // Fallback to PowerShell method
-                self.restore_system_notifications_powershell().await
-            }
-        }
-    }
-    
-    /// Fallback PowerShell method for notification restoration
-    #[cfg(windows)]
-    async fn restore_system_notifications_powershell(&self) -> std::result::Result<(), String> {
-        use std::process::Command;
-        
-        let ps_script = r#"
-            $ErrorActionPreference = "Continue"
-            
-            Write-Host "[Notifications] Restoring system-wide notification settings..."
-            
-            # Restore global toast notifications
-            $notifPath = "HKCU:\Software\Microsoft\Windows\CurrentVersion\Notifications\Settings"
-            if (Test-Path $notifPath) {
-                # Remove the disabled properties or set them back to enabled
-                Remove-ItemProperty -Path $notifPath -Name "NOC_GLOBAL_SETTING_TOAST_ENABLED" -Force -ErrorAction SilentlyContinue
-                Remove-ItemProperty -Path $notifPath -Name "NOC_GLOBAL_SETTING_ACTION_CENTER_ENABLED" -Force -ErrorAction SilentlyContinue
-                Write-Host "[Notifications] Restored global notification settings"
-            }
-            
-            # Force Windows to reload notification settings
-            Start-Sleep -Milliseconds 500
-            
-            # Restart ShellExperienceHost to apply changes
-            $shellProcess = Get-Process -Name "ShellExperienceHost" -ErrorAction SilentlyContinue
-            if ($shellProcess) {
-                Stop-Process -Name "ShellExperienceHost" -Force -ErrorAction SilentlyContinue
-                Write-Host "[Notifications] Restarted ShellExperienceHost"
-                Start-Sleep -Milliseconds 1000
-            }
-            
-            Write-Host "[Notifications] System notification restoration completed"
-            exit 0
-        "#;
-        
-        let output = Command::new("powershell")
-            .args(["-ExecutionPolicy", "Bypass", "-NoProfile", "-Command", &ps_script])
-            .output()
-            .map_err(|e| format!("Failed to execute PowerShell: {}", e))?;
-        
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        println!("[Focus] System notifications restore stdout: {}", stdout);
-        
-        Ok(())
-    }
-    
-    #[cfg(not(windows))]
-    async fn restore_system_notifications(&self) -> std::result::Result<(), String> {
-        Ok(())
-    }
-
     /// Temporarily mute notifications for a specified duration (in minutes)
     /// If duration is None, mute until manually unmuted
     pub async fn temporarily_mute_notifications(&self, duration_minutes: Option<u32>) -> std::result::Result<(), String> {
@@ -1152,32 +1865,20 @@ impl FocusService {
         };
 
         if !is_already_muted {
-            // Use Windows API to mute notifications
-            println!("[Focus] Using Windows API to mute notifications...");
-            
-            #[cfg(windows)]
-            {
-                use crate::utils::notification_suppression::mute_notifications_windows_api;
-                match mute_notifications_windows_api() {
-                    Ok(_) => {
-                        println!("[Focus] ✓ Notifications muted using Windows API");
-                        *self.notification_state.write().await = true;
-                    }
-                    Err(e) => {
-                        eprintln!("[Focus] ✗ Failed to mute notifications via Windows API: {}", e);
-                        // Fallback to PowerShell method if Windows API fails
-                        println!("[Focus] Falling back to PowerShell method...");
+            println!("[Focus] Muting notifications via {} backend...", self.suppressor.name());
+            let allowlist = self.notification_allowlist_for(&[]).await;
+            match self.suppressor.mute(&allowlist) {
+                Ok(_) => println!("[Focus] ✓ Notifications muted"),
+                Err(e) => {
+                    eprintln!("[Focus] ✗ Failed to mute notifications: {}", e);
+                    // Focus Assist still silences most toasts on Windows, but it can't spare allowlisted apps
+                    if allowlist.is_empty() {
                         let _ = self.enable_focus_assist().await;
-                        let _ = self.disable_notifications_system_wide().await;
-                        *self.notification_state.write().await = true;
                     }
                 }
             }
-            
-            #[cfg(not(windows))]
-            {
-                println!("[Focus] Windows API not available on this platform");
-            }
+            self.start_notification_capture();
+            self.set_notifications_muted(true).await;
         }
 
         // Set end time if duration is specified
@@ -1208,30 +1909,17 @@ impl FocusService {
                 if should_restore {
                     println!("[Focus] Auto-restoring notifications after temporary mute duration");
                     
-                    #[cfg(windows)]
-                    {
-                        use crate::utils::notification_suppression::unmute_notifications_windows_api;
-                        if let Err(e) = unmute_notifications_windows_api() {
-                            eprintln!("[Focus] Failed to unmute via Windows API: {}, using fallback", e);
-                            let _ = service.restore_focus_assist().await;
-                            let _ = service.restore_notifications().await;
-                        } else {
-                            println!("[Focus] ✓ Notifications restored using Windows API");
-                        }
-                    }
-                    
-                    #[cfg(not(windows))]
-                    {
-                        let _ = service.restore_focus_assist().await;
-                        let _ = service.restore_notifications().await;
+                    service.present_notification_digest().await;
+                    match service.suppressor.unmute() {
+                        Ok(_) => println!("[Focus] ✓ Notifications restored"),
+                        Err(e) => eprintln!("[Focus] Failed to restore notifications: {}", e),
                     }
+                    let _ = service.restore_focus_assist().await;
                     
                     *service.temporary_mute_end_time.write().await = None;
-                    *service.notification_state.write().await = false;
+                    service.set_notifications_muted(false).await;
                     
-                    if let Some(ref app) = service.app_handle {
-                        let _ = app.emit("notifications-restored", ());
-                    }
+                    service.emit("notifications-restored", ());
                 }
             });
         } else {
@@ -1249,89 +1937,57 @@ impl FocusService {
         // Clear temporary mute end time
         *self.temporary_mute_end_time.write().await = None;
         
-        // Use Windows API to unmute notifications
-        #[cfg(windows)]
-        {
-            use crate::utils::notification_suppression::unmute_notifications_windows_api;
-            match unmute_notifications_windows_api() {
-                Ok(_) => {
-                    println!("[Focus] ✓ Notifications unmuted using Windows API");
-                    *self.notification_state.write().await = false;
-                }
-                Err(e) => {
-                    eprintln!("[Focus] ✗ Failed to unmute via Windows API: {}, using fallback", e);
-                    // Fallback to PowerShell methods
-                    self.restore_focus_assist().await?;
-                    self.restore_notifications().await?;
-                    *self.notification_state.write().await = false;
-                }
+        // Stop capturing before unmuting so nothing that arrives in between is lost
+        self.present_notification_digest().await;
+        match self.suppressor.unmute() {
+            Ok(_) => println!("[Focus] ✓ Notifications unmuted via {} backend", self.suppressor.name()),
+            Err(e) => {
+                eprintln!("[Focus] ✗ Failed to unmute notifications: {}", e);
+                self.restore_focus_assist().await?;
             }
         }
-        
-        #[cfg(not(windows))]
-        {
-            self.restore_focus_assist().await?;
-            self.restore_notifications().await?;
-            *self.notification_state.write().await = false;
-        }
+        self.set_notifications_muted(false).await;
 
-        if let Some(ref app) = self.app_handle {
-            let _ = app.emit("notifications-restored", ());
-        }
+        self.emit("notifications-restored", ());
 
         Ok(())
     }
 
-    /// Restore app-specific notifications
-    #[cfg(windows)]
-    async fn restore_app_notifications(&self) -> std::result::Result<(), String> {
-        use std::process::Command;
-        
-        let ps_script = r#"
-            $ErrorActionPreference = "Continue"
-            
-            Write-Host "[Notifications] Restoring app notifications..."
-            
-            # Base registry path for app notifications
-            $basePath = "HKCU:\Software\Microsoft\Windows\CurrentVersion\Notifications\Settings"
-            
-            # Re-enable notifications for all apps (remove the disabled state)
-            try {
-                $allApps = Get-ChildItem -Path $basePath -ErrorAction SilentlyContinue
-                foreach ($app in $allApps) {
-                    try {
-                        # Remove the Enabled property if it was set to 0, or set it to 1
-                        $currentValue = Get-ItemProperty -Path $app.PSPath -Name "Enabled" -ErrorAction SilentlyContinue
-                        if ($currentValue -and $currentValue.Enabled -eq 0) {
-                            Remove-ItemProperty -Path $app.PSPath -Name "Enabled" -Force -ErrorAction SilentlyContinue
-                            Write-Host "[Notifications] Restored notifications for: $($app.PSChildName)"
-                        }
-                    } catch {
-                        # Ignore errors - some apps may not have this property
-                    }
-                }
-            } catch {
-                Write-Host "[Notifications] Error restoring app notifications: $_"
-            }
-            
-            Write-Host "[Notifications] App notification restoration completed"
-            exit 0
-        "#;
-        
-        let output = Command::new("powershell")
-            .args(["-ExecutionPolicy", "Bypass", "-NoProfile", "-Command", &ps_script])
-            .output()
-            .map_err(|e| format!("Failed to execute PowerShell: {}", e))?;
-        
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        println!("[Focus] App notifications restore stdout: {}", stdout);
-        
-        Ok(())
+    /// Put back every notification setting we changed, including ones left behind by a crash
+    /// Returns how many recorded settings were restored
+    pub async fn restore_all_notification_settings(&self) -> std::result::Result<usize, String> {
+        let pending = self.notification_ledger.entries()?.len();
+        println!("[Focus] Restoring all notification settings ({} recorded)...", pending);
+
+        self.suppressor.stop_blocker();
+        self.stop_notification_capture();
+        if let Err(e) = self.suppressor.unmute() {
+            eprintln!("[Focus] Warning: Failed to unmute notifications: {}", e);
+        }
+        // Anything the backend didn't restore itself, e.g. entries from a previous run
+        self.notification_ledger.replay(&PlatformRestorer)?;
+        let _ = self.restore_focus_assist().await;
+
+        *self.temporary_mute_end_time.write().await = None;
+        self.set_notifications_muted(false).await;
+        self.emit("notifications-restored", ());
+        Ok(pending)
     }
-    
-    #[cfg(not(windows))]
-    async fn restore_app_notifications(&self) -> std::result::Result<(), String> {
-        Ok(())
+
+    /// Replay settings left in the ledger by a crash, unless a resumed session still needs them
+    async fn recover_notification_ledger(&self) {
+        if self.is_active().await || self.notification_ledger.is_empty() {
+            return;
+        }
+
+        println!("[Focus] Found notification settings left over from a previous run - restoring...");
+        match self.notification_ledger.replay(&PlatformRestorer) {
+            Ok(count) => {
+                println!("[Focus] ✓ Restored {} notification settings", count);
+                self.emit("notifications-restored", ());
+            }
+            Err(e) => eprintln!("[Focus] Failed to restore notification settings: {}", e),
+        }
     }
 
     /// Get remaining time for temporary mute (in seconds)
@@ -1360,254 +2016,369 @@ impl FocusService {
         *state
     }
 
-    /// Disable notifications system-wide using direct Windows API
-    #[cfg(windows)]
-    async fn disable_notifications_system_wide(&self) -> std::result::Result<(), String> {
-        // Use direct Windows API instead of PowerShell for reliability
-        use crate::utils::notification_suppression::suppress_notifications_direct;
-        
-        match suppress_notifications_direct() {
+    pub async fn get_planner_config(&self) -> PlannerConfig {
+        self.planner_config.read().await.clone()
+    }
+
+    pub async fn set_planner_config(&self, config: PlannerConfig) -> std::result::Result<(), String> {
+        config.validate()?;
+        config.save(&self.data_dir)?;
+        *self.planner_config.write().await = config;
+        Ok(())
+    }
+
+    /// Propose focus blocks in the free gaps of a (local) day's calendar
+    pub async fn get_focus_plan(&self, date: chrono::NaiveDate) -> std::result::Result<FocusPlan, String> {
+        use crate::services::calendar_service::get_calendar_service;
+
+        let events = {
+            let calendar_service = get_calendar_service().await;
+            let calendar_service = calendar_service.lock().await;
+            calendar_service.get_events().await?
+        };
+
+        let config = self.planner_config.read().await.clone();
+        Ok(focus_planner::plan_day(&events, date, Utc::now(), &config, &chrono::Local))
+    }
+
+    /// Put the proposed blocks for a day on the calendar
+    pub async fn add_focus_plan_to_calendar(&self, date: chrono::NaiveDate) -> std::result::Result<Vec<CalendarEvent>, String> {
+        use crate::services::calendar_service::get_calendar_service;
+
+        let plan = self.get_focus_plan(date).await?;
+        let title = self.planner_config.read().await.block_title.clone();
+
+        let calendar_service = get_calendar_service().await;
+        let calendar_service = calendar_service.lock().await;
+
+        let mut created = Vec::new();
+        // Blocks added earlier come back from the calendar as part of the plan
+        for block in plan.blocks.iter().filter(|b| b.event_id.is_none()) {
+            let event = calendar_service
+                .create_event(&title, Some(focus_planner::PLANNED_BLOCK_DESCRIPTION), block.start, block.end)
+                .await?;
+            created.push(event);
+        }
+
+        println!("[Planner] Added {} focus blocks to the calendar for {}", created.len(), date);
+        Ok(created)
+    }
+
+    /// Start focus sessions automatically when a planned block begins (if enabled)
+    pub fn spawn_focus_planner(&self) {
+        use crate::services::calendar_service::get_calendar_service;
+
+        let focus_service = Arc::new(self.clone_for_timer());
+
+        tokio::spawn(async move {
+            let mut last_started: Option<String> = None;
+
+            loop {
+                sleep(TokioDuration::from_secs(30)).await;
+
+                if !focus_service.planner_config.read().await.auto_start {
+                    continue;
+                }
+
+                let events = {
+                    let calendar_service = get_calendar_service().await;
+                    let calendar_service = calendar_service.lock().await;
+                    calendar_service.get_events().await
+                };
+                match events {
+                    Ok(events) => {
+                        focus_service.auto_start_focus_block(&events, Utc::now(), &chrono::Local, &mut last_started).await;
+                    }
+                    Err(e) => eprintln!("[Planner] Failed to build focus plan: {}", e),
+                }
+            }
+        });
+    }
+
+    /// Start the planned block that began in the last couple of minutes
+    /// `last_started` keeps the id of the block started last, so a session the user stopped isn't restarted
+    async fn auto_start_focus_block<Tz: chrono::TimeZone>(
+        &self,
+        events: &[CalendarEvent],
+        now: DateTime<Utc>,
+        tz: &Tz,
+        last_started: &mut Option<String>,
+    ) -> Option<FocusBlock> {
+        let config = self.planner_config.read().await.clone();
+        if !config.auto_start || self.is_active().await || self.pomodoro.read().await.is_some() {
+            return None;
+        }
+
+        let today = now.with_timezone(tz).date_naive();
+        let plan = focus_planner::plan_day(events, today, now, &config, tz);
+        let block = plan
+            .blocks
+            .into_iter()
+            .find(|b| b.start <= now && now < b.start + Duration::minutes(2) && last_started.as_deref() != Some(b.id.as_str()))?;
+
+        let minutes = (block.end - now).num_minutes().max(1) as u32;
+        println!("[Planner] Auto-starting planned focus block: {} minutes", minutes);
+        *last_started = Some(block.id.clone());
+
+        match self.start_focus_mode(FocusMode::Custom(minutes)).await {
             Ok(_) => {
-                println!("[Focus] ✓ Notifications suppressed using direct Windows API");
-                Ok(())
+                self.emit("focus-block-started", block.clone());
+                Some(block)
             }
             Err(e) => {
-                eprintln!("[Focus] Direct API suppression failed: {}, falling back to PowerShell", e);
-                // Fallback to PowerShell method
-                self.disable_notifications_system_wide_powershell().await
+                eprintln!("[Planner] Failed to auto-start focus block: {}", e);
+                None
             }
         }
     }
-    
-    /// Fallback PowerShell method for notification suppression
-    #[cfg(windows)]
-    async fn disable_notifications_system_wide_powershell(&self) -> std::result::Result<(), String> {
-        use std::process::Command;
-        
-        let ps_script = r#"
-            $ErrorActionPreference = "Stop"
-            
-            Write-Host "[Notifications] Disabling notifications system-wide..."
-            
-            # Method 1: Disable toast notifications globally
-            $notifPath = "HKCU:\Software\Microsoft\Windows\CurrentVersion\Notifications\Settings"
-            if (-not (Test-Path $notifPath)) {
-                New-Item -Path $notifPath -Force | Out-Null
+
+    /// Apply and lift quiet-hours mutes as their windows start and end
+    pub fn spawn_quiet_hours_scheduler(&self) {
+        use crate::services::calendar_service::get_calendar_service;
+
+        let focus_service = Arc::new(self.clone_for_timer());
+
+        tokio::spawn(async move {
+            loop {
+                let events = {
+                    let calendar_service = get_calendar_service().await;
+                    let calendar_service = calendar_service.lock().await;
+                    calendar_service.get_events().await.unwrap_or_default()
+                };
+                focus_service.apply_quiet_hours(&events, Utc::now()).await;
+
+                sleep(TokioDuration::from_secs(30)).await;
             }
-            
-            # Disable toast notifications
-            Set-ItemProperty -Path $notifPath -Name "NOC_GLOBAL_SETTING_TOAST_ENABLED" -Value 0 -Type DWord -Force
-            Set-ItemProperty -Path $notifPath -Name "NOC_GLOBAL_SETTING_ACTION_CENTER_ENABLED" -Value 0 -Type DWord -Force
-            
-            Write-Host "[Notifications] Disabled global toast notifications"
-            
-            # Method 2: Set Focus Assist to Alarms Only via registry (more aggressive)
-            $basePath = "HKCU:\Software\Microsoft\Windows\CurrentVersion\CloudStore\Store\Cache\DefaultAccount"
-            
-            # Find and update all quiescence keys
-            $keys = Get-ChildItem -Path $basePath -Recurse -ErrorAction SilentlyContinue | 
-                Where-Object { $_.PSChildName -like "*quiescence*" -or $_.Name -like "*quiescence*" }
-            
-            foreach ($key in $keys) {
-                try {
-                    $keyPath = $key.PSPath
-                    if (Test-Path $keyPath) {
-                        # Set to Alarms Only (mode 2)
-                        Set-ItemProperty -Path $keyPath -Name "Data" -Value ([byte[]](0x43,0x42,0x01,0x00,0x02,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00)) -Type Binary -Force -ErrorAction SilentlyContinue
-                        Write-Host "[Notifications] Updated Focus Assist key: $keyPath"
+        });
+    }
+
+    /// Bring muting in line with the quiet-hours window (if any) that contains `now`
+    async fn apply_quiet_hours(&self, events: &[CalendarEvent], now: DateTime<Utc>) {
+        let rules = match self.quiet_hours.list() {
+            Ok(rules) => rules,
+            Err(e) => {
+                eprintln!("[QuietHours] Failed to load rules: {}", e);
+                return;
+            }
+        };
+        let desired = quiet_hours::active_window(&rules, events, now);
+        let current = self.quiet_hours_active.read().await.clone();
+
+        match (current, desired) {
+            (Some(current), Some(desired)) if current.rule_id == desired.rule_id && current.ends_at == desired.ends_at => {
+                // A focus session ending or a restart may have lifted our mute
+                if current.enforcing && !self.is_active().await && !self.is_notifications_muted().await {
+                    println!("[QuietHours] Re-applying mute for '{}'", current.name);
+                    match self.temporarily_mute_notifications(None).await {
+                        Ok(()) => self.set_quiet_hours_active(Some(ActiveQuietHours { muted: true, ..current })).await,
+                        Err(e) => eprintln!("[QuietHours] Failed to mute notifications: {}", e),
                     }
-                } catch {
-                    Write-Host "[Notifications] Error updating $($key.PSPath): $_"
                 }
             }
-            
-            # Method 3: Create/update direct registry paths
-            $directPaths = @(
-                "HKCU:\Software\Microsoft\Windows\CurrentVersion\CloudStore\Store\Cache\DefaultAccount\`$windows.data.notifications.quiescence.win10\Current",
-                "HKCU:\Software\Microsoft\Windows\CurrentVersion\CloudStore\Store\Cache\DefaultAccount\`$windows.data.notifications.quiescence.win11\Current"
-            )
-            
-            foreach ($directPath in $directPaths) {
-                try {
-                    if (-not (Test-Path $directPath)) {
-                        $parentPath = Split-Path -Path $directPath -Parent
-                        if (-not (Test-Path $parentPath)) {
-                            New-Item -Path $parentPath -Force -ErrorAction SilentlyContinue | Out-Null
-                        }
-                        New-Item -Path $directPath -Force -ErrorAction SilentlyContinue | Out-Null
-                    }
-                    
-                    Set-ItemProperty -Path $directPath -Name "Data" -Value ([byte[]](0x43,0x42,0x01,0x00,0x02,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00)) -Type Binary -Force -ErrorAction SilentlyContinue
-                    Write-Host "[Notifications] Updated direct path: $directPath"
-                } catch {
-                    Write-Host "[Notifications] Error with direct path $directPath : $_"
+            (current, desired) => {
+                if let Some(current) = current {
+                    let exception = rules
+                        .iter()
+                        .find(|r| r.id == current.rule_id)
+                        .and_then(|r| r.exception_at(events, now))
+                        .map(|e| e.title.clone());
+                    self.end_quiet_hours(current, exception).await;
+                }
+                if let Some(desired) = desired {
+                    self.start_quiet_hours(desired).await;
                 }
             }
-            
-            # Method 4: Force Windows to reload notification settings
-            Start-Sleep -Milliseconds 500
-            
-            # Restart ShellExperienceHost to apply changes
-            $shellProcess = Get-Process -Name "ShellExperienceHost" -ErrorAction SilentlyContinue
-            if ($shellProcess) {
-                Stop-Process -Name "ShellExperienceHost" -Force -ErrorAction SilentlyContinue
-                Write-Host "[Notifications] Restarted ShellExperienceHost"
-                Start-Sleep -Milliseconds 1000
+        }
+    }
+
+    async fn start_quiet_hours(&self, mut active: ActiveQuietHours) {
+        println!("[QuietHours] '{}' started, muting notifications until {}", active.name, active.ends_at);
+        // A running focus session already mutes, and lifts it when it ends; the next check re-applies ours.
+        // A mute the user already had stays theirs, so the end of the window leaves it alone
+        if !self.is_active().await && !self.is_notifications_muted().await {
+            match self.temporarily_mute_notifications(None).await {
+                Ok(()) => active.muted = true,
+                Err(e) => eprintln!("[QuietHours] Failed to mute notifications: {}", e),
             }
-            
-            # Broadcast settings change
-            try {
-                Add-Type -TypeDefinition @"
-                    using System;
-                    using System.Runtime.InteropServices;
-                    public class Win32 {
-                        [DllImport("user32.dll", CharSet=CharSet.Auto)]
-                        public static extern IntPtr SendMessageTimeout(
-                            IntPtr hWnd, uint Msg, IntPtr wParam, string lParam,
-                            uint fuFlags, uint uTimeout, out IntPtr lpdwResult);
-                        public static readonly IntPtr HWND_BROADCAST = new IntPtr(0xffff);
-                        public static readonly uint WM_SETTINGCHANGE = 0x001A;
-                        public static readonly uint SMTO_ABORTIFHUNG = 0x0002;
-                    }
-"@ -ErrorAction SilentlyContinue
-                $result = [IntPtr]::Zero
-                [Win32]::SendMessageTimeout(
-                    [Win32]::HWND_BROADCAST,
-                    [Win32]::WM_SETTINGCHANGE,
-                    [IntPtr]::Zero,
-                    "Environment",
-                    [Win32]::SMTO_ABORTIFHUNG,
-                    5000,
-                    [ref]$result
-                ) | Out-Null
-                Write-Host "[Notifications] Broadcasted settings change"
-            } catch {
-                Write-Host "[Notifications] Could not broadcast settings change: $_"
+        }
+
+        self.emit("quiet-hours-started", QuietHoursTransition {
+            rule_id: active.rule_id.clone(),
+            name: active.name.clone(),
+            ends_at: active.ends_at,
+            exception: None,
+        });
+        self.set_quiet_hours_active(Some(active)).await;
+    }
+
+    async fn end_quiet_hours(&self, active: ActiveQuietHours, exception: Option<String>) {
+        match exception {
+            Some(ref title) => println!("[QuietHours] '{}' lifted for calendar event '{}'", active.name, title),
+            None => println!("[QuietHours] '{}' ended", active.name),
+        }
+        self.set_quiet_hours_active(None).await;
+
+        if active.enforcing && active.muted && !self.is_active().await && self.is_notifications_muted().await {
+            if let Err(e) = self.unmute_notifications().await {
+                eprintln!("[QuietHours] Failed to unmute notifications: {}", e);
             }
-            
-            Write-Host "[Notifications] System-wide notification suppression applied"
-            exit 0
-        "#;
-        
-        let output = Command::new("powershell")
-            .args(["-ExecutionPolicy", "Bypass", "-NoProfile", "-Command", &ps_script])
-            .output()
-            .map_err(|e| format!("Failed to execute PowerShell: {}", e))?;
-        
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        
-        println!("[Focus] System-wide disable stdout: {}", stdout);
-        if !stderr.is_empty() {
-            println!("[Focus] System-wide disable stderr: {}", stderr);
         }
-        
-        if output.status.success() {
-            Ok(())
-        } else {
-            Err(format!("PowerShell script failed with exit code: {:?}", output.status.code()))
+
+        self.emit("quiet-hours-ended", QuietHoursTransition {
+            rule_id: active.rule_id,
+            name: active.name,
+            ends_at: active.ends_at,
+            exception,
+        });
+    }
+
+    async fn set_quiet_hours_active(&self, active: Option<ActiveQuietHours>) {
+        self.quiet_hours.save_active(active.as_ref());
+        *self.quiet_hours_active.write().await = active;
+    }
+
+    /// Mute at the user's request; a mute started during quiet hours outlasts them
+    pub async fn temporarily_mute_notifications_by_user(&self, duration_minutes: Option<u32>) -> std::result::Result<(), String> {
+        let active = self.quiet_hours_active.read().await.clone();
+        if let Some(mut active) = active.filter(|a| a.muted) {
+            println!("[QuietHours] User took over the mute during '{}'", active.name);
+            active.muted = false;
+            self.set_quiet_hours_active(Some(active)).await;
         }
+        self.temporarily_mute_notifications(duration_minutes).await
     }
-    
-    #[cfg(not(windows))]
-    async fn disable_notifications_system_wide(&self) -> std::result::Result<(), String> {
-        Err("Not available on this platform".to_string())
+
+    /// Unmute at the user's request; quiet hours stay off until their next window
+    pub async fn unmute_notifications_by_user(&self) -> std::result::Result<(), String> {
+        let active = self.quiet_hours_active.read().await.clone();
+        if let Some(mut active) = active.filter(|a| a.enforcing) {
+            println!("[QuietHours] '{}' overridden by the user", active.name);
+            active.enforcing = false;
+            self.set_quiet_hours_active(Some(active)).await;
+        }
+        self.unmute_notifications().await
     }
 
-    /// Disable notifications for specific apps (Teams, Outlook, etc.)
-    #[cfg(windows)]
-    async fn disable_app_notifications(&self) -> std::result::Result<(), String> {
-        use std::process::Command;
-        
-        let ps_script = r#"
-            $ErrorActionPreference = "Continue"
-            
-            Write-Host "[Notifications] Disabling notifications for specific apps..."
-            
-            # Apps to disable notifications for
-            $appsToDisable = @(
-                "Microsoft.SkypeApp",           # Skype/Teams
-                "MSTeams",                      # Microsoft Teams
-                "MicrosoftTeams",                # Microsoft Teams (alternative)
-                "com.microsoft.teams",          # Teams UWP
-                "Microsoft.Outlook",            # Outlook
-                "Microsoft.Office.Outlook",     # Outlook (alternative)
-                "Discord",                      # Discord
-                "com.discordapp.Discord",       # Discord UWP
-                "WhatsApp",                     # WhatsApp
-                "com.whatsapp.WhatsApp",        # WhatsApp UWP
-                "Slack",                        # Slack
-                "com.tinyspeck.chatlyio",       # Slack UWP
-                "Telegram",                     # Telegram
-                "com.telegram.telegram",         # Telegram UWP
-                "Zoom",                         # Zoom
-                "Zoom.Zoom",                    # Zoom UWP
-                "com.zoom.Zoom"                 # Zoom (alternative)
-            )
-            
-            # Base registry path for app notifications
-            $basePath = "HKCU:\Software\Microsoft\Windows\CurrentVersion\Notifications\Settings"
-            
-            foreach ($appId in $appsToDisable) {
-                try {
-                    $appPath = Join-Path $basePath $appId
-                    
-                    if (Test-Path $appPath) {
-                        # Disable notifications for this app
-                        Set-ItemProperty -Path $appPath -Name "Enabled" -Value 0 -Type DWord -Force -ErrorAction SilentlyContinue
-                        Set-ItemProperty -Path $appPath -Name "ShowInActionCenter" -Value 0 -Type DWord -Force -ErrorAction SilentlyContinue
-                        Write-Host "[Notifications] Disabled notifications for: $appId"
-                    } else {
-                        # Create the path and disable
-                        New-Item -Path $appPath -Force -ErrorAction SilentlyContinue | Out-Null
-                        Set-ItemProperty -Path $appPath -Name "Enabled" -Value 0 -Type DWord -Force -ErrorAction SilentlyContinue
-                        Set-ItemProperty -Path $appPath -Name "ShowInActionCenter" -Value 0 -Type DWord -Force -ErrorAction SilentlyContinue
-                        Write-Host "[Notifications] Created and disabled notifications for: $appId"
-                    }
-                } catch {
-                    Write-Host "[Notifications] Error disabling $appId : $_"
+    pub async fn get_idle_config(&self) -> IdleConfig {
+        self.idle_config.read().await.clone()
+    }
+
+    pub async fn set_idle_config(&self, config: IdleConfig) -> std::result::Result<(), String> {
+        config.validate()?;
+        config.save(&self.data_dir)?;
+        *self.idle_config.write().await = config;
+        Ok(())
+    }
+
+    /// Poll for keyboard/mouse inactivity in the background
+    pub fn spawn_idle_monitor(&self) {
+        let focus_service = Arc::new(self.clone_for_timer());
+        println!("[Idle] Watching for inactivity via {}", self.idle_source.name());
+
+        tokio::spawn(async move {
+            loop {
+                focus_service.check_idle(Utc::now()).await;
+                sleep(TokioDuration::from_secs(5)).await;
+            }
+        });
+    }
+
+    /// Read the idle source and react if the user just left or came back
+    async fn check_idle(&self, now: DateTime<Utc>) {
+        let config = self.idle_config.read().await.clone();
+        if !config.enabled {
+            return;
+        }
+
+        // Sources may block on the X server or D-Bus, so keep them off the async workers
+        let source = Arc::clone(&self.idle_source);
+        let idle_seconds = tokio::task::spawn_blocking(move || source.idle_seconds())
+            .await
+            .unwrap_or_else(|e| Err(format!("Idle check failed: {}", e)));
+        let idle_seconds = match idle_seconds {
+            Ok(seconds) => seconds,
+            Err(e) => {
+                let mut state = self.idle_state.write().await;
+                if !state.source_failed {
+                    eprintln!("[Idle] Can't read idle time from {}: {}", self.idle_source.name(), e);
+                    state.source_failed = true;
                 }
+                return;
             }
-            
-            # Also try to find Teams by searching for it
-            try {
-                $allApps = Get-ChildItem -Path $basePath -ErrorAction SilentlyContinue
-                foreach ($app in $allApps) {
-                    $appName = $app.PSChildName
-                    if ($appName -like "*team*" -or $appName -like "*skype*" -or $appName -like "*outlook*") {
-                        Set-ItemProperty -Path $app.PSPath -Name "Enabled" -Value 0 -Type DWord -Force -ErrorAction SilentlyContinue
-                        Set-ItemProperty -Path $app.PSPath -Name "ShowInActionCenter" -Value 0 -Type DWord -Force -ErrorAction SilentlyContinue
-                        Write-Host "[Notifications] Disabled notifications for found app: $appName"
+        };
+
+        let transition = {
+            let mut state = self.idle_state.write().await;
+            state.source_failed = false;
+            state.update(idle_seconds, config.threshold_seconds(), now)
+        };
+        match transition {
+            Some(IdleTransition::WentIdle { since }) => self.user_went_idle(since, now, &config).await,
+            Some(IdleTransition::CameBack { since, back_at }) => self.user_came_back(since, back_at, &config).await,
+            None => {}
+        }
+    }
+
+    /// Pause the running session from the moment input stopped
+    async fn user_went_idle(&self, since: DateTime<Utc>, now: DateTime<Utc>, config: &IdleConfig) {
+        println!("[Idle] No input since {}", since);
+        let session = self.get_current_session().await.filter(|s| s.is_active);
+
+        let mut paused_session = None;
+        if let Some(ref s) = session {
+            if config.auto_pause && !s.is_paused {
+                match self.pause_session_at(false, since).await {
+                    Ok(paused) => {
+                        println!("[Idle] Paused session {} while the user is away", paused.id);
+                        paused_session = Some(paused.id);
                     }
+                    Err(e) => eprintln!("[Idle] Failed to pause session: {}", e),
                 }
-            } catch {
-                Write-Host "[Notifications] Error searching for apps: $_"
             }
-            
-            Write-Host "[Notifications] App-specific notification suppression completed"
-            exit 0
-        "#;
-        
-        let output = Command::new("powershell")
-            .args(["-ExecutionPolicy", "Bypass", "-NoProfile", "-Command", &ps_script])
-            .output()
-            .map_err(|e| format!("Failed to execute PowerShell: {}", e))?;
-        
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        
-        println!("[Focus] App notifications disable stdout: {}", stdout);
-        if !stderr.is_empty() {
-            println!("[Focus] App notifications disable stderr: {}", stderr);
         }
-        
-        // Don't fail if this doesn't work - it's a best-effort approach
-        Ok(())
+
+        self.emit("user-idle", IdleEvent {
+            idle_since: since,
+            idle_seconds: (now - since).num_seconds().max(0) as u64,
+            session_id: session.map(|s| s.id),
+            session_paused: paused_session.is_some(),
+        });
+        self.idle_state.write().await.paused_session = paused_session;
     }
-    
-    #[cfg(not(windows))]
-    async fn disable_app_notifications(&self) -> std::result::Result<(), String> {
-        Err("Not available on this platform".to_string())
+
+    /// Record the time away on the session and resume it if we paused it
+    async fn user_came_back(&self, since: DateTime<Utc>, back_at: DateTime<Utc>, config: &IdleConfig) {
+        let idle_seconds = (back_at - since).num_seconds().max(0) as u64;
+        println!("[Idle] User back after {}s", idle_seconds);
+        let paused_by_us = self.idle_state.write().await.paused_session.take();
+
+        let session_id = {
+            let mut session = self.current_session.write().await;
+            match session.as_mut() {
+                Some(s) if s.is_active => {
+                    // Only the part of the absence that overlaps the session counts
+                    s.idle_seconds += (back_at - since.max(s.start_time)).num_seconds().max(0) as u64;
+                    Some(s.id.clone())
+                }
+                _ => None,
+            }
+        };
+        self.write_journal().await;
+
+        let mut still_paused = self.get_current_session().await.map(|s| s.is_active && s.is_paused).unwrap_or(false);
+        if still_paused && config.auto_resume && paused_by_us.is_some() && paused_by_us == session_id {
+            match self.resume_focus_mode().await {
+                Ok(_) => still_paused = false,
+                Err(e) => eprintln!("[Idle] Failed to resume session: {}", e),
+            }
+        }
+
+        self.emit("user-active", IdleEvent {
+            idle_since: since,
+            idle_seconds,
+            session_id,
+            session_paused: still_paused,
+        });
     }
 
     /// Get meeting reschedule suggestions based on focus session
@@ -1644,70 +2415,69 @@ impl FocusService {
                 .map(|s| s.mode.name())
                 .unwrap_or_else(|| "active".to_string())
         };
+
+        let planner_config = self.planner_config.read().await.clone();
+        // Leave a 15 minute buffer after focus before anything gets moved in
+        let not_before = focus_end_time.max(Utc::now()) + Duration::minutes(15);
         
         // Check each event to see if it conflicts with focus session
-        for event in events {
-            let event_start = event.start_time;
-            let event_end = event.end_time;
+        for event in events.iter() {
+            let conflict = match meeting_scheduler::conflict_kind(event, focus_start_time, focus_end_time) {
+                Some(conflict) => conflict,
+                None => continue,
+            };
+
+            // All-day events and other people's meetings can't be moved by us
+            if !meeting_scheduler::can_reschedule(event) {
+                println!("[Focus] Skipping conflicting event we can't reschedule: {}", event.title);
+                continue;
+            }
+
+            let alternatives = meeting_scheduler::find_slots(
+                &events,
+                event,
+                not_before,
+                &planner_config,
+                &chrono::Local,
+                MEETING_ALTERNATIVES,
+            );
+            let suggested_time = alternatives.first().map(|slot| slot.start).unwrap_or(not_before);
             
-            // Check if event conflicts with focus session in any way:
-            // 1. Event starts during focus session
-            // 2. Event overlaps with focus session (starts before, ends during)
-            // 3. Event starts shortly before focus session (within 15 minutes) - user might want to reschedule
-            let conflicts = 
-                // Event starts during focus session
-                (event_start >= focus_start_time && event_start < focus_end_time) ||
-                // Event overlaps (starts before, ends during or after)
-                (event_start < focus_start_time && event_end > focus_start_time) ||
-                // Event starts shortly before focus session (within 15 min) - might want buffer time
-                (event_start < focus_start_time && event_start >= focus_start_time - chrono::Duration::minutes(15));
+            // Generate reason based on conflict type
+            let reason = match conflict {
+                ConflictKind::Overlaps => format!(
+                    "This meeting overlaps with your {} focus session ({} - {}). Consider rescheduling to avoid interruption.",
+                    session_mode_name,
+                    focus_start_time.format("%H:%M"),
+                    focus_end_time.format("%H:%M")
+                ),
+                ConflictKind::StartsDuring => format!(
+                    "This meeting starts during your {} focus session ({} - {}). Reschedule to maintain focus.",
+                    session_mode_name,
+                    focus_start_time.format("%H:%M"),
+                    focus_end_time.format("%H:%M")
+                ),
+                ConflictKind::StartsJustBefore => format!(
+                    "This meeting starts just before your {} focus session ({} - {}). Consider rescheduling to allow buffer time.",
+                    session_mode_name,
+                    focus_start_time.format("%H:%M"),
+                    focus_end_time.format("%H:%M")
+                ),
+            };
             
-            if conflicts {
-                // Determine suggested time based on conflict type
-                let suggested_time = if event_start < focus_start_time {
-                    // Event starts before focus - suggest moving to after focus ends
-                    focus_end_time + chrono::Duration::minutes(15) // 15 min buffer after focus
-                } else {
-                    // Event starts during focus - suggest moving to after focus ends
-                    focus_end_time + chrono::Duration::minutes(15) // 15 min buffer after focus
-                };
-                
-                // Generate reason based on conflict type
-                let reason = if event_start < focus_start_time && event_end > focus_start_time {
-                    format!(
-                        "This meeting overlaps with your {} focus session ({} - {}). Consider rescheduling to avoid interruption.",
-                        session_mode_name,
-                        focus_start_time.format("%H:%M"),
-                        focus_end_time.format("%H:%M")
-                    )
-                } else if event_start >= focus_start_time && event_start < focus_end_time {
-                    format!(
-                        "This meeting starts during your {} focus session ({} - {}). Reschedule to maintain focus.",
-                        session_mode_name,
-                        focus_start_time.format("%H:%M"),
-                        focus_end_time.format("%H:%M")
-                    )
-                } else {
-                    format!(
-                        "This meeting starts just before your {} focus session ({} - {}). Consider rescheduling to allow buffer time.",
-                        session_mode_name,
-                        focus_start_time.format("%H:%M"),
-                        focus_end_time.format("%H:%M")
-                    )
-                };
-                
-                let suggestion = MeetingSuggestion {
-                    event_id: event.id.clone(),
-                    title: event.title.clone(),
-                    original_time: event.start_time,
-                    suggested_time,
-                    reason,
-                };
-                
-                suggestions.push(suggestion);
-                println!("[Focus] Found conflicting meeting: {} at {} (conflicts with focus {} - {})", 
-                    event.title, event.start_time, focus_start_time, focus_end_time);
-            }
+            let suggestion = MeetingSuggestion {
+                event_id: event.id.clone(),
+                title: event.title.clone(),
+                original_time: event.start_time,
+                suggested_time,
+                reason,
+                conflict,
+                alternatives,
+            };
+            
+            suggestions.push(suggestion);
+            println!("[Focus] Found conflicting meeting: {} at {} (conflicts with focus {} - {})", 
+                event.title, event.start_time, focus_start_time, focus_end_time);
         }
         
         // Sort suggestions by original time
@@ -1724,6 +2494,9 @@ impl FocusService {
             if !s.is_active {
                 return None;
             }
+            if s.is_paused {
+                return Some(s.remaining_seconds);
+            }
             // Calculate remaining seconds dynamically based on end_time
             let now = Utc::now();
             if now >= s.end_time {
@@ -1753,7 +2526,29 @@ impl FocusService {
             focus_assist_state: Arc::clone(&self.focus_assist_state),
             notification_state: Arc::clone(&self.notification_state),
             temporary_mute_end_time: Arc::clone(&self.temporary_mute_end_time),
-            app_handle: self.app_handle.clone(),
+            pomodoro: Arc::clone(&self.pomodoro),
+            pomodoro_generation: Arc::clone(&self.pomodoro_generation),
+            timer_generation: Arc::clone(&self.timer_generation),
+            data_dir: self.data_dir.clone(),
+            history: self.history.clone(),
+            presets: self.presets.clone(),
+            quiet_hours: self.quiet_hours.clone(),
+            quiet_hours_active: Arc::clone(&self.quiet_hours_active),
+            distraction_config: Arc::clone(&self.distraction_config),
+            window_source: Arc::clone(&self.window_source),
+            suppressor: Arc::clone(&self.suppressor),
+            notification_ledger: self.notification_ledger.clone(),
+            notification_source: Arc::clone(&self.notification_source),
+            notification_queue: self.notification_queue.clone(),
+            last_digest: Arc::clone(&self.last_digest),
+            planner_config: Arc::clone(&self.planner_config),
+            meeting_guard_config: Arc::clone(&self.meeting_guard_config),
+            notification_allowlist: Arc::clone(&self.notification_allowlist),
+            event_bus: self.event_bus.clone(),
+            idle_source: Arc::clone(&self.idle_source),
+            idle_config: Arc::clone(&self.idle_config),
+            idle_state: Arc::clone(&self.idle_state),
+            app_handle: Arc::clone(&self.app_handle),
         }
     }
 }
@@ -1765,21 +2560,581 @@ impl Clone for FocusService {
             focus_assist_state: Arc::clone(&self.focus_assist_state),
             notification_state: Arc::clone(&self.notification_state),
             temporary_mute_end_time: Arc::clone(&self.temporary_mute_end_time),
-            app_handle: self.app_handle.clone(),
+            pomodoro: Arc::clone(&self.pomodoro),
+            pomodoro_generation: Arc::clone(&self.pomodoro_generation),
+            timer_generation: Arc::clone(&self.timer_generation),
+            data_dir: self.data_dir.clone(),
+            history: self.history.clone(),
+            presets: self.presets.clone(),
+            quiet_hours: self.quiet_hours.clone(),
+            quiet_hours_active: Arc::clone(&self.quiet_hours_active),
+            distraction_config: Arc::clone(&self.distraction_config),
+            window_source: Arc::clone(&self.window_source),
+            suppressor: Arc::clone(&self.suppressor),
+            notification_ledger: self.notification_ledger.clone(),
+            notification_source: Arc::clone(&self.notification_source),
+            notification_queue: self.notification_queue.clone(),
+            last_digest: Arc::clone(&self.last_digest),
+            planner_config: Arc::clone(&self.planner_config),
+            meeting_guard_config: Arc::clone(&self.meeting_guard_config),
+            notification_allowlist: Arc::clone(&self.notification_allowlist),
+            event_bus: self.event_bus.clone(),
+            idle_source: Arc::clone(&self.idle_source),
+            idle_config: Arc::clone(&self.idle_config),
+            idle_state: Arc::clone(&self.idle_state),
+            app_handle: Arc::clone(&self.app_handle),
         }
     }
 }
 
 // Global focus service instance
-use std::sync::OnceLock;
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, OnceCell};
 
-static FOCUS_SERVICE: OnceLock<Arc<Mutex<FocusService>>> = OnceLock::new();
+static FOCUS_SERVICE: OnceCell<Arc<Mutex<FocusService>>> = OnceCell::const_new();
 
 pub async fn get_focus_service() -> Arc<Mutex<FocusService>> {
     FOCUS_SERVICE
-        .get_or_init(|| {
-            Arc::new(Mutex::new(FocusService::new()))
+        .get_or_init(|| async {
+            // Resume or clean up a session left behind by a crash or restart before anyone can
+            // start a new one; callers wait here until recovery has finished
+            let service = FocusService::new();
+            service.recover_from_journal().await;
+            service.recover_notification_ledger().await;
+            service.spawn_focus_planner();
+            service.spawn_quiet_hours_scheduler();
+            service.spawn_idle_monitor();
+            Arc::new(Mutex::new(service))
         })
+        .await
         .clone()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::notification_suppressor::mock::RecordingSuppressor;
+    use crate::utils::idle_source::mock::ManualIdle;
+    use crate::utils::notification_source::mock::ManualSource;
+    use std::path::Path;
+
+    fn test_data_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("pause-menu-test-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// A service with every platform backend mocked, so tests never touch the session bus or the desktop
+    fn test_service(dir: &Path) -> FocusService {
+        FocusService::with_data_dir(dir.to_path_buf())
+            .with_suppressor(Arc::new(RecordingSuppressor::default()))
+            .with_notification_source(Arc::new(ManualSource::default()))
+            .with_idle_source(Arc::new(ManualIdle::default()))
+    }
+
+    fn journal_for(mode: FocusMode, start_time: DateTime<Utc>) -> FocusJournal {
+        FocusJournal {
+            session: FocusSession::new("test-session".to_string(), mode, start_time),
+            focus_assist_state: Some(FocusAssistState { was_enabled: false, previous_mode: 0 }),
+            notifications_muted: true,
+        }
+    }
+
+    #[test]
+    fn test_focus_mode_serialization() {
+        let preset = FocusMode::Preset { id: "writing".to_string(), name: "Writing".to_string(), minutes: 45 };
+        let json = serde_json::to_string(&preset).unwrap();
+        assert_eq!(serde_json::from_str::<FocusMode>(&json).unwrap(), preset);
+        assert_eq!(serde_json::from_str::<FocusMode>("\"deepwork60\"").unwrap(), FocusMode::DeepWork60);
+        assert_eq!(serde_json::from_str::<FocusMode>(r#"{"type":"custom","minutes":20}"#).unwrap(), FocusMode::Custom(20));
+    }
+
+    #[tokio::test]
+    async fn test_recover_resumes_running_session() {
+        let dir = test_data_dir("resume");
+        let journal = journal_for(FocusMode::DeepWork60, Utc::now() - Duration::minutes(10));
+        app_data::write_json(&dir.join(FOCUS_JOURNAL_FILE), &journal).unwrap();
+
+        let suppressor = Arc::new(RecordingSuppressor::default());
+        let service = test_service(&dir).with_suppressor(suppressor.clone());
+        service.recover_from_journal().await;
+
+        let session = service.get_current_session().await.expect("session should be resumed");
+        assert!(session.is_active);
+        assert!(session.remaining_seconds > 49 * 60 && session.remaining_seconds <= 50 * 60);
+        assert!(service.is_notifications_muted().await);
+        assert_eq!(suppressor.calls(), vec!["mute"]);
+        assert!(dir.join(FOCUS_JOURNAL_FILE).exists());
+
+        service.stop_focus_mode().await.unwrap();
+        assert!(!dir.join(FOCUS_JOURNAL_FILE).exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_muting_goes_through_suppressor() {
+        let dir = test_data_dir("suppressor");
+        let suppressor = Arc::new(RecordingSuppressor::default());
+        let service = test_service(&dir).with_suppressor(suppressor.clone());
+
+        service.start_focus_mode(FocusMode::Focus25).await.unwrap();
+        assert_eq!(suppressor.calls(), vec!["mute"]);
+        assert!(service.is_notifications_muted().await);
+
+        // Pausing with notifications restored unmutes, resuming mutes again
+        service.pause_focus_mode(true).await.unwrap();
+        assert!(!service.is_notifications_muted().await);
+        service.resume_focus_mode().await.unwrap();
+        assert!(service.is_notifications_muted().await);
+
+        service.stop_focus_mode().await.unwrap();
+        assert_eq!(suppressor.calls(), vec!["mute", "stop_blocker", "unmute", "mute", "stop_blocker", "unmute"]);
+        assert!(!service.is_notifications_muted().await);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_session_starts_when_muting_fails() {
+        let dir = test_data_dir("suppressor-fail");
+        let suppressor = Arc::new(RecordingSuppressor { fail_mute: true, ..RecordingSuppressor::default() });
+        let service = test_service(&dir).with_suppressor(suppressor.clone());
+
+        assert!(service.start_focus_mode(FocusMode::Focus15).await.is_ok());
+        assert_eq!(suppressor.calls(), vec!["mute"]);
+
+        service.stop_focus_mode().await.unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_muting_spares_allowlisted_apps() {
+        let dir = test_data_dir("allowlist");
+        let suppressor = Arc::new(RecordingSuppressor::default());
+        let service = test_service(&dir).with_suppressor(suppressor.clone());
+        service
+            .set_notification_allowlist(NotificationAllowlist { apps: vec!["PagerDuty".to_string()] })
+            .await
+            .unwrap();
+
+        let options = FocusOptions { app_allowlist: vec!["slack".to_string()], ..FocusOptions::default() };
+        service.start_focus_mode_with_options(FocusMode::Focus25, options).await.unwrap();
+        assert_eq!(suppressor.last_allowlist(), vec!["PagerDuty".to_string(), "slack".to_string()]);
+        service.stop_focus_mode().await.unwrap();
+
+        // Temporary mutes only use the saved allowlist, which survives a restart
+        let service = test_service(&dir).with_suppressor(suppressor.clone());
+        service.temporarily_mute_notifications(None).await.unwrap();
+        assert_eq!(suppressor.last_allowlist(), vec!["PagerDuty".to_string()]);
+        service.unmute_notifications().await.unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_allowlist_warns_when_the_backend_cant_spare_apps() {
+        let dir = test_data_dir("allowlist-unsupported");
+        let service = test_service(&dir);
+        assert!(!service.warn_if_allowlist_unsupported(&["PagerDuty".to_string()]));
+
+        let service = service.with_suppressor(Arc::new(RecordingSuppressor { no_allowlist: true, ..RecordingSuppressor::default() }));
+        assert!(service.warn_if_allowlist_unsupported(&["PagerDuty".to_string()]));
+        assert!(!service.warn_if_allowlist_unsupported(&[]));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_session_events_reach_the_event_log() {
+        use crate::services::event_bus::{EventSink, RetryPolicy, SinkTarget};
+
+        let dir = test_data_dir("event-bus");
+        let service = test_service(&dir);
+        let log = EventSink {
+            name: "log".to_string(),
+            enabled: true,
+            events: Vec::new(),
+            target: SinkTarget::JsonlLog { path: PathBuf::from("events.jsonl") },
+            retry: RetryPolicy::default(),
+        };
+        service.set_event_bus_config(EventBusConfig { sinks: vec![log] }).unwrap();
+
+        service.start_focus_mode(FocusMode::Focus25).await.unwrap();
+        service.pause_focus_mode(false).await.unwrap();
+        service.resume_focus_mode().await.unwrap();
+        service.stop_focus_mode().await.unwrap();
+
+        let expected = ["mute_toggled", "session_started", "session_paused", "session_resumed", "mute_toggled", "session_ended"];
+        let mut kinds: Vec<String> = Vec::new();
+        for _ in 0..100 {
+            let contents = std::fs::read_to_string(dir.join("events.jsonl")).unwrap_or_default();
+            kinds = contents
+                .lines()
+                .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap()["type"].as_str().unwrap().to_string())
+                .collect();
+            if kinds.len() >= expected.len() {
+                break;
+            }
+            sleep(TokioDuration::from_millis(20)).await;
+        }
+        assert_eq!(kinds, expected);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_idle_pauses_session_and_records_time_away() {
+        let dir = test_data_dir("idle");
+        let idle = Arc::new(ManualIdle::default());
+        let service = test_service(&dir).with_idle_source(idle.clone());
+        service.start_focus_mode(FocusMode::Focus25).await.unwrap();
+        // Pretend the session started 20 minutes ago
+        if let Some(ref mut s) = *service.current_session.write().await {
+            s.start_time -= Duration::minutes(20);
+            s.end_time -= Duration::minutes(20);
+        }
+
+        idle.set_idle_seconds(200);
+        service.check_idle(Utc::now()).await;
+        assert!(!service.get_current_session().await.unwrap().is_paused);
+
+        // Past the 5-minute threshold: paused from the last input, so the threshold isn't lost
+        idle.set_idle_seconds(400);
+        let now = Utc::now();
+        service.check_idle(now).await;
+        let paused = service.get_current_session().await.unwrap();
+        assert!(paused.is_paused);
+        assert_eq!(paused.paused_at, Some(now - Duration::seconds(400)));
+        assert!((699..=701).contains(&paused.remaining_seconds));
+
+        idle.set_idle_seconds(0);
+        service.check_idle(Utc::now()).await;
+        let resumed = service.get_current_session().await.unwrap();
+        assert!(!resumed.is_paused);
+        assert!((400..=401).contains(&resumed.idle_seconds));
+
+        service.stop_focus_mode().await.unwrap();
+        assert_eq!(service.get_history(Some(1)).unwrap()[0].idle_minutes, 7);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_goal_tags_and_reflection_end_up_in_history() {
+        let dir = test_data_dir("reflection");
+        let service = test_service(&dir);
+
+        let options = FocusOptions {
+            goal: Some("  Draft the Q3 plan ".to_string()),
+            tags: vec!["#Planning".to_string(), "writing".to_string()],
+            ..FocusOptions::default()
+        };
+        let session = service.start_focus_mode_with_options(FocusMode::Focus25, options).await.unwrap();
+        assert_eq!(session.goal.as_deref(), Some("Draft the Q3 plan"));
+        service.stop_focus_mode().await.unwrap();
+        service.start_focus_mode(FocusMode::Focus15).await.unwrap();
+        service.stop_focus_mode().await.unwrap();
+
+        assert!(service.reflect_on_session(Some(session.id.clone()), 0, "").is_err());
+        let record = service.reflect_on_session(Some(session.id.clone()), 4, "Outline done").unwrap();
+        assert_eq!(record.reflection.map(|r| r.note), Some("Outline done".to_string()));
+
+        let planning = service.search_history("planning", None).unwrap();
+        assert_eq!(planning.len(), 1);
+        assert_eq!(planning[0].goal.as_deref(), Some("Draft the Q3 plan"));
+        assert_eq!(planning[0].tags, vec!["planning".to_string(), "writing".to_string()]);
+
+        let csv = service.export_history(ExportFormat::Csv, Some(7), Some("writing")).unwrap();
+        assert_eq!(csv.lines().count(), 2);
+        assert!(csv.contains(",Draft the Q3 plan,planning;writing,4,Outline done"));
+        let all_time = service.export_history(ExportFormat::Csv, Some(u32::MAX), None).unwrap();
+        assert_eq!(all_time.lines().count(), 3);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_notifications_while_muted_become_a_digest() {
+        let dir = test_data_dir("digest");
+        let source = Arc::new(ManualSource::default());
+        let service = test_service(&dir).with_notification_source(source.clone());
+
+        assert!(!source.deliver("Slack", "before focus"));
+        service.start_focus_mode(FocusMode::Focus25).await.unwrap();
+        assert!(source.deliver("Slack", "standup?"));
+        assert!(source.deliver("Mail", "Invoice"));
+
+        // Nothing is captured while a pause lets notifications through, but the queue is kept
+        service.pause_focus_mode(true).await.unwrap();
+        assert!(!source.deliver("Slack", "while paused"));
+        service.resume_focus_mode().await.unwrap();
+        assert!(source.deliver("Slack", "lunch"));
+
+        service.stop_focus_mode().await.unwrap();
+        let digest = service.get_notification_digest().await.unwrap();
+        assert_eq!(digest.total, 3);
+        assert_eq!((digest.groups[0].app.as_str(), digest.groups[0].count), ("Slack", 2));
+        assert_eq!((digest.groups[1].app.as_str(), digest.groups[1].count), ("Mail", 1));
+
+        // A manual mute gets a digest of its own
+        service.temporarily_mute_notifications(None).await.unwrap();
+        assert!(source.deliver("Pager", "disk full"));
+        service.unmute_notifications().await.unwrap();
+        assert_eq!(service.get_notification_digest().await.unwrap().total, 1);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_quiet_hours_mute_and_survive_restart() {
+        use crate::services::quiet_hours::{CalendarExceptions, QuietHoursRule};
+        use chrono::{NaiveTime, Weekday};
+
+        let dir = test_data_dir("quiet-hours");
+        let suppressor = Arc::new(RecordingSuppressor::default());
+        let service = test_service(&dir).with_suppressor(suppressor.clone());
+        let rule = service
+            .quiet_hours()
+            .add(QuietHoursRule {
+                id: String::new(),
+                name: "Lunch".to_string(),
+                days: vec![Weekday::Fri],
+                start: NaiveTime::from_hms_opt(12, 0, 0).unwrap(),
+                end: NaiveTime::from_hms_opt(13, 0, 0).unwrap(),
+                time_zone: Some("UTC".to_string()),
+                enabled: true,
+                exceptions: CalendarExceptions { any_event: false, title_keywords: vec!["on-call".to_string()] },
+            })
+            .unwrap();
+        // 2024-06-14 is a Friday
+        let at = |time: &str| DateTime::parse_from_rfc3339(&format!("2024-06-14T{}:00Z", time)).unwrap().with_timezone(&Utc);
+
+        service.apply_quiet_hours(&[], at("12:10")).await;
+        assert!(service.is_notifications_muted().await);
+        assert_eq!(suppressor.calls(), vec!["mute"]);
+
+        // After a restart the window is still known, and the lifted mute is put back
+        let service = test_service(&dir).with_suppressor(suppressor.clone());
+        assert_eq!(service.quiet_hours_active.read().await.as_ref().map(|a| a.rule_id.clone()), Some(rule.id.clone()));
+        service.apply_quiet_hours(&[], at("12:20")).await;
+        assert!(service.is_notifications_muted().await);
+        assert_eq!(suppressor.calls(), vec!["mute", "mute"]);
+
+        // A matching calendar event lifts quiet hours while it runs
+        let on_call = CalendarEvent {
+            id: "e1".to_string(),
+            title: "On-call handover".to_string(),
+            start_time: at("12:30"),
+            end_time: at("12:45"),
+            is_organizer: false,
+            ..Default::default()
+        };
+        service.apply_quiet_hours(std::slice::from_ref(&on_call), at("12:35")).await;
+        assert!(!service.is_notifications_muted().await);
+        assert!(service.quiet_hours_active.read().await.is_none());
+
+        // Back on afterwards, until the user unmutes by hand
+        service.apply_quiet_hours(std::slice::from_ref(&on_call), at("12:50")).await;
+        assert!(service.is_notifications_muted().await);
+        service.unmute_notifications_by_user().await.unwrap();
+        service.apply_quiet_hours(&[], at("12:55")).await;
+        assert!(!service.is_notifications_muted().await);
+
+        service.apply_quiet_hours(&[], at("13:00")).await;
+        assert!(service.quiet_hours_active.read().await.is_none());
+        assert!(!dir.join("quiet_hours_state.json").exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_quiet_hours_leave_the_users_own_mute_alone() {
+        use crate::services::quiet_hours::{CalendarExceptions, QuietHoursRule};
+        use chrono::{NaiveTime, Weekday};
+
+        let lunch = || QuietHoursRule {
+            id: String::new(),
+            name: "Lunch".to_string(),
+            days: vec![Weekday::Fri],
+            start: NaiveTime::from_hms_opt(12, 0, 0).unwrap(),
+            end: NaiveTime::from_hms_opt(13, 0, 0).unwrap(),
+            time_zone: Some("UTC".to_string()),
+            enabled: true,
+            exceptions: CalendarExceptions::default(),
+        };
+        // 2024-06-14 is a Friday
+        let at = |time: &str| DateTime::parse_from_rfc3339(&format!("2024-06-14T{}:00Z", time)).unwrap().with_timezone(&Utc);
+
+        // Quiet hours mute, then the user asks for two hours of quiet
+        let dir = test_data_dir("quiet-hours-user-mute");
+        let suppressor = Arc::new(RecordingSuppressor::default());
+        let service = test_service(&dir).with_suppressor(suppressor.clone());
+        service.quiet_hours().add(lunch()).unwrap();
+        service.apply_quiet_hours(&[], at("12:10")).await;
+        service.temporarily_mute_notifications_by_user(Some(120)).await.unwrap();
+        service.apply_quiet_hours(&[], at("13:00")).await;
+        assert!(service.quiet_hours_active.read().await.is_none());
+        assert!(service.is_notifications_muted().await);
+        assert!(service.get_temporary_mute_remaining().await.is_some());
+        assert_eq!(suppressor.calls(), vec!["mute"]);
+        let _ = std::fs::remove_dir_all(&dir);
+
+        // Already muted when the window opens: nothing of ours to undo when it closes
+        let dir = test_data_dir("quiet-hours-muted-before");
+        let suppressor = Arc::new(RecordingSuppressor::default());
+        let service = test_service(&dir).with_suppressor(suppressor.clone());
+        service.quiet_hours().add(lunch()).unwrap();
+        service.temporarily_mute_notifications_by_user(None).await.unwrap();
+        service.apply_quiet_hours(&[], at("12:10")).await;
+        service.apply_quiet_hours(&[], at("13:00")).await;
+        assert!(service.is_notifications_muted().await);
+        assert_eq!(suppressor.calls(), vec!["mute"]);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_pause_and_resume_survive_restart() {
+        let dir = test_data_dir("pause");
+        let journal = journal_for(FocusMode::Focus25, Utc::now() - Duration::minutes(5));
+        app_data::write_json(&dir.join(FOCUS_JOURNAL_FILE), &journal).unwrap();
+
+        let service = test_service(&dir);
+        service.recover_from_journal().await;
+        let paused = service.pause_focus_mode(false).await.unwrap();
+        assert!(paused.is_paused);
+        assert!(service.pause_focus_mode(false).await.is_err());
+        assert_eq!(service.get_remaining_seconds().await, Some(paused.remaining_seconds));
+
+        // A paused session is restored as paused, even long after its original end time
+        let mut journal: FocusJournal = app_data::read_json(&dir.join(FOCUS_JOURNAL_FILE)).unwrap().unwrap();
+        journal.session.end_time = Utc::now() - Duration::hours(1);
+        app_data::write_json(&dir.join(FOCUS_JOURNAL_FILE), &journal).unwrap();
+
+        let restarted = test_service(&dir);
+        restarted.recover_from_journal().await;
+        let session = restarted.get_current_session().await.expect("paused session should be restored");
+        assert!(session.is_paused);
+        assert_eq!(session.remaining_seconds, paused.remaining_seconds);
+
+        let resumed = restarted.resume_focus_mode().await.unwrap();
+        assert!(!resumed.is_paused);
+        assert!(resumed.end_time > Utc::now());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_distraction_counted_on_session() {
+        use crate::services::distraction_blocker::mock::{window, MockWindowSource};
+
+        let dir = test_data_dir("distraction");
+        let mut journal = journal_for(FocusMode::Focus25, Utc::now());
+        journal.session.app_allowlist = vec!["slack".to_string()];
+        app_data::write_json(&dir.join(FOCUS_JOURNAL_FILE), &journal).unwrap();
+
+        let source = Arc::new(MockWindowSource::with_changes(vec![
+            Some(window(1, "slack.exe", "general")),
+            Some(window(2, "discord.exe", "friends")),
+        ]));
+        let service = test_service(&dir).with_window_source(source.clone());
+        service.set_distraction_config(DistractionConfig {
+            enabled: true,
+            policy: DistractionPolicy::Minimize,
+            ..DistractionConfig::default()
+        }).await.unwrap();
+        service.recover_from_journal().await;
+
+        // Slack is on the session allowlist, Discord isn't
+        assert!(service.check_distractions().await.is_none());
+        let event = service.check_distractions().await.expect("discord should be blocked");
+        assert_eq!(event.session_id, "test-session");
+        assert_eq!(*source.minimized.lock().unwrap(), vec![2]);
+        assert_eq!(service.get_current_session().await.unwrap().distraction_attempts, 1);
+
+        service.stop_focus_mode().await.unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_stopped_focus_block_is_not_restarted() {
+        use crate::models::test_support::{event, utc};
+
+        let dir = test_data_dir("planner-auto-start");
+        let service = test_service(&dir);
+        service.set_planner_config(PlannerConfig { auto_start: true, ..PlannerConfig::default() }).await.unwrap();
+        let events = vec![event("lunch", "12:00", "13:00")];
+        let mut last_started = None;
+
+        // Nothing before the block begins, then one session at its start
+        assert!(service.auto_start_focus_block(&events, utc("08:59:30"), &Utc, &mut last_started).await.is_none());
+        let block = service.auto_start_focus_block(&events, utc("09:00:30"), &Utc, &mut last_started).await.unwrap();
+        assert_eq!(block.start, utc("09:00"));
+        assert!(service.is_active().await);
+
+        // The user stops it; the next tick leaves it stopped
+        service.stop_focus_mode().await.unwrap();
+        assert!(service.auto_start_focus_block(&events, utc("09:01:00"), &Utc, &mut last_started).await.is_none());
+        assert!(!service.is_active().await);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_meeting_guard_ends_session_with_reason() {
+        let dir = test_data_dir("meeting");
+        app_data::write_json(&dir.join(FOCUS_JOURNAL_FILE), &journal_for(FocusMode::DeepWork60, Utc::now())).unwrap();
+
+        let service = test_service(&dir);
+        service.recover_from_journal().await;
+
+        let meeting = CalendarEvent {
+            id: "google_standup".to_string(),
+            title: "Standup".to_string(),
+            start_time: Utc::now() + Duration::seconds(30),
+            end_time: Utc::now() + Duration::minutes(15),
+            ..Default::default()
+        };
+        service.apply_meeting_guard(&meeting).await;
+
+        assert!(service.get_current_session().await.is_none());
+        let history = service.get_history(Some(1)).unwrap();
+        assert_eq!(history[0].end_reason, FocusEndReason::Meeting);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_meeting_guard_shortens_session() {
+        let dir = test_data_dir("meeting-shorten");
+        app_data::write_json(&dir.join(FOCUS_JOURNAL_FILE), &journal_for(FocusMode::DeepWork60, Utc::now())).unwrap();
+
+        let service = test_service(&dir);
+        service.set_meeting_guard_config(MeetingGuardConfig {
+            action: crate::services::meeting_guard::MeetingGuardAction::ShortenSession,
+            ..MeetingGuardConfig::default()
+        }).await.unwrap();
+        service.recover_from_journal().await;
+
+        let meeting_start = Utc::now() + Duration::minutes(3);
+        let meeting = CalendarEvent {
+            id: "google_review".to_string(),
+            title: "Review".to_string(),
+            start_time: meeting_start,
+            end_time: meeting_start + Duration::minutes(30),
+            ..Default::default()
+        };
+        service.apply_meeting_guard(&meeting).await;
+
+        let session = service.get_current_session().await.unwrap();
+        assert_eq!(session.end_time, meeting_start - Duration::minutes(1));
+        assert_eq!(session.warned_meeting_id.as_deref(), Some("google_review"));
+        assert!(session.end_reason.unwrap().contains("Review"));
+
+        service.stop_focus_mode().await.unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_recover_expired_session_restores_notifications() {
+        let dir = test_data_dir("expired");
+        let journal = journal_for(FocusMode::Focus15, Utc::now() - Duration::minutes(30));
+        app_data::write_json(&dir.join(FOCUS_JOURNAL_FILE), &journal).unwrap();
+
+        let service = test_service(&dir);
+        service.recover_from_journal().await;
+
+        assert!(service.get_current_session().await.is_none());
+        assert!(!service.is_notifications_muted().await);
+        assert!(!dir.join(FOCUS_JOURNAL_FILE).exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
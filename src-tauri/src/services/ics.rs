@@ -0,0 +1,804 @@
+// iCalendar (RFC 5545) parsing
+// Turns the VEVENTs in an .ics document into concrete occurrences inside a time window.
+// Used by the CalDAV provider and by read-only .ics subscriptions. Recurring events are
+// expanded for the common rules (DAILY, WEEKLY with BYDAY, MONTHLY, YEARLY with INTERVAL,
+// COUNT and UNTIL), honouring EXDATE and RECURRENCE-ID overrides. Anything fancier only
+// contributes its first occurrence.
+
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, NaiveDateTime, TimeZone, Utc, Weekday};
+use chrono_tz::Tz;
+use std::collections::{HashMap, HashSet};
+use crate::models::action::{CalendarEvent, EventPerson};
+use crate::services::calendar_selection::CalendarInfo;
+
+// Stop expanding a rule after this many periods, however far the window reaches
+const MAX_RECURRENCE_STEPS: u32 = 10_000;
+// Larger intervals are rejected, so a hostile feed can't push the date math out of range
+const MAX_RECURRENCE_INTERVAL: u32 = 1_000;
+
+// One occurrence of an event
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IcsEvent {
+    pub uid: String,
+    pub summary: Option<String>,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub all_day: bool,
+    pub location: Option<String>,
+    pub description: Option<String>,
+    pub organizer: Option<String>, // Organizer email, lowercased, without "mailto:"
+    pub recurring: bool,           // One of several occurrences of the same UID
+}
+
+impl IcsEvent {
+    /// Convert to the app's event type; recurring occurrences get their start time in the id
+    pub fn to_calendar_event(&self, id_prefix: &str, calendar: &CalendarInfo, is_organizer: bool) -> CalendarEvent {
+        let series = series_id(id_prefix, calendar, &self.uid);
+        let id = if self.recurring {
+            format!("{}_{}", series, self.start.format("%Y%m%dT%H%M%SZ"))
+        } else {
+            series.clone()
+        };
+        CalendarEvent {
+            id,
+            title: self.summary.clone().unwrap_or_else(|| "(No title)".to_string()),
+            start_time: self.start,
+            end_time: self.end,
+            location: self.location.clone(),
+            description: self.description.clone(),
+            all_day: self.all_day,
+            is_organizer,
+            calendar_id: Some(calendar.id.clone()),
+            calendar_name: Some(calendar.name.clone()),
+            calendar_color: calendar.color.clone(),
+            organizer: self.organizer.clone().map(|email| EventPerson { email, name: None }),
+            recurrence_id: self.recurring.then_some(series),
+            ..Default::default()
+        }
+    }
+}
+
+/// Id of an event (or a whole series) from `calendar`
+/// UIDs are only unique within a calendar, and the same feed may be subscribed to twice, so the
+/// calendar is part of the id
+pub(crate) fn series_id(id_prefix: &str, calendar: &CalendarInfo, uid: &str) -> String {
+    format!("{}_{}_{}", id_prefix, &short_hash(&[&calendar.id])[..8], uid)
+}
+
+/// FNV-1a, as hex; std's hasher may change between Rust releases and these ids are saved
+fn short_hash(parts: &[&str]) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for part in parts {
+        // The separator keeps ("ab", "c") and ("a", "bc") apart
+        for byte in part.bytes().chain([0]) {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    format!("{:016x}", hash)
+}
+
+/// Occurrences of every event in `ics` that overlap `from..to`, soonest first
+pub fn parse_events(ics: &str, from: DateTime<Utc>, to: DateTime<Utc>) -> Result<Vec<IcsEvent>, String> {
+    let lines = unfold(ics);
+    if !lines.iter().any(|line| line.eq_ignore_ascii_case("BEGIN:VCALENDAR")) {
+        return Err("Not an iCalendar document (no BEGIN:VCALENDAR)".to_string());
+    }
+
+    let components = vevents(&lines);
+    // Moved or edited occurrences of recurring events, keyed by UID
+    let mut overridden: HashMap<String, HashSet<DateTime<Utc>>> = HashMap::new();
+    for component in &components {
+        if let Some(recurrence_id) = component.get("RECURRENCE-ID").and_then(|p| p.time()) {
+            overridden.entry(component.uid()).or_default().insert(recurrence_id.to_utc());
+        }
+    }
+
+    let mut events = Vec::new();
+    for component in &components {
+        match component.occurrences(from, to, &overridden) {
+            Ok(occurrences) => events.extend(occurrences),
+            Err(e) => eprintln!("[Ics] Skipping event {}: {}", component.uid(), e),
+        }
+    }
+    events.sort_by_key(|e| e.start);
+    Ok(events)
+}
+
+/// The calendar's display name and color, if the feed sets them (X-WR-CALNAME, X-APPLE-CALENDAR-COLOR)
+pub fn calendar_properties(ics: &str) -> (Option<String>, Option<String>) {
+    let mut name = None;
+    let mut color = None;
+    for line in unfold(ics) {
+        if line.eq_ignore_ascii_case("BEGIN:VEVENT") {
+            break;
+        }
+        let Some(property) = Property::parse(&line) else { continue };
+        match property.name.as_str() {
+            "X-WR-CALNAME" => name = Some(property.text()),
+            // Apple appends an alpha channel ("#FF2968FF")
+            "X-APPLE-CALENDAR-COLOR" => color = Some(property.value.chars().take(7).collect()),
+            _ => {}
+        }
+    }
+    (name, color)
+}
+
+/// Join folded lines (continuations start with a space or tab)
+fn unfold(ics: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for raw in ics.lines() {
+        let raw = raw.trim_end_matches('\r');
+        match raw.strip_prefix(' ').or_else(|| raw.strip_prefix('\t')) {
+            Some(rest) if !lines.is_empty() => lines.last_mut().unwrap().push_str(rest),
+            _ if raw.is_empty() => {}
+            _ => lines.push(raw.to_string()),
+        }
+    }
+    lines
+}
+
+// NAME;PARAM=VALUE:value
+#[derive(Debug, Clone)]
+struct Property {
+    name: String,
+    params: HashMap<String, String>,
+    value: String,
+}
+
+impl Property {
+    fn parse(line: &str) -> Option<Self> {
+        // The value starts at the first colon outside a quoted parameter
+        let mut in_quotes = false;
+        let split = line.char_indices().find(|&(_, c)| {
+            if c == '"' {
+                in_quotes = !in_quotes;
+            }
+            c == ':' && !in_quotes
+        })?;
+        let (head, value) = (&line[..split.0], &line[split.0 + 1..]);
+
+        let mut parts = head.split(';');
+        let name = parts.next()?.trim().to_ascii_uppercase();
+        let params = parts
+            .filter_map(|param| param.split_once('='))
+            .map(|(k, v)| (k.trim().to_ascii_uppercase(), v.trim_matches('"').to_string()))
+            .collect();
+        Some(Self { name, params, value: value.to_string() })
+    }
+
+    /// Value with TEXT escapes undone
+    fn text(&self) -> String {
+        let mut out = String::with_capacity(self.value.len());
+        let mut chars = self.value.chars();
+        while let Some(c) = chars.next() {
+            if c != '\\' {
+                out.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some('n') | Some('N') => out.push('\n'),
+                Some(other) => out.push(other),
+                None => {}
+            }
+        }
+        out
+    }
+
+    fn time(&self) -> Option<IcsTime> {
+        IcsTime::parse(&self.value, self.params.get("VALUE").map(String::as_str), self.params.get("TZID").map(String::as_str))
+    }
+
+    /// Every date-time in a comma-separated list (EXDATE)
+    fn times(&self) -> Vec<IcsTime> {
+        let value_type = self.params.get("VALUE").map(String::as_str);
+        let tzid = self.params.get("TZID").map(String::as_str);
+        self.value.split(',').filter_map(|v| IcsTime::parse(v.trim(), value_type, tzid)).collect()
+    }
+}
+
+// Which clock a date-time is on
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Zone {
+    Utc,
+    Named(Tz),
+    Floating, // No zone given: the user's local time
+}
+
+// A DTSTART-like value: wall-clock time on some zone, or a whole day
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct IcsTime {
+    local: NaiveDateTime,
+    zone: Zone,
+    date_only: bool,
+}
+
+impl IcsTime {
+    fn parse(value: &str, value_type: Option<&str>, tzid: Option<&str>) -> Option<Self> {
+        if value_type == Some("DATE") || value.len() == 8 {
+            let date = NaiveDate::parse_from_str(value, "%Y%m%d").ok()?;
+            return Some(Self { local: date.and_hms_opt(0, 0, 0)?, zone: Zone::Utc, date_only: true });
+        }
+
+        if let Some(utc) = value.strip_suffix('Z') {
+            let local = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?;
+            return Some(Self { local, zone: Zone::Utc, date_only: false });
+        }
+
+        let local = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+        let zone = match tzid {
+            // Some servers prefix the IANA name ("/Europe/Berlin"); unknown (e.g. Windows) names fall back to local time
+            Some(tzid) => tzid.trim_start_matches('/').parse::<Tz>().map(Zone::Named).unwrap_or_else(|_| {
+                eprintln!("[Ics] Unknown time zone '{}', treating it as local time", tzid);
+                Zone::Floating
+            }),
+            None => Zone::Floating,
+        };
+        Some(Self { local, zone, date_only: false })
+    }
+
+    /// Same zone, different wall-clock time (recurrences keep the local time across DST changes)
+    fn with_local(self, local: NaiveDateTime) -> Self {
+        Self { local, ..self }
+    }
+
+    fn to_utc(self) -> DateTime<Utc> {
+        match self.zone {
+            Zone::Utc => self.local.and_utc(),
+            Zone::Named(tz) => localize(&tz, self.local),
+            Zone::Floating => localize(&Local, self.local),
+        }
+    }
+}
+
+/// Resolve a wall-clock time, taking the earlier instant when it's ambiguous
+/// and skipping ahead an hour when it falls into a DST gap
+fn localize<Z: TimeZone>(tz: &Z, local: NaiveDateTime) -> DateTime<Utc> {
+    tz.from_local_datetime(&local)
+        .earliest()
+        .or_else(|| tz.from_local_datetime(&(local + Duration::hours(1))).earliest())
+        .map(|dt| dt.with_timezone(&Utc))
+        .unwrap_or_else(|| local.and_utc())
+}
+
+/// RFC 5545 DURATION, e.g. "PT1H30M", "P1D", "-PT15M"
+fn parse_duration(value: &str) -> Option<Duration> {
+    let (sign, rest) = match value.strip_prefix('-') {
+        Some(rest) => (-1, rest),
+        None => (1, value.strip_prefix('+').unwrap_or(value)),
+    };
+    let rest = rest.strip_prefix('P')?;
+    let mut total = Duration::zero();
+    let mut number = String::new();
+    for c in rest.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'T' => {}
+            unit => {
+                let n: i64 = number.parse().ok()?;
+                number.clear();
+                let part = match unit {
+                    'W' => Duration::try_weeks(n),
+                    'D' => Duration::try_days(n),
+                    'H' => Duration::try_hours(n),
+                    'M' => Duration::try_minutes(n),
+                    'S' => Duration::try_seconds(n),
+                    _ => return None,
+                };
+                total = total.checked_add(&part?)?;
+            }
+        }
+    }
+    Some(total * sign)
+}
+
+// The properties of one VEVENT
+struct Component {
+    properties: Vec<Property>,
+    index: usize, // Position in the document, to tell UID-less events apart
+}
+
+/// Split the document into VEVENTs, leaving out nested components such as VALARM
+fn vevents(lines: &[String]) -> Vec<Component> {
+    let mut components = Vec::new();
+    let mut current: Option<Vec<Property>> = None;
+    let mut nested = 0;
+    for line in lines {
+        let upper = line.to_ascii_uppercase();
+        if upper == "BEGIN:VEVENT" {
+            current = Some(Vec::new());
+            nested = 0;
+        } else if upper == "END:VEVENT" {
+            if let Some(properties) = current.take() {
+                components.push(Component { properties, index: components.len() });
+            }
+        } else if let Some(properties) = current.as_mut() {
+            if upper.starts_with("BEGIN:") {
+                nested += 1;
+            } else if upper.starts_with("END:") {
+                nested -= 1;
+            } else if nested == 0 {
+                if let Some(property) = Property::parse(line) {
+                    properties.push(property);
+                }
+            }
+        }
+    }
+    components
+}
+
+impl Component {
+    fn get(&self, name: &str) -> Option<&Property> {
+        self.properties.iter().find(|p| p.name == name)
+    }
+
+    fn text(&self, name: &str) -> Option<String> {
+        self.get(name).map(Property::text).filter(|t| !t.trim().is_empty())
+    }
+
+    /// The UID, or one made up from the event itself for feeds that leave it out
+    fn uid(&self) -> String {
+        self.get("UID").map(|p| p.value.clone()).unwrap_or_else(|| {
+            let value = |name: &str| self.get(name).map_or("", |p| p.value.as_str());
+            format!("no-uid-{}", short_hash(&[value("DTSTART"), value("SUMMARY"), &self.index.to_string()]))
+        })
+    }
+
+    fn occurrences(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        overridden: &HashMap<String, HashSet<DateTime<Utc>>>,
+    ) -> Result<Vec<IcsEvent>, String> {
+        if self.get("STATUS").is_some_and(|s| s.value.eq_ignore_ascii_case("CANCELLED")) {
+            return Ok(Vec::new());
+        }
+        let start = self.get("DTSTART").and_then(Property::time).ok_or("missing or invalid DTSTART")?;
+        let length = match (self.get("DTEND").and_then(Property::time), self.get("DURATION")) {
+            (Some(end), _) => end.to_utc() - start.to_utc(),
+            (None, Some(duration)) => parse_duration(&duration.value).ok_or("invalid DURATION")?,
+            (None, None) if start.date_only => Duration::days(1),
+            (None, None) => Duration::zero(),
+        };
+
+        let uid = self.uid();
+        let rule = match self.get("RRULE") {
+            // An override replaces a single occurrence and never recurs itself
+            Some(rrule) if self.get("RECURRENCE-ID").is_none() => match RecurrenceRule::parse(&rrule.value) {
+                Ok(rule) => Some(rule),
+                Err(e) => {
+                    eprintln!("[Ics] Only showing the first occurrence of {}: {}", uid, e);
+                    None
+                }
+            },
+            _ => None,
+        };
+        let starts = match rule {
+            Some(ref rule) => {
+                let mut skipped: HashSet<DateTime<Utc>> = self
+                    .properties
+                    .iter()
+                    .filter(|p| p.name == "EXDATE")
+                    .flat_map(Property::times)
+                    .map(IcsTime::to_utc)
+                    .collect();
+                if let Some(moved) = overridden.get(&uid) {
+                    skipped.extend(moved);
+                }
+                rule.expand(start, from.checked_sub_signed(length).unwrap_or(DateTime::<Utc>::MIN_UTC), to)
+                    .into_iter()
+                    .filter(|s| !skipped.contains(s))
+                    .collect()
+            }
+            None => vec![start.to_utc()],
+        };
+
+        let organizer = self.get("ORGANIZER").map(|p| {
+            let value = p.value.trim();
+            let email = value.strip_prefix("mailto:").or_else(|| value.strip_prefix("MAILTO:")).unwrap_or(value);
+            email.to_ascii_lowercase()
+        });
+        Ok(starts
+            .into_iter()
+            .filter_map(|s| Some((s, s.checked_add_signed(length)?)))
+            .filter(|&(s, e)| (e > from || s == e && s >= from) && s < to)
+            .map(|(s, e)| IcsEvent {
+                uid: uid.clone(),
+                summary: self.text("SUMMARY"),
+                start: s,
+                end: e,
+                all_day: start.date_only,
+                location: self.text("LOCATION"),
+                description: self.text("DESCRIPTION"),
+                organizer: organizer.clone(),
+                recurring: self.get("RRULE").is_some() || self.get("RECURRENCE-ID").is_some(),
+            })
+            .collect())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct RecurrenceRule {
+    frequency: Frequency,
+    interval: u32,
+    count: Option<usize>,
+    until: Option<IcsTime>,
+    by_day: Vec<Weekday>, // WEEKLY only
+}
+
+impl RecurrenceRule {
+    fn parse(value: &str) -> Result<Self, String> {
+        let mut rule = RecurrenceRule { frequency: Frequency::Daily, interval: 1, count: None, until: None, by_day: Vec::new() };
+        let mut frequency = None;
+        for part in value.split(';').filter(|p| !p.is_empty()) {
+            let (key, val) = part.split_once('=').ok_or_else(|| format!("malformed RRULE part '{}'", part))?;
+            match key.to_ascii_uppercase().as_str() {
+                "FREQ" => {
+                    frequency = Some(match val.to_ascii_uppercase().as_str() {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        "YEARLY" => Frequency::Yearly,
+                        other => return Err(format!("unsupported FREQ={}", other)),
+                    })
+                }
+                "INTERVAL" => {
+                    rule.interval = val
+                        .parse()
+                        .ok()
+                        .filter(|&i| i > 0 && i <= MAX_RECURRENCE_INTERVAL)
+                        .ok_or("invalid INTERVAL")?
+                }
+                "COUNT" => rule.count = Some(val.parse().map_err(|_| "invalid COUNT")?),
+                "UNTIL" => rule.until = Some(IcsTime::parse(val, None, None).ok_or("invalid UNTIL")?),
+                "BYDAY" => {
+                    rule.by_day = val
+                        .split(',')
+                        .map(|day| parse_weekday(day).ok_or_else(|| format!("unsupported BYDAY={}", day)))
+                        .collect::<Result<_, _>>()?
+                }
+                "WKST" => {}
+                other => return Err(format!("unsupported RRULE part {}", other)),
+            }
+        }
+        rule.frequency = frequency.ok_or("RRULE without FREQ")?;
+        if !rule.by_day.is_empty() && rule.frequency != Frequency::Weekly {
+            return Err("BYDAY is only supported for weekly rules".to_string());
+        }
+        Ok(rule)
+    }
+
+    /// Start times (UTC) of the occurrences starting in [from, to)
+    fn expand(&self, start: IcsTime, from: DateTime<Utc>, to: DateTime<Utc>) -> Vec<DateTime<Utc>> {
+        let until = self.until.map(|u| {
+            if u.date_only {
+                u.local.and_utc().checked_add_signed(Duration::days(1)).unwrap_or(DateTime::<Utc>::MAX_UTC)
+            } else {
+                u.to_utc()
+            }
+        });
+        let mut starts = Vec::new();
+        let mut emitted = 0;
+
+        // COUNT has to be counted from DTSTART; otherwise jump to just before the window.
+        // A day's slack covers the zone offset between the local and UTC wall clock
+        let first_step = match self.count {
+            Some(_) => 0,
+            None => self.periods_between(start.local, from.checked_sub_signed(Duration::days(1)).unwrap_or(from).naive_utc()),
+        };
+        for step in first_step..first_step.saturating_add(MAX_RECURRENCE_STEPS) {
+            // Past the end of the representable calendar
+            let Some(mut candidates) = self.period_candidates(start.local, step) else {
+                break;
+            };
+            candidates.sort();
+            let mut past_end = false;
+            for local in candidates.into_iter().filter(|&c| c >= start.local) {
+                let occurrence = start.with_local(local).to_utc();
+                if self.count.is_some_and(|count| emitted >= count)
+                    || until.is_some_and(|until| occurrence > until)
+                    || occurrence >= to
+                {
+                    past_end = true;
+                    break;
+                }
+                emitted += 1;
+                if occurrence >= from {
+                    starts.push(occurrence);
+                }
+            }
+            if past_end {
+                break;
+            }
+        }
+        starts
+    }
+
+    /// Whole steps from the period containing `first` to at most the one containing `from`
+    fn periods_between(&self, first: NaiveDateTime, from: NaiveDateTime) -> u32 {
+        let elapsed = match self.frequency {
+            Frequency::Daily => (from.date() - first.date()).num_days(),
+            // Rounded down, so BYDAY's Monday-based weeks are never overshot
+            Frequency::Weekly => (from.date() - first.date()).num_days().div_euclid(7),
+            Frequency::Monthly => (from.year() - first.year()) as i64 * 12 + from.month0() as i64 - first.month0() as i64,
+            Frequency::Yearly => (from.year() - first.year()) as i64,
+        };
+        // One period early, so nothing that overlaps the window's start is missed
+        (elapsed / self.interval as i64 - 1).clamp(0, u32::MAX as i64) as u32
+    }
+
+    /// Wall-clock starts in the `step`-th period after the one containing `first`
+    /// None once the period lies beyond what chrono can represent
+    fn period_candidates(&self, first: NaiveDateTime, step: u32) -> Option<Vec<NaiveDateTime>> {
+        let n = step.checked_mul(self.interval)?;
+        let time = first.time();
+        let candidates = match self.frequency {
+            Frequency::Daily => vec![first.checked_add_signed(Duration::try_days(n as i64)?)?],
+            Frequency::Weekly if self.by_day.is_empty() => vec![first.checked_add_signed(Duration::try_weeks(n as i64)?)?],
+            Frequency::Weekly => {
+                let monday = first
+                    .date()
+                    .checked_sub_signed(Duration::days(first.weekday().num_days_from_monday() as i64))?
+                    .checked_add_signed(Duration::try_weeks(n as i64)?)?;
+                self.by_day
+                    .iter()
+                    .filter_map(|day| monday.checked_add_signed(Duration::days(day.num_days_from_monday() as i64)))
+                    .map(|d| d.and_time(time))
+                    .collect()
+            }
+            Frequency::Monthly => {
+                let months = first.month0().checked_add(n)?;
+                let year = first.year().checked_add(i32::try_from(months / 12).ok()?)?;
+                if year > NaiveDate::MAX.year() {
+                    return None;
+                }
+                // Months without that day (the 31st, say) are skipped, not clamped
+                NaiveDate::from_ymd_opt(year, months % 12 + 1, first.day())
+                    .map(|d| vec![d.and_time(time)])
+                    .unwrap_or_default()
+            }
+            Frequency::Yearly => {
+                let year = first.year().checked_add(i32::try_from(n).ok()?)?;
+                if year > NaiveDate::MAX.year() {
+                    return None;
+                }
+                NaiveDate::from_ymd_opt(year, first.month(), first.day())
+                    .map(|d| vec![d.and_time(time)])
+                    .unwrap_or_default()
+            }
+        };
+        Some(candidates)
+    }
+}
+
+fn parse_weekday(day: &str) -> Option<Weekday> {
+    Some(match day.trim().to_ascii_uppercase().as_str() {
+        "MO" => Weekday::Mon,
+        "TU" => Weekday::Tue,
+        "WE" => Weekday::Wed,
+        "TH" => Weekday::Thu,
+        "FR" => Weekday::Fri,
+        "SA" => Weekday::Sat,
+        "SU" => Weekday::Sun,
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_support::utc;
+
+    const CALENDAR: &str = "BEGIN:VCALENDAR\r\n\
+VERSION:2.0\r\n\
+X-WR-CALNAME:Team\r\n\
+X-APPLE-CALENDAR-COLOR:#FF2968FF\r\n\
+BEGIN:VEVENT\r\n\
+UID:review@example.com\r\n\
+DTSTART:20240614T130000Z\r\n\
+DTEND:20240614T140000Z\r\n\
+SUMMARY:Design review\\, round 2\r\n\
+DESCRIPTION:Agenda:\\nslides\r\n\
+\x20 and demo\r\n\
+ORGANIZER;CN=\"Doe, Jane\":mailto:Jane@Example.com\r\n\
+BEGIN:VALARM\r\n\
+TRIGGER:-PT15M\r\n\
+DESCRIPTION:Reminder\r\n\
+END:VALARM\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+UID:standup@example.com\r\n\
+DTSTART;TZID=Europe/Berlin:20240610T093000\r\n\
+DURATION:PT15M\r\n\
+RRULE:FREQ=WEEKLY;BYDAY=MO,WE,FR;COUNT=6\r\n\
+EXDATE;TZID=Europe/Berlin:20240612T093000\r\n\
+SUMMARY:Standup\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+UID:standup@example.com\r\n\
+RECURRENCE-ID;TZID=Europe/Berlin:20240614T093000\r\n\
+DTSTART;TZID=Europe/Berlin:20240614T110000\r\n\
+DTEND;TZID=Europe/Berlin:20240614T111500\r\n\
+SUMMARY:Standup (moved)\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+UID:offsite@example.com\r\n\
+DTSTART;VALUE=DATE:20240617\r\n\
+SUMMARY:Offsite\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+UID:cancelled@example.com\r\n\
+DTSTART:20240614T150000Z\r\n\
+DTEND:20240614T160000Z\r\n\
+STATUS:CANCELLED\r\n\
+END:VEVENT\r\n\
+END:VCALENDAR\r\n";
+
+    #[test]
+    fn test_parse_expands_recurrences_and_applies_overrides() {
+        let events = parse_events(CALENDAR, utc("2024-06-10T00:00:00Z"), utc("2024-06-18T00:00:00Z")).unwrap();
+        let summary: Vec<(String, DateTime<Utc>)> = events
+            .iter()
+            .map(|e| (e.summary.clone().unwrap(), e.start))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("Standup".to_string(), utc("2024-06-10T07:30:00Z")),
+                // Wednesday is excluded, Friday's occurrence was moved to 11:00
+                ("Standup (moved)".to_string(), utc("2024-06-14T09:00:00Z")),
+                ("Design review, round 2".to_string(), utc("2024-06-14T13:00:00Z")),
+                ("Offsite".to_string(), utc("2024-06-17T00:00:00Z")),
+                ("Standup".to_string(), utc("2024-06-17T07:30:00Z")),
+            ]
+        );
+
+        let review = events.iter().find(|e| e.uid == "review@example.com").unwrap();
+        assert_eq!(review.description.as_deref(), Some("Agenda:\nslides and demo"));
+        assert_eq!(review.organizer.as_deref(), Some("jane@example.com"));
+        assert!(!review.recurring);
+
+        let standup = &events[0];
+        assert_eq!(standup.end - standup.start, Duration::minutes(15));
+        let offsite = events.iter().find(|e| e.uid == "offsite@example.com").unwrap();
+        assert!(offsite.all_day);
+        assert_eq!(offsite.end, utc("2024-06-18T00:00:00Z"));
+
+        let calendar = CalendarInfo::fallback_primary(crate::services::calendar_service::CalendarProvider::IcsUrl);
+        let ids: Vec<String> = events.iter().map(|e| e.to_calendar_event("ics", &calendar, false).id).collect();
+        let series = |uid: &str| series_id("ics", &calendar, uid);
+        assert!(ids.contains(&format!("{}_20240610T073000Z", series("standup@example.com"))));
+        assert!(ids.contains(&series("review@example.com")));
+        assert_eq!(events[0].to_calendar_event("ics", &calendar, false).recurrence_id, Some(series("standup@example.com")));
+
+        assert_eq!(calendar_properties(CALENDAR), (Some("Team".to_string()), Some("#FF2968".to_string())));
+        assert!(parse_events("<html></html>", utc("2024-06-10T00:00:00Z"), utc("2024-06-18T00:00:00Z")).is_err());
+    }
+
+    #[test]
+    fn test_event_ids_dont_collide() {
+        let no_uids = "BEGIN:VCALENDAR\r\n\
+BEGIN:VEVENT\r\n\
+DTSTART:20240614T090000Z\r\n\
+SUMMARY:Dentist\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+DTSTART:20240614T090000Z\r\n\
+SUMMARY:Dentist\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+DTSTART:20240615T090000Z\r\n\
+SUMMARY:Haircut\r\n\
+END:VEVENT\r\n\
+END:VCALENDAR\r\n";
+        let events = parse_events(no_uids, utc("2024-06-10T00:00:00Z"), utc("2024-06-18T00:00:00Z")).unwrap();
+        let calendar = CalendarInfo::fallback_primary(crate::services::calendar_service::CalendarProvider::IcsUrl);
+        let ids: HashSet<String> = events.iter().map(|e| e.to_calendar_event("ics", &calendar, false).id).collect();
+        assert_eq!(ids.len(), 3);
+        // Stable across fetches, so the cache and merges see the same event
+        let again = parse_events(no_uids, utc("2024-06-10T00:00:00Z"), utc("2024-06-18T00:00:00Z")).unwrap();
+        assert_eq!(events.iter().map(|e| &e.uid).collect::<Vec<_>>(), again.iter().map(|e| &e.uid).collect::<Vec<_>>());
+
+        // The same invite in two subscribed calendars
+        let work = CalendarInfo { id: "https://example.com/work.ics".to_string(), ..calendar.clone() };
+        let team = CalendarInfo { id: "https://example.com/team.ics".to_string(), ..calendar };
+        assert_ne!(events[0].to_calendar_event("ics", &work, false).id, events[0].to_calendar_event("ics", &team, false).id);
+    }
+
+    #[test]
+    fn test_old_series_still_reach_this_week() {
+        // Started long enough ago that walking from DTSTART would run out of steps
+        let standup = "BEGIN:VCALENDAR\r\n\
+BEGIN:VEVENT\r\n\
+UID:standup@example.com\r\n\
+DTSTART;TZID=Europe/Berlin:19900102T093000\r\n\
+DURATION:PT15M\r\n\
+RRULE:FREQ=DAILY\r\n\
+SUMMARY:Standup\r\n\
+END:VEVENT\r\n\
+END:VCALENDAR\r\n";
+        let events = parse_events(standup, utc("2024-06-10T00:00:00Z"), utc("2024-06-17T00:00:00Z")).unwrap();
+        let starts: Vec<DateTime<Utc>> = events.iter().map(|e| e.start).collect();
+        let this_week: Vec<DateTime<Utc>> = (10..17).map(|day| utc(&format!("2024-06-{}T07:30:00Z", day))).collect();
+        assert_eq!(starts, this_week);
+
+        let weekly = RecurrenceRule::parse("FREQ=WEEKLY;INTERVAL=2;BYDAY=TU,TH").unwrap();
+        let start = IcsTime::parse("19900102T093000", None, Some("Europe/Berlin")).unwrap();
+        assert_eq!(
+            weekly.expand(start, utc("2024-06-10T00:00:00Z"), utc("2024-06-24T00:00:00Z")).len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_huge_intervals_dont_overflow() {
+        let feed = "BEGIN:VCALENDAR\r\n\
+BEGIN:VEVENT\r\n\
+UID:forever@example.com\r\n\
+DTSTART:20240610T080000Z\r\n\
+DURATION:PT1H\r\n\
+RRULE:FREQ=DAILY;INTERVAL=100000000\r\n\
+SUMMARY:Forever\r\n\
+END:VEVENT\r\n\
+END:VCALENDAR\r\n";
+        // The rule is rejected, so only the first occurrence shows
+        let events = parse_events(feed, utc("2024-06-01T00:00:00Z"), utc("2024-07-01T00:00:00Z")).unwrap();
+        assert_eq!(events.len(), 1);
+        assert!(RecurrenceRule::parse("FREQ=DAILY;INTERVAL=100000000").is_err());
+
+        // Even the largest accepted interval stops at the end of the calendar instead of panicking
+        let start = IcsTime::parse("20240610T080000Z", None, None).unwrap();
+        for frequency in ["DAILY", "WEEKLY", "WEEKLY;BYDAY=MO,FR", "MONTHLY", "YEARLY"] {
+            let rule = RecurrenceRule::parse(&format!("FREQ={};INTERVAL={}", frequency, MAX_RECURRENCE_INTERVAL)).unwrap();
+            let starts = rule.expand(start, utc("2024-06-01T00:00:00Z"), DateTime::<Utc>::MAX_UTC);
+            assert!(!starts.is_empty() && starts.len() <= MAX_RECURRENCE_STEPS as usize * 2);
+        }
+    }
+
+    #[test]
+    fn test_recurrence_rules_keep_wall_clock_time() {
+        let rule = RecurrenceRule::parse("FREQ=MONTHLY;INTERVAL=1;UNTIL=20240731T235959Z").unwrap();
+        let start = IcsTime::parse("20240131T090000", None, Some("America/New_York")).unwrap();
+        // February through June have no 31st; March's occurrence is after the DST switch
+        assert_eq!(
+            rule.expand(start, utc("2024-01-01T00:00:00Z"), utc("2025-01-01T00:00:00Z")),
+            vec![utc("2024-01-31T14:00:00Z"), utc("2024-03-31T13:00:00Z"), utc("2024-05-31T13:00:00Z"), utc("2024-07-31T13:00:00Z")]
+        );
+
+        let daily = RecurrenceRule::parse("FREQ=DAILY;INTERVAL=2;COUNT=3").unwrap();
+        let start = IcsTime::parse("20240610T080000Z", None, None).unwrap();
+        assert_eq!(
+            daily.expand(start, utc("2024-06-01T00:00:00Z"), utc("2025-01-01T00:00:00Z")),
+            vec![utc("2024-06-10T08:00:00Z"), utc("2024-06-12T08:00:00Z"), utc("2024-06-14T08:00:00Z")]
+        );
+        // COUNT still counts from DTSTART when the window starts later
+        assert_eq!(
+            daily.expand(start, utc("2024-06-11T00:00:00Z"), utc("2025-01-01T00:00:00Z")),
+            vec![utc("2024-06-12T08:00:00Z"), utc("2024-06-14T08:00:00Z")]
+        );
+
+        // Rules we can't expand still show their first occurrence
+        let second_tuesday = "BEGIN:VCALENDAR\r\n\
+BEGIN:VEVENT\r\n\
+UID:board@example.com\r\n\
+DTSTART:20240611T150000Z\r\n\
+DTEND:20240611T160000Z\r\n\
+RRULE:FREQ=MONTHLY;BYDAY=2TU\r\n\
+SUMMARY:Board meeting\r\n\
+END:VEVENT\r\n\
+END:VCALENDAR\r\n";
+        let events = parse_events(second_tuesday, utc("2024-06-01T00:00:00Z"), utc("2024-09-01T00:00:00Z")).unwrap();
+        assert_eq!(events.iter().map(|e| e.start).collect::<Vec<_>>(), vec![utc("2024-06-11T15:00:00Z")]);
+        assert!(events[0].recurring);
+        assert_eq!(parse_duration("P1DT2H"), Some(Duration::hours(26)));
+        assert_eq!(parse_duration("-PT15M"), Some(Duration::minutes(-15)));
+    }
+}
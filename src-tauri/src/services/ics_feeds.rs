@@ -0,0 +1,184 @@
+// ICS subscriptions
+// Read-only .ics feeds (holiday calendars, a colleague's published calendar, a secret Google or
+// Outlook address) polled alongside the signed-in providers. The list lives in ics_feeds.json.
+
+use chrono::{DateTime, Utc};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use crate::models::action::CalendarEvent;
+use crate::services::calendar_selection::{self, CalendarInfo, CalendarSelection};
+use crate::services::calendar_service::CalendarProvider;
+use crate::services::ics;
+use crate::utils::app_data;
+
+const ICS_FEEDS_FILE: &str = "ics_feeds.json";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IcsFeed {
+    pub name: String,
+    pub url: String, // http(s); webcal:// links are stored as https://
+    #[serde(default)]
+    pub color: Option<String>,
+}
+
+impl IcsFeed {
+    /// Feeds show up as calendars of the IcsUrl provider, keyed by URL
+    pub fn calendar_info(&self) -> CalendarInfo {
+        CalendarInfo {
+            provider: CalendarProvider::IcsUrl,
+            id: self.url.clone(),
+            name: self.name.clone(),
+            color: self.color.clone(),
+            primary: false,
+            selected: false,
+        }
+    }
+
+    /// Download the feed's raw .ics text
+    pub async fn download(&self, http_client: &Client) -> Result<String, String> {
+        let response = http_client
+            .get(&self.url)
+            .header("Accept", "text/calendar")
+            .send()
+            .await
+            .map_err(|e| format!("Failed to fetch {}: {}", self.url, e))?;
+        if !response.status().is_success() {
+            return Err(format!("{} returned {}", self.url, response.status()));
+        }
+        response.text().await.map_err(|e| format!("Failed to read {}: {}", self.url, e))
+    }
+
+    /// Occurrences overlapping `from..to`
+    pub async fn fetch_events(&self, http_client: &Client, from: DateTime<Utc>, to: DateTime<Utc>) -> Result<Vec<CalendarEvent>, String> {
+        let ics = self.download(http_client).await?;
+        let calendar = self.calendar_info();
+        // Subscriptions are read-only, so nothing in them can be rescheduled
+        Ok(ics::parse_events(&ics, from, to)?
+            .iter()
+            .map(|e| e.to_calendar_event("ics", &calendar, false))
+            .collect())
+    }
+}
+
+/// Turn a pasted link into the URL we fetch
+pub fn normalize_url(url: &str) -> Result<String, String> {
+    let url = url.trim();
+    let url = match url.strip_prefix("webcal://") {
+        Some(rest) => format!("https://{}", rest),
+        None => url.to_string(),
+    };
+    if !url.starts_with("https://") && !url.starts_with("http://") {
+        return Err(format!("Calendar feed URL must start with http://, https:// or webcal://: {}", url));
+    }
+    Ok(url)
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IcsFeedsConfig {
+    pub feeds: Vec<IcsFeed>,
+}
+
+impl IcsFeedsConfig {
+    pub fn load(data_dir: &Path) -> Result<Self, String> {
+        Ok(app_data::read_json(&config_path(data_dir))?.unwrap_or_default())
+    }
+
+    pub fn save(&self, data_dir: &Path) -> Result<(), String> {
+        app_data::write_json(&config_path(data_dir), self)
+    }
+
+    /// Add a feed, or replace the one with the same URL
+    pub fn upsert(&mut self, feed: IcsFeed) {
+        match self.feeds.iter_mut().find(|f| f.url == feed.url) {
+            Some(existing) => *existing = feed,
+            None => self.feeds.push(feed),
+        }
+    }
+
+    /// Returns whether a feed with that URL was subscribed
+    pub fn remove(&mut self, url: &str) -> bool {
+        let before = self.feeds.len();
+        self.feeds.retain(|f| f.url != url);
+        self.feeds.len() != before
+    }
+}
+
+fn config_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(ICS_FEEDS_FILE)
+}
+
+/// Events from every selected feed
+/// A feed that can't be fetched is skipped; it's only an error when none of them could be (e.g. offline)
+pub async fn fetch_selected_events(
+    http_client: &Client,
+    config: &IcsFeedsConfig,
+    selection: &CalendarSelection,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<Vec<CalendarEvent>, String> {
+    let mut events = Vec::new();
+    let mut fetched_any = false;
+    let mut last_error = None;
+    for feed in config.feeds.iter().filter(|f| selection.includes(&f.calendar_info())) {
+        match feed.fetch_events(http_client, from, to).await {
+            Ok(fetched) => {
+                events.extend(fetched);
+                fetched_any = true;
+            }
+            Err(e) => {
+                eprintln!("[Calendar] ✗ Failed to fetch calendar feed '{}': {}", feed.name, e);
+                last_error = Some(e);
+            }
+        }
+    }
+    match last_error {
+        Some(e) if !fetched_any => Err(e),
+        _ => Ok(calendar_selection::merge_events(events)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_support::utc;
+    use crate::utils::http_stub::HttpStub;
+
+    #[tokio::test]
+    async fn test_feeds_are_fetched_and_deselectable() {
+        let server = HttpStub::start(|req| match req.path.as_str() {
+            "/holidays.ics" => (
+                200,
+                "BEGIN:VCALENDAR\nBEGIN:VEVENT\nUID:juneteenth\nDTSTART;VALUE=DATE:20240619\nSUMMARY:Juneteenth\nEND:VEVENT\nEND:VCALENDAR\n".to_string(),
+            ),
+            _ => (404, String::new()),
+        })
+        .await;
+
+        let mut config = IcsFeedsConfig::default();
+        let holidays = IcsFeed { name: "Holidays".to_string(), url: format!("{}/holidays.ics", server.base_url), color: None };
+        config.upsert(holidays.clone());
+        config.upsert(IcsFeed { color: Some("#00aa00".to_string()), ..holidays.clone() });
+        config.upsert(IcsFeed { name: "Gone".to_string(), url: format!("{}/gone.ics", server.base_url), color: None });
+        assert_eq!(config.feeds.len(), 2);
+
+        let client = Client::new();
+        let (from, to) = (utc("2024-06-14T00:00:00Z"), utc("2024-06-21T00:00:00Z"));
+        // The missing feed is skipped, not fatal
+        let events = fetch_selected_events(&client, &config, &CalendarSelection::default(), from, to).await.unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].id, ics::series_id("ics", &holidays.calendar_info(), "juneteenth"));
+        assert!(events[0].all_day && !events[0].is_organizer);
+        assert_eq!(events[0].calendar_color.as_deref(), Some("#00aa00"));
+
+        let mut selection = CalendarSelection::default();
+        selection.set(CalendarProvider::IcsUrl, Vec::new());
+        assert!(fetch_selected_events(&client, &config, &selection, from, to).await.unwrap().is_empty());
+        // Only the broken feed left: that's an error, so the caller keeps what it had
+        assert!(config.remove(&holidays.url));
+        assert!(fetch_selected_events(&client, &config, &CalendarSelection::default(), from, to).await.is_err());
+
+        assert_eq!(normalize_url(" webcal://example.com/cal.ics ").unwrap(), "https://example.com/cal.ics");
+        assert!(normalize_url("ftp://example.com/cal.ics").is_err());
+    }
+}
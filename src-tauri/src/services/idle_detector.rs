@@ -0,0 +1,128 @@
+// Idle detection
+// Notices when the user walks away from the desk so focus timers stop counting.
+// FocusService polls an IdleSource, pauses the session once the threshold passes and
+// resumes it (recording the time away) when keyboard or mouse input comes back.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use crate::utils::app_data;
+
+const IDLE_CONFIG_FILE: &str = "idle_config.json";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IdleConfig {
+    pub enabled: bool,
+    pub threshold_minutes: u32, // No input for this long counts as away
+    pub auto_pause: bool,       // Pause the running session while away
+    pub auto_resume: bool,      // Resume it when the user is back (only if we paused it)
+}
+
+impl Default for IdleConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            threshold_minutes: 5,
+            auto_pause: true,
+            auto_resume: true,
+        }
+    }
+}
+
+impl IdleConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(1..=240).contains(&self.threshold_minutes) {
+            return Err("Idle threshold must be between 1 and 240 minutes".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn threshold_seconds(&self) -> u64 {
+        self.threshold_minutes as u64 * 60
+    }
+
+    pub fn load(data_dir: &Path) -> Result<Self, String> {
+        Ok(app_data::read_json(&config_path(data_dir))?.unwrap_or_default())
+    }
+
+    pub fn save(&self, data_dir: &Path) -> Result<(), String> {
+        app_data::write_json(&config_path(data_dir), self)
+    }
+}
+
+fn config_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(IDLE_CONFIG_FILE)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IdleTransition {
+    WentIdle { since: DateTime<Utc> },
+    CameBack { since: DateTime<Utc>, back_at: DateTime<Utc> },
+}
+
+/// Tracks whether the user is away between polls
+#[derive(Debug, Default)]
+pub struct IdleState {
+    idle_since: Option<DateTime<Utc>>,
+    pub paused_session: Option<String>, // Session we paused for this absence, to resume on return
+    pub source_failed: bool,            // The idle source errored last poll (so we only log once)
+}
+
+impl IdleState {
+    /// Feed the latest idle reading; returns a transition when the user leaves or comes back
+    /// Going idle is backdated to the last input, so the threshold itself counts as time away
+    pub fn update(&mut self, idle_seconds: u64, threshold_seconds: u64, now: DateTime<Utc>) -> Option<IdleTransition> {
+        let last_input = now - Duration::seconds(idle_seconds as i64);
+        match self.idle_since {
+            None if idle_seconds >= threshold_seconds => {
+                self.idle_since = Some(last_input);
+                Some(IdleTransition::WentIdle { since: last_input })
+            }
+            Some(since) if idle_seconds < threshold_seconds => {
+                self.idle_since = None;
+                Some(IdleTransition::CameBack { since, back_at: last_input.max(since) })
+            }
+            _ => None,
+        }
+    }
+}
+
+// Payload of the user-idle and user-active events
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdleEvent {
+    pub idle_since: DateTime<Utc>,
+    pub idle_seconds: u64,
+    pub session_id: Option<String>, // Focus session running at the time
+    pub session_paused: bool,       // user-idle: we paused it; user-active: it's still paused
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_support::utc;
+
+    #[test]
+    fn test_idle_is_backdated_to_last_input() {
+        let mut state = IdleState::default();
+        assert_eq!(state.update(120, 300, utc("2024-06-14T10:00:00Z")), None);
+
+        let went_idle = state.update(310, 300, utc("2024-06-14T10:05:00Z"));
+        assert_eq!(went_idle, Some(IdleTransition::WentIdle { since: utc("2024-06-14T09:59:50Z") }));
+        // Still away: nothing new to report
+        assert_eq!(state.update(900, 300, utc("2024-06-14T10:15:00Z")), None);
+
+        let came_back = state.update(4, 300, utc("2024-06-14T10:20:04Z"));
+        assert_eq!(
+            came_back,
+            Some(IdleTransition::CameBack { since: utc("2024-06-14T09:59:50Z"), back_at: utc("2024-06-14T10:20:00Z") })
+        );
+        assert_eq!(state.update(30, 300, utc("2024-06-14T10:20:30Z")), None);
+    }
+
+    #[test]
+    fn test_validate_threshold() {
+        assert!(IdleConfig::default().validate().is_ok());
+        assert!(IdleConfig { threshold_minutes: 0, ..IdleConfig::default() }.validate().is_err());
+        assert!(IdleConfig { threshold_minutes: 600, ..IdleConfig::default() }.validate().is_err());
+    }
+}
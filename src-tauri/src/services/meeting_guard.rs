@@ -0,0 +1,216 @@
+// Meeting guard
+// Decides when a focus session that runs into the next meeting should warn, shorten, or end
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use crate::models::action::{CalendarEvent, ResponseStatus};
+use crate::utils::app_data;
+
+const MEETING_GUARD_CONFIG_FILE: &str = "meeting_guard.json";
+
+// What to do with a session that overlaps a meeting
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MeetingGuardAction {
+    EndSession,     // Stop the session when the grace period before the meeting starts
+    ShortenSession, // Move the session's end time to the grace period when warning
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MeetingGuardConfig {
+    pub enabled: bool,
+    pub warn_minutes: u32,  // Warn this long before the meeting
+    pub grace_minutes: u32, // End focus this long before the meeting
+    pub action: MeetingGuardAction,
+}
+
+impl Default for MeetingGuardConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            warn_minutes: 5,
+            grace_minutes: 1,
+            action: MeetingGuardAction::EndSession,
+        }
+    }
+}
+
+impl MeetingGuardConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.warn_minutes > 60 {
+            return Err("Meeting warning must be at most 60 minutes before the meeting".to_string());
+        }
+        // Otherwise the session would end (or need shortening) before the user is warned
+        if self.warn_minutes < self.grace_minutes {
+            return Err("Meeting warning must come no later than the session ends for it".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn load(data_dir: &Path) -> Result<Self, String> {
+        Ok(app_data::read_json(&config_path(data_dir))?.unwrap_or_default())
+    }
+
+    pub fn save(&self, data_dir: &Path) -> Result<(), String> {
+        app_data::write_json(&config_path(data_dir), self)
+    }
+}
+
+fn config_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(MEETING_GUARD_CONFIG_FILE)
+}
+
+// What the guard wants done this tick
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GuardDecision {
+    pub warn: bool,
+    pub new_end: Option<DateTime<Utc>>, // Shorten the session to end here
+    pub end_now: bool,
+}
+
+// Payload of the focus-meeting-warning event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MeetingWarning {
+    pub event_id: String,
+    pub title: String,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub meeting_start: DateTime<Utc>,
+    pub minutes_until: i64,
+    pub action: MeetingGuardAction,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub session_ends_at: DateTime<Utc>, // When the session will end because of the meeting
+}
+
+/// The earliest timed meeting the user hasn't declined that runs into the session
+/// Meetings already underway (past the grace period) don't count, same as in `evaluate`
+pub fn next_conflict<'a>(
+    config: &MeetingGuardConfig,
+    session_end: DateTime<Utc>,
+    events: &'a [CalendarEvent],
+    now: DateTime<Utc>,
+) -> Option<&'a CalendarEvent> {
+    let grace = Duration::minutes(config.grace_minutes as i64);
+    events
+        .iter()
+        .filter(|event| !event.all_day && event.my_response != Some(ResponseStatus::Declined))
+        .filter(|event| event.start_time > now - grace && event.start_time - grace < session_end)
+        .min_by_key(|event| event.start_time)
+}
+
+/// Decide how to react to the next meeting
+/// `already_warned` is true once the warning for this meeting has been sent
+pub fn evaluate(
+    config: &MeetingGuardConfig,
+    session_end: DateTime<Utc>,
+    meeting: &CalendarEvent,
+    now: DateTime<Utc>,
+    already_warned: bool,
+) -> GuardDecision {
+    let cutoff = meeting.start_time - Duration::minutes(config.grace_minutes as i64);
+
+    // All-day events, meetings that start after the session (plus grace) or are already underway aren't a conflict
+    if !config.enabled || meeting.all_day || cutoff >= session_end || meeting.start_time <= now - Duration::minutes(config.grace_minutes as i64) {
+        return GuardDecision::default();
+    }
+
+    let in_warning_window = now >= meeting.start_time - Duration::minutes(config.warn_minutes as i64);
+
+    let mut decision = GuardDecision {
+        warn: in_warning_window && !already_warned,
+        ..GuardDecision::default()
+    };
+
+    match config.action {
+        MeetingGuardAction::EndSession => decision.end_now = now >= cutoff,
+        MeetingGuardAction::ShortenSession => {
+            if in_warning_window {
+                decision.new_end = Some(cutoff.max(now));
+            }
+        }
+    }
+
+    decision
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_support::{event, utc};
+
+    // A 15 minute standup
+    fn meeting(start: &str) -> CalendarEvent {
+        CalendarEvent {
+            title: "Standup".to_string(),
+            end_time: utc(start) + Duration::minutes(15),
+            ..event("google_abc", start, start)
+        }
+    }
+
+    #[test]
+    fn test_meeting_after_session_is_ignored() {
+        let config = MeetingGuardConfig::default();
+        assert_eq!(evaluate(&config, utc("10:00"), &meeting("10:01"), utc("09:58"), false), GuardDecision::default());
+    }
+
+    #[test]
+    fn test_end_session_warns_then_ends_at_grace() {
+        let config = MeetingGuardConfig::default();
+        let standup = meeting("10:00");
+
+        let early = evaluate(&config, utc("10:30"), &standup, utc("09:50"), false);
+        assert_eq!(early, GuardDecision::default());
+
+        let warning = evaluate(&config, utc("10:30"), &standup, utc("09:55"), false);
+        assert!(warning.warn);
+        assert!(!warning.end_now);
+
+        let warned = evaluate(&config, utc("10:30"), &standup, utc("09:57"), true);
+        assert_eq!(warned, GuardDecision::default());
+
+        let end = evaluate(&config, utc("10:30"), &standup, utc("09:59"), true);
+        assert!(end.end_now);
+        assert!(!end.warn);
+    }
+
+    #[test]
+    fn test_shorten_session_moves_end_to_grace() {
+        let config = MeetingGuardConfig {
+            action: MeetingGuardAction::ShortenSession,
+            grace_minutes: 2,
+            ..MeetingGuardConfig::default()
+        };
+        let decision = evaluate(&config, utc("11:00"), &meeting("10:00"), utc("09:56"), false);
+        assert!(decision.warn);
+        assert_eq!(decision.new_end, Some(utc("09:58")));
+        assert!(!decision.end_now);
+    }
+
+    #[test]
+    fn test_next_conflict_skips_declined_and_all_day_events() {
+        let config = MeetingGuardConfig::default();
+        let holiday = CalendarEvent { id: "google_holiday".to_string(), all_day: true, ..meeting("00:00") };
+        let declined = CalendarEvent { id: "google_declined".to_string(), my_response: Some(ResponseStatus::Declined), ..meeting("10:00") };
+        let review = CalendarEvent { id: "google_review".to_string(), ..meeting("10:15") };
+        let later = CalendarEvent { id: "google_later".to_string(), ..meeting("12:00") };
+        let events = vec![later, review, declined, holiday];
+
+        let conflict = next_conflict(&config, utc("11:00"), &events, utc("09:50")).unwrap();
+        assert_eq!(conflict.id, "google_review");
+        assert!(next_conflict(&config, utc("10:10"), &events, utc("09:50")).is_none());
+    }
+
+    #[test]
+    fn test_validate_requires_warning_before_grace() {
+        assert!(MeetingGuardConfig::default().validate().is_ok());
+        assert!(MeetingGuardConfig { warn_minutes: 0, grace_minutes: 1, ..MeetingGuardConfig::default() }.validate().is_err());
+        assert!(MeetingGuardConfig { warn_minutes: 2, grace_minutes: 2, ..MeetingGuardConfig::default() }.validate().is_ok());
+        assert!(MeetingGuardConfig { warn_minutes: 600, ..MeetingGuardConfig::default() }.validate().is_err());
+    }
+
+    #[test]
+    fn test_disabled_guard_does_nothing() {
+        let config = MeetingGuardConfig { enabled: false, ..MeetingGuardConfig::default() };
+        assert_eq!(evaluate(&config, utc("11:00"), &meeting("10:00"), utc("09:59"), false), GuardDecision::default());
+    }
+}
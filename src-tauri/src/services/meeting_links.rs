@@ -0,0 +1,362 @@
+// Meeting join links
+// Finds how to join an event: the conference link the provider attached, then Zoom, Teams, Meet,
+// Webex and similar links buried in the location or the (often HTML) description, unwrapping
+// Outlook Safe Links and Google redirects. Dial-in numbers are picked up with their PIN so the
+// overlay can offer "join by phone" too.
+
+use serde::{Deserialize, Serialize};
+use url::Url;
+use crate::models::action::CalendarEvent;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MeetingProvider {
+    Zoom,
+    Teams,
+    GoogleMeet,
+    Webex,
+    GoToMeeting,
+    Jitsi,
+    Chime,
+}
+
+// Where a link was found, most trustworthy first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkSource {
+    ConferenceData,
+    Location,
+    Description,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JoinLink {
+    pub provider: MeetingProvider,
+    pub url: String,
+    pub source: LinkSource,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DialIn {
+    pub number: String, // E.164, e.g. "+16465550100"
+    #[serde(default)]
+    pub pin: Option<String>, // Meeting or conference id to key in after dialing
+    pub uri: String,         // tel: URI that dials the number and then the PIN
+}
+
+// What get_join_info returns; `url` and `title` go straight to open_meeting_window or launch_url
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JoinInfo {
+    pub event_id: String,
+    pub title: String,
+    pub url: Option<String>,
+    pub provider: Option<MeetingProvider>,
+    pub links: Vec<JoinLink>, // Best first
+    pub dial_ins: Vec<DialIn>,
+}
+
+// Labels that precede the code to key in after dialing, in order of preference
+// (Zoom's "Passcode" is for the web client, so its "Meeting ID" comes first)
+const PIN_LABELS: [&str; 6] = ["conference id", "pin", "access code", "meeting id", "meeting number", "passcode"];
+
+/// Everything we can find on how to join `event`
+pub fn join_info(event: &CalendarEvent) -> JoinInfo {
+    let sources = [
+        (LinkSource::ConferenceData, event.conference_url.as_deref()),
+        (LinkSource::Location, event.location.as_deref()),
+        (LinkSource::Description, event.description.as_deref()),
+    ];
+
+    let mut links: Vec<JoinLink> = Vec::new();
+    for (source, text) in sources {
+        for (provider, url) in find_urls(text.unwrap_or_default()).iter().filter_map(|url| classify(url)) {
+            if !links.iter().any(|l| l.url == url) {
+                links.push(JoinLink { provider, url, source });
+            }
+        }
+    }
+    // Sources are already in order; within one, a Zoom link with its passcode saves typing it
+    links.sort_by_key(|l| (l.source, !l.url.contains("pwd=")));
+
+    let mut dial_ins: Vec<DialIn> = Vec::new();
+    for text in [event.location.as_deref(), event.description.as_deref()].into_iter().flatten() {
+        for dial_in in find_dial_ins(text) {
+            if !dial_ins.iter().any(|d| d.number == dial_in.number) {
+                dial_ins.push(dial_in);
+            }
+        }
+    }
+
+    JoinInfo {
+        event_id: event.id.clone(),
+        title: event.title.clone(),
+        url: links.first().map(|l| l.url.clone()),
+        provider: links.first().map(|l| l.provider),
+        links,
+        dial_ins,
+    }
+}
+
+/// http(s) URLs in free text or HTML, in order of appearance
+fn find_urls(text: &str) -> Vec<String> {
+    let text = decode_entities(text);
+    let lower = text.to_ascii_lowercase();
+    let mut urls = Vec::new();
+    let mut from = 0;
+    while let Some(start) = ["https://", "http://"].iter().filter_map(|scheme| lower[from..].find(scheme)).min() {
+        let candidate = &text[from + start..];
+        let end = candidate
+            .find(|c: char| c.is_whitespace() || matches!(c, '"' | '\'' | '<' | '>' | '(' | ')' | '[' | ']' | '{' | '}' | '|' | '\\'))
+            .unwrap_or(candidate.len());
+        // Sentence punctuation after a pasted link isn't part of it
+        urls.push(candidate[..end].trim_end_matches(['.', ',', ';', ':', '!', '?']).to_string());
+        from += start + end;
+    }
+    urls
+}
+
+/// The provider and normalized form of a join link; None for anything else
+fn classify(raw: &str) -> Option<(MeetingProvider, String)> {
+    let mut url = Url::parse(raw).ok()?;
+    if let Some(inner) = unwrap_redirect(&url) {
+        url = inner;
+    }
+    url.set_fragment(None);
+    let host = url.host_str()?.to_ascii_lowercase();
+    let path = url.path().to_string();
+    let has_path = path.len() > 1;
+
+    let provider = if host_is(&host, "zoom.us") || host_is(&host, "zoomgov.com") {
+        if !["/j/", "/w/", "/s/", "/my/", "/wc/join/"].iter().any(|p| path.starts_with(p)) {
+            return None;
+        }
+        // Only the passcode matters; the rest is tracking
+        let pwd = url.query_pairs().find(|(k, _)| k == "pwd").map(|(_, v)| v.into_owned());
+        url.set_query(None);
+        if let Some(pwd) = pwd {
+            url.query_pairs_mut().append_pair("pwd", &pwd);
+        }
+        MeetingProvider::Zoom
+    } else if host == "teams.microsoft.com" || host == "teams.live.com" {
+        if !path.starts_with("/l/meetup-join/") && !path.starts_with("/meet/") {
+            return None;
+        }
+        MeetingProvider::Teams
+    } else if host == "meet.google.com" {
+        if !is_meet_code(&path[1..]) && !path.starts_with("/lookup/") {
+            return None;
+        }
+        url.set_query(None); // ?hs=122&authuser=0
+        MeetingProvider::GoogleMeet
+    } else if host_is(&host, "webex.com") {
+        if !(path.contains("j.php") || path.contains("/meet/") || path.contains("/join/") || path.contains("/wbxmjs/")) {
+            return None;
+        }
+        MeetingProvider::Webex
+    } else if (host_is(&host, "gotomeeting.com") && path.starts_with("/join/")) || (host == "meet.goto.com" && has_path) {
+        MeetingProvider::GoToMeeting
+    } else if host == "meet.jit.si" && has_path {
+        MeetingProvider::Jitsi
+    } else if host == "chime.aws" && has_path {
+        MeetingProvider::Chime
+    } else {
+        return None;
+    };
+    Some((provider, url.to_string()))
+}
+
+/// The real link inside an Outlook Safe Links or Google redirect URL
+fn unwrap_redirect(url: &Url) -> Option<Url> {
+    let host = url.host_str()?;
+    let param = if host.ends_with("safelinks.protection.outlook.com") {
+        "url"
+    } else if (host == "www.google.com" || host == "google.com") && url.path() == "/url" {
+        "q"
+    } else {
+        return None;
+    };
+    let target = url.query_pairs().find(|(k, _)| k == param)?.1;
+    Url::parse(&target).ok()
+}
+
+fn host_is(host: &str, domain: &str) -> bool {
+    host == domain || host.strip_suffix(domain).is_some_and(|sub| sub.ends_with('.'))
+}
+
+/// Meet codes look like abc-defg-hij
+fn is_meet_code(code: &str) -> bool {
+    let parts: Vec<&str> = code.split('-').collect();
+    parts.len() == 3
+        && parts.iter().zip([3, 4, 3]).all(|(part, len)| part.len() == len && part.chars().all(|c| c.is_ascii_lowercase()))
+}
+
+/// Dial-in numbers: one-tap tel: links first (they carry the PIN), then "+1 555 …" numbers in the
+/// text, which get the PIN from a "Conference ID:"-style label
+fn find_dial_ins(text: &str) -> Vec<DialIn> {
+    let text = decode_entities(text);
+    let lower = text.to_ascii_lowercase();
+    let mut dial_ins = Vec::new();
+
+    let mut from = 0;
+    while let Some(pos) = lower[from..].find("tel:") {
+        let start = from + pos + "tel:".len();
+        let end = text[start..]
+            .find(|c: char| c.is_whitespace() || matches!(c, '"' | '\'' | '<' | '>'))
+            .map_or(text.len(), |end| start + end);
+        let tel = &text[start..end];
+        // tel:+12065550123,,987654321# or tel:+1-414-555-0100;pin=123456789#
+        let split = tel.find([',', ';']).unwrap_or(tel.len());
+        let number = normalize_number(&tel[..split]);
+        let rest = &tel[split..];
+        let pin_from = rest.to_ascii_lowercase().find("pin=").map_or(0, |i| i + "pin=".len());
+        let pin: String = rest[pin_from..]
+            .chars()
+            .skip_while(|c| !c.is_ascii_digit())
+            .take_while(|c| c.is_ascii_digit())
+            .collect();
+        if let Some(number) = number {
+            dial_ins.push(dial_in(number, Some(pin).filter(|p| !p.is_empty())));
+        }
+        from = end;
+    }
+
+    let plain = plain_text(&text);
+    let pin = PIN_LABELS.iter().find_map(|label| digits_after_label(&plain, label));
+    let mut chars = plain.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        if c != '+' || !chars.peek().is_some_and(|(_, next)| next.is_ascii_digit()) {
+            continue;
+        }
+        let end = plain[i + 1..]
+            .find(|c: char| !(c.is_ascii_digit() || matches!(c, ' ' | '-' | '.' | '(' | ')')))
+            .map_or(plain.len(), |end| i + 1 + end);
+        if let Some(number) = normalize_number(&plain[i..end]) {
+            if !dial_ins.iter().any(|d: &DialIn| d.number == number) {
+                dial_ins.push(dial_in(number, pin.clone()));
+            }
+        }
+    }
+    dial_ins
+}
+
+fn dial_in(number: String, pin: Option<String>) -> DialIn {
+    let uri = match &pin {
+        Some(pin) => format!("tel:{},,{}#", number, pin),
+        None => format!("tel:{}", number),
+    };
+    DialIn { number, pin, uri }
+}
+
+/// "+1 (646) 558-8656" -> "+16465588656"; None unless it's a plausible international number
+fn normalize_number(raw: &str) -> Option<String> {
+    let digits: String = raw.chars().filter(|c| c.is_ascii_digit()).collect();
+    (raw.trim_start().starts_with('+') && (8..=15).contains(&digits.len())).then(|| format!("+{}", digits))
+}
+
+/// Digits (spaces and dashes allowed) after a whole-word label like "Conference ID: 987 654 321#"
+fn digits_after_label(text: &str, label: &str) -> Option<String> {
+    let lower = text.to_ascii_lowercase();
+    let mut from = 0;
+    while let Some(pos) = lower[from..].find(label) {
+        let start = from + pos;
+        let end = start + label.len();
+        from = end;
+        let whole_word = !lower[..start].ends_with(|c: char| c.is_ascii_alphabetic())
+            && !lower[end..].starts_with(|c: char| c.is_ascii_alphabetic());
+        if !whole_word {
+            continue;
+        }
+        let digits: String = lower[end..]
+            .trim_start_matches([':', ' ', '\t'])
+            .chars()
+            .take_while(|c| c.is_ascii_digit() || matches!(c, ' ' | '-'))
+            .filter(|c| c.is_ascii_digit())
+            .collect();
+        if digits.len() >= 4 {
+            return Some(digits);
+        }
+    }
+    None
+}
+
+/// HTML descriptions as text: tags dropped, line breaks kept
+fn plain_text(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut in_tag = false;
+    let mut tag = String::new();
+    for c in html.chars() {
+        match c {
+            '<' => {
+                in_tag = true;
+                tag.clear();
+            }
+            '>' if in_tag => {
+                in_tag = false;
+                let name = tag.trim_start_matches('/').split_whitespace().next().unwrap_or("").to_ascii_lowercase();
+                if matches!(name.as_str(), "br" | "br/" | "p" | "div" | "li" | "tr") {
+                    text.push('\n');
+                }
+            }
+            _ if in_tag => tag.push(c),
+            _ => text.push(c),
+        }
+    }
+    text
+}
+
+fn decode_entities(text: &str) -> String {
+    text.replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&#61;", "=")
+        .replace("&#x3D;", "=")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Deserialize)]
+    struct Case {
+        name: String,
+        event: CalendarEvent,
+        expect: Expected,
+    }
+
+    #[derive(Deserialize)]
+    struct Expected {
+        url: Option<String>,
+        provider: Option<MeetingProvider>,
+        links: Vec<String>,
+        dial_ins: Vec<(String, Option<String>)>, // Number and PIN
+    }
+
+    #[test]
+    fn test_fixture_corpus() {
+        let cases: Vec<Case> = serde_json::from_str(include_str!("../../tests/fixtures/meeting_links.json")).unwrap();
+        assert!(cases.len() >= 8);
+        for case in cases {
+            let info = join_info(&case.event);
+            assert_eq!(info.url, case.expect.url, "{}: best link", case.name);
+            assert_eq!(info.provider, case.expect.provider, "{}: provider", case.name);
+            assert_eq!(info.links.iter().map(|l| l.url.clone()).collect::<Vec<_>>(), case.expect.links, "{}: links", case.name);
+            assert_eq!(
+                info.dial_ins.iter().map(|d| (d.number.clone(), d.pin.clone())).collect::<Vec<_>>(),
+                case.expect.dial_ins,
+                "{}: dial-ins",
+                case.name
+            );
+            assert_eq!(info.title, case.event.title);
+        }
+    }
+
+    #[test]
+    fn test_dial_in_uri_keys_in_the_pin() {
+        assert_eq!(dial_in("+12065550123".to_string(), Some("987654321".to_string())).uri, "tel:+12065550123,,987654321#");
+        assert_eq!(normalize_number("+1 (646) 558-8656"), Some("+16465588656".to_string()));
+        assert_eq!(normalize_number("2024-06-14"), None);
+    }
+}
@@ -0,0 +1,266 @@
+// Meeting reminders
+// Fires meeting-reminder at configurable offsets before each upcoming event (10, 2 and 0 minutes
+// by default), and can bring up the overlay on the at-start alert. The scheduler sleeps until the
+// next alert and works out what's due from the calendar cache on disk each time it wakes, so it
+// never waits on the calendar service, and a sync that moves, adds or cancels a meeting only has
+// to save the cache and call rearm().
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::{OnceLock, RwLock};
+use tauri::{AppHandle, Emitter};
+use tokio::sync::Notify;
+use crate::models::action::{CalendarEvent, ResponseStatus};
+use crate::services::calendar_cache::CalendarCache;
+use crate::services::meeting_links;
+use crate::utils::app_data;
+
+const MEETING_REMINDERS_CONFIG_FILE: &str = "meeting_reminders.json";
+/// Longest the scheduler sleeps without looking again (wall-clock changes, sleep/resume)
+const MAX_SLEEP_SECONDS: i64 = 60;
+/// An alert this late for a meeting that has started is dropped rather than shown
+const LATE_GRACE_SECONDS: i64 = 60;
+
+static CONFIG: OnceLock<RwLock<MeetingReminderConfig>> = OnceLock::new();
+static REARM: OnceLock<Notify> = OnceLock::new();
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MeetingReminderConfig {
+    pub enabled: bool,
+    pub offsets_minutes: Vec<u32>,   // Alert this many minutes before each meeting; 0 is "starting now"
+    pub show_overlay_at_start: bool, // Bring up the overlay with the 0-minute alert
+}
+
+impl Default for MeetingReminderConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            offsets_minutes: vec![10, 2, 0],
+            show_overlay_at_start: true,
+        }
+    }
+}
+
+impl MeetingReminderConfig {
+    pub fn load(data_dir: &Path) -> Result<Self, String> {
+        Ok(app_data::read_json(&config_path(data_dir))?.unwrap_or_default())
+    }
+
+    pub fn save(&self, data_dir: &Path) -> Result<(), String> {
+        app_data::write_json(&config_path(data_dir), self)
+    }
+}
+
+fn config_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(MEETING_REMINDERS_CONFIG_FILE)
+}
+
+// Payload of the meeting-reminder event
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MeetingReminder {
+    pub event_id: String,
+    pub title: String,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub meeting_start: DateTime<Utc>,
+    pub minutes_before: u32,
+    pub starting: bool,           // The at-start alert, the most urgent one
+    pub join_url: Option<String>, // Best join link, for a "Join" button on the alert
+}
+
+// One alert for one meeting
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScheduledReminder {
+    pub fire_at: DateTime<Utc>,
+    pub minutes_before: u32,
+    pub event: CalendarEvent,
+}
+
+impl ScheduledReminder {
+    fn to_payload(&self) -> MeetingReminder {
+        MeetingReminder {
+            event_id: self.event.id.clone(),
+            title: self.event.title.clone(),
+            meeting_start: self.event.start_time,
+            minutes_before: self.minutes_before,
+            starting: self.minutes_before == 0,
+            join_url: meeting_links::join_info(&self.event).url,
+        }
+    }
+}
+
+/// Alerts that fire after `after`, soonest first
+/// All-day events and meetings the user declined get none
+pub fn schedule(config: &MeetingReminderConfig, events: &[CalendarEvent], after: DateTime<Utc>) -> Vec<ScheduledReminder> {
+    if !config.enabled {
+        return Vec::new();
+    }
+    let mut offsets = config.offsets_minutes.clone();
+    offsets.sort_unstable();
+    offsets.dedup();
+
+    let mut reminders: Vec<ScheduledReminder> = events
+        .iter()
+        .filter(|event| !event.all_day && event.my_response != Some(ResponseStatus::Declined))
+        .flat_map(|event| {
+            offsets.iter().map(move |&minutes_before| ScheduledReminder {
+                fire_at: event.start_time - Duration::minutes(minutes_before as i64),
+                minutes_before,
+                event: event.clone(),
+            })
+        })
+        .filter(|reminder| reminder.fire_at > after)
+        .collect();
+    reminders.sort_by(|a, b| (a.fire_at, &a.event.id).cmp(&(b.fire_at, &b.event.id)));
+    reminders
+}
+
+/// Alerts that came due in (since, now]
+/// If the machine slept through several for one meeting only the most urgent is kept, and
+/// nothing is sent for a meeting that started more than LATE_GRACE_SECONDS ago
+pub fn due(config: &MeetingReminderConfig, events: &[CalendarEvent], since: DateTime<Utc>, now: DateTime<Utc>) -> Vec<ScheduledReminder> {
+    let mut due: Vec<ScheduledReminder> = Vec::new();
+    for reminder in schedule(config, events, since) {
+        if reminder.fire_at > now {
+            break;
+        }
+        if now - reminder.event.start_time > Duration::seconds(LATE_GRACE_SECONDS) {
+            continue;
+        }
+        // Later alerts for a meeting are the more urgent ones
+        due.retain(|earlier| earlier.event.id != reminder.event.id);
+        due.push(reminder);
+    }
+    due
+}
+
+fn config_lock() -> &'static RwLock<MeetingReminderConfig> {
+    CONFIG.get_or_init(|| {
+        RwLock::new(MeetingReminderConfig::load(&app_data::app_data_dir_or_temp()).unwrap_or_else(|e| {
+            eprintln!("[Reminders] Failed to load config, using defaults: {}", e);
+            MeetingReminderConfig::default()
+        }))
+    })
+}
+
+fn rearm_signal() -> &'static Notify {
+    REARM.get_or_init(Notify::new)
+}
+
+pub fn config() -> MeetingReminderConfig {
+    config_lock().read().map(|config| config.clone()).unwrap_or_default()
+}
+
+pub fn set_config(config: MeetingReminderConfig) -> Result<(), String> {
+    config.save(&app_data::app_data_dir_or_temp())?;
+    *config_lock().write().map_err(|e| format!("Reminder config lock poisoned: {}", e))? = config;
+    rearm();
+    Ok(())
+}
+
+/// Wake the scheduler to recompute its next alert, e.g. after the calendar cache changed
+pub fn rearm() {
+    rearm_signal().notify_one();
+}
+
+/// Run the scheduler for the lifetime of the app
+pub async fn run(app: AppHandle) {
+    let data_dir = app_data::app_data_dir_or_temp();
+    let mut last_checked = Utc::now();
+    loop {
+        let config = config();
+        let now = Utc::now();
+        let events = match CalendarCache::load(&data_dir, now) {
+            Ok(cache) => cache.events,
+            Err(e) => {
+                eprintln!("[Reminders] Failed to read calendar cache: {}", e);
+                Vec::new()
+            }
+        };
+
+        for reminder in due(&config, &events, last_checked, now) {
+            println!("[Reminders] '{}' starts in {} minute(s)", reminder.event.title, reminder.minutes_before);
+            let payload = reminder.to_payload();
+            let _ = app.emit("meeting-reminder", payload.clone());
+            if payload.starting && config.show_overlay_at_start {
+                if let Err(e) = crate::commands::overlay::show_overlay(app.clone()).await {
+                    eprintln!("[Reminders] Failed to show overlay: {}", e);
+                }
+            }
+        }
+        last_checked = now;
+
+        let wait = schedule(&config, &events, now)
+            .first()
+            .map_or(Duration::seconds(MAX_SLEEP_SECONDS), |next| (next.fire_at - now).min(Duration::seconds(MAX_SLEEP_SECONDS)));
+        // Either the next alert is due or something changed; both mean looking again
+        let _ = tokio::time::timeout(wait.to_std().unwrap_or_default(), rearm_signal().notified()).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_support::{event, utc};
+
+    // A half-hour Zoom meeting
+    fn meeting(id: &str, start: &str) -> CalendarEvent {
+        CalendarEvent {
+            end_time: utc(start) + Duration::minutes(30),
+            location: Some("https://zoom.us/j/123456789".to_string()),
+            ..event(id, start, start)
+        }
+    }
+
+    fn fired(reminders: &[ScheduledReminder]) -> Vec<(&str, u32)> {
+        reminders.iter().map(|r| (r.event.id.as_str(), r.minutes_before)).collect()
+    }
+
+    #[test]
+    fn test_alerts_escalate_and_skip_declined_and_all_day_events() {
+        let config = MeetingReminderConfig::default();
+        let declined = CalendarEvent { my_response: Some(ResponseStatus::Declined), ..meeting("google_declined", "10:00:00") };
+        let all_day = CalendarEvent { all_day: true, ..meeting("google_holiday", "00:00:00") };
+        let events = vec![meeting("google_review", "10:05:00"), meeting("google_standup", "10:00:00"), declined, all_day];
+
+        let upcoming = schedule(&config, &events, utc("09:40:00"));
+        assert_eq!(
+            fired(&upcoming),
+            vec![("google_standup", 10), ("google_review", 10), ("google_standup", 2), ("google_standup", 0), ("google_review", 2), ("google_review", 0)]
+        );
+
+        let now = due(&config, &events, utc("09:59:30"), utc("10:00:00"));
+        assert_eq!(fired(&now), vec![("google_standup", 0)]);
+        let payload = now[0].to_payload();
+        assert!(payload.starting);
+        assert_eq!(payload.join_url.as_deref(), Some("https://zoom.us/j/123456789"));
+
+        assert!(schedule(&MeetingReminderConfig { enabled: false, ..config }, &events, utc("09:40:00")).is_empty());
+    }
+
+    #[test]
+    fn test_waking_late_sends_only_the_most_urgent_alert() {
+        let config = MeetingReminderConfig::default();
+        let events = vec![meeting("google_standup", "10:00:00"), meeting("google_earlier", "09:30:00")];
+
+        // Asleep from 09:45 to 09:59: the 10 and 2 minute alerts collapse into one, and the
+        // meeting that started half an hour ago stays quiet
+        assert_eq!(fired(&due(&config, &events, utc("09:25:00"), utc("09:59:00"))), vec![("google_standup", 2)]);
+        assert!(due(&config, &events, utc("09:25:00"), utc("09:31:30")).iter().all(|r| r.event.id != "google_earlier"));
+        assert_eq!(fired(&due(&config, &events, utc("09:25:00"), utc("09:30:30"))), vec![("google_earlier", 0)]);
+    }
+
+    #[test]
+    fn test_moved_meeting_is_rearmed() {
+        let config = MeetingReminderConfig::default();
+        let since = utc("09:50:00");
+        assert_eq!(fired(&due(&config, &[meeting("google_standup", "10:00:00")], utc("09:49:00"), since)), vec![("google_standup", 10)]);
+
+        // Pushed back by 15 minutes: its alerts are ahead again, none fire now
+        let moved = vec![meeting("google_standup", "10:15:00")];
+        assert!(due(&config, &moved, since, utc("09:58:00")).is_empty());
+        assert_eq!(schedule(&config, &moved, utc("09:58:00"))[0].fire_at, utc("10:05:00"));
+        // Cancelled: nothing left to fire
+        assert!(schedule(&config, &[], since).is_empty());
+    }
+}
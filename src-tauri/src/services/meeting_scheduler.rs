@@ -0,0 +1,158 @@
+// Meeting rescheduling
+// Classifies meetings that clash with a focus session and finds free slots to move them to
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use crate::models::action::CalendarEvent;
+use crate::services::focus_planner::{self, PlannerConfig};
+
+const SLOT_STEP_MINUTES: i64 = 15; // Suggested slots start on quarter hours
+const SEARCH_DAYS: i64 = 7;        // How far ahead to look for free slots
+const LEAD_IN_MINUTES: i64 = 15;   // Meetings starting this soon before focus still count as a conflict
+
+// How a meeting clashes with the focus session
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictKind {
+    StartsDuring,     // Starts while the session is running
+    Overlaps,         // Started before the session and runs into it
+    StartsJustBefore, // Ends before the session but leaves no breathing room
+}
+
+// A free slot the meeting could move to; rank 1 is the best fit
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SuggestedSlot {
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub start: DateTime<Utc>,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub end: DateTime<Utc>,
+    pub rank: u32,
+}
+
+/// How `event` clashes with a focus session, if at all
+pub fn conflict_kind(event: &CalendarEvent, focus_start: DateTime<Utc>, focus_end: DateTime<Utc>) -> Option<ConflictKind> {
+    if event.start_time >= focus_start && event.start_time < focus_end {
+        Some(ConflictKind::StartsDuring)
+    } else if event.start_time < focus_start && event.end_time > focus_start {
+        Some(ConflictKind::Overlaps)
+    } else if event.start_time < focus_start && event.start_time >= focus_start - Duration::minutes(LEAD_IN_MINUTES) {
+        Some(ConflictKind::StartsJustBefore)
+    } else {
+        None
+    }
+}
+
+/// Only timed events the user organizes can be moved
+pub fn can_reschedule(event: &CalendarEvent) -> bool {
+    !event.all_day && event.is_organizer
+}
+
+/// Round up to the next slot boundary
+fn round_up(time: DateTime<Utc>) -> DateTime<Utc> {
+    let step = SLOT_STEP_MINUTES * 60;
+    let rem = time.timestamp().rem_euclid(step);
+    if rem == 0 && time.timestamp_subsec_nanos() == 0 {
+        time
+    } else {
+        DateTime::from_timestamp(time.timestamp() - rem + step, 0).unwrap_or(time)
+    }
+}
+
+/// Up to `limit` conflict-free slots with the meeting's duration, inside working hours and after `not_before`
+/// The meeting itself doesn't count as busy; earlier slots rank higher
+pub fn find_slots<Tz: TimeZone>(
+    events: &[CalendarEvent],
+    meeting: &CalendarEvent,
+    not_before: DateTime<Utc>,
+    config: &PlannerConfig,
+    tz: &Tz,
+    limit: usize,
+) -> Vec<SuggestedSlot> {
+    let duration = meeting.end_time - meeting.start_time;
+    let others: Vec<CalendarEvent> = events.iter().filter(|e| e.id != meeting.id).cloned().collect();
+
+    let mut slots = Vec::new();
+    let first_day = not_before.with_timezone(tz).date_naive();
+
+    for offset in 0..SEARCH_DAYS {
+        let date = first_day + Duration::days(offset);
+        let (work_start, work_end) = match focus_planner::working_window(date, config, tz) {
+            Some(window) => window,
+            None => continue,
+        };
+        let window_start = work_start.max(not_before);
+        if window_start >= work_end {
+            continue;
+        }
+
+        // One slot per gap so the alternatives are spread out rather than back to back
+        for gap in focus_planner::find_free_gaps(&others, window_start, work_end) {
+            let start = round_up(gap.start);
+            if start + duration <= gap.end {
+                slots.push(SuggestedSlot {
+                    start,
+                    end: start + duration,
+                    rank: slots.len() as u32 + 1,
+                });
+                if slots.len() >= limit {
+                    return slots;
+                }
+            }
+        }
+    }
+
+    slots
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_support::{event, utc};
+
+    #[test]
+    fn test_conflict_kinds() {
+        let (start, end) = (utc("10:00"), utc("11:00"));
+        assert_eq!(conflict_kind(&event("a", "10:30", "11:30"), start, end), Some(ConflictKind::StartsDuring));
+        assert_eq!(conflict_kind(&event("b", "09:30", "10:15"), start, end), Some(ConflictKind::Overlaps));
+        assert_eq!(conflict_kind(&event("c", "09:50", "09:55"), start, end), Some(ConflictKind::StartsJustBefore));
+        assert_eq!(conflict_kind(&event("d", "11:00", "11:30"), start, end), None);
+        assert_eq!(conflict_kind(&event("e", "08:00", "09:00"), start, end), None);
+    }
+
+    #[test]
+    fn test_only_owned_timed_events_can_move() {
+        assert!(can_reschedule(&event("a", "10:00", "10:30")));
+        assert!(!can_reschedule(&CalendarEvent { is_organizer: false, ..event("a", "10:00", "10:30") }));
+        assert!(!can_reschedule(&CalendarEvent { all_day: true, ..event("a", "10:00", "10:30") }));
+    }
+
+    #[test]
+    fn test_slots_skip_busy_time_and_keep_duration() {
+        let meeting = event("sync", "10:30", "11:15");
+        let events = vec![
+            meeting.clone(),
+            event("review", "11:20", "12:00"),
+            event("lunch", "12:30", "13:00"),
+            CalendarEvent { all_day: true, ..event("holiday", "00:00", "23:59") },
+        ];
+
+        let slots = find_slots(&events, &meeting, utc("11:07"), &PlannerConfig::default(), &Utc, 2);
+
+        // 11:15-11:20 is too short; 12:00-12:30 can't fit 45 minutes; the all-day event doesn't block
+        assert_eq!(slots.len(), 2);
+        assert_eq!(slots[0], SuggestedSlot { start: utc("13:00"), end: utc("13:45"), rank: 1 });
+        // Next working day once today is exhausted
+        assert_eq!(slots[1].start, utc("09:00") + Duration::days(1));
+        assert_eq!(slots[1].rank, 2);
+    }
+
+    #[test]
+    fn test_slots_round_to_quarter_hours_and_respect_limit() {
+        let meeting = event("sync", "10:00", "10:30");
+        let events = vec![meeting.clone(), event("call", "14:00", "14:10")];
+        let slots = find_slots(&events, &meeting, utc("10:52"), &PlannerConfig::default(), &Utc, 2);
+        assert_eq!(slots.len(), 2);
+        assert_eq!(slots[0].start, utc("11:00"));
+        assert_eq!(slots[1].start, utc("14:15"));
+    }
+}
@@ -3,7 +3,28 @@ pub mod file_tracker;
 pub mod shortcut_manager;
 pub mod overlay_manager;
 pub mod calendar_service;
+pub mod calendar_cache;
+pub mod calendar_selection;
+pub mod calendar_sync;
+pub mod caldav;
+pub mod ics;
+pub mod ics_feeds;
+pub mod token_refresh;
+pub mod meeting_links;
 pub mod focus_service;
+pub mod focus_history;
+pub mod focus_presets;
+pub mod distraction_blocker;
+pub mod event_bus;
+pub mod focus_planner;
+pub mod idle_detector;
+pub mod meeting_guard;
+pub mod meeting_reminders;
+pub mod meeting_scheduler;
+pub mod notification_allowlist;
+pub mod notification_digest;
+pub mod pomodoro;
+pub mod quiet_hours;
 pub mod gmail_service;
 pub mod multi_monitor;
 pub mod oauth_server;
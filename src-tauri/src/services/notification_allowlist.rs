@@ -0,0 +1,130 @@
+// Notification allowlist ("priority contacts")
+// Apps that keep notifying while everything else is muted, e.g. an on-call pager.
+// Entries match process names, window titles and Windows notification app ids; `*` is a wildcard,
+// and an entry without one matches anywhere in the name ("pager" matches "PagerDuty.exe").
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+#[cfg(any(windows, test))]
+use crate::services::distraction_blocker::glob_match;
+use crate::utils::app_data;
+
+const NOTIFICATION_ALLOWLIST_FILE: &str = "notification_allowlist.json";
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NotificationAllowlist {
+    pub apps: Vec<String>,
+}
+
+impl NotificationAllowlist {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.apps.iter().any(|app| app.trim().is_empty() || app.trim().chars().all(|c| c == '*')) {
+            return Err("Allowlist entries must name an app".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn load(data_dir: &Path) -> Result<Self, String> {
+        Ok(app_data::read_json(&config_path(data_dir))?.unwrap_or_default())
+    }
+
+    pub fn save(&self, data_dir: &Path) -> Result<(), String> {
+        app_data::write_json(&config_path(data_dir), self)
+    }
+
+    /// This allowlist plus a session's own allowed apps, without duplicates
+    pub fn merged_with(&self, extra: &[String]) -> Vec<String> {
+        let mut apps: Vec<String> = Vec::new();
+        for app in self.apps.iter().chain(extra.iter()) {
+            let app = app.trim();
+            if !app.is_empty() && !apps.iter().any(|a| a.eq_ignore_ascii_case(app)) {
+                apps.push(app.to_string());
+            }
+        }
+        apps
+    }
+}
+
+fn config_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(NOTIFICATION_ALLOWLIST_FILE)
+}
+
+/// Wildcard pattern for an entry, lowercased
+#[cfg(any(windows, test))]
+pub fn entry_pattern(entry: &str) -> String {
+    let entry = entry.trim().to_lowercase();
+    let entry = entry.strip_suffix(".exe").unwrap_or(&entry).to_string();
+    if entry.contains('*') {
+        entry
+    } else {
+        format!("*{}*", entry)
+    }
+}
+
+/// Whether any of `names` (process name, title, app id...) is on the allowlist
+#[cfg(any(windows, test))]
+pub fn is_allowed(allowlist: &[String], names: &[&str]) -> bool {
+    allowlist.iter().map(|entry| entry_pattern(entry)).any(|pattern| {
+        names
+            .iter()
+            .filter(|name| !name.is_empty())
+            .any(|name| glob_match(&pattern, &name.to_lowercase()))
+    })
+}
+
+/// PowerShell defining `$allowlist`, `$hasAllowlist` and `Test-Allowed $name` for the suppression scripts
+#[cfg(any(windows, test))]
+pub fn powershell_prelude(allowlist: &[String]) -> String {
+    let patterns: Vec<String> = allowlist
+        .iter()
+        .map(|entry| format!("'{}'", entry_pattern(entry).replace('\'', "''")))
+        .collect();
+    format!(
+        r#"
+        $allowlist = @({})
+        $hasAllowlist = $allowlist.Count -gt 0
+        function Test-Allowed($name) {{
+            foreach ($pattern in $allowlist) {{
+                if ($name.ToLower() -like $pattern) {{ return $true }}
+            }}
+            return $false
+        }}
+"#,
+        patterns.join(", ")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apps(entries: &[&str]) -> Vec<String> {
+        entries.iter().map(|e| e.to_string()).collect()
+    }
+
+    #[test]
+    fn test_entries_match_anywhere_unless_wildcarded() {
+        let allowlist = apps(&["pagerduty", "Slack.exe", "com.squirrel.*"]);
+        assert!(is_allowed(&allowlist, &["PagerDuty.exe"]));
+        assert!(is_allowed(&allowlist, &["", "Slack - #oncall"]));
+        assert!(is_allowed(&allowlist, &["com.squirrel.Teams.Teams"]));
+        assert!(!is_allowed(&allowlist, &["org.squirrel.app"]));
+        assert!(!is_allowed(&allowlist, &["Discord.exe", "Discord"]));
+        assert!(!is_allowed(&[], &["PagerDuty.exe"]));
+    }
+
+    #[test]
+    fn test_merge_and_validate() {
+        let config = NotificationAllowlist { apps: apps(&["PagerDuty", "slack"]) };
+        assert_eq!(config.merged_with(&apps(&["Slack", "code", " "])), apps(&["PagerDuty", "slack", "code"]));
+        assert!(config.validate().is_ok());
+        assert!(NotificationAllowlist { apps: apps(&["*"]) }.validate().is_err());
+    }
+
+    #[test]
+    fn test_powershell_prelude_quotes_patterns() {
+        let prelude = powershell_prelude(&apps(&["PagerDuty", "O'Brien*"]));
+        assert!(prelude.contains("$allowlist = @('*pagerduty*', 'o''brien*')"));
+        assert!(powershell_prelude(&[]).contains("$allowlist = @()"));
+    }
+}
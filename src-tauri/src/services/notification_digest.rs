@@ -0,0 +1,195 @@
+// Notification digest
+// Notifications that arrive while we're muting are queued here instead of being lost,
+// then handed back as one digest grouped by app when focus ends or notifications are unmuted.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CapturedNotification {
+    pub app: String,
+    pub summary: String,
+    pub body: String,
+    pub received_at: DateTime<Utc>,
+}
+
+impl CapturedNotification {
+    pub fn new(app: &str, summary: &str, body: &str) -> Self {
+        Self {
+            app: app.to_string(),
+            summary: summary.to_string(),
+            body: body.to_string(),
+            received_at: Utc::now(),
+        }
+    }
+}
+
+// Everything one app sent while muted, oldest first
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DigestGroup {
+    pub app: String,
+    pub count: usize,
+    pub first_at: DateTime<Utc>,
+    pub last_at: DateTime<Utc>,
+    pub notifications: Vec<CapturedNotification>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NotificationDigest {
+    pub total: usize,
+    pub muted_since: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub groups: Vec<DigestGroup>, // Busiest app first
+}
+
+#[derive(Default)]
+struct QueueState {
+    capturing: bool,
+    muted_since: Option<DateTime<Utc>>,
+    items: Vec<CapturedNotification>,
+}
+
+/// Shared queue notification sources push into; cheap to clone
+#[derive(Clone, Default)]
+pub struct NotificationQueue {
+    state: Arc<Mutex<QueueState>>,
+}
+
+impl NotificationQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start accepting notifications; items from an earlier, unreported mute are kept
+    pub fn start_capture(&self) {
+        if let Ok(mut state) = self.state.lock() {
+            state.capturing = true;
+            state.muted_since.get_or_insert_with(Utc::now);
+        }
+    }
+
+    /// Stop accepting notifications without reporting the ones queued so far
+    pub fn stop_capture(&self) {
+        if let Ok(mut state) = self.state.lock() {
+            state.capturing = false;
+        }
+    }
+
+    /// Queue a notification; returns false if we're not capturing
+    pub fn push(&self, notification: CapturedNotification) -> bool {
+        match self.state.lock() {
+            Ok(mut state) if state.capturing => {
+                state.items.push(notification);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Stop capturing and turn everything queued into a digest, or None if nothing arrived
+    pub fn take_digest(&self) -> Option<NotificationDigest> {
+        let mut state = self.state.lock().ok()?;
+        state.capturing = false;
+        let muted_since = state.muted_since.take();
+        let items = std::mem::take(&mut state.items);
+        if items.is_empty() {
+            return None;
+        }
+        Some(build_digest(items, muted_since, Utc::now()))
+    }
+}
+
+/// Group notifications by app, busiest app first, with counts and first/last timestamps
+pub fn build_digest(
+    mut items: Vec<CapturedNotification>,
+    muted_since: Option<DateTime<Utc>>,
+    created_at: DateTime<Utc>,
+) -> NotificationDigest {
+    items.sort_by_key(|n| n.received_at);
+    let total = items.len();
+
+    let mut groups: Vec<DigestGroup> = Vec::new();
+    for notification in items {
+        let app = if notification.app.trim().is_empty() { "Unknown".to_string() } else { notification.app.clone() };
+        match groups.iter_mut().find(|g| g.app.eq_ignore_ascii_case(&app)) {
+            Some(group) => {
+                group.count += 1;
+                group.last_at = notification.received_at;
+                group.notifications.push(notification);
+            }
+            None => groups.push(DigestGroup {
+                app,
+                count: 1,
+                first_at: notification.received_at,
+                last_at: notification.received_at,
+                notifications: vec![notification],
+            }),
+        }
+    }
+    // Stable sort keeps apps with equal counts in order of their first notification
+    groups.sort_by_key(|g| std::cmp::Reverse(g.count));
+
+    NotificationDigest {
+        total,
+        muted_since,
+        created_at,
+        groups,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn at(app: &str, summary: &str, minutes: i64) -> CapturedNotification {
+        CapturedNotification {
+            received_at: DateTime::from_timestamp(1_700_000_000, 0).unwrap() + Duration::minutes(minutes),
+            ..CapturedNotification::new(app, summary, "")
+        }
+    }
+
+    #[test]
+    fn test_digest_groups_by_app_busiest_first() {
+        let items = vec![
+            at("Slack", "standup?", 5),
+            at("Mail", "Invoice", 1),
+            at("slack", "lunch", 9),
+            at("", "mystery", 3),
+            at("Slack", "ping", 2),
+        ];
+        let digest = build_digest(items, None, Utc::now());
+
+        assert_eq!(digest.total, 5);
+        let apps: Vec<(&str, usize)> = digest.groups.iter().map(|g| (g.app.as_str(), g.count)).collect();
+        assert_eq!(apps, vec![("Slack", 3), ("Mail", 1), ("Unknown", 1)]);
+
+        let slack = &digest.groups[0];
+        assert_eq!(slack.first_at, at("", "", 2).received_at);
+        assert_eq!(slack.last_at, at("", "", 9).received_at);
+        let summaries: Vec<&str> = slack.notifications.iter().map(|n| n.summary.as_str()).collect();
+        assert_eq!(summaries, vec!["ping", "standup?", "lunch"]);
+    }
+
+    #[test]
+    fn test_queue_only_captures_while_muted() {
+        let queue = NotificationQueue::new();
+        assert!(!queue.push(CapturedNotification::new("Slack", "early", "")));
+
+        queue.start_capture();
+        assert!(queue.push(CapturedNotification::new("Slack", "during", "")));
+        queue.stop_capture();
+        assert!(!queue.push(CapturedNotification::new("Slack", "paused", "")));
+
+        // Resuming keeps what was queued before the pause
+        queue.start_capture();
+        assert!(queue.push(CapturedNotification::new("Mail", "resumed", "")));
+
+        let digest = queue.take_digest().unwrap();
+        assert_eq!(digest.total, 2);
+        assert!(digest.muted_since.is_some());
+        assert!(queue.take_digest().is_none());
+        assert!(!queue.push(CapturedNotification::new("Slack", "after", "")));
+    }
+}
@@ -0,0 +1,275 @@
+// Pomodoro cycle engine
+// Pure phase state machine (work -> short break -> ... -> long break); timers and
+// notification muting are driven by FocusService
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use crate::services::focus_service::FocusMode;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PomodoroConfig {
+    pub work_minutes: u32,
+    pub short_break_minutes: u32,
+    pub long_break_minutes: u32,
+    pub cycles_before_long_break: u32, // Long break after every N work phases
+}
+
+impl Default for PomodoroConfig {
+    fn default() -> Self {
+        Self {
+            work_minutes: 25,
+            short_break_minutes: 5,
+            long_break_minutes: 15,
+            cycles_before_long_break: 4,
+        }
+    }
+}
+
+impl PomodoroConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.work_minutes == 0 || self.short_break_minutes == 0 || self.long_break_minutes == 0 {
+            return Err("Pomodoro phase lengths must be at least 1 minute".to_string());
+        }
+        if self.cycles_before_long_break == 0 {
+            return Err("Cycles before a long break must be at least 1".to_string());
+        }
+        Ok(())
+    }
+
+    /// Focus mode used when recording work phases in history
+    pub fn work_mode(&self) -> FocusMode {
+        match self.work_minutes {
+            25 => FocusMode::Focus25,
+            minutes => FocusMode::Custom(minutes),
+        }
+    }
+
+    pub fn phase_minutes(&self, phase: PomodoroPhase) -> u32 {
+        match phase {
+            PomodoroPhase::Work => self.work_minutes,
+            PomodoroPhase::ShortBreak => self.short_break_minutes,
+            PomodoroPhase::LongBreak => self.long_break_minutes,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PomodoroPhase {
+    Work,
+    ShortBreak,
+    LongBreak,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PomodoroState {
+    pub config: PomodoroConfig,
+    pub phase: PomodoroPhase,
+    pub cycle: u32,                 // Work phase number within the current set (1..=cycles_before_long_break)
+    pub completed_work_cycles: u32, // Work phases that ran to completion (skips don't count)
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub phase_started_at: DateTime<Utc>,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub phase_ends_at: DateTime<Utc>,
+    pub is_paused: bool,
+    pub remaining_seconds: u64,
+    #[serde(default, with = "chrono::serde::ts_seconds_option")]
+    pub paused_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub paused_seconds: u64, // Time the current phase spent paused (excluding a pause in progress)
+}
+
+impl PomodoroState {
+    /// Start a fresh set with the first work phase
+    pub fn new(config: PomodoroConfig, now: DateTime<Utc>) -> Self {
+        let mut state = Self {
+            config,
+            phase: PomodoroPhase::Work,
+            cycle: 1,
+            completed_work_cycles: 0,
+            phase_started_at: now,
+            phase_ends_at: now,
+            is_paused: false,
+            remaining_seconds: 0,
+            paused_at: None,
+            paused_seconds: 0,
+        };
+        state.begin_phase(PomodoroPhase::Work, now);
+        state
+    }
+
+    fn begin_phase(&mut self, phase: PomodoroPhase, now: DateTime<Utc>) {
+        let minutes = self.config.phase_minutes(phase);
+        self.phase = phase;
+        self.phase_started_at = now;
+        self.phase_ends_at = now + Duration::minutes(minutes as i64);
+        self.is_paused = false;
+        self.remaining_seconds = minutes as u64 * 60;
+        self.paused_at = None;
+        self.paused_seconds = 0;
+    }
+
+    /// Time the current phase has spent paused, including a pause that's still in progress
+    pub fn paused_seconds_at(&self, now: DateTime<Utc>) -> u64 {
+        let current = match self.paused_at {
+            Some(paused_at) if self.is_paused => (now - paused_at).num_seconds().max(0) as u64,
+            _ => 0,
+        };
+        self.paused_seconds + current
+    }
+
+    /// Seconds left in the current phase (frozen while paused)
+    pub fn remaining_at(&self, now: DateTime<Utc>) -> u64 {
+        if self.is_paused {
+            self.remaining_seconds
+        } else {
+            (self.phase_ends_at - now).num_seconds().max(0) as u64
+        }
+    }
+
+    /// Refresh `remaining_seconds` and return it
+    pub fn tick(&mut self, now: DateTime<Utc>) -> u64 {
+        self.remaining_seconds = self.remaining_at(now);
+        self.remaining_seconds
+    }
+
+    /// The phase that follows the current one
+    pub fn next_phase(&self) -> PomodoroPhase {
+        match self.phase {
+            PomodoroPhase::Work if self.cycle >= self.config.cycles_before_long_break => PomodoroPhase::LongBreak,
+            PomodoroPhase::Work => PomodoroPhase::ShortBreak,
+            PomodoroPhase::ShortBreak | PomodoroPhase::LongBreak => PomodoroPhase::Work,
+        }
+    }
+
+    /// Move to the next phase
+    /// `completed` is false when the current phase was skipped
+    pub fn advance(&mut self, now: DateTime<Utc>, completed: bool) {
+        let next = self.next_phase();
+        match self.phase {
+            PomodoroPhase::Work => {
+                if completed {
+                    self.completed_work_cycles += 1;
+                }
+            }
+            PomodoroPhase::ShortBreak => self.cycle += 1,
+            PomodoroPhase::LongBreak => self.cycle = 1,
+        }
+        self.begin_phase(next, now);
+    }
+
+    pub fn pause(&mut self, now: DateTime<Utc>) -> Result<(), String> {
+        if self.is_paused {
+            return Err("Pomodoro is already paused".to_string());
+        }
+        self.remaining_seconds = self.remaining_at(now);
+        self.is_paused = true;
+        self.paused_at = Some(now);
+        Ok(())
+    }
+
+    pub fn resume(&mut self, now: DateTime<Utc>) -> Result<(), String> {
+        if !self.is_paused {
+            return Err("Pomodoro is not paused".to_string());
+        }
+        self.phase_ends_at = now + Duration::seconds(self.remaining_seconds as i64);
+        self.paused_seconds = self.paused_seconds_at(now);
+        self.is_paused = false;
+        self.paused_at = None;
+        Ok(())
+    }
+
+    /// Start the set over from the first work phase, keeping the config
+    pub fn reset(&mut self, now: DateTime<Utc>) {
+        self.cycle = 1;
+        self.completed_work_cycles = 0;
+        self.begin_phase(PomodoroPhase::Work, now);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> PomodoroConfig {
+        PomodoroConfig {
+            work_minutes: 25,
+            short_break_minutes: 5,
+            long_break_minutes: 15,
+            cycles_before_long_break: 2,
+        }
+    }
+
+    #[test]
+    fn test_phase_sequence_with_long_break() {
+        let now = Utc::now();
+        let mut state = PomodoroState::new(config(), now);
+        let mut phases = vec![(state.phase, state.cycle)];
+        for _ in 0..5 {
+            state.advance(now, true);
+            phases.push((state.phase, state.cycle));
+        }
+
+        assert_eq!(phases, vec![
+            (PomodoroPhase::Work, 1),
+            (PomodoroPhase::ShortBreak, 1),
+            (PomodoroPhase::Work, 2),
+            (PomodoroPhase::LongBreak, 2),
+            (PomodoroPhase::Work, 1),
+            (PomodoroPhase::ShortBreak, 1),
+        ]);
+        assert_eq!(state.completed_work_cycles, 3);
+        assert_eq!(state.remaining_seconds, 5 * 60);
+    }
+
+    #[test]
+    fn test_skipped_work_does_not_count() {
+        let now = Utc::now();
+        let mut state = PomodoroState::new(config(), now);
+        state.advance(now, false);
+        assert_eq!(state.phase, PomodoroPhase::ShortBreak);
+        assert_eq!(state.completed_work_cycles, 0);
+    }
+
+    #[test]
+    fn test_pause_freezes_remaining_time() {
+        let start = Utc::now();
+        let mut state = PomodoroState::new(config(), start);
+
+        let paused_at = start + Duration::minutes(10);
+        state.pause(paused_at).unwrap();
+        assert!(state.pause(paused_at).is_err());
+        assert_eq!(state.remaining_at(paused_at + Duration::minutes(30)), 15 * 60);
+
+        let resumed_at = paused_at + Duration::minutes(30);
+        state.resume(resumed_at).unwrap();
+        assert_eq!(state.tick(resumed_at + Duration::minutes(5)), 10 * 60);
+        assert_eq!(state.phase_ends_at, resumed_at + Duration::minutes(15));
+        assert_eq!(state.paused_seconds_at(resumed_at + Duration::minutes(5)), 30 * 60);
+
+        // A second pause still in progress counts too, and the next phase starts from zero
+        state.pause(resumed_at + Duration::minutes(5)).unwrap();
+        assert_eq!(state.paused_seconds_at(resumed_at + Duration::minutes(7)), 32 * 60);
+        state.advance(resumed_at + Duration::minutes(7), false);
+        assert_eq!(state.paused_seconds_at(resumed_at + Duration::minutes(8)), 0);
+    }
+
+    #[test]
+    fn test_reset_starts_new_set() {
+        let now = Utc::now();
+        let mut state = PomodoroState::new(config(), now);
+        state.advance(now, true);
+        state.advance(now, true);
+        state.reset(now);
+        assert_eq!(state.phase, PomodoroPhase::Work);
+        assert_eq!(state.cycle, 1);
+        assert_eq!(state.completed_work_cycles, 0);
+    }
+
+    #[test]
+    fn test_config_validation() {
+        assert!(PomodoroConfig::default().validate().is_ok());
+        let invalid = PomodoroConfig { cycles_before_long_break: 0, ..PomodoroConfig::default() };
+        assert!(invalid.validate().is_err());
+    }
+}
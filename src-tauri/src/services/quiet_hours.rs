@@ -0,0 +1,292 @@
+// Quiet hours
+// Recurring windows (e.g. weeknights 22:00-07:00) during which notifications are muted automatically.
+// Rules live in quiet_hours.json; the window currently being enforced is kept in quiet_hours_state.json
+// so a restart picks up where it left off. FocusService runs the scheduler.
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use crate::models::action::CalendarEvent;
+use crate::utils::app_data;
+use crate::utils::serde_defaults::default_true;
+
+const QUIET_HOURS_FILE: &str = "quiet_hours.json";
+const QUIET_HOURS_STATE_FILE: &str = "quiet_hours_state.json";
+
+// Calendar events that lift quiet hours while they run, e.g. an on-call shift or a late meeting
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CalendarExceptions {
+    pub any_event: bool,             // Any timed event lifts quiet hours
+    pub title_keywords: Vec<String>, // Events whose title contains one of these (case-insensitive)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuietHoursRule {
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    pub days: Vec<Weekday>, // Days the window starts on
+    pub start: NaiveTime,
+    pub end: NaiveTime,     // At or before `start` means the window runs past midnight
+    #[serde(default)]
+    pub time_zone: Option<String>, // IANA name such as "Europe/Berlin"; None uses the system zone
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub exceptions: CalendarExceptions,
+}
+
+impl QuietHoursRule {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("Quiet hours name cannot be empty".to_string());
+        }
+        if self.days.is_empty() {
+            return Err("Quiet hours need at least one day".to_string());
+        }
+        if self.start == self.end {
+            return Err("Quiet hours must end at a different time than they start".to_string());
+        }
+        if let Some(ref name) = self.time_zone {
+            name.parse::<chrono_tz::Tz>().map_err(|_| format!("Unknown time zone: {}", name))?;
+        }
+        Ok(())
+    }
+
+    /// The window of this rule that contains `now`, if any
+    pub fn window_at(&self, now: DateTime<Utc>) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+        if !self.enabled {
+            return None;
+        }
+        match self.time_zone.as_deref().map(|name| name.parse::<chrono_tz::Tz>()) {
+            Some(Ok(tz)) => self.window_in(now, &tz),
+            Some(Err(_)) => None,
+            None => self.window_in(now, &chrono::Local),
+        }
+    }
+
+    fn window_in<Tz: TimeZone>(&self, now: DateTime<Utc>, tz: &Tz) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+        // A window that crosses midnight may have started yesterday
+        let today = now.with_timezone(tz).date_naive();
+        [today - Duration::days(1), today]
+            .into_iter()
+            .filter_map(|date| self.window_on(date, tz))
+            .find(|(start, end)| *start <= now && now < *end)
+    }
+
+    /// The window starting on `date` in `tz`, or None if the rule doesn't run that day
+    fn window_on<Tz: TimeZone>(&self, date: NaiveDate, tz: &Tz) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+        if !self.days.contains(&date.weekday()) {
+            return None;
+        }
+        let end_date = if self.end > self.start { date } else { date.succ_opt()? };
+        let start = localize(tz, date.and_time(self.start))?;
+        let end = localize(tz, end_date.and_time(self.end))?;
+        Some((start, end))
+    }
+
+    /// A running calendar event that lifts this rule, if any
+    pub fn exception_at<'a>(&self, events: &'a [CalendarEvent], now: DateTime<Utc>) -> Option<&'a CalendarEvent> {
+        let keywords: Vec<String> = self
+            .exceptions
+            .title_keywords
+            .iter()
+            .map(|k| k.trim().to_lowercase())
+            .filter(|k| !k.is_empty())
+            .collect();
+        events.iter().find(|event| {
+            if event.start_time > now || event.end_time <= now {
+                return false;
+            }
+            let title = event.title.to_lowercase();
+            (self.exceptions.any_event && !event.all_day) || keywords.iter().any(|k| title.contains(k.as_str()))
+        })
+    }
+}
+
+// A local time skipped by a DST change resolves to the same wall-clock time an hour later
+fn localize<Tz: TimeZone>(tz: &Tz, local: NaiveDateTime) -> Option<DateTime<Utc>> {
+    tz.from_local_datetime(&local)
+        .earliest()
+        .or_else(|| tz.from_local_datetime(&(local + Duration::hours(1))).earliest())
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+// The quiet-hours window currently in force
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ActiveQuietHours {
+    pub rule_id: String,
+    pub name: String,
+    pub started_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub enforcing: bool, // Cleared when the user unmutes by hand, so we don't re-mute until the next window
+    #[serde(default)]
+    pub muted: bool, // We muted for this window, so its end unmutes; false if the mute belongs to the user
+}
+
+// Payload of the quiet-hours-started / quiet-hours-ended events
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuietHoursTransition {
+    pub rule_id: String,
+    pub name: String,
+    pub ends_at: DateTime<Utc>,
+    pub exception: Option<String>, // Title of the calendar event that lifted quiet hours early
+}
+
+/// The first enabled rule whose window contains `now` and isn't lifted by a calendar event
+pub fn active_window(rules: &[QuietHoursRule], events: &[CalendarEvent], now: DateTime<Utc>) -> Option<ActiveQuietHours> {
+    rules.iter().find_map(|rule| {
+        let (started_at, ends_at) = rule.window_at(now)?;
+        if rule.exception_at(events, now).is_some() {
+            return None;
+        }
+        Some(ActiveQuietHours {
+            rule_id: rule.id.clone(),
+            name: rule.name.clone(),
+            started_at,
+            ends_at,
+            enforcing: true,
+            muted: false,
+        })
+    })
+}
+
+/// Quiet-hours rules and scheduler state, stored under the app data dir
+#[derive(Debug, Clone)]
+pub struct QuietHoursStore {
+    path: PathBuf,
+    state_path: PathBuf,
+}
+
+impl QuietHoursStore {
+    pub fn new(data_dir: &Path) -> Self {
+        Self {
+            path: data_dir.join(QUIET_HOURS_FILE),
+            state_path: data_dir.join(QUIET_HOURS_STATE_FILE),
+        }
+    }
+
+    pub fn list(&self) -> Result<Vec<QuietHoursRule>, String> {
+        Ok(app_data::read_json(&self.path)?.unwrap_or_default())
+    }
+
+    /// Add a rule, giving it a fresh id
+    pub fn add(&self, mut rule: QuietHoursRule) -> Result<QuietHoursRule, String> {
+        rule.validate()?;
+        rule.id = format!("quiet-{:08x}", rand::random::<u32>());
+
+        let mut rules = self.list()?;
+        rules.push(rule.clone());
+        app_data::write_json(&self.path, &rules)?;
+        Ok(rule)
+    }
+
+    pub fn remove(&self, id: &str) -> Result<(), String> {
+        let mut rules = self.list()?;
+        let before = rules.len();
+        rules.retain(|r| r.id != id);
+        if rules.len() == before {
+            return Err(format!("Quiet hours rule not found: {}", id));
+        }
+        app_data::write_json(&self.path, &rules)
+    }
+
+    pub fn load_active(&self) -> Option<ActiveQuietHours> {
+        app_data::read_json(&self.state_path).unwrap_or_else(|e| {
+            eprintln!("[QuietHours] Failed to read quiet hours state: {}", e);
+            None
+        })
+    }
+
+    pub fn save_active(&self, active: Option<&ActiveQuietHours>) {
+        let result = match active {
+            Some(active) => app_data::write_json(&self.state_path, active),
+            None => app_data::remove_file(&self.state_path),
+        };
+        if let Err(e) = result {
+            eprintln!("[QuietHours] Failed to save quiet hours state: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_support::{event, utc};
+
+    fn time(h: u32, m: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(h, m, 0).unwrap()
+    }
+
+    fn rule(days: Vec<Weekday>, start: NaiveTime, end: NaiveTime, time_zone: &str) -> QuietHoursRule {
+        QuietHoursRule {
+            id: "r1".to_string(),
+            name: "Night".to_string(),
+            days,
+            start,
+            end,
+            time_zone: Some(time_zone.to_string()),
+            enabled: true,
+            exceptions: CalendarExceptions::default(),
+        }
+    }
+
+    #[test]
+    fn test_overnight_window_uses_start_day_and_time_zone() {
+        // Fridays 22:00 -> 07:00 Berlin time (UTC+2 in June)
+        let night = rule(vec![Weekday::Fri], time(22, 0), time(7, 0), "Europe/Berlin");
+
+        // 2024-06-14 is a Friday
+        let (start, end) = night.window_at(utc("2024-06-15T01:00:00Z")).unwrap();
+        assert_eq!(start, utc("2024-06-14T20:00:00Z"));
+        assert_eq!(end, utc("2024-06-15T05:00:00Z"));
+
+        assert!(night.window_at(utc("2024-06-14T19:59:00Z")).is_none());
+        assert!(night.window_at(utc("2024-06-15T05:00:00Z")).is_none());
+        // Saturday night isn't covered
+        assert!(night.window_at(utc("2024-06-15T21:00:00Z")).is_none());
+        assert!(QuietHoursRule { enabled: false, ..night }.window_at(utc("2024-06-15T01:00:00Z")).is_none());
+    }
+
+    #[test]
+    fn test_window_starting_in_a_dst_gap_still_runs() {
+        // Berlin skips 02:00-03:00 on 2024-03-31 (a Sunday)
+        let early = rule(vec![Weekday::Sun], time(2, 30), time(6, 0), "Europe/Berlin");
+
+        let (start, end) = early.window_at(utc("2024-03-31T02:00:00Z")).unwrap();
+        assert_eq!(start, utc("2024-03-31T01:30:00Z"));
+        assert_eq!(end, utc("2024-03-31T04:00:00Z"));
+        assert!(early.window_at(utc("2024-03-31T01:00:00Z")).is_none());
+
+        // Ending in the gap works too
+        let late = rule(vec![Weekday::Sat], time(23, 0), time(2, 30), "Europe/Berlin");
+        let (_, end) = late.window_at(utc("2024-03-31T00:30:00Z")).unwrap();
+        assert_eq!(end, utc("2024-03-31T01:30:00Z"));
+    }
+
+    #[test]
+    fn test_calendar_events_lift_quiet_hours() {
+        let mut lunch = rule(vec![Weekday::Fri], time(12, 0), time(13, 0), "UTC");
+        lunch.exceptions.title_keywords = vec!["On-Call".to_string()];
+        let now = utc("2024-06-14T12:30:00Z");
+
+        let events = vec![event("Team lunch", "2024-06-14T12:00:00Z", "2024-06-14T13:00:00Z")];
+        assert_eq!(active_window(&[lunch.clone()], &events, now).unwrap().ends_at, utc("2024-06-14T13:00:00Z"));
+
+        let events = vec![event("on-call handover", "2024-06-14T12:15:00Z", "2024-06-14T12:45:00Z")];
+        assert!(active_window(&[lunch.clone()], &events, now).is_none());
+
+        lunch.exceptions = CalendarExceptions { any_event: true, title_keywords: Vec::new() };
+        let events = vec![event("Team lunch", "2024-06-14T12:00:00Z", "2024-06-14T13:00:00Z")];
+        assert!(active_window(&[lunch], &events, now).is_none());
+    }
+
+    #[test]
+    fn test_validate_rejects_bad_rules() {
+        let good = rule(vec![Weekday::Mon], time(22, 0), time(7, 0), "America/New_York");
+        assert!(good.validate().is_ok());
+        assert!(QuietHoursRule { time_zone: Some("Mars/Olympus".to_string()), ..good.clone() }.validate().is_err());
+        assert!(QuietHoursRule { days: Vec::new(), ..good.clone() }.validate().is_err());
+        assert!(QuietHoursRule { end: time(22, 0), ..good }.validate().is_err());
+    }
+}
@@ -0,0 +1,333 @@
+// OAuth token refresh
+// Google and Microsoft access tokens last about an hour. `CalendarService::get_token` goes through
+// the TokenRefresher here, which swaps an expiring access token for a new one using the stored
+// refresh token and writes it back to the keyring. Refreshes are single-flight per provider, so
+// the calendar poller, Gmail and the overlay asking at once cost one token request.
+// token-refresh-failed is only emitted when the refresh token itself is rejected, i.e. when the
+// user has to sign in again; network hiccups are just retried on the next call.
+
+use chrono::{DateTime, Duration, Utc};
+use oauth2::basic::{BasicClient, BasicErrorResponseType};
+use oauth2::reqwest::async_http_client;
+use oauth2::{AuthUrl, ClientId, ClientSecret, RefreshToken, RequestTokenError, TokenResponse, TokenUrl};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Mutex, OnceLock};
+use tauri::{AppHandle, Emitter};
+use crate::services::calendar_service::{CalendarProvider, CalendarService, TokenData};
+
+// Refresh this long before expiry so a request never goes out with a token that dies mid-flight
+const REFRESH_MARGIN_SECONDS: i64 = 120;
+
+static APP_HANDLE: OnceLock<AppHandle> = OnceLock::new();
+static TOKEN_REFRESHER: OnceLock<TokenRefresher<KeyringTokens>> = OnceLock::new();
+
+// Payload of the token-refresh-failed event
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TokenRefreshFailed {
+    pub provider: CalendarProvider,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RefreshError {
+    ReconsentRequired(String), // The refresh token was revoked or expired
+    Transient(String),         // Network trouble, provider outage, missing client config
+}
+
+/// Where tokens are kept and how a refresh token is exchanged
+pub trait TokenBackend: Send + Sync {
+    fn load(&self, provider: CalendarProvider) -> impl Future<Output = Result<TokenData, String>> + Send;
+    fn save(&self, provider: CalendarProvider, token: &TokenData) -> impl Future<Output = Result<(), String>> + Send;
+    fn exchange(&self, provider: CalendarProvider, refresh_token: &str) -> impl Future<Output = Result<TokenData, RefreshError>> + Send;
+}
+
+/// Whether the access token should be replaced before use
+pub fn needs_refresh(token: &TokenData, now: DateTime<Utc>) -> bool {
+    token.expires_at.is_some_and(|expires_at| expires_at - Duration::seconds(REFRESH_MARGIN_SECONDS) <= now)
+}
+
+pub struct TokenRefresher<B: TokenBackend> {
+    backend: B,
+    locks: HashMap<CalendarProvider, tokio::sync::Mutex<()>>,
+    // Refresh tokens the provider rejected, so we don't keep asking (or keep telling the user)
+    dead_refresh_tokens: Mutex<HashMap<CalendarProvider, String>>,
+    on_reconsent: Box<dyn Fn(&TokenRefreshFailed) + Send + Sync>,
+}
+
+impl<B: TokenBackend> TokenRefresher<B> {
+    pub fn new(backend: B, on_reconsent: impl Fn(&TokenRefreshFailed) + Send + Sync + 'static) -> Self {
+        let locks = [CalendarProvider::Google, CalendarProvider::Microsoft]
+            .into_iter()
+            .map(|provider| (provider, tokio::sync::Mutex::new(())))
+            .collect();
+        Self {
+            backend,
+            locks,
+            dead_refresh_tokens: Mutex::new(HashMap::new()),
+            on_reconsent: Box::new(on_reconsent),
+        }
+    }
+
+    /// A usable access token, refreshed first if it's about to expire
+    pub async fn token(&self, provider: CalendarProvider) -> Result<TokenData, String> {
+        let token = self.backend.load(provider).await?;
+        if !needs_refresh(&token, Utc::now()) {
+            return Ok(token);
+        }
+
+        let lock = self.locks.get(&provider).ok_or_else(|| format!("{:?} tokens can't be refreshed", provider))?;
+        let _guard = lock.lock().await;
+        // Whoever held the lock before us may already have refreshed it
+        let token = self.backend.load(provider).await?;
+        if !needs_refresh(&token, Utc::now()) {
+            return Ok(token);
+        }
+
+        // A missing refresh token is remembered as "", so it's reported once as well
+        let refresh_token = token.refresh_token.clone().unwrap_or_default();
+        let already_rejected = self
+            .dead_refresh_tokens
+            .lock()
+            .map(|dead| dead.get(&provider) == Some(&refresh_token))
+            .unwrap_or(false);
+        if already_rejected {
+            return Err(format!("{:?} sign-in expired. Please re-authenticate.", provider));
+        }
+        if refresh_token.is_empty() {
+            return Err(self.reconsent(provider, "", "No refresh token stored".to_string()));
+        }
+
+        println!("[Calendar] Refreshing {:?} access token", provider);
+        match self.backend.exchange(provider, &refresh_token).await {
+            Ok(mut refreshed) => {
+                // Google only sends a new refresh token on consent; Microsoft rotates it
+                if refreshed.refresh_token.is_none() {
+                    refreshed.refresh_token = Some(refresh_token);
+                }
+                self.backend.save(provider, &refreshed).await?;
+                println!("[Calendar] ✓ Refreshed {:?} access token, expires at {:?}", provider, refreshed.expires_at);
+                Ok(refreshed)
+            }
+            Err(RefreshError::ReconsentRequired(reason)) => Err(self.reconsent(provider, &refresh_token, reason)),
+            Err(RefreshError::Transient(e)) => {
+                eprintln!("[Calendar] ✗ Failed to refresh {:?} token, will retry: {}", provider, e);
+                // Still good for a minute or two: use it rather than failing the request
+                match token.expires_at {
+                    Some(expires_at) if expires_at > Utc::now() => Ok(token),
+                    _ => Err(format!("Failed to refresh {:?} token: {}", provider, e)),
+                }
+            }
+        }
+    }
+
+    /// Remember the rejected refresh token, tell the UI once, and return the error for the caller
+    fn reconsent(&self, provider: CalendarProvider, refresh_token: &str, reason: String) -> String {
+        eprintln!("[Calendar] ✗ {:?} needs re-authentication: {}", provider, reason);
+        if let Ok(mut dead) = self.dead_refresh_tokens.lock() {
+            dead.insert(provider, refresh_token.to_string());
+        }
+        (self.on_reconsent)(&TokenRefreshFailed { provider, reason });
+        format!("{:?} sign-in expired. Please re-authenticate.", provider)
+    }
+}
+
+/// Lets the refresher announce token-refresh-failed; called once during app setup
+pub fn set_app_handle(app: AppHandle) {
+    let _ = APP_HANDLE.set(app);
+}
+
+/// The app-wide refresher backed by the keyring and the providers' token endpoints
+pub fn token_refresher() -> &'static TokenRefresher<KeyringTokens> {
+    TOKEN_REFRESHER.get_or_init(|| {
+        TokenRefresher::new(KeyringTokens, |failure| {
+            if let Some(app) = APP_HANDLE.get() {
+                let _ = app.emit("token-refresh-failed", failure.clone());
+            }
+        })
+    })
+}
+
+/// Tokens stored in the OS keyring, refreshed with the client credentials from the environment
+pub struct KeyringTokens;
+
+impl TokenBackend for KeyringTokens {
+    async fn load(&self, provider: CalendarProvider) -> Result<TokenData, String> {
+        CalendarService::read_stored_token(provider).await
+    }
+
+    async fn save(&self, provider: CalendarProvider, token: &TokenData) -> Result<(), String> {
+        CalendarService::store_token(provider, token).await
+    }
+
+    async fn exchange(&self, provider: CalendarProvider, refresh_token: &str) -> Result<TokenData, RefreshError> {
+        let (id_var, secret_var, auth_url, token_url) = match provider {
+            CalendarProvider::Google => (
+                "GOOGLE_CLIENT_ID",
+                "GOOGLE_CLIENT_SECRET",
+                "https://accounts.google.com/o/oauth2/v2/auth",
+                "https://oauth2.googleapis.com/token",
+            ),
+            CalendarProvider::Microsoft => (
+                "MICROSOFT_CLIENT_ID",
+                "MICROSOFT_CLIENT_SECRET",
+                "https://login.microsoftonline.com/common/oauth2/v2.0/authorize",
+                "https://login.microsoftonline.com/common/oauth2/v2.0/token",
+            ),
+            CalendarProvider::CalDav | CalendarProvider::IcsUrl => {
+                return Err(RefreshError::Transient(format!("{:?} calendars don't use OAuth tokens", provider)));
+            }
+        };
+        let env = |name: &str| std::env::var(name).map_err(|_| RefreshError::Transient(format!("{} not configured", name)));
+        let client = BasicClient::new(
+            ClientId::new(env(id_var)?),
+            Some(ClientSecret::new(env(secret_var)?)),
+            AuthUrl::new(auth_url.to_string()).map_err(|e| RefreshError::Transient(format!("Invalid auth URL: {}", e)))?,
+            Some(TokenUrl::new(token_url.to_string()).map_err(|e| RefreshError::Transient(format!("Invalid token URL: {}", e)))?),
+        );
+
+        let response = client
+            .exchange_refresh_token(&RefreshToken::new(refresh_token.to_string()))
+            .request_async(async_http_client)
+            .await
+            .map_err(|e| match e {
+                // invalid_grant: revoked, expired, or issued before a password change
+                RequestTokenError::ServerResponse(ref response) if *response.error() == BasicErrorResponseType::InvalidGrant => {
+                    RefreshError::ReconsentRequired(
+                        response.error_description().cloned().unwrap_or_else(|| "Refresh token was rejected".to_string()),
+                    )
+                }
+                other => RefreshError::Transient(other.to_string()),
+            })?;
+
+        Ok(TokenData {
+            access_token: response.access_token().secret().clone(),
+            refresh_token: response.refresh_token().map(|rt| rt.secret().clone()),
+            expires_at: response
+                .expires_in()
+                .map(|duration| Utc::now() + Duration::seconds(duration.as_secs() as i64)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    // In-memory tokens and a token endpoint that counts its calls
+    #[derive(Default)]
+    struct FakeBackend {
+        stored: Mutex<Option<TokenData>>,
+        exchanges: AtomicUsize,
+        saves: AtomicUsize,
+        reply: Mutex<Option<Result<TokenData, RefreshError>>>,
+    }
+
+    impl TokenBackend for Arc<FakeBackend> {
+        async fn load(&self, _provider: CalendarProvider) -> Result<TokenData, String> {
+            self.stored.lock().unwrap().clone().ok_or_else(|| "no token".to_string())
+        }
+
+        async fn save(&self, _provider: CalendarProvider, token: &TokenData) -> Result<(), String> {
+            self.saves.fetch_add(1, Ordering::SeqCst);
+            *self.stored.lock().unwrap() = Some(token.clone());
+            Ok(())
+        }
+
+        async fn exchange(&self, _provider: CalendarProvider, _refresh_token: &str) -> Result<TokenData, RefreshError> {
+            self.exchanges.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+            self.reply.lock().unwrap().clone().unwrap()
+        }
+    }
+
+    fn token(access: &str, refresh: Option<&str>, expires_in_minutes: i64) -> TokenData {
+        TokenData {
+            access_token: access.to_string(),
+            refresh_token: refresh.map(String::from),
+            expires_at: Some(Utc::now() + Duration::minutes(expires_in_minutes)),
+        }
+    }
+
+    fn refresher(backend: &Arc<FakeBackend>) -> (TokenRefresher<Arc<FakeBackend>>, Arc<Mutex<Vec<TokenRefreshFailed>>>) {
+        let failures = Arc::new(Mutex::new(Vec::new()));
+        let seen = Arc::clone(&failures);
+        let refresher = TokenRefresher::new(Arc::clone(backend), move |f| seen.lock().unwrap().push(f.clone()));
+        (refresher, failures)
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_callers_share_one_refresh() {
+        let backend = Arc::new(FakeBackend::default());
+        *backend.stored.lock().unwrap() = Some(token("old", Some("refresh-1"), -5));
+        *backend.reply.lock().unwrap() = Some(Ok(token("new", None, 60)));
+        let (refresher, failures) = refresher(&backend);
+        let refresher = Arc::new(refresher);
+
+        let callers: Vec<_> = (0..5)
+            .map(|_| {
+                let refresher = Arc::clone(&refresher);
+                tokio::spawn(async move { refresher.token(CalendarProvider::Google).await })
+            })
+            .collect();
+        for caller in callers {
+            assert_eq!(caller.await.unwrap().unwrap().access_token, "new");
+        }
+
+        assert_eq!(backend.exchanges.load(Ordering::SeqCst), 1);
+        assert_eq!(backend.saves.load(Ordering::SeqCst), 1);
+        // Google didn't send a new refresh token, so the old one is kept
+        assert_eq!(backend.stored.lock().unwrap().as_ref().unwrap().refresh_token.as_deref(), Some("refresh-1"));
+        assert!(failures.lock().unwrap().is_empty());
+
+        // Fresh tokens are handed out without touching the endpoint
+        refresher.token(CalendarProvider::Google).await.unwrap();
+        assert_eq!(backend.exchanges.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_only_rejected_refresh_tokens_ask_for_reconsent() {
+        let backend = Arc::new(FakeBackend::default());
+        let (refresher, failures) = refresher(&backend);
+
+        // A network error while the token still has a minute left: keep using it, no event
+        *backend.stored.lock().unwrap() = Some(token("almost", Some("refresh-1"), 1));
+        *backend.reply.lock().unwrap() = Some(Err(RefreshError::Transient("connection reset".to_string())));
+        assert_eq!(refresher.token(CalendarProvider::Microsoft).await.unwrap().access_token, "almost");
+        assert!(failures.lock().unwrap().is_empty());
+
+        // Revoked: one event, and later calls don't hit the endpoint again
+        *backend.stored.lock().unwrap() = Some(token("expired", Some("refresh-1"), -1));
+        *backend.reply.lock().unwrap() = Some(Err(RefreshError::ReconsentRequired("invalid_grant".to_string())));
+        assert!(refresher.token(CalendarProvider::Microsoft).await.is_err());
+        assert!(refresher.token(CalendarProvider::Microsoft).await.is_err());
+        assert_eq!(backend.exchanges.load(Ordering::SeqCst), 2);
+        assert_eq!(
+            *failures.lock().unwrap(),
+            vec![TokenRefreshFailed { provider: CalendarProvider::Microsoft, reason: "invalid_grant".to_string() }]
+        );
+
+        // Signing in again stores a new refresh token, which is tried normally
+        *backend.stored.lock().unwrap() = Some(token("expired", Some("refresh-2"), -1));
+        *backend.reply.lock().unwrap() = Some(Ok(token("new", Some("refresh-3"), 60)));
+        assert_eq!(refresher.token(CalendarProvider::Microsoft).await.unwrap().refresh_token.as_deref(), Some("refresh-3"));
+    }
+
+    #[tokio::test]
+    async fn test_missing_refresh_token_is_reported_once() {
+        let backend = Arc::new(FakeBackend::default());
+        let (refresher, failures) = refresher(&backend);
+        *backend.stored.lock().unwrap() = Some(token("expired", None, -1));
+
+        assert!(refresher.token(CalendarProvider::Google).await.is_err());
+        assert!(refresher.token(CalendarProvider::Google).await.is_err());
+        assert_eq!(backend.exchanges.load(Ordering::SeqCst), 0);
+        assert_eq!(
+            *failures.lock().unwrap(),
+            vec![TokenRefreshFailed { provider: CalendarProvider::Google, reason: "No refresh token stored".to_string() }]
+        );
+    }
+}
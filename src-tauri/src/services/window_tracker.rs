@@ -19,7 +19,7 @@ use ::windows::{
 pub struct WindowTracker {
     cached_windows: Arc<Mutex<HashMap<isize, WindowInfo>>>,
     last_poll: Arc<Mutex<Instant>>,
-    foreground_window: Arc<Mutex<Option<isize>>>,
+    foreground_window: Arc<Mutex<Option<(isize, String)>>>, // Handle and title last reported by track_focus_changes
 }
 
 impl WindowTracker {
@@ -176,16 +176,8 @@ impl WindowTracker {
                 }
 
                 let handle = hwnd.0 as isize;
-                
-                // Check cache first
-                {
-                    let cache = self.cached_windows.lock().unwrap();
-                    if let Some(window) = cache.get(&handle) {
-                        return Ok(Some(window.clone()));
-                    }
-                }
 
-                // Get window info
+                // Always read the title fresh - a browser keeps its window while switching tabs
                 let mut title = [0u16; 256];
                 let title_len = GetWindowTextW(hwnd, &mut title);
                 let title_str = if title_len > 0 {
@@ -194,19 +186,29 @@ impl WindowTracker {
                     String::new()
                 };
 
-                let mut process_id: u32 = 0;
-                GetWindowThreadProcessId(hwnd, Some(&mut process_id));
-                let process_name = WindowTracker::get_process_name(process_id).unwrap_or_default();
-                let executable_path = WindowTracker::get_executable_path(process_id).unwrap_or_default();
-                let is_visible = IsWindowVisible(hwnd).as_bool();
-
-                let window = WindowInfo {
-                    handle,
-                    title: title_str,
-                    process_name,
-                    executable_path,
-                    last_active: Utc::now(),
-                    is_visible,
+                // The process doesn't change, so reuse the cached lookup when there is one
+                let cached = {
+                    let cache = self.cached_windows.lock().unwrap();
+                    cache.get(&handle).cloned()
+                };
+                let window = match cached {
+                    Some(window) => WindowInfo { title: title_str, ..window },
+                    None => {
+                        let mut process_id: u32 = 0;
+                        GetWindowThreadProcessId(hwnd, Some(&mut process_id));
+                        let process_name = WindowTracker::get_process_name(process_id).unwrap_or_default();
+                        let executable_path = WindowTracker::get_executable_path(process_id).unwrap_or_default();
+                        let is_visible = IsWindowVisible(hwnd).as_bool();
+
+                        WindowInfo {
+                            handle,
+                            title: title_str,
+                            process_name,
+                            executable_path,
+                            last_active: Utc::now(),
+                            is_visible,
+                        }
+                    }
                 };
 
                 // Update cache
@@ -215,12 +217,6 @@ impl WindowTracker {
                     cache.insert(handle, window.clone());
                 }
 
-                // Update foreground window
-                {
-                    let mut fg = self.foreground_window.lock().unwrap();
-                    *fg = Some(handle);
-                }
-
                 Ok(Some(window))
             }
         }
@@ -231,33 +227,41 @@ impl WindowTracker {
         }
     }
 
-    /// Track window focus changes
+    /// Track window focus changes: another window came to the front, or the front one changed its title
     pub fn track_focus_changes(&self) -> Result<Option<WindowInfo>, String> {
         let active = self.get_active_window()?;
-        
-        if let Some(ref window) = active {
-            let handle = window.handle;
-            
-            // Check if focus changed
-            let focus_changed = {
-                let fg = self.foreground_window.lock().unwrap();
-                *fg != Some(handle)
-            };
-
-            if focus_changed {
-                println!("Focus changed to: {} ({})", window.title, window.process_name);
-                
-                // Update foreground window
-                {
-                    let mut fg = self.foreground_window.lock().unwrap();
-                    *fg = Some(handle);
-                }
+        Ok(self.note_foreground(active))
+    }
+
+    /// Remember the foreground window, returning it if it differs from the last one seen
+    pub(crate) fn note_foreground(&self, active: Option<WindowInfo>) -> Option<WindowInfo> {
+        let window = active?;
+        let current = (window.handle, window.title.clone());
 
-                return Ok(active);
+        let mut fg = self.foreground_window.lock().unwrap();
+        if fg.as_ref() == Some(&current) {
+            return None;
+        }
+        println!("Focus changed to: {} ({})", window.title, window.process_name);
+        *fg = Some(current);
+        Some(window)
+    }
+
+    /// Minimize a window by handle
+    pub fn minimize_window(&self, handle: isize) -> Result<(), String> {
+        #[cfg(windows)]
+        {
+            unsafe {
+                let _ = ShowWindow(HWND(handle), SW_MINIMIZE);
             }
+            Ok(())
         }
 
-        Ok(None)
+        #[cfg(not(windows))]
+        {
+            let _ = handle;
+            Err("Not available on this platform".to_string())
+        }
     }
 
     /// Track window open/close events by comparing with cache
@@ -0,0 +1,67 @@
+// App data directory helpers
+// Mirrors Tauri's app data dir (<data dir>/<bundle identifier>) so services can
+// persist state before an AppHandle is available
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// Bundle identifier from tauri.conf.json
+pub const APP_IDENTIFIER: &str = "com.jamtech.pause-menu";
+
+/// Get the app data directory, creating it if needed
+pub fn app_data_dir() -> Result<PathBuf, String> {
+    let base = dirs::data_dir().ok_or("Could not determine the user data directory")?;
+    let dir = base.join(APP_IDENTIFIER);
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create app data dir {}: {}", dir.display(), e))?;
+    Ok(dir)
+}
+
+/// App data directory, falling back to the temp dir if it can't be created
+pub fn app_data_dir_or_temp() -> PathBuf {
+    app_data_dir().unwrap_or_else(|e| {
+        eprintln!("[AppData] {} - falling back to temp dir", e);
+        std::env::temp_dir().join(APP_IDENTIFIER)
+    })
+}
+
+/// Read a JSON file, returning None if it doesn't exist
+pub fn read_json<T: DeserializeOwned>(path: &Path) -> Result<Option<T>, String> {
+    let contents = match std::fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e)),
+    };
+
+    serde_json::from_str(&contents)
+        .map(Some)
+        .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+}
+
+/// Write a JSON file atomically (write to a temp file, then rename over the original)
+/// so a crash mid-write never leaves a truncated file behind
+pub fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+
+    let json = serde_json::to_string_pretty(value)
+        .map_err(|e| format!("Failed to serialize {}: {}", path.display(), e))?;
+
+    let tmp_path = path.with_extension("tmp");
+    std::fs::write(&tmp_path, json)
+        .map_err(|e| format!("Failed to write {}: {}", tmp_path.display(), e))?;
+    std::fs::rename(&tmp_path, path)
+        .map_err(|e| format!("Failed to replace {}: {}", path.display(), e))
+}
+
+/// Remove a file, ignoring it if it doesn't exist
+pub fn remove_file(path: &Path) -> Result<(), String> {
+    match std::fs::remove_file(path) {
+        Ok(_) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(format!("Failed to remove {}: {}", path.display(), e)),
+    }
+}
@@ -0,0 +1,78 @@
+// Local HTTP stand-in for tests
+// Serves canned responses on 127.0.0.1 so calendar providers can be exercised without a real server.
+
+use bytes::Bytes;
+use http_body_util::{BodyExt, Full};
+use hyper::server::conn::http1;
+use hyper::service::service_fn;
+use hyper::{Request, Response, StatusCode};
+use hyper_util::rt::TokioIo;
+use std::convert::Infallible;
+use std::sync::{Arc, Mutex};
+use tokio::net::TcpListener;
+
+// What the stand-in saw
+#[derive(Debug, Clone)]
+pub struct StubRequest {
+    pub method: String,
+    pub path: String, // Including the query string
+    pub authorization: Option<String>,
+    pub depth: Option<String>,
+    pub body: String,
+}
+
+type Handler = dyn Fn(&StubRequest) -> (u16, String) + Send + Sync;
+
+pub struct HttpStub {
+    pub base_url: String, // "http://127.0.0.1:<port>"
+    requests: Arc<Mutex<Vec<StubRequest>>>,
+}
+
+impl HttpStub {
+    /// Serve `handler`'s (status, body) for every request until the test ends
+    pub async fn start(handler: impl Fn(&StubRequest) -> (u16, String) + Send + Sync + 'static) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let handler: Arc<Handler> = Arc::new(handler);
+
+        let seen = Arc::clone(&requests);
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let handler = Arc::clone(&handler);
+                let seen = Arc::clone(&seen);
+                tokio::spawn(async move {
+                    let service = service_fn(move |req: Request<hyper::body::Incoming>| {
+                        let handler = Arc::clone(&handler);
+                        let seen = Arc::clone(&seen);
+                        async move {
+                            let header = |name: &str| req.headers().get(name).and_then(|v| v.to_str().ok()).map(String::from);
+                            let mut request = StubRequest {
+                                method: req.method().to_string(),
+                                path: req.uri().path_and_query().map(|p| p.to_string()).unwrap_or_default(),
+                                authorization: header("authorization"),
+                                depth: header("depth"),
+                                body: String::new(),
+                            };
+                            let body = req.into_body().collect().await.map(|b| b.to_bytes()).unwrap_or_default();
+                            request.body = String::from_utf8_lossy(&body).to_string();
+
+                            let (status, body) = handler(&request);
+                            seen.lock().unwrap().push(request);
+                            let mut response = Response::new(Full::new(Bytes::from(body)));
+                            *response.status_mut() = StatusCode::from_u16(status).unwrap();
+                            Ok::<_, Infallible>(response)
+                        }
+                    });
+                    let _ = http1::Builder::new().serve_connection(TokioIo::new(stream), service).await;
+                });
+            }
+        });
+
+        Self { base_url, requests }
+    }
+
+    pub fn requests(&self) -> Vec<StubRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
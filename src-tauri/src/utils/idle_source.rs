@@ -0,0 +1,190 @@
+// Idle sources
+// How long it's been since the user last touched the keyboard or mouse.
+// Windows asks GetLastInputInfo; Linux asks the X11 screensaver extension (MIT-SCREEN-SAVER)
+// and falls back to logind's idle hint, which also works under Wayland.
+
+use std::sync::Arc;
+
+/// Something that knows how long the user has been away
+pub trait IdleSource: Send + Sync {
+    /// Short name for logs
+    fn name(&self) -> &'static str;
+    /// Seconds since the last keyboard or mouse input
+    fn idle_seconds(&self) -> Result<u64, String>;
+}
+
+/// The idle source for the platform we're running on
+pub fn platform_idle_source() -> Arc<dyn IdleSource> {
+    #[cfg(windows)]
+    {
+        Arc::new(LastInputInfo)
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        Arc::new(LinuxIdle::default())
+    }
+
+    #[cfg(not(any(windows, target_os = "linux")))]
+    {
+        Arc::new(NoIdle)
+    }
+}
+
+#[cfg(windows)]
+pub struct LastInputInfo;
+
+#[cfg(windows)]
+impl IdleSource for LastInputInfo {
+    fn name(&self) -> &'static str {
+        "GetLastInputInfo"
+    }
+
+    fn idle_seconds(&self) -> Result<u64, String> {
+        use windows::Win32::System::SystemInformation::GetTickCount;
+        use windows::Win32::UI::Input::KeyboardAndMouse::{GetLastInputInfo, LASTINPUTINFO};
+
+        let mut info = LASTINPUTINFO {
+            cbSize: std::mem::size_of::<LASTINPUTINFO>() as u32,
+            dwTime: 0,
+        };
+        unsafe {
+            if !GetLastInputInfo(&mut info).as_bool() {
+                return Err("GetLastInputInfo failed".to_string());
+            }
+            // Both are 32-bit tick counts, so wrap around together every ~49 days
+            Ok((GetTickCount().wrapping_sub(info.dwTime) / 1000) as u64)
+        }
+    }
+}
+
+/// Linux: the X11 screensaver extension when running under X, otherwise logind's IdleHint
+/// Connections are kept between polls and only re-made after an error
+#[cfg(target_os = "linux")]
+#[derive(Default)]
+pub struct LinuxIdle {
+    x11: std::sync::Mutex<Option<(x11rb::rust_connection::RustConnection, u32)>>, // Connection and root window
+    logind: std::sync::Mutex<Option<zbus::blocking::Proxy<'static>>>,
+}
+
+#[cfg(target_os = "linux")]
+impl LinuxIdle {
+    /// Seconds idle according to the X11 screensaver extension
+    fn x11_idle_seconds(&self) -> Result<u64, String> {
+        use x11rb::connection::Connection;
+        use x11rb::protocol::screensaver::ConnectionExt as _;
+
+        let mut x11 = self.x11.lock().map_err(|_| "X11 connection lock poisoned".to_string())?;
+        // Taken out so a failed query drops the connection and the next poll reconnects
+        let (connection, root) = match x11.take() {
+            Some(x11) => x11,
+            None => {
+                let (connection, screen) = x11rb::connect(None).map_err(|e| format!("Failed to connect to the X server: {}", e))?;
+                let root = connection.setup().roots[screen].root;
+                (connection, root)
+            }
+        };
+        let info = connection
+            .screensaver_query_info(root)
+            .map_err(|e| format!("Failed to query the screensaver extension: {}", e))?
+            .reply()
+            .map_err(|e| format!("Screensaver extension unavailable: {}", e))?;
+        *x11 = Some((connection, root));
+        Ok(info.ms_since_user_input as u64 / 1000)
+    }
+
+    /// Seconds since logind marked our session idle (0 if it isn't)
+    fn logind_idle_seconds(&self) -> Result<u64, String> {
+        let mut logind = self.logind.lock().map_err(|_| "logind proxy lock poisoned".to_string())?;
+        let session = match logind.take() {
+            Some(session) => session,
+            None => {
+                let connection = zbus::blocking::Connection::system()
+                    .map_err(|e| format!("Failed to connect to the system bus: {}", e))?;
+                zbus::blocking::Proxy::new(
+                    &connection,
+                    "org.freedesktop.login1",
+                    "/org/freedesktop/login1/session/auto",
+                    "org.freedesktop.login1.Session",
+                )
+                .map_err(|e| format!("Failed to reach logind: {}", e))?
+            }
+        };
+
+        let idle: bool = session.get_property("IdleHint").map_err(|e| format!("Failed to read IdleHint: {}", e))?;
+        let seconds = if idle {
+            // Microseconds since the epoch
+            let since: u64 = session
+                .get_property("IdleSinceHint")
+                .map_err(|e| format!("Failed to read IdleSinceHint: {}", e))?;
+            let now = chrono::Utc::now().timestamp_micros().max(0) as u64;
+            now.saturating_sub(since) / 1_000_000
+        } else {
+            0
+        };
+        *logind = Some(session);
+        Ok(seconds)
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl IdleSource for LinuxIdle {
+    fn name(&self) -> &'static str {
+        "linux"
+    }
+
+    fn idle_seconds(&self) -> Result<u64, String> {
+        let x11 = std::env::var_os("DISPLAY").is_some() && std::env::var_os("WAYLAND_DISPLAY").is_none();
+        if x11 {
+            match self.x11_idle_seconds() {
+                Ok(seconds) => return Ok(seconds),
+                Err(x11_error) => {
+                    return self.logind_idle_seconds().map_err(|e| format!("{}; {}", x11_error, e));
+                }
+            }
+        }
+        self.logind_idle_seconds()
+    }
+}
+
+#[cfg(not(any(windows, target_os = "linux")))]
+pub struct NoIdle;
+
+#[cfg(not(any(windows, target_os = "linux")))]
+impl IdleSource for NoIdle {
+    fn name(&self) -> &'static str {
+        "none"
+    }
+
+    fn idle_seconds(&self) -> Result<u64, String> {
+        Err("Idle detection isn't supported on this platform".to_string())
+    }
+}
+
+#[cfg(test)]
+pub mod mock {
+    use super::*;
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    /// Lets tests say how long the user has been away
+    #[derive(Default)]
+    pub struct ManualIdle {
+        seconds: AtomicU64,
+    }
+
+    impl ManualIdle {
+        pub fn set_idle_seconds(&self, seconds: u64) {
+            self.seconds.store(seconds, Ordering::SeqCst);
+        }
+    }
+
+    impl IdleSource for ManualIdle {
+        fn name(&self) -> &'static str {
+            "manual"
+        }
+
+        fn idle_seconds(&self) -> Result<u64, String> {
+            Ok(self.seconds.load(Ordering::SeqCst))
+        }
+    }
+}
@@ -1,3 +1,10 @@
 pub mod windows_api;
 pub mod notification_suppression;
-
+pub mod notification_suppressor;
+pub mod notification_ledger;
+pub mod notification_source;
+pub mod idle_source;
+pub mod app_data;
+pub mod serde_defaults;
+#[cfg(test)]
+pub mod http_stub;
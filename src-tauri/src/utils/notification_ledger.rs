@@ -0,0 +1,386 @@
+// Notification settings ledger
+// Every system setting changed to silence notifications is written here, with its original value,
+// before it gets changed. If the app dies mid-session the ledger is replayed on the next start.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use crate::utils::app_data;
+
+const NOTIFICATION_LEDGER_FILE: &str = "notification_ledger.json";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
+pub enum RegistryValue {
+    Dword(u32),
+    Binary(Vec<u8>),
+}
+
+// A changed setting and the value it had before we touched it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum LedgerEntry {
+    Registry {
+        path: String,
+        name: String,
+        previous: Option<RegistryValue>, // None if the value didn't exist
+    },
+    Gsetting {
+        schema: String,
+        key: String,
+        previous: String, // As printed by `gsettings get`
+    },
+}
+
+impl LedgerEntry {
+    /// Identifies the setting, regardless of its value
+    fn setting_id(&self) -> String {
+        match self {
+            LedgerEntry::Registry { path, name, .. } => format!("registry:{}:{}", path.to_lowercase(), name.to_lowercase()),
+            LedgerEntry::Gsetting { schema, key, .. } => format!("gsetting:{}:{}", schema, key),
+        }
+    }
+}
+
+/// Writes original values back
+pub trait SettingRestorer {
+    /// Restore the entries, returning the ones that couldn't be restored
+    fn restore(&self, entries: &[LedgerEntry]) -> Vec<LedgerEntry>;
+}
+
+#[derive(Debug, Clone)]
+pub struct NotificationLedger {
+    path: PathBuf,
+}
+
+impl NotificationLedger {
+    pub fn new(data_dir: &Path) -> Self {
+        Self {
+            path: data_dir.join(NOTIFICATION_LEDGER_FILE),
+        }
+    }
+
+    pub fn entries(&self) -> Result<Vec<LedgerEntry>, String> {
+        Ok(app_data::read_json(&self.path)?.unwrap_or_default())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries().map(|entries| entries.is_empty()).unwrap_or(true)
+    }
+
+    /// Add settings about to be changed
+    /// A setting already in the ledger keeps its first value, which is the user's real original
+    pub fn record(&self, new_entries: Vec<LedgerEntry>) -> Result<(), String> {
+        let mut entries = self.entries()?;
+        let before = entries.len();
+        for entry in new_entries {
+            let id = entry.setting_id();
+            if !entries.iter().any(|e| e.setting_id() == id) {
+                entries.push(entry);
+            }
+        }
+        if entries.len() != before {
+            app_data::write_json(&self.path, &entries)?;
+        }
+        Ok(())
+    }
+
+    pub fn clear(&self) -> Result<(), String> {
+        app_data::remove_file(&self.path)
+    }
+
+    /// Restore every recorded setting, most recent change first
+    /// Entries that fail stay in the ledger so a later replay can retry them
+    pub fn replay(&self, restorer: &dyn SettingRestorer) -> Result<usize, String> {
+        let mut entries = self.entries()?;
+        if entries.is_empty() {
+            return Ok(0);
+        }
+
+        entries.reverse();
+        let failed = restorer.restore(&entries);
+        let restored = entries.len() - failed.len();
+
+        if failed.is_empty() {
+            self.clear()?;
+            println!("[Notifications] Restored {} notification settings from the ledger", restored);
+            Ok(restored)
+        } else {
+            let mut remaining = failed;
+            remaining.reverse();
+            app_data::write_json(&self.path, &remaining)?;
+            Err(format!("Failed to restore {} of {} notification settings", remaining.len(), entries.len()))
+        }
+    }
+}
+
+/// Restores settings on the machine we're running on
+pub struct PlatformRestorer;
+
+impl SettingRestorer for PlatformRestorer {
+    fn restore(&self, entries: &[LedgerEntry]) -> Vec<LedgerEntry> {
+        let mut failed = Vec::new();
+        let mut registry = Vec::new();
+
+        for entry in entries {
+            match entry {
+                LedgerEntry::Registry { .. } => registry.push(entry.clone()),
+                LedgerEntry::Gsetting { schema, key, previous } => {
+                    if let Err(e) = restore_gsetting(schema, key, previous) {
+                        eprintln!("[Notifications] Failed to restore {} {}: {}", schema, key, e);
+                        failed.push(entry.clone());
+                    }
+                }
+            }
+        }
+
+        if !registry.is_empty() {
+            failed.extend(restore_registry(&registry));
+        }
+        failed
+    }
+}
+
+fn restore_gsetting(schema: &str, key: &str, value: &str) -> Result<(), String> {
+    let output = std::process::Command::new("gsettings")
+        .args(["set", schema, key, value])
+        .output()
+        .map_err(|e| format!("Failed to run gsettings: {}", e))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    Ok(())
+}
+
+/// Restore registry values with a single PowerShell run; returns the entries that failed
+#[cfg(windows)]
+fn restore_registry(entries: &[LedgerEntry]) -> Vec<LedgerEntry> {
+    use std::process::Command;
+
+    let output = match Command::new("powershell")
+        .args(["-ExecutionPolicy", "Bypass", "-NoProfile", "-Command", &registry_restore_script(entries)])
+        .output()
+    {
+        Ok(output) => output,
+        Err(e) => {
+            eprintln!("[Notifications] Failed to execute PowerShell: {}", e);
+            return entries.to_vec();
+        }
+    };
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    stdout
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("FAILED "))
+        .filter_map(|index| index.parse::<usize>().ok())
+        .filter_map(|index| entries.get(index).cloned())
+        .collect()
+}
+
+#[cfg(not(windows))]
+fn restore_registry(entries: &[LedgerEntry]) -> Vec<LedgerEntry> {
+    eprintln!("[Notifications] Registry settings can only be restored on Windows");
+    entries.to_vec()
+}
+
+/// PowerShell that writes each original value back, printing "FAILED <index>" for entries it couldn't restore
+#[cfg(any(windows, test))]
+fn registry_restore_script(entries: &[LedgerEntry]) -> String {
+    let quote = |s: &str| format!("'{}'", s.replace('\'', "''"));
+
+    let mut script = String::new();
+    for (index, entry) in entries.iter().enumerate() {
+        let (path, name, previous) = match entry {
+            LedgerEntry::Registry { path, name, previous } => (quote(path), quote(name), previous),
+            _ => continue,
+        };
+        let command = match previous {
+            Some(RegistryValue::Dword(value)) => format!(
+                "if (-not (Test-Path -LiteralPath {p})) {{ New-Item -Path {p} -Force | Out-Null }}; Set-ItemProperty -LiteralPath {p} -Name {n} -Value {v} -Type DWord -Force -ErrorAction Stop",
+                p = path, n = name, v = value
+            ),
+            Some(RegistryValue::Binary(bytes)) => {
+                let bytes: Vec<String> = bytes.iter().map(|b| format!("0x{:02x}", b)).collect();
+                format!(
+                    "Set-ItemProperty -LiteralPath {p} -Name {n} -Value ([byte[]]({b})) -Type Binary -Force -ErrorAction Stop",
+                    p = path, n = name, b = bytes.join(",")
+                )
+            }
+            // The value didn't exist before, so remove the one we created
+            None => format!(
+                "Remove-ItemProperty -LiteralPath {p} -Name {n} -Force -ErrorAction SilentlyContinue",
+                p = path, n = name
+            ),
+        };
+        script.push_str(&format!("try {{ {} }} catch {{ Write-Output \"FAILED {}\" }}\n", command, index));
+    }
+    script
+}
+
+/// Read every registry value the Windows suppression code may change
+#[cfg(windows)]
+pub fn snapshot_windows_settings() -> Result<Vec<LedgerEntry>, String> {
+    use base64::Engine;
+    use std::process::Command;
+
+    #[derive(Deserialize)]
+    struct SnapshotValue {
+        path: String,
+        name: String,
+        #[serde(default)]
+        dword: Option<u32>,
+        #[serde(default)]
+        binary: Option<String>, // Base64
+    }
+
+    let ps_script = r#"
+        $ErrorActionPreference = "SilentlyContinue"
+        $out = New-Object System.Collections.ArrayList
+        function Add-Value($path, $name) {
+            $item = Get-ItemProperty -LiteralPath $path -Name $name -ErrorAction SilentlyContinue
+            $value = if ($item) { $item.$name } else { $null }
+            if ($value -is [byte[]]) {
+                [void]$out.Add([pscustomobject]@{ path = $path; name = $name; binary = [Convert]::ToBase64String($value) })
+            } else {
+                [void]$out.Add([pscustomobject]@{ path = $path; name = $name; dword = $value })
+            }
+        }
+
+        $notifPath = "HKCU:\Software\Microsoft\Windows\CurrentVersion\Notifications\Settings"
+        Add-Value $notifPath "NOC_GLOBAL_SETTING_TOAST_ENABLED"
+        Add-Value $notifPath "NOC_GLOBAL_SETTING_ACTION_CENTER_ENABLED"
+        foreach ($app in Get-ChildItem -Path $notifPath) {
+            foreach ($name in @("Enabled", "ShowInActionCenter", "ToastEnabled")) {
+                Add-Value $app.PSPath $name
+            }
+        }
+
+        $basePath = "HKCU:\Software\Microsoft\Windows\CurrentVersion\CloudStore\Store\Cache\DefaultAccount"
+        foreach ($key in Get-ChildItem -Path $basePath -Recurse | Where-Object { $_.PSChildName -like "*quiescence*" }) {
+            Add-Value $key.PSPath "Data"
+        }
+
+        ConvertTo-Json -InputObject @($out) -Compress
+    "#;
+
+    let output = Command::new("powershell")
+        .args(["-ExecutionPolicy", "Bypass", "-NoProfile", "-Command", ps_script])
+        .output()
+        .map_err(|e| format!("Failed to execute PowerShell: {}", e))?;
+    if !output.status.success() {
+        return Err(format!("Settings snapshot failed with exit code: {:?}", output.status.code()));
+    }
+
+    let values: Vec<SnapshotValue> = serde_json::from_slice(&output.stdout)
+        .map_err(|e| format!("Failed to parse settings snapshot: {}", e))?;
+
+    Ok(values
+        .into_iter()
+        .map(|v| {
+            let previous = match (v.dword, v.binary) {
+                (Some(value), _) => Some(RegistryValue::Dword(value)),
+                (None, Some(data)) => base64::engine::general_purpose::STANDARD.decode(data).ok().map(RegistryValue::Binary),
+                (None, None) => None,
+            };
+            LedgerEntry::Registry { path: v.path, name: v.name, previous }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    fn test_ledger(name: &str) -> (NotificationLedger, PathBuf) {
+        let dir = std::env::temp_dir().join(format!("pause-menu-test-ledger-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        (NotificationLedger::new(&dir), dir)
+    }
+
+    fn toast(previous: Option<u32>) -> LedgerEntry {
+        LedgerEntry::Registry {
+            path: "HKCU:\\Software\\Microsoft\\Windows\\CurrentVersion\\Notifications\\Settings".to_string(),
+            name: "NOC_GLOBAL_SETTING_TOAST_ENABLED".to_string(),
+            previous: previous.map(RegistryValue::Dword),
+        }
+    }
+
+    fn banners(previous: &str) -> LedgerEntry {
+        LedgerEntry::Gsetting {
+            schema: "org.gnome.desktop.notifications".to_string(),
+            key: "show-banners".to_string(),
+            previous: previous.to_string(),
+        }
+    }
+
+    // Records what it was asked to restore and fails the gsettings entries
+    #[derive(Default)]
+    struct FakeRestorer {
+        fail_gsettings: bool,
+        restored: Mutex<Vec<LedgerEntry>>,
+    }
+
+    impl SettingRestorer for FakeRestorer {
+        fn restore(&self, entries: &[LedgerEntry]) -> Vec<LedgerEntry> {
+            self.restored.lock().unwrap().extend(entries.iter().cloned());
+            entries
+                .iter()
+                .filter(|e| self.fail_gsettings && matches!(e, LedgerEntry::Gsetting { .. }))
+                .cloned()
+                .collect()
+        }
+    }
+
+    #[test]
+    fn test_record_keeps_original_value() {
+        let (ledger, dir) = test_ledger("record");
+        ledger.record(vec![toast(Some(1)), banners("true")]).unwrap();
+        // A second mute sees our own value; the original must win
+        ledger.record(vec![toast(Some(0)), banners("false")]).unwrap();
+
+        assert_eq!(ledger.entries().unwrap(), vec![toast(Some(1)), banners("true")]);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_replay_restores_in_reverse_and_clears() {
+        let (ledger, dir) = test_ledger("replay");
+        ledger.record(vec![toast(None), banners("true")]).unwrap();
+
+        let restorer = FakeRestorer::default();
+        assert_eq!(ledger.replay(&restorer).unwrap(), 2);
+        assert_eq!(*restorer.restored.lock().unwrap(), vec![banners("true"), toast(None)]);
+        assert!(ledger.is_empty());
+        assert_eq!(ledger.replay(&restorer).unwrap(), 0);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_failed_entries_stay_in_ledger() {
+        let (ledger, dir) = test_ledger("failed");
+        ledger.record(vec![toast(Some(1)), banners("true")]).unwrap();
+
+        let restorer = FakeRestorer { fail_gsettings: true, ..FakeRestorer::default() };
+        assert!(ledger.replay(&restorer).is_err());
+        assert_eq!(ledger.entries().unwrap(), vec![banners("true")]);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_registry_restore_script() {
+        let quiet_hours = LedgerEntry::Registry {
+            path: "HKCU:\\Quiet's".to_string(),
+            name: "Data".to_string(),
+            previous: Some(RegistryValue::Binary(vec![0x43, 0x42, 0x01])),
+        };
+        let script = registry_restore_script(&[toast(Some(1)), toast(None), quiet_hours]);
+        let lines: Vec<&str> = script.lines().collect();
+
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].contains("-Name 'NOC_GLOBAL_SETTING_TOAST_ENABLED' -Value 1 -Type DWord"));
+        assert!(lines[0].ends_with("catch { Write-Output \"FAILED 0\" }"));
+        assert!(lines[1].contains("Remove-ItemProperty"));
+        assert!(lines[2].contains("-LiteralPath 'HKCU:\\Quiet''s'"));
+        assert!(lines[2].contains("([byte[]](0x43,0x42,0x01))"));
+    }
+}
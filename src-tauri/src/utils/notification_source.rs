@@ -0,0 +1,289 @@
+// Notification capture sources
+// While notifications are muted, a NotificationSource feeds whatever still arrives into the digest queue.
+// On Linux we watch Notify calls on the bus, or stand in for the freedesktop notification server
+// when the bus doesn't allow monitoring.
+
+use std::sync::Arc;
+use crate::services::notification_digest::NotificationQueue;
+
+/// Something that can observe desktop notifications while we're muting them
+pub trait NotificationSource: Send + Sync {
+    /// Short name for logs
+    fn name(&self) -> &'static str;
+    /// Start delivering notifications into `queue`; calling it again while running is a no-op
+    fn start(&self, queue: NotificationQueue) -> Result<(), String>;
+    fn stop(&self);
+}
+
+/// The capture source for the platform we're running on
+pub fn platform_source() -> Arc<dyn NotificationSource> {
+    #[cfg(target_os = "linux")]
+    {
+        Arc::new(FreedesktopNotificationProxy::new())
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        Arc::new(NoCapture)
+    }
+}
+
+#[cfg(target_os = "linux")]
+use crate::services::notification_digest::CapturedNotification;
+#[cfg(target_os = "linux")]
+use crate::utils::notification_suppressor::{NOTIFICATIONS_DEST, NOTIFICATIONS_PATH};
+
+// Serves org.freedesktop.Notifications while we own the name, queueing instead of showing
+#[cfg(target_os = "linux")]
+struct NotificationServer {
+    queue: NotificationQueue,
+    next_id: std::sync::atomic::AtomicU32,
+}
+
+#[cfg(target_os = "linux")]
+#[zbus::interface(name = "org.freedesktop.Notifications")]
+impl NotificationServer {
+    #[allow(clippy::too_many_arguments)]
+    fn notify(
+        &self,
+        app_name: String,
+        _replaces_id: u32,
+        _app_icon: String,
+        summary: String,
+        body: String,
+        _actions: Vec<String>,
+        _hints: std::collections::HashMap<String, zbus::zvariant::OwnedValue>,
+        _expire_timeout: i32,
+    ) -> u32 {
+        self.queue.push(CapturedNotification::new(&app_name, &summary, &body));
+        self.next_id.fetch_add(1, std::sync::atomic::Ordering::SeqCst)
+    }
+
+    fn close_notification(&self, _id: u32) {}
+
+    fn get_capabilities(&self) -> Vec<String> {
+        vec!["body".to_string()]
+    }
+
+    fn get_server_information(&self) -> (String, String, String, String) {
+        (
+            "Pause Menu".to_string(),
+            "pause-menu".to_string(),
+            env!("CARGO_PKG_VERSION").to_string(),
+            "1.2".to_string(),
+        )
+    }
+}
+
+#[cfg(target_os = "linux")]
+enum ProxyState {
+    Stopped,
+    // Notify calls are observed; closing the connection ends the monitor thread
+    Monitoring {
+        connection: zbus::blocking::Connection,
+        stop: Arc<std::sync::atomic::AtomicBool>,
+    },
+    // The bus won't let us monitor, so we own the notification server name until stop
+    Serving(zbus::blocking::Connection),
+}
+
+/// Linux source: eavesdrops on Notify calls through the bus monitoring interface, otherwise takes over
+/// org.freedesktop.Notifications while muted (servers that allow replacement) and hands it back after
+#[cfg(target_os = "linux")]
+pub struct FreedesktopNotificationProxy {
+    state: std::sync::Mutex<ProxyState>,
+}
+
+#[cfg(target_os = "linux")]
+impl FreedesktopNotificationProxy {
+    pub fn new() -> Self {
+        Self {
+            state: std::sync::Mutex::new(ProxyState::Stopped),
+        }
+    }
+
+    fn notifications_name() -> zbus::names::WellKnownName<'static> {
+        zbus::names::WellKnownName::from_static_str_unchecked(NOTIFICATIONS_DEST)
+    }
+
+    fn serve(queue: NotificationQueue) -> Result<zbus::blocking::Connection, String> {
+        use zbus::fdo::{RequestNameFlags, RequestNameReply};
+        use zbus::names::BusName;
+
+        let server = NotificationServer {
+            queue,
+            next_id: std::sync::atomic::AtomicU32::new(1),
+        };
+        let connection = zbus::blocking::connection::Builder::session()
+            .and_then(|b| b.serve_at(NOTIFICATIONS_PATH, server))
+            .and_then(|b| b.build())
+            .map_err(|e| format!("Failed to connect to the session bus: {}", e))?;
+        let dbus = zbus::blocking::fdo::DBusProxy::new(&connection).map_err(|e| format!("Failed to reach the bus: {}", e))?;
+        let previous_owner = dbus.get_name_owner(BusName::from(Self::notifications_name())).ok();
+        let reply = connection
+            .request_name_with_flags(NOTIFICATIONS_DEST, RequestNameFlags::ReplaceExisting | RequestNameFlags::DoNotQueue)
+            .map_err(|e| format!("Failed to request {}: {}", NOTIFICATIONS_DEST, e))?;
+        if !matches!(reply, RequestNameReply::PrimaryOwner | RequestNameReply::AlreadyOwner) {
+            return Err("The notification server doesn't allow replacement".to_string());
+        }
+        // Servers that didn't queue for the name lose it for good; stop() starts them again
+        let queued = dbus.list_queued_owners(Self::notifications_name()).unwrap_or_default();
+        if previous_owner.is_some_and(|owner| !queued.contains(&owner)) {
+            println!("[Notifications] The notification server didn't queue for its name, it'll be restarted afterwards");
+        }
+        Ok(connection)
+    }
+
+    /// Give the notification server name back, starting the real server again if it didn't queue for it
+    fn hand_back(connection: &zbus::blocking::Connection) -> Result<(), String> {
+        use zbus::names::BusName;
+
+        connection
+            .release_name(NOTIFICATIONS_DEST)
+            .map_err(|e| format!("Failed to release {}: {}", NOTIFICATIONS_DEST, e))?;
+        let dbus = zbus::blocking::fdo::DBusProxy::new(connection).map_err(|e| format!("Failed to reach the bus: {}", e))?;
+        if !dbus.name_has_owner(BusName::from(Self::notifications_name())).unwrap_or(true) {
+            dbus.start_service_by_name(Self::notifications_name(), 0)
+                .map_err(|e| format!("Failed to restart the notification server: {}", e))?;
+        }
+        Ok(())
+    }
+
+    fn monitor(queue: NotificationQueue) -> Result<(zbus::blocking::Connection, Arc<std::sync::atomic::AtomicBool>), String> {
+        use std::collections::HashMap;
+        use std::sync::atomic::{AtomicBool, Ordering};
+        use zbus::message::Type;
+        use zbus::zvariant::OwnedValue;
+
+        let connection = zbus::blocking::Connection::session()
+            .map_err(|e| format!("Failed to connect to the session bus: {}", e))?;
+        let rule = zbus::MatchRule::builder()
+            .msg_type(Type::MethodCall)
+            .interface(NOTIFICATIONS_DEST)
+            .and_then(|b| b.member("Notify"))
+            .map_err(|e| e.to_string())?
+            .build();
+        zbus::blocking::fdo::MonitoringProxy::new(&connection)
+            .and_then(|proxy| proxy.become_monitor(&[rule], 0).map_err(zbus::Error::from))
+            .map_err(|e| format!("BecomeMonitor failed: {}", e))?;
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let stop_flag = Arc::clone(&stop);
+        let reader = connection.clone();
+        std::thread::spawn(move || {
+            // Blocks until the next Notify call; stop() closes the connection so this returns
+            for message in zbus::blocking::MessageIterator::from(&reader) {
+                if stop_flag.load(Ordering::SeqCst) {
+                    break;
+                }
+                let Ok(message) = message else { continue };
+                type NotifyArgs = (String, u32, String, String, String, Vec<String>, HashMap<String, OwnedValue>, i32);
+                if let Ok((app_name, _, _, summary, body, _, _, _)) = message.body().deserialize::<NotifyArgs>() {
+                    queue.push(CapturedNotification::new(&app_name, &summary, &body));
+                }
+            }
+            println!("[Notifications] Notification monitor stopped");
+        });
+        Ok((connection, stop))
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl NotificationSource for FreedesktopNotificationProxy {
+    fn name(&self) -> &'static str {
+        "freedesktop"
+    }
+
+    fn start(&self, queue: NotificationQueue) -> Result<(), String> {
+        let mut state = self.state.lock().map_err(|e| e.to_string())?;
+        if !matches!(*state, ProxyState::Stopped) {
+            return Ok(());
+        }
+
+        match Self::monitor(queue.clone()) {
+            Ok((connection, stop)) => {
+                println!("[Notifications] Capturing notifications by monitoring Notify calls");
+                *state = ProxyState::Monitoring { connection, stop };
+                Ok(())
+            }
+            Err(monitor_error) => {
+                println!("[Notifications] {}, standing in for the notification server instead", monitor_error);
+                let connection = Self::serve(queue).map_err(|e| format!("{}; {}", monitor_error, e))?;
+                *state = ProxyState::Serving(connection);
+                Ok(())
+            }
+        }
+    }
+
+    fn stop(&self) {
+        let Ok(mut state) = self.state.lock() else { return };
+        match std::mem::replace(&mut *state, ProxyState::Stopped) {
+            ProxyState::Stopped => {}
+            ProxyState::Monitoring { connection, stop } => {
+                stop.store(true, std::sync::atomic::Ordering::SeqCst);
+                if let Err(e) = connection.close() {
+                    eprintln!("[Notifications] Failed to close the notification monitor: {}", e);
+                }
+            }
+            ProxyState::Serving(connection) => match Self::hand_back(&connection) {
+                Ok(()) => println!("[Notifications] Handed notifications back to the desktop"),
+                Err(e) => eprintln!("[Notifications] {}", e),
+            },
+        }
+    }
+}
+
+// Windows toasts can only be read through the packaged-app UserNotificationListener API
+#[cfg(not(target_os = "linux"))]
+pub struct NoCapture;
+
+#[cfg(not(target_os = "linux"))]
+impl NotificationSource for NoCapture {
+    fn name(&self) -> &'static str {
+        "none"
+    }
+
+    fn start(&self, _queue: NotificationQueue) -> Result<(), String> {
+        Err("Capturing notifications isn't supported on this platform".to_string())
+    }
+
+    fn stop(&self) {}
+}
+
+#[cfg(test)]
+pub mod mock {
+    use super::*;
+    use crate::services::notification_digest::CapturedNotification;
+    use std::sync::Mutex;
+
+    /// Lets tests deliver notifications as if the desktop had sent them
+    #[derive(Default)]
+    pub struct ManualSource {
+        queue: Mutex<Option<NotificationQueue>>,
+    }
+
+    impl ManualSource {
+        /// Returns whether the notification was captured
+        pub fn deliver(&self, app: &str, summary: &str) -> bool {
+            match self.queue.lock().unwrap().as_ref() {
+                Some(queue) => queue.push(CapturedNotification::new(app, summary, "")),
+                None => false,
+            }
+        }
+    }
+
+    impl NotificationSource for ManualSource {
+        fn name(&self) -> &'static str {
+            "manual"
+        }
+
+        fn start(&self, queue: NotificationQueue) -> Result<(), String> {
+            *self.queue.lock().unwrap() = Some(queue);
+            Ok(())
+        }
+
+        fn stop(&self) {
+            *self.queue.lock().unwrap() = None;
+        }
+    }
+}
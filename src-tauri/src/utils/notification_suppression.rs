@@ -1,5 +1,6 @@
 // Direct Windows API implementation for notification suppression
 // This uses native Windows APIs instead of PowerShell for reliability
+// Other platforms go through utils/notification_suppressor.rs
 
 #[cfg(windows)]
 use windows::Win32::System::Registry::*;
@@ -117,11 +118,6 @@ pub fn suppress_notifications_direct() -> std::result::Result<(), String> {
     }
 }
 
-#[cfg(not(windows))]
-pub fn suppress_notifications_direct() -> std::result::Result<(), String> {
-    Err("Not available on this platform".to_string())
-}
-
 /// Restore notifications using PowerShell
 #[cfg(windows)]
 pub fn restore_notifications_direct() -> std::result::Result<(), String> {
@@ -170,11 +166,6 @@ pub fn restore_notifications_direct() -> std::result::Result<(), String> {
     Ok(())
 }
 
-#[cfg(not(windows))]
-pub fn restore_notifications_direct() -> std::result::Result<(), String> {
-    Err("Not available on this platform".to_string())
-}
-
 #[cfg(windows)]
 unsafe fn disable_global_notifications() -> std::result::Result<(), String> {
     let key_path = w!("Software\\Microsoft\\Windows\\CurrentVersion\\Notifications\\Settings");
@@ -351,7 +342,7 @@ unsafe fn restore_focus_assist() -> std::result::Result<(), String> {
 }
 
 #[cfg(windows)]
-unsafe fn disable_app_notifications() -> std::result::Result<(), String> {
+unsafe fn disable_app_notifications_registry() -> std::result::Result<(), String> {
     let apps = [
         "Microsoft.SkypeApp",
         "MSTeams",
@@ -406,7 +397,7 @@ unsafe fn disable_app_notifications() -> std::result::Result<(), String> {
 }
 
 #[cfg(windows)]
-unsafe fn restore_app_notifications() -> std::result::Result<(), String> {
+unsafe fn restore_app_notifications_registry() -> std::result::Result<(), String> {
     let base_path = w!("Software\\Microsoft\\Windows\\CurrentVersion\\Notifications\\Settings");
     
     let mut hkey = HKEY::default();
@@ -582,31 +573,39 @@ unsafe fn restart_shell_experience_host() -> std::result::Result<(), String> {
 
 /// Mute notifications using Windows API (for overlay toggle)
 /// Uses PowerShell as primary method for reliability, Windows API as supplement
+/// Apps on `allowlist` keep notifying: the global and Focus Assist switches are left alone
+/// and only the other apps are disabled
 #[cfg(windows)]
-pub fn mute_notifications_windows_api() -> std::result::Result<(), String> {
+pub fn mute_notifications_windows_api(allowlist: &[String]) -> std::result::Result<(), String> {
     println!("[Notifications] Muting notifications using comprehensive approach...");
     
     // Use PowerShell as primary method (more reliable for app-specific notifications)
     use std::process::Command;
     
-    let ps_script = r#"
+    let ps_script = crate::services::notification_allowlist::powershell_prelude(allowlist) + r#"
         $ErrorActionPreference = "Stop"
         
         Write-Host "[Notifications] Starting comprehensive notification suppression..."
         
-        # Method 1: Disable global toast notifications
+        # Method 1: Disable global toast notifications (would also silence allowlisted apps)
         $notifPath = "HKCU:\Software\Microsoft\Windows\CurrentVersion\Notifications\Settings"
         if (-not (Test-Path $notifPath)) {
             New-Item -Path $notifPath -Force | Out-Null
         }
-        Set-ItemProperty -Path $notifPath -Name "NOC_GLOBAL_SETTING_TOAST_ENABLED" -Value 0 -Type DWord -Force
-        Set-ItemProperty -Path $notifPath -Name "NOC_GLOBAL_SETTING_ACTION_CENTER_ENABLED" -Value 0 -Type DWord -Force
-        Write-Host "[Notifications] Disabled global toast notifications"
+        if (-not $hasAllowlist) {
+            Set-ItemProperty -Path $notifPath -Name "NOC_GLOBAL_SETTING_TOAST_ENABLED" -Value 0 -Type DWord -Force
+            Set-ItemProperty -Path $notifPath -Name "NOC_GLOBAL_SETTING_ACTION_CENTER_ENABLED" -Value 0 -Type DWord -Force
+            Write-Host "[Notifications] Disabled global toast notifications"
+        }
         
         # Method 2: Disable ALL app notifications (comprehensive approach)
         Write-Host "[Notifications] Disabling notifications for all apps..."
         $allApps = Get-ChildItem -Path $notifPath -ErrorAction SilentlyContinue
         foreach ($app in $allApps) {
+            if (Test-Allowed $app.PSChildName) {
+                Write-Host "[Notifications] Allowlisted, leaving on: $($app.PSChildName)"
+                continue
+            }
             try {
                 Set-ItemProperty -Path $app.PSPath -Name "Enabled" -Value 0 -Type DWord -Force -ErrorAction SilentlyContinue
                 Set-ItemProperty -Path $app.PSPath -Name "ShowInActionCenter" -Value 0 -Type DWord -Force -ErrorAction SilentlyContinue
@@ -631,6 +630,7 @@ pub fn mute_notifications_windows_api() -> std::result::Result<(), String> {
             "Microsoft.MicrosoftEdge.Stable_8wekyb3d8bbwe!https://web.skype.com/"
         )
         foreach ($teamsId in $teamsVariants) {
+            if (Test-Allowed $teamsId) { continue }
             try {
                 $teamsPath = Join-Path $notifPath $teamsId
                 if (-not (Test-Path $teamsPath)) {
@@ -650,6 +650,7 @@ pub fn mute_notifications_windows_api() -> std::result::Result<(), String> {
         Write-Host "[Notifications] Searching for Teams-related apps..."
         foreach ($app in $allApps) {
             $appName = $app.PSChildName.ToLower()
+            if (Test-Allowed $appName) { continue }
             if ($appName -like "*team*" -or $appName -like "*skype*" -or $appName -like "*microsoftteams*") {
                 try {
                     Set-ItemProperty -Path $app.PSPath -Name "Enabled" -Value 0 -Type DWord -Force -ErrorAction SilentlyContinue
@@ -662,9 +663,10 @@ pub fn mute_notifications_windows_api() -> std::result::Result<(), String> {
             }
         }
         
-        # Method 3: Set Focus Assist to Alarms Only
+        # Method 3: Set Focus Assist to Alarms Only (skipped with an allowlist, it silences every app)
         $basePath = "HKCU:\Software\Microsoft\Windows\CurrentVersion\CloudStore\Store\Cache\DefaultAccount"
         $keys = Get-ChildItem -Path $basePath -Recurse -ErrorAction SilentlyContinue | Where-Object { $_.PSChildName -like "*quiescence*" }
+        if ($hasAllowlist) { $keys = @() }
         foreach ($key in $keys) {
             try {
                 Set-ItemProperty -Path $key.PSPath -Name "Data" -Value ([byte[]](0x43,0x42,0x01,0x00,0x02,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00)) -Type Binary -Force -ErrorAction SilentlyContinue
@@ -676,6 +678,7 @@ pub fn mute_notifications_windows_api() -> std::result::Result<(), String> {
             "HKCU:\Software\Microsoft\Windows\CurrentVersion\CloudStore\Store\Cache\DefaultAccount\`$windows.data.notifications.quiescence.win10\Current",
             "HKCU:\Software\Microsoft\Windows\CurrentVersion\CloudStore\Store\Cache\DefaultAccount\`$windows.data.notifications.quiescence.win11\Current"
         )
+        if ($hasAllowlist) { $directPaths = @() }
         foreach ($directPath in $directPaths) {
             try {
                 if (-not (Test-Path $directPath)) {
@@ -692,7 +695,7 @@ pub fn mute_notifications_windows_api() -> std::result::Result<(), String> {
         # Method 5: Also disable notifications in Teams app data (if Teams is installed)
         Write-Host "[Notifications] Attempting to disable Teams notifications in app data..."
         $teamsAppDataPath = "$env:APPDATA\Microsoft\Teams"
-        if (Test-Path $teamsAppDataPath) {
+        if ((Test-Path $teamsAppDataPath) -and -not (Test-Allowed "teams")) {
             try {
                 $teamsSettingsPath = Join-Path $teamsAppDataPath "desktop-config.json"
                 if (Test-Path $teamsSettingsPath) {
@@ -719,7 +722,7 @@ pub fn mute_notifications_windows_api() -> std::result::Result<(), String> {
         # Method 7: Try to restart Teams process to apply settings
         Write-Host "[Notifications] Attempting to restart Teams to apply notification settings..."
         $teamsProcesses = Get-Process -Name "Teams" -ErrorAction SilentlyContinue
-        if ($teamsProcesses) {
+        if ($teamsProcesses -and -not (Test-Allowed "teams")) {
             foreach ($proc in $teamsProcesses) {
                 try {
                     Stop-Process -Id $proc.Id -Force -ErrorAction SilentlyContinue
@@ -781,7 +784,7 @@ pub fn mute_notifications_windows_api() -> std::result::Result<(), String> {
                                       $windowTitle -like "*Edge*" -or
                                       $windowTitle -like "*Explorer*")
                         
-                        if ($isTeamsNotification -and -not $isExcluded) {
+                        if ($isTeamsNotification -and -not $isExcluded -and -not (Test-Allowed $windowTitle)) {
                             [Win32]::PostMessage($hWnd, [Win32]::WM_CLOSE, [IntPtr]::Zero, [IntPtr]::Zero) | Out-Null
                             Write-Host "[Notifications] Closed Teams notification window: $windowTitle"
                             $script:teamsNotificationClosed = $true
@@ -831,15 +834,17 @@ pub fn mute_notifications_windows_api() -> std::result::Result<(), String> {
     if output.status.success() {
         // Also try Windows API as supplement
         unsafe {
-            let _ = disable_global_notifications();
-            let _ = set_focus_assist_alarms_only();
+            if allowlist.is_empty() {
+                let _ = disable_global_notifications();
+                let _ = set_focus_assist_alarms_only();
+            }
             let _ = broadcast_settings_change();
             let _ = restart_shell_experience_host();
         }
         
         // Start background task to continuously block ALL notification windows
         // This aggressively closes notification popups in real-time
-        let _ = start_notification_blocker();
+        let _ = start_notification_blocker(allowlist);
         
         println!("[Notifications] ✓ Notifications muted via comprehensive approach");
         Ok(())
@@ -1071,27 +1076,65 @@ pub fn unmute_notifications_windows_api() -> std::result::Result<(), String> {
     }
 }
 
-#[cfg(not(windows))]
-pub fn mute_notifications_windows_api() -> std::result::Result<(), String> {
-    Err("Not available on this platform".to_string())
-}
-
-#[cfg(not(windows))]
-pub fn unmute_notifications_windows_api() -> std::result::Result<(), String> {
-    Err("Not available on this platform".to_string())
-}
-
 // Shared flag for notification blocker - must be accessible from both start and stop functions
 #[cfg(windows)]
 static BLOCKER_RUNNING: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
 
-/// Start a background task that continuously closes Teams notification windows
+// Apps whose windows the blocker must leave alone; replaced on every start
+#[cfg(windows)]
+static BLOCKER_ALLOWLIST: std::sync::Mutex<Vec<String>> = std::sync::Mutex::new(Vec::new());
+
+/// Whether a window belongs to an allowlisted app, by title, class or owning process
+/// Toasts drawn by the shell only carry the sender in their title, so all three are checked
+#[cfg(windows)]
+fn is_allowlisted_window(hwnd: HWND) -> bool {
+    use crate::services::notification_allowlist::is_allowed;
+
+    let allowlist = match BLOCKER_ALLOWLIST.lock() {
+        Ok(allowlist) => allowlist,
+        Err(_) => return false,
+    };
+    if allowlist.is_empty() {
+        return false;
+    }
+
+    unsafe {
+        let mut title = [0u16; 512];
+        let title_len = GetWindowTextW(hwnd, &mut title).max(0) as usize;
+        let title = String::from_utf16_lossy(&title[..title_len]);
+
+        let mut class_name = [0u16; 256];
+        let class_len = GetClassNameW(hwnd, &mut class_name).max(0) as usize;
+        let class_name = String::from_utf16_lossy(&class_name[..class_len]);
+
+        let mut process_id = 0u32;
+        GetWindowThreadProcessId(hwnd, Some(&mut process_id));
+        let mut process_name = String::new();
+        if let Ok(handle) = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, process_id) {
+            let mut buffer = [0u16; 260];
+            let mut size = buffer.len() as u32;
+            if QueryFullProcessImageNameW(handle, PROCESS_NAME_WIN32, PWSTR(buffer.as_mut_ptr()), &mut size).is_ok() {
+                let path = String::from_utf16_lossy(&buffer[..size as usize]);
+                process_name = path.rsplit('\\').next().unwrap_or_default().to_string();
+            }
+            let _ = CloseHandle(handle);
+        }
+
+        is_allowed(&allowlist, &[&title, &class_name, &process_name])
+    }
+}
+
+/// Start a background task that continuously closes notification windows, sparing apps on `allowlist`
 /// This runs while notifications are muted
 #[cfg(windows)]
-pub fn start_notification_blocker() -> std::result::Result<(), String> {
+pub fn start_notification_blocker(allowlist: &[String]) -> std::result::Result<(), String> {
     use std::sync::atomic::Ordering;
     use std::time::Duration;
     
+    if let Ok(mut current) = BLOCKER_ALLOWLIST.lock() {
+        *current = allowlist.to_vec();
+    }
+    
     // Check if blocker is already running
     if BLOCKER_RUNNING.swap(true, Ordering::SeqCst) {
         println!("[Notifications] Notification blocker already running");
@@ -1105,6 +1148,9 @@ pub fn start_notification_blocker() -> std::result::Result<(), String> {
     std::thread::spawn(move || {
         unsafe {
             extern "system" fn enum_windows_proc(hwnd: HWND, _lparam: LPARAM) -> BOOL {
+                if is_allowlisted_window(hwnd) {
+                    return BOOL::from(true);
+                }
                 unsafe {
                     // Get window text
                     let mut title = [0u16; 512];
@@ -1286,11 +1332,6 @@ pub fn start_notification_blocker() -> std::result::Result<(), String> {
     Ok(())
 }
 
-#[cfg(not(windows))]
-pub fn start_notification_blocker() -> std::result::Result<(), String> {
-    Err("Not available on this platform".to_string())
-}
-
 /// Stop the background notification blocker
 #[cfg(windows)]
 pub fn stop_notification_blocker() {
@@ -1301,8 +1342,515 @@ pub fn stop_notification_blocker() {
     std::thread::sleep(std::time::Duration::from_millis(50));
 }
 
-#[cfg(not(windows))]
-pub fn stop_notification_blocker() {
-    // No-op on non-Windows
+// PowerShell-based suppression, used as a fallback when the Windows API calls above fail
+
+/// Suppress notifications directly using Windows Runtime API via PowerShell
+#[cfg(windows)]
+pub fn suppress_notifications_directly() -> std::result::Result<(), String> {
+    use std::process::Command;
+    
+    // Use PowerShell to call Windows Runtime API for notification suppression
+    let ps_script = r#"
+        try {
+            # Load Windows Runtime types
+            Add-Type -AssemblyName System.Runtime.WindowsRuntime -ErrorAction Stop
+            
+            # Load Windows.UI.Shell types for FocusSessionManager
+            [Windows.UI.Shell.FocusSessionManager, Windows.UI.Shell, ContentType = WindowsRuntime] | Out-Null
+            
+            # Get the FocusSessionManager
+            $manager = [Windows.UI.Shell.FocusSessionManager]::GetDefault()
+            
+            # Create a helper function to await async operations
+            $asTaskGeneric = ([System.WindowsRuntimeSystemExtensions].GetMethods() | Where-Object { 
+                $_.Name -eq 'AsTask' -and 
+                $_.GetParameters().Count -eq 1 -and 
+                $_.GetParameters()[0].ParameterType.Name -eq 'IAsyncOperation`1' 
+            })[0]
+            
+            Function Await($WinRtTask, $ResultType) {
+                $asTask = $asTaskGeneric.MakeGenericMethod($ResultType)
+                $netTask = $asTask.Invoke($null, @($WinRtTask))
+                $netTask.Wait() | Out-Null
+                $netTask.Result
+            }
+            
+            # Try multiple methods to create a FocusSession
+            $session = $null
+            
+            # Method 1: Try using reflection to find a constructor
+            try {
+                $sessionType = [Windows.UI.Shell.FocusSession]
+                $constructors = $sessionType.GetConstructors([System.Reflection.BindingFlags]::NonPublic -bor [System.Reflection.BindingFlags]::Instance)
+                if ($constructors.Count -gt 0) {
+                    # Try to invoke the first constructor
+                    $ctor = $constructors[0]
+                    $paramCount = $ctor.GetParameters().Count
+                    if ($paramCount -eq 0) {
+                        $session = $ctor.Invoke($null)
+                    } else {
+                        # Create default parameters
+                        $params = New-Object object[] $paramCount
+                        $session = $ctor.Invoke($params)
+                    }
+                    Write-Host "[Notifications] Created FocusSession using reflection constructor"
+                }
+            } catch {
+                Write-Host "[Notifications] Reflection method failed: $_"
+            }
+            
+            # Method 2: Try using Activator with different parameters
+            if (-not $session) {
+                try {
+                    $sessionType = [Windows.UI.Shell.FocusSession]
+                    # Try with empty object array
+                    $session = [System.Activator]::CreateInstance($sessionType, $true, $false, $null, $null, $null)
+                    Write-Host "[Notifications] Created FocusSession using Activator with parameters"
+                } catch {
+                    Write-Host "[Notifications] Activator method failed: $_"
+                }
+            }
+            
+            # Method 3: Try using NotificationListener API directly
+            if (-not $session) {
+                try {
+                    # Try to use NotificationListener to suppress notifications
+                    [Windows.UI.Notifications.Management.NotificationListener, Windows.UI.Notifications, ContentType = WindowsRuntime] | Out-Null
+                    $listener = [Windows.UI.Notifications.Management.NotificationListener]::Current
+                    
+                    # Get all notification sources
+                    $sources = Await ($listener.GetNotificationSourcesAsync()) ([Windows.Foundation.Collections.IVectorView[Windows.UI.Notifications.Management.NotificationListenerAccessStatus]])
+                    
+                    Write-Host "[Notifications] Found notification sources, attempting to suppress..."
+                    Write-Host "[Notifications] Note: NotificationListener requires app manifest capabilities"
+                    Write-Host "[Notifications] This may not work from a desktop app without proper permissions"
+                } catch {
+                    Write-Host "[Notifications] NotificationListener method failed: $_"
+                }
+            }
+            
+            # Method 4: Try to get an existing session or use a factory method
+            if (-not $session) {
+                try {
+                    # Check if there's a static factory method
+                    $sessionType = [Windows.UI.Shell.FocusSession]
+                    $methods = $sessionType.GetMethods([System.Reflection.BindingFlags]::Static -bor [System.Reflection.BindingFlags]::Public -bor [System.Reflection.BindingFlags]::NonPublic)
+                    $factoryMethod = $methods | Where-Object { $_.Name -like "*Create*" -or $_.Name -like "*New*" } | Select-Object -First 1
+                    if ($factoryMethod) {
+                        $session = $factoryMethod.Invoke($null, $null)
+                        Write-Host "[Notifications] Created FocusSession using factory method: $($factoryMethod.Name)"
+                    }
+                } catch {
+                    Write-Host "[Notifications] Factory method failed: $_"
+                }
+            }
+            
+            if ($session) {
+                # Set session properties
+                try {
+                    $session.Kind = [Windows.UI.Shell.FocusSessionKind]::AlarmsOnly
+                    $endTime = [DateTimeOffset]::Now.AddHours(2)
+                    $session.EndTime = $endTime
+                    
+                    # Start the focus session
+                    $asyncOp = $manager.StartFocusSession($session)
+                    $result = Await $asyncOp ([Windows.UI.Shell.FocusSession])
+                    
+                    Write-Host "[Notifications] Focus session started via Windows Runtime API - Session ID: $($result.Id)"
+                    Write-Host "[Notifications] SUCCESS - Notifications suppressed directly"
+                    exit 0
+                } catch {
+                    Write-Host "[Notifications] Failed to start session: $_"
+                    exit 1
+                }
+            } else {
+                Write-Host "[Notifications] Failed to create FocusSession object - all methods failed"
+                Write-Host "[Notifications] LIMITATION: Windows Runtime API is not accessible from desktop apps on Windows 11 24H2"
+                Write-Host "[Notifications] Focus Assist must be enabled manually:"
+                Write-Host "[Notifications] 1. Press Win+A to open Action Center"
+                Write-Host "[Notifications] 2. Click the Focus Assist button to set it to 'Alarms only'"
+                Write-Host "[Notifications] OR go to Settings > System > Focus > Set to 'Alarms only'"
+                exit 1
+            }
+        } catch {
+            $errorMsg = $_.Exception.Message
+            $errorType = $_.Exception.GetType().FullName
+            Write-Host "[Notifications] Windows Runtime API error [$errorType]: $errorMsg"
+            Write-Host "[Notifications] Stack trace: $($_.Exception.StackTrace)"
+            exit 1
+        }
+    "#;
+    
+    let output = Command::new("powershell")
+        .args(["-ExecutionPolicy", "Bypass", "-NoProfile", "-Command", &ps_script])
+        .output()
+        .map_err(|e| format!("Failed to execute PowerShell: {}", e))?;
+    
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    
+    println!("[Notifications] PowerShell stdout: {}", stdout);
+    if !stderr.is_empty() {
+        println!("[Notifications] PowerShell stderr: {}", stderr);
+    }
+    
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(format!("PowerShell script failed with exit code: {:?}", output.status.code()))
+    }
 }
 
+/// Restore system-wide notification settings using direct Windows API
+#[cfg(windows)]
+pub fn restore_system_notifications() -> std::result::Result<(), String> {
+    // Use direct Windows API instead of PowerShell for reliability
+    
+    match restore_notifications_direct() {
+        Ok(_) => {
+            println!("[Notifications] ✓ Notifications restored using direct Windows API");
+            Ok(())
+        }
+        Err(e) => {
+            eprintln!("[Notifications] Direct API restoration failed: {}, falling back to PowerShell", e);
+            // Fallback to PowerShell method
+            restore_system_notifications_powershell()
+        }
+    }
+}
+
+/// Fallback PowerShell method for notification restoration
+#[cfg(windows)]
+fn restore_system_notifications_powershell() -> std::result::Result<(), String> {
+    use std::process::Command;
+    
+    let ps_script = r#"
+        $ErrorActionPreference = "Continue"
+        
+        Write-Host "[Notifications] Restoring system-wide notification settings..."
+        
+        # Restore global toast notifications
+        $notifPath = "HKCU:\Software\Microsoft\Windows\CurrentVersion\Notifications\Settings"
+        if (Test-Path $notifPath) {
+            # Remove the disabled properties or set them back to enabled
+            Remove-ItemProperty -Path $notifPath -Name "NOC_GLOBAL_SETTING_TOAST_ENABLED" -Force -ErrorAction SilentlyContinue
+            Remove-ItemProperty -Path $notifPath -Name "NOC_GLOBAL_SETTING_ACTION_CENTER_ENABLED" -Force -ErrorAction SilentlyContinue
+            Write-Host "[Notifications] Restored global notification settings"
+        }
+        
+        # Force Windows to reload notification settings
+        Start-Sleep -Milliseconds 500
+        
+        # Restart ShellExperienceHost to apply changes
+        $shellProcess = Get-Process -Name "ShellExperienceHost" -ErrorAction SilentlyContinue
+        if ($shellProcess) {
+            Stop-Process -Name "ShellExperienceHost" -Force -ErrorAction SilentlyContinue
+            Write-Host "[Notifications] Restarted ShellExperienceHost"
+            Start-Sleep -Milliseconds 1000
+        }
+        
+        Write-Host "[Notifications] System notification restoration completed"
+        exit 0
+    "#;
+    
+    let output = Command::new("powershell")
+        .args(["-ExecutionPolicy", "Bypass", "-NoProfile", "-Command", &ps_script])
+        .output()
+        .map_err(|e| format!("Failed to execute PowerShell: {}", e))?;
+    
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    println!("[Notifications] System notifications restore stdout: {}", stdout);
+    
+    Ok(())
+}
+
+/// Restore app-specific notifications
+#[cfg(windows)]
+pub fn restore_app_notifications() -> std::result::Result<(), String> {
+    use std::process::Command;
+    
+    let ps_script = r#"
+        $ErrorActionPreference = "Continue"
+        
+        Write-Host "[Notifications] Restoring app notifications..."
+        
+        # Base registry path for app notifications
+        $basePath = "HKCU:\Software\Microsoft\Windows\CurrentVersion\Notifications\Settings"
+        
+        # Re-enable notifications for all apps (remove the disabled state)
+        try {
+            $allApps = Get-ChildItem -Path $basePath -ErrorAction SilentlyContinue
+            foreach ($app in $allApps) {
+                try {
+                    # Remove the Enabled property if it was set to 0, or set it to 1
+                    $currentValue = Get-ItemProperty -Path $app.PSPath -Name "Enabled" -ErrorAction SilentlyContinue
+                    if ($currentValue -and $currentValue.Enabled -eq 0) {
+                        Remove-ItemProperty -Path $app.PSPath -Name "Enabled" -Force -ErrorAction SilentlyContinue
+                        Write-Host "[Notifications] Restored notifications for: $($app.PSChildName)"
+                    }
+                } catch {
+                    # Ignore errors - some apps may not have this property
+                }
+            }
+        } catch {
+            Write-Host "[Notifications] Error restoring app notifications: $_"
+        }
+        
+        Write-Host "[Notifications] App notification restoration completed"
+        exit 0
+    "#;
+    
+    let output = Command::new("powershell")
+        .args(["-ExecutionPolicy", "Bypass", "-NoProfile", "-Command", &ps_script])
+        .output()
+        .map_err(|e| format!("Failed to execute PowerShell: {}", e))?;
+    
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    println!("[Notifications] App notifications restore stdout: {}", stdout);
+    
+    Ok(())
+}
+
+/// Disable notifications system-wide using direct Windows API
+#[cfg(windows)]
+pub fn disable_notifications_system_wide() -> std::result::Result<(), String> {
+    // Use direct Windows API instead of PowerShell for reliability
+    
+    match suppress_notifications_direct() {
+        Ok(_) => {
+            println!("[Notifications] ✓ Notifications suppressed using direct Windows API");
+            Ok(())
+        }
+        Err(e) => {
+            eprintln!("[Notifications] Direct API suppression failed: {}, falling back to PowerShell", e);
+            // Fallback to PowerShell method
+            disable_notifications_system_wide_powershell()
+        }
+    }
+}
+
+/// Fallback PowerShell method for notification suppression
+#[cfg(windows)]
+fn disable_notifications_system_wide_powershell() -> std::result::Result<(), String> {
+    use std::process::Command;
+    
+    let ps_script = r#"
+        $ErrorActionPreference = "Stop"
+        
+        Write-Host "[Notifications] Disabling notifications system-wide..."
+        
+        # Method 1: Disable toast notifications globally
+        $notifPath = "HKCU:\Software\Microsoft\Windows\CurrentVersion\Notifications\Settings"
+        if (-not (Test-Path $notifPath)) {
+            New-Item -Path $notifPath -Force | Out-Null
+        }
+        
+        # Disable toast notifications
+        Set-ItemProperty -Path $notifPath -Name "NOC_GLOBAL_SETTING_TOAST_ENABLED" -Value 0 -Type DWord -Force
+        Set-ItemProperty -Path $notifPath -Name "NOC_GLOBAL_SETTING_ACTION_CENTER_ENABLED" -Value 0 -Type DWord -Force
+        
+        Write-Host "[Notifications] Disabled global toast notifications"
+        
+        # Method 2: Set Focus Assist to Alarms Only via registry (more aggressive)
+        $basePath = "HKCU:\Software\Microsoft\Windows\CurrentVersion\CloudStore\Store\Cache\DefaultAccount"
+        
+        # Find and update all quiescence keys
+        $keys = Get-ChildItem -Path $basePath -Recurse -ErrorAction SilentlyContinue | 
+            Where-Object { $_.PSChildName -like "*quiescence*" -or $_.Name -like "*quiescence*" }
+        
+        foreach ($key in $keys) {
+            try {
+                $keyPath = $key.PSPath
+                if (Test-Path $keyPath) {
+                    # Set to Alarms Only (mode 2)
+                    Set-ItemProperty -Path $keyPath -Name "Data" -Value ([byte[]](0x43,0x42,0x01,0x00,0x02,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00)) -Type Binary -Force -ErrorAction SilentlyContinue
+                    Write-Host "[Notifications] Updated Focus Assist key: $keyPath"
+                }
+            } catch {
+                Write-Host "[Notifications] Error updating $($key.PSPath): $_"
+            }
+        }
+        
+        # Method 3: Create/update direct registry paths
+        $directPaths = @(
+            "HKCU:\Software\Microsoft\Windows\CurrentVersion\CloudStore\Store\Cache\DefaultAccount\`$windows.data.notifications.quiescence.win10\Current",
+            "HKCU:\Software\Microsoft\Windows\CurrentVersion\CloudStore\Store\Cache\DefaultAccount\`$windows.data.notifications.quiescence.win11\Current"
+        )
+        
+        foreach ($directPath in $directPaths) {
+            try {
+                if (-not (Test-Path $directPath)) {
+                    $parentPath = Split-Path -Path $directPath -Parent
+                    if (-not (Test-Path $parentPath)) {
+                        New-Item -Path $parentPath -Force -ErrorAction SilentlyContinue | Out-Null
+                    }
+                    New-Item -Path $directPath -Force -ErrorAction SilentlyContinue | Out-Null
+                }
+                
+                Set-ItemProperty -Path $directPath -Name "Data" -Value ([byte[]](0x43,0x42,0x01,0x00,0x02,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00)) -Type Binary -Force -ErrorAction SilentlyContinue
+                Write-Host "[Notifications] Updated direct path: $directPath"
+            } catch {
+                Write-Host "[Notifications] Error with direct path $directPath : $_"
+            }
+        }
+        
+        # Method 4: Force Windows to reload notification settings
+        Start-Sleep -Milliseconds 500
+        
+        # Restart ShellExperienceHost to apply changes
+        $shellProcess = Get-Process -Name "ShellExperienceHost" -ErrorAction SilentlyContinue
+        if ($shellProcess) {
+            Stop-Process -Name "ShellExperienceHost" -Force -ErrorAction SilentlyContinue
+            Write-Host "[Notifications] Restarted ShellExperienceHost"
+            Start-Sleep -Milliseconds 1000
+        }
+        
+        # Broadcast settings change
+        try {
+            Add-Type -TypeDefinition @"
+                using System;
+                using System.Runtime.InteropServices;
+                public class Win32 {
+                    [DllImport("user32.dll", CharSet=CharSet.Auto)]
+                    public static extern IntPtr SendMessageTimeout(
+                        IntPtr hWnd, uint Msg, IntPtr wParam, string lParam,
+                        uint fuFlags, uint uTimeout, out IntPtr lpdwResult);
+                    public static readonly IntPtr HWND_BROADCAST = new IntPtr(0xffff);
+                    public static readonly uint WM_SETTINGCHANGE = 0x001A;
+                    public static readonly uint SMTO_ABORTIFHUNG = 0x0002;
+                }
+"@ -ErrorAction SilentlyContinue
+            $result = [IntPtr]::Zero
+            [Win32]::SendMessageTimeout(
+                [Win32]::HWND_BROADCAST,
+                [Win32]::WM_SETTINGCHANGE,
+                [IntPtr]::Zero,
+                "Environment",
+                [Win32]::SMTO_ABORTIFHUNG,
+                5000,
+                [ref]$result
+            ) | Out-Null
+            Write-Host "[Notifications] Broadcasted settings change"
+        } catch {
+            Write-Host "[Notifications] Could not broadcast settings change: $_"
+        }
+        
+        Write-Host "[Notifications] System-wide notification suppression applied"
+        exit 0
+    "#;
+    
+    let output = Command::new("powershell")
+        .args(["-ExecutionPolicy", "Bypass", "-NoProfile", "-Command", &ps_script])
+        .output()
+        .map_err(|e| format!("Failed to execute PowerShell: {}", e))?;
+    
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    
+    println!("[Notifications] System-wide disable stdout: {}", stdout);
+    if !stderr.is_empty() {
+        println!("[Notifications] System-wide disable stderr: {}", stderr);
+    }
+    
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(format!("PowerShell script failed with exit code: {:?}", output.status.code()))
+    }
+}
+
+/// Disable notifications for specific apps (Teams, Outlook, etc.), except those on `allowlist`
+#[cfg(windows)]
+pub fn disable_app_notifications(allowlist: &[String]) -> std::result::Result<(), String> {
+    use std::process::Command;
+    
+    let ps_script = crate::services::notification_allowlist::powershell_prelude(allowlist) + r#"
+        $ErrorActionPreference = "Continue"
+        
+        Write-Host "[Notifications] Disabling notifications for specific apps..."
+        
+        # Apps to disable notifications for
+        $appsToDisable = @(
+            "Microsoft.SkypeApp",           # Skype/Teams
+            "MSTeams",                      # Microsoft Teams
+            "MicrosoftTeams",                # Microsoft Teams (alternative)
+            "com.microsoft.teams",          # Teams UWP
+            "Microsoft.Outlook",            # Outlook
+            "Microsoft.Office.Outlook",     # Outlook (alternative)
+            "Discord",                      # Discord
+            "com.discordapp.Discord",       # Discord UWP
+            "WhatsApp",                     # WhatsApp
+            "com.whatsapp.WhatsApp",        # WhatsApp UWP
+            "Slack",                        # Slack
+            "com.tinyspeck.chatlyio",       # Slack UWP
+            "Telegram",                     # Telegram
+            "com.telegram.telegram",         # Telegram UWP
+            "Zoom",                         # Zoom
+            "Zoom.Zoom",                    # Zoom UWP
+            "com.zoom.Zoom"                 # Zoom (alternative)
+        )
+        
+        # Base registry path for app notifications
+        $basePath = "HKCU:\Software\Microsoft\Windows\CurrentVersion\Notifications\Settings"
+        
+        foreach ($appId in $appsToDisable) {
+            if (Test-Allowed $appId) {
+                Write-Host "[Notifications] Allowlisted, leaving on: $appId"
+                continue
+            }
+            try {
+                $appPath = Join-Path $basePath $appId
+                
+                if (Test-Path $appPath) {
+                    # Disable notifications for this app
+                    Set-ItemProperty -Path $appPath -Name "Enabled" -Value 0 -Type DWord -Force -ErrorAction SilentlyContinue
+                    Set-ItemProperty -Path $appPath -Name "ShowInActionCenter" -Value 0 -Type DWord -Force -ErrorAction SilentlyContinue
+                    Write-Host "[Notifications] Disabled notifications for: $appId"
+                } else {
+                    # Create the path and disable
+                    New-Item -Path $appPath -Force -ErrorAction SilentlyContinue | Out-Null
+                    Set-ItemProperty -Path $appPath -Name "Enabled" -Value 0 -Type DWord -Force -ErrorAction SilentlyContinue
+                    Set-ItemProperty -Path $appPath -Name "ShowInActionCenter" -Value 0 -Type DWord -Force -ErrorAction SilentlyContinue
+                    Write-Host "[Notifications] Created and disabled notifications for: $appId"
+                }
+            } catch {
+                Write-Host "[Notifications] Error disabling $appId : $_"
+            }
+        }
+        
+        # Also try to find Teams by searching for it
+        try {
+            $allApps = Get-ChildItem -Path $basePath -ErrorAction SilentlyContinue
+            foreach ($app in $allApps) {
+                $appName = $app.PSChildName
+                if (Test-Allowed $appName) { continue }
+                if ($appName -like "*team*" -or $appName -like "*skype*" -or $appName -like "*outlook*") {
+                    Set-ItemProperty -Path $app.PSPath -Name "Enabled" -Value 0 -Type DWord -Force -ErrorAction SilentlyContinue
+                    Set-ItemProperty -Path $app.PSPath -Name "ShowInActionCenter" -Value 0 -Type DWord -Force -ErrorAction SilentlyContinue
+                    Write-Host "[Notifications] Disabled notifications for found app: $appName"
+                }
+            }
+        } catch {
+            Write-Host "[Notifications] Error searching for apps: $_"
+        }
+        
+        Write-Host "[Notifications] App-specific notification suppression completed"
+        exit 0
+    "#;
+    
+    let output = Command::new("powershell")
+        .args(["-ExecutionPolicy", "Bypass", "-NoProfile", "-Command", &ps_script])
+        .output()
+        .map_err(|e| format!("Failed to execute PowerShell: {}", e))?;
+    
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    
+    println!("[Notifications] App notifications disable stdout: {}", stdout);
+    if !stderr.is_empty() {
+        println!("[Notifications] App notifications disable stderr: {}", stderr);
+    }
+    
+    // Don't fail if this doesn't work - it's a best-effort approach
+    Ok(())
+}
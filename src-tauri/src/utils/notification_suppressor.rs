@@ -0,0 +1,318 @@
+// Notification suppression backends
+// FocusService mutes and restores notifications through the NotificationSuppressor trait:
+// Windows uses the registry/PowerShell code in notification_suppression.rs,
+// Linux asks the freedesktop notification server to inhibit notifications over D-Bus.
+// Settings that outlive the process are recorded in the notification ledger before they change.
+
+use std::sync::Arc;
+use crate::utils::notification_ledger::NotificationLedger;
+
+/// A platform mechanism for silencing desktop notifications
+pub trait NotificationSuppressor: Send + Sync {
+    /// Short name for logs
+    fn name(&self) -> &'static str;
+    /// Silence notifications from every app except those matching `allowlist`
+    fn mute(&self, allowlist: &[String]) -> Result<(), String>;
+    fn unmute(&self) -> Result<(), String>;
+
+    /// Whether `mute` can spare allowlisted apps; backends that can't mute them along with everything else
+    fn supports_allowlist(&self) -> bool {
+        true
+    }
+
+    /// Stop any background task dismissing notifications that slipped through
+    fn stop_blocker(&self) {}
+}
+
+/// The suppressor for the platform we're running on
+pub fn platform_suppressor(ledger: NotificationLedger) -> Arc<dyn NotificationSuppressor> {
+    #[cfg(windows)]
+    {
+        Arc::new(WindowsSuppressor { ledger })
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        Arc::new(FreedesktopSuppressor::new(ledger))
+    }
+
+    #[cfg(not(any(windows, target_os = "linux")))]
+    {
+        let _ = ledger;
+        Arc::new(UnsupportedSuppressor)
+    }
+}
+
+#[cfg(windows)]
+pub struct WindowsSuppressor {
+    ledger: NotificationLedger,
+}
+
+#[cfg(windows)]
+impl NotificationSuppressor for WindowsSuppressor {
+    fn name(&self) -> &'static str {
+        "Windows"
+    }
+
+    fn mute(&self, allowlist: &[String]) -> Result<(), String> {
+        use crate::utils::notification_suppression::{
+            disable_app_notifications, disable_notifications_system_wide, mute_notifications_windows_api,
+            suppress_notifications_directly,
+        };
+        use crate::utils::notification_ledger::snapshot_windows_settings;
+
+        // Never change a setting we couldn't record first
+        let snapshot = snapshot_windows_settings()
+            .map_err(|e| format!("Not muting, failed to record current notification settings: {}", e))?;
+        self.ledger.record(snapshot)?;
+
+        match mute_notifications_windows_api(allowlist) {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                eprintln!("[Notifications] Windows API mute failed: {}, falling back to PowerShell", e);
+                let _ = disable_app_notifications(allowlist);
+                // The system-wide switches can't spare individual apps
+                if !allowlist.is_empty() {
+                    return Ok(());
+                }
+                let _ = disable_notifications_system_wide();
+                suppress_notifications_directly()
+            }
+        }
+    }
+
+    fn unmute(&self) -> Result<(), String> {
+        use crate::utils::notification_suppression::{
+            restore_app_notifications, restore_system_notifications, unmute_notifications_windows_api,
+        };
+        use crate::utils::notification_ledger::PlatformRestorer;
+
+        let result = match unmute_notifications_windows_api() {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                eprintln!("[Notifications] Windows API unmute failed: {}, falling back to PowerShell", e);
+                let _ = restore_app_notifications();
+                restore_system_notifications()
+            }
+        };
+
+        // The unmute above turns everything on; put back the values the user actually had
+        self.ledger.replay(&PlatformRestorer)?;
+        result
+    }
+
+    fn stop_blocker(&self) {
+        crate::utils::notification_suppression::stop_notification_blocker()
+    }
+}
+
+#[cfg(target_os = "linux")]
+use crate::utils::notification_ledger::{LedgerEntry, PlatformRestorer};
+
+#[cfg(target_os = "linux")]
+pub(crate) const NOTIFICATIONS_DEST: &str = "org.freedesktop.Notifications";
+#[cfg(target_os = "linux")]
+pub(crate) const NOTIFICATIONS_PATH: &str = "/org/freedesktop/Notifications";
+#[cfg(target_os = "linux")]
+const GNOME_NOTIFICATIONS_SCHEMA: &str = "org.gnome.desktop.notifications";
+
+#[cfg(target_os = "linux")]
+enum LinuxMuteState {
+    Unmuted,
+    // The server drops the inhibition if our connection closes, so it's kept open until unmute
+    Inhibited {
+        connection: zbus::blocking::Connection,
+        cookie: u32,
+    },
+    // GNOME Shell doesn't implement Inhibit; banners are turned off through gsettings instead
+    BannersHidden,
+}
+
+/// Linux backend: the `Inhibit` method of the freedesktop notifications spec (KDE Plasma and others),
+/// falling back to GNOME's show-banners setting
+#[cfg(target_os = "linux")]
+pub struct FreedesktopSuppressor {
+    state: std::sync::Mutex<LinuxMuteState>,
+    ledger: NotificationLedger,
+}
+
+#[cfg(target_os = "linux")]
+impl FreedesktopSuppressor {
+    pub fn new(ledger: NotificationLedger) -> Self {
+        Self {
+            state: std::sync::Mutex::new(LinuxMuteState::Unmuted),
+            ledger,
+        }
+    }
+
+    fn inhibit() -> Result<(zbus::blocking::Connection, u32), String> {
+        use std::collections::HashMap;
+        use zbus::zvariant::Value;
+
+        let connection = zbus::blocking::Connection::session()
+            .map_err(|e| format!("Failed to connect to the session bus: {}", e))?;
+        let hints: HashMap<&str, Value> = HashMap::new();
+        let reply = connection
+            .call_method(
+                Some(NOTIFICATIONS_DEST),
+                NOTIFICATIONS_PATH,
+                Some(NOTIFICATIONS_DEST),
+                "Inhibit",
+                &("pause-menu", "Focus session", hints),
+            )
+            .map_err(|e| format!("Inhibit call failed: {}", e))?;
+        let cookie: u32 = reply
+            .body()
+            .deserialize()
+            .map_err(|e| format!("Unexpected Inhibit reply: {}", e))?;
+        Ok((connection, cookie))
+    }
+
+    fn gsettings(args: &[&str]) -> Result<String, String> {
+        let output = std::process::Command::new("gsettings")
+            .args(args)
+            .output()
+            .map_err(|e| format!("Failed to run gsettings: {}", e))?;
+        if !output.status.success() {
+            return Err(format!("gsettings failed: {}", String::from_utf8_lossy(&output.stderr).trim()));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl NotificationSuppressor for FreedesktopSuppressor {
+    fn name(&self) -> &'static str {
+        "freedesktop"
+    }
+
+    fn mute(&self, _allowlist: &[String]) -> Result<(), String> {
+        let mut state = self.state.lock().map_err(|e| e.to_string())?;
+        if !matches!(*state, LinuxMuteState::Unmuted) {
+            return Ok(());
+        }
+        match Self::inhibit() {
+            Ok((connection, cookie)) => {
+                println!("[Notifications] Inhibited notifications over D-Bus (cookie {})", cookie);
+                *state = LinuxMuteState::Inhibited { connection, cookie };
+                Ok(())
+            }
+            Err(inhibit_error) => {
+                println!("[Notifications] {}, trying gsettings", inhibit_error);
+                let previous = Self::gsettings(&["get", GNOME_NOTIFICATIONS_SCHEMA, "show-banners"])
+                    .map_err(|e| format!("{}; {}", inhibit_error, e))?;
+                self.ledger.record(vec![LedgerEntry::Gsetting {
+                    schema: GNOME_NOTIFICATIONS_SCHEMA.to_string(),
+                    key: "show-banners".to_string(),
+                    previous: previous.clone(),
+                }])?;
+                Self::gsettings(&["set", GNOME_NOTIFICATIONS_SCHEMA, "show-banners", "false"])?;
+                println!("[Notifications] Hid GNOME notification banners (was {})", previous);
+                *state = LinuxMuteState::BannersHidden;
+                Ok(())
+            }
+        }
+    }
+
+    // Neither Inhibit nor show-banners works per app
+    fn supports_allowlist(&self) -> bool {
+        false
+    }
+
+    fn unmute(&self) -> Result<(), String> {
+        let mut state = self.state.lock().map_err(|e| e.to_string())?;
+        match std::mem::replace(&mut *state, LinuxMuteState::Unmuted) {
+            LinuxMuteState::Unmuted => Ok(()),
+            LinuxMuteState::Inhibited { connection, cookie } => {
+                // Closing the connection releases the inhibition too, so a failed call isn't fatal
+                if let Err(e) = connection.call_method(
+                    Some(NOTIFICATIONS_DEST),
+                    NOTIFICATIONS_PATH,
+                    Some(NOTIFICATIONS_DEST),
+                    "UnInhibit",
+                    &(cookie,),
+                ) {
+                    eprintln!("[Notifications] UnInhibit call failed: {}", e);
+                }
+                println!("[Notifications] Released D-Bus notification inhibition");
+                Ok(())
+            }
+            LinuxMuteState::BannersHidden => {
+                self.ledger.replay(&PlatformRestorer)?;
+                println!("[Notifications] Restored GNOME notification banners");
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(not(any(windows, target_os = "linux")))]
+pub struct UnsupportedSuppressor;
+
+#[cfg(not(any(windows, target_os = "linux")))]
+impl NotificationSuppressor for UnsupportedSuppressor {
+    fn name(&self) -> &'static str {
+        "unsupported"
+    }
+
+    fn mute(&self, _allowlist: &[String]) -> Result<(), String> {
+        Err("Not available on this platform".to_string())
+    }
+
+    fn unmute(&self) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+pub mod mock {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Records every call so tests can assert on muting without touching the desktop
+    #[derive(Default)]
+    pub struct RecordingSuppressor {
+        pub calls: Mutex<Vec<&'static str>>,
+        pub last_allowlist: Mutex<Vec<String>>,
+        pub fail_mute: bool,
+        pub no_allowlist: bool, // Behave like a backend that can't spare allowlisted apps
+    }
+
+    impl RecordingSuppressor {
+        pub fn calls(&self) -> Vec<&'static str> {
+            self.calls.lock().unwrap().clone()
+        }
+
+        pub fn last_allowlist(&self) -> Vec<String> {
+            self.last_allowlist.lock().unwrap().clone()
+        }
+    }
+
+    impl NotificationSuppressor for RecordingSuppressor {
+        fn name(&self) -> &'static str {
+            "recording"
+        }
+
+        fn mute(&self, allowlist: &[String]) -> Result<(), String> {
+            self.calls.lock().unwrap().push("mute");
+            *self.last_allowlist.lock().unwrap() = allowlist.to_vec();
+            if self.fail_mute {
+                return Err("mute failed".to_string());
+            }
+            Ok(())
+        }
+
+        fn supports_allowlist(&self) -> bool {
+            !self.no_allowlist
+        }
+
+        fn unmute(&self) -> Result<(), String> {
+            self.calls.lock().unwrap().push("unmute");
+            Ok(())
+        }
+
+        fn stop_blocker(&self) {
+            self.calls.lock().unwrap().push("stop_blocker");
+        }
+    }
+}
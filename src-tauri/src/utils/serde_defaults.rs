@@ -0,0 +1,7 @@
+// Serde default helpers
+// Shared `#[serde(default = "...")]` functions for fields added to saved files and API payloads
+
+/// For flags that default to on when a field is missing
+pub fn default_true() -> bool {
+    true
+}